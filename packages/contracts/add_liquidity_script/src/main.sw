@@ -0,0 +1,57 @@
+script;
+
+use std::{
+    contract_id::ContractId,
+    identity::Identity,
+    token::force_transfer_to_contract,
+};
+use exchange_abi::Exchange;
+use router_abi::LiquidityOutput;
+use microchain_helpers::quote;
+
+enum Error {
+    InsufficentToken0: (),
+    InsufficentToken1: (),
+}
+
+// Adds liquidity from the transaction's own coins, so the tokens never sit in a contract
+// between transactions where someone else could spend them. Tokens the pool doesn't take
+// stay in the transaction and are returned through its change outputs.
+
+fn main(
+    pool: b256,
+    amount_0_desired: u64,
+    amount_1_desired: u64,
+    amount_0_min: u64,
+    amount_1_min: u64,
+    recipient: Identity,
+) -> LiquidityOutput {
+    let exchange = abi(Exchange, pool);
+    let (token0, token1) = exchange.get_tokens();
+    let pool_info = exchange.get_pool_info();
+
+    let (amount_0, amount_1) = if pool_info.token_0_reserve == 0 && pool_info.token_1_reserve == 0 {
+        (amount_0_desired, amount_1_desired)
+    } else {
+        let amount_1_optional = quote(amount_0_desired, pool_info.token_0_reserve, pool_info.token_1_reserve);
+        if amount_1_optional <= amount_1_desired {
+            require(amount_1_optional >= amount_1_min, Error::InsufficentToken1);
+            (amount_0_desired, amount_1_optional)
+        } else {
+            let amount_0_optional = quote(amount_1_desired, pool_info.token_1_reserve, pool_info.token_0_reserve);
+            require(amount_0_optional >= amount_0_min, Error::InsufficentToken0);
+            (amount_0_optional, amount_1_desired)
+        }
+    };
+
+    force_transfer_to_contract(amount_0, ContractId::from(token0), ContractId::from(pool));
+    force_transfer_to_contract(amount_1, ContractId::from(token1), ContractId::from(pool));
+
+    let liquidity = exchange.add_liquidity(recipient);
+
+    LiquidityOutput {
+        amount_0: amount_0,
+        amount_1: amount_1,
+        liquidity: liquidity,
+    }
+}
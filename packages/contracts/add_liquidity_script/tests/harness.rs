@@ -0,0 +1,174 @@
+use std::str::FromStr;
+use fuels::{
+    prelude::*,
+    fuels_abigen::abigen,
+    signers::WalletUnlocked,
+    tx::{AssetId, Bytes32, StorageSlot},
+};
+use test_helpers::{add_liquidity_with_script, deployer_storage, get_wallets, MIN_FEE_CHANGE_DELAY};
+
+abigen!(Exchange, "../exchange_contract/out/debug/exchange_contract-abi.json");
+abigen!(TestToken, "../token_contract/out/debug/token_contract-abi.json");
+abigen!(Vault, "../vault_contract/out/debug/vault_contract-abi.json");
+
+const MINIMUM_LIQUIDITY: u64 = 1000;
+
+struct Fixture {
+    wallet: WalletUnlocked,
+    token_asset_id: AssetId,
+    exchange_contract_id: Bech32ContractId,
+    exchange_asset_id: AssetId,
+    exchange_instance: Exchange,
+    vault_contract_id: Bech32ContractId,
+}
+
+fn to_9_decimal(num: u64) -> u64 {
+    num * 1_000_000_000
+}
+
+/// Deploy an empty ETH/token pool
+async fn setup() -> Fixture {
+    let wallets = get_wallets().await;
+    let wallet = wallets.get(0).unwrap().clone();
+
+    let token_contract_id = Contract::deploy(
+        "../token_contract/out/debug/token_contract.bin",
+        &wallet,
+        TxParameters::default(),
+        StorageConfiguration::new(None, None),
+    )
+    .await
+    .unwrap();
+
+    let token_instance = TestToken::new(token_contract_id.clone(), wallet.clone());
+    token_instance
+        .methods()
+        .initialize(to_9_decimal(20000), wallet.address().into())
+        .call()
+        .await
+        .unwrap();
+    token_instance
+        .methods()
+        .mint()
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .unwrap();
+
+    let token1_slot = Bytes32::from_str("0x0000000000000000000000000000000000000000000000000000000000000001").unwrap();
+    let storage_vec = vec![
+        StorageSlot::new(token1_slot, token_contract_id.hash()),
+    ];
+    let exchange_contract_id = Contract::deploy_with_parameters(
+        "../exchange_contract/out/debug/exchange_contract.bin",
+        &wallet,
+        TxParameters::default(),
+        StorageConfiguration::with_manual_storage(Some(storage_vec)),
+        Salt::from([0u8; 32]),
+    )
+    .await
+    .unwrap();
+
+    let vault_contract_id = Contract::deploy(
+        "../vault_contract/out/debug/vault_contract.bin",
+        &wallet,
+        TxParameters::default(),
+        deployer_storage(&wallet),
+    )
+    .await
+    .unwrap();
+
+    Vault::new(vault_contract_id.clone(), wallet.clone())
+        .methods()
+        .initialize(Identity::Address(wallet.address().into()), MIN_FEE_CHANGE_DELAY)
+        .call()
+        .await
+        .unwrap();
+
+    let exchange_instance = Exchange::new(exchange_contract_id.clone(), wallet.clone());
+    exchange_instance
+        .methods()
+        .initialize(Bits256(vault_contract_id.hash().into()))
+        .set_contracts(&[vault_contract_id.clone()])
+        .call()
+        .await
+        .unwrap();
+
+    Fixture {
+        wallet: wallet,
+        token_asset_id: AssetId::new(*token_contract_id.hash()),
+        exchange_contract_id: exchange_contract_id.clone(),
+        exchange_asset_id: AssetId::new(*exchange_contract_id.hash()),
+        exchange_instance: exchange_instance,
+        vault_contract_id: vault_contract_id,
+    }
+}
+
+async fn add_liquidity(fixture: &Fixture, amount_0_desired: u64, amount_1_desired: u64, amount_0_min: u64, amount_1_min: u64) -> Result<test_helpers::ScriptLiquidityOutput, Error> {
+    add_liquidity_with_script(
+        &fixture.wallet,
+        (BASE_ASSET_ID, fixture.token_asset_id),
+        amount_0_desired,
+        amount_1_desired,
+        amount_0_min,
+        amount_1_min,
+        Identity::Address(fixture.wallet.address().into()),
+        &[fixture.exchange_contract_id.clone(), fixture.vault_contract_id.clone()],
+    )
+    .await
+}
+
+#[tokio::test]
+async fn add_liquidity_in_one_transaction() {
+    let fixture = setup().await;
+
+    let starting_eth_balance = fixture.wallet.get_asset_balance(&BASE_ASSET_ID).await.unwrap();
+    let starting_token_balance = fixture.wallet.get_asset_balance(&fixture.token_asset_id).await.unwrap();
+
+    // The first deposit sets the price
+    let result = add_liquidity(&fixture, to_9_decimal(5), to_9_decimal(10), 0, 0).await.unwrap();
+    assert_eq!(result.amount_0, to_9_decimal(5));
+    assert_eq!(result.amount_1, to_9_decimal(10));
+    let expected_liquidity = 7071067811;
+    assert_eq!(result.liquidity, expected_liquidity - MINIMUM_LIQUIDITY);
+    assert_eq!(fixture.wallet.get_asset_balance(&fixture.exchange_asset_id).await.unwrap(), result.liquidity);
+
+    // Later deposits are taken at the pool's ratio, with the rest returned as change
+    let result = add_liquidity(&fixture, to_9_decimal(1), to_9_decimal(5), 0, 0).await.unwrap();
+    assert_eq!(result.amount_0, to_9_decimal(1));
+    assert_eq!(result.amount_1, to_9_decimal(2));
+
+    let end_eth_balance = fixture.wallet.get_asset_balance(&BASE_ASSET_ID).await.unwrap();
+    let end_token_balance = fixture.wallet.get_asset_balance(&fixture.token_asset_id).await.unwrap();
+    assert_eq!(starting_eth_balance - end_eth_balance, to_9_decimal(6));
+    assert_eq!(starting_token_balance - end_token_balance, to_9_decimal(12));
+
+    let pool_info = fixture.exchange_instance.methods().get_pool_info().call().await.unwrap().value;
+    assert_eq!(pool_info.token_0_reserve, to_9_decimal(6));
+    assert_eq!(pool_info.token_1_reserve, to_9_decimal(12));
+}
+
+#[tokio::test]
+async fn add_liquidity_script_minimums() {
+    let fixture = setup().await;
+    add_liquidity(&fixture, to_9_decimal(5), to_9_decimal(10), 0, 0).await.unwrap();
+
+    let starting_eth_balance = fixture.wallet.get_asset_balance(&BASE_ASSET_ID).await.unwrap();
+    let starting_token_balance = fixture.wallet.get_asset_balance(&fixture.token_asset_id).await.unwrap();
+
+    let is_err = add_liquidity(&fixture, to_9_decimal(1), to_9_decimal(5), 0, to_9_decimal(3)).await.is_err();
+    assert!(is_err, "Only 2 tokens can be added with 1 ETH");
+
+    let is_err = add_liquidity(&fixture, to_9_decimal(5), to_9_decimal(2), to_9_decimal(2), 0).await.is_err();
+    assert!(is_err, "Only 1 ETH can be added with 2 tokens");
+
+    // Reverted deposits are returned
+    let end_eth_balance = fixture.wallet.get_asset_balance(&BASE_ASSET_ID).await.unwrap();
+    let end_token_balance = fixture.wallet.get_asset_balance(&fixture.token_asset_id).await.unwrap();
+    assert_eq!(end_eth_balance, starting_eth_balance);
+    assert_eq!(end_token_balance, starting_token_balance);
+
+    let pool_info = fixture.exchange_instance.methods().get_pool_info().call().await.unwrap().value;
+    assert_eq!(pool_info.token_0_reserve, to_9_decimal(5));
+    assert_eq!(pool_info.token_1_reserve, to_9_decimal(10));
+}
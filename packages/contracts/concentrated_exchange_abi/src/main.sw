@@ -0,0 +1,146 @@
+library concentrated_exchange_abi;
+
+use std::{
+    identity::Identity,
+    u128::U128,
+};
+
+// Ticks are offset by the pool's MAX_TICK so they fit in a u64. Tick MAX_TICK is a
+// price of 1, and each tick above or below it moves the price by 0.01%.
+// Square root prices are fixed-point numbers with 18 decimals.
+
+// Events
+
+pub struct PositionIncreased {
+    owner: b256,
+    position_id: u64,
+    tick_lower: u64,
+    tick_upper: u64,
+    liquidity: u64,
+    amount_0: u64,
+    amount_1: u64,
+}
+
+pub struct PositionDecreased {
+    owner: b256,
+    position_id: u64,
+    tick_lower: u64,
+    tick_upper: u64,
+    liquidity: u64,
+    amount_0: u64,
+    amount_1: u64,
+}
+
+pub struct FeesCollected {
+    owner: b256,
+    position_id: u64,
+    amount_0: u64,
+    amount_1: u64,
+    recipient: b256,
+}
+
+pub struct ConcentratedSwap {
+    sender: b256,
+    amount_0_in: u64,
+    amount_1_in: u64,
+    amount_0_out: u64,
+    amount_1_out: u64,
+    recipient: b256,
+    sqrt_price: U128,
+    tick: u64,
+}
+
+// Returns
+
+pub struct PoolState {
+    sqrt_price: U128,
+    /// Tick the current price is in
+    tick: u64,
+    /// Liquidity of the positions in range of the current price
+    liquidity: u64,
+    /// LP fees earned per unit of liquidity, with 18 decimals
+    fee_growth_global_0: U128,
+    fee_growth_global_1: U128,
+    /// Tokens held for all positions, including uncollected fees
+    token_0_reserve: u64,
+    token_1_reserve: u64,
+}
+
+pub struct TickInfo {
+    /// Liquidity of all positions using this tick as a bound
+    liquidity_gross: u64,
+    /// Liquidity that comes into range when the price crosses the tick upwards
+    liquidity_added: u64,
+    /// Liquidity that goes out of range when the price crosses the tick upwards
+    liquidity_removed: u64,
+    /// Fee growth on the other side of the tick from the current price
+    fee_growth_outside_0: U128,
+    fee_growth_outside_1: U128,
+    /// Neighbouring initialized ticks
+    prev: u64,
+    next: u64,
+}
+
+pub struct Position {
+    tick_lower: u64,
+    tick_upper: u64,
+    liquidity: u64,
+    fee_growth_inside_0_last: U128,
+    fee_growth_inside_1_last: U128,
+    /// Fees earned up to the last time the position was updated
+    tokens_owed_0: u64,
+    tokens_owed_1: u64,
+}
+
+pub struct LiquidityChange {
+    liquidity: u64,
+    amount_0: u64,
+    amount_1: u64,
+}
+
+pub struct TickTWAPInfo {
+    current_element: u64,
+    buffer_size: u64,
+    next_buffer_size: u64,
+}
+
+pub struct TickObservation {
+    timestamp: u64,
+    /// Sum of the tick the price was in over each second
+    tick_cumulative: u64,
+}
+
+abi ConcentratedExchange {
+    ////////////////////
+    // Read only
+    ////////////////////
+    #[storage(read)]fn get_pool_state() -> PoolState;
+    #[storage(read)]fn get_tick(tick: u64) -> TickInfo;
+    /// Positions are held by whoever added their liquidity, under an ID of their choosing
+    #[storage(read)]fn get_position(owner: b256, position_id: u64) -> Position;
+    /// Fees a position could collect now, including those earned since it was last updated
+    #[storage(read)]fn get_position_fees(owner: b256, position_id: u64) -> (u64, u64);
+    /// Output a swap of `amount_in` would receive, after the LP fee
+    #[storage(read)]fn get_amount_out(amount_in: u64, token_0_in: bool) -> u64;
+    /// Get the two tokens held in the pool
+    #[storage(read)]fn get_tokens() -> (b256, b256);
+    #[storage(read)]fn get_twap_info() -> TickTWAPInfo;
+    #[storage(read)]fn get_observation(element: u64) -> TickObservation;
+    /// Average tick across the whole TWAP buffer, which needs at least two observations
+    #[storage(read)]fn get_twap_tick() -> u64;
+    ////////////////////
+    // Actions
+    ////////////////////
+    /// Set the starting price, at the bottom of `tick`
+    #[storage(read, write)]fn initialize(tick: u64);
+    /// Add the tokens sent to the pool as liquidity between two ticks, returning any
+    /// tokens beyond the current price ratio to `refund`
+    #[storage(read, write)]fn add_liquidity(position_id: u64, tick_lower: u64, tick_upper: u64, refund: Identity) -> LiquidityChange;
+    /// Withdraw liquidity from a position. Fees earned are kept for `collect_fees`.
+    #[storage(read, write)]fn remove_liquidity(position_id: u64, liquidity: u64, recipient: Identity) -> LiquidityChange;
+    #[storage(read, write)]fn collect_fees(position_id: u64, recipient: Identity) -> (u64, u64);
+    /// Swap all of the token sent to the pool for the other token
+    #[storage(read, write)]fn swap(min_amount_out: u64, recipient: Identity) -> u64;
+    /// Increase the size of the TWAP buffer to the given size
+    #[storage(read, write)]fn expand_twap_buffer(new_slots: u64);
+}
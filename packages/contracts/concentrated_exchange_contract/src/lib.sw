@@ -0,0 +1,162 @@
+library lib;
+
+use std::{revert::require, u128::U128, u256::U256};
+use microchain_helpers::{exp_fixed, ln_fixed};
+
+enum Error {
+    TickOutOfRange: (),
+}
+
+/// Highest tick below and above a price of 1. Prices at the outer ticks are close to the
+/// smallest and largest u64 amounts, so wider ranges can't be used by any position.
+pub const MAX_TICK = 443_636;
+
+/// Fixed-point math uses 18 decimals, matching microchain_helpers
+const FIXED_ONE = U128::from((0, 1_000_000_000_000_000_000));
+
+/// ln(1.0001) with 18 decimals, the price step between ticks
+const LN_TICK_BASE = 99_995_000_333_308;
+
+fn to_u256(value: U128) -> U256 {
+    U256::from((0, 0, value.upper, value.lower))
+}
+
+fn to_u128(value: U256) -> U128 {
+    require(value.a == 0 && value.b == 0, Error::TickOutOfRange);
+    U128::from((value.c, value.d))
+}
+
+fn div_up(numerator: U256, denominator: U256) -> U256 {
+    (numerator + denominator - U256::from((0, 0, 0, 1))) / denominator
+}
+
+/// Subtract, wrapping around on underflow. Fee growth inside a range is only used as a
+/// difference between two readings, which is correct even when each reading has wrapped.
+pub fn wrapping_sub(a: U128, b: U128) -> U128 {
+    if a > b || a == b {
+        a - b
+    } else {
+        U128::max() - (b - a) + U128::from((0, 1))
+    }
+}
+
+/// Square root of the price at the bottom of a tick, sqrt(1.0001^(tick - MAX_TICK))
+pub fn get_sqrt_price_at_tick(tick: u64) -> U128 {
+    require(tick <= MAX_TICK * 2, Error::TickOutOfRange);
+    let two = U128::from((0, 2));
+    let ln_base = U128::from((0, LN_TICK_BASE));
+    if tick >= MAX_TICK {
+        exp_fixed(U128::from((0, tick - MAX_TICK)) * ln_base / two)
+    } else {
+        FIXED_ONE * FIXED_ONE / exp_fixed(U128::from((0, MAX_TICK - tick)) * ln_base / two)
+    }
+}
+
+/// Tick whose range holds a square root price
+pub fn get_tick_at_sqrt_price(sqrt_price: U128) -> u64 {
+    let two = U128::from((0, 2));
+    let ln_base = U128::from((0, LN_TICK_BASE));
+    let mut tick = if sqrt_price > FIXED_ONE || sqrt_price == FIXED_ONE {
+        let ticks = (ln_fixed(sqrt_price) * two / ln_base).as_u64().unwrap();
+        if ticks > MAX_TICK { MAX_TICK * 2 } else { MAX_TICK + ticks }
+    } else {
+        let log = ln_fixed(FIXED_ONE * FIXED_ONE / sqrt_price) * two;
+        let ticks = ((log + ln_base - U128::from((0, 1))) / ln_base).as_u64().unwrap();
+        if ticks > MAX_TICK { 0 } else { MAX_TICK - ticks }
+    };
+
+    // The logs are approximate, so step to the tick that holds the price
+    while tick < MAX_TICK * 2 && (get_sqrt_price_at_tick(tick + 1) < sqrt_price || get_sqrt_price_at_tick(tick + 1) == sqrt_price) {
+        tick += 1;
+    }
+    while tick > 0 && get_sqrt_price_at_tick(tick) > sqrt_price {
+        tick -= 1;
+    }
+    tick
+}
+
+/// Token 0 held by `liquidity` between two square root prices, L * (b - a) / (a * b)
+pub fn get_amount_0(sqrt_price_a: U128, sqrt_price_b: U128, liquidity: u64, round_up: bool) -> u64 {
+    let a = to_u256(sqrt_price_a);
+    let b = to_u256(sqrt_price_b);
+    let numerator = U256::from((0, 0, 0, liquidity)) * (b - a) * to_u256(FIXED_ONE);
+    let denominator = a * b;
+    let amount = if round_up { div_up(numerator, denominator) } else { numerator / denominator };
+    amount.as_u64().unwrap()
+}
+
+/// Token 1 held by `liquidity` between two square root prices, L * (b - a)
+pub fn get_amount_1(sqrt_price_a: U128, sqrt_price_b: U128, liquidity: u64, round_up: bool) -> u64 {
+    let numerator = U256::from((0, 0, 0, liquidity)) * to_u256(sqrt_price_b - sqrt_price_a);
+    let denominator = to_u256(FIXED_ONE);
+    let amount = if round_up { div_up(numerator, denominator) } else { numerator / denominator };
+    amount.as_u64().unwrap()
+}
+
+/// Largest liquidity that `amount_0` and `amount_1` can provide between two square root prices,
+/// given the current square root price
+pub fn get_liquidity_for_amounts(
+    sqrt_price: U128,
+    sqrt_price_lower: U128,
+    sqrt_price_upper: U128,
+    amount_0: u64,
+    amount_1: u64,
+) -> u64 {
+    let one = to_u256(FIXED_ONE);
+    let lower = to_u256(sqrt_price_lower);
+    let upper = to_u256(sqrt_price_upper);
+    let current = to_u256(sqrt_price);
+
+    let liquidity = if current < lower || current == lower {
+        // Below the range, the position is all token 0
+        U256::from((0, 0, 0, amount_0)) * lower * upper / ((upper - lower) * one)
+    } else if current < upper {
+        let liquidity_0 = U256::from((0, 0, 0, amount_0)) * current * upper / ((upper - current) * one);
+        let liquidity_1 = U256::from((0, 0, 0, amount_1)) * one / (current - lower);
+        if liquidity_0 < liquidity_1 { liquidity_0 } else { liquidity_1 }
+    } else {
+        // Above the range, the position is all token 1
+        U256::from((0, 0, 0, amount_1)) * one / (upper - lower)
+    };
+    liquidity.as_u64().unwrap()
+}
+
+/// Tokens held by `liquidity` between two square root prices, given the current square root price
+pub fn get_amounts_for_liquidity(
+    sqrt_price: U128,
+    sqrt_price_lower: U128,
+    sqrt_price_upper: U128,
+    liquidity: u64,
+    round_up: bool,
+) -> (u64, u64) {
+    if sqrt_price < sqrt_price_lower || sqrt_price == sqrt_price_lower {
+        (get_amount_0(sqrt_price_lower, sqrt_price_upper, liquidity, round_up), 0)
+    } else if sqrt_price < sqrt_price_upper {
+        (
+            get_amount_0(sqrt_price, sqrt_price_upper, liquidity, round_up),
+            get_amount_1(sqrt_price_lower, sqrt_price, liquidity, round_up),
+        )
+    } else {
+        (0, get_amount_1(sqrt_price_lower, sqrt_price_upper, liquidity, round_up))
+    }
+}
+
+/// Square root price after adding token 0, L * s / (L + amount * s), rounded up
+pub fn get_next_sqrt_price_from_amount_0(sqrt_price: U128, liquidity: u64, amount: u64) -> U128 {
+    let one = to_u256(FIXED_ONE);
+    let current = to_u256(sqrt_price);
+    let liquidity = U256::from((0, 0, 0, liquidity));
+    let numerator = liquidity * current * one;
+    let denominator = liquidity * one + U256::from((0, 0, 0, amount)) * current;
+    to_u128(div_up(numerator, denominator))
+}
+
+/// Square root price after adding token 1, s + amount / L, rounded down
+pub fn get_next_sqrt_price_from_amount_1(sqrt_price: U128, liquidity: u64, amount: u64) -> U128 {
+    sqrt_price + U128::from((0, amount)) * FIXED_ONE / U128::from((0, liquidity))
+}
+
+/// Fees earned by `liquidity` as the fee growth inside its range moved
+pub fn get_fees_earned(liquidity: u64, fee_growth_inside: U128, fee_growth_inside_last: U128) -> u64 {
+    (U128::from((0, liquidity)) * wrapping_sub(fee_growth_inside, fee_growth_inside_last) / FIXED_ONE).as_u64().unwrap()
+}
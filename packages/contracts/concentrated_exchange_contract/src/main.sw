@@ -0,0 +1,742 @@
+contract;
+
+dep lib;
+
+use std::{
+    auth::msg_sender,
+    block::timestamp,
+    context::*,
+    contract_id::ContractId,
+    identity::Identity,
+    logging::log,
+    storage::get,
+    token::transfer,
+    u128::U128,
+    vec::Vec,
+};
+
+use concentrated_exchange_abi::{
+    ConcentratedExchange,
+    ConcentratedSwap,
+    FeesCollected,
+    LiquidityChange,
+    PoolState,
+    Position,
+    PositionDecreased,
+    PositionIncreased,
+    TickInfo,
+    TickObservation,
+    TickTWAPInfo,
+};
+use microchain_helpers::{identity_to_b256, mutiply_div};
+use lib::{
+    get_amount_0,
+    get_amount_1,
+    get_amounts_for_liquidity,
+    get_fees_earned,
+    get_liquidity_for_amounts,
+    get_next_sqrt_price_from_amount_0,
+    get_next_sqrt_price_from_amount_1,
+    get_sqrt_price_at_tick,
+    get_tick_at_sqrt_price,
+    MAX_TICK,
+    wrapping_sub,
+};
+
+enum Error {
+    AlreadyInitialized: (),
+    NotInitialized: (),
+    InvalidRange: (),
+    InsufficentOutput: (),
+    InsufficentLiquidity: (),
+    InsufficentInput: (),
+    InsufficentLiquidityMinted: (),
+    InsufficentLiquidityBurned: (),
+    TWAPOutOfRange: (),
+    TWAPPeriodTooShort: (),
+}
+
+// Exchange where LPs provide liquidity between two ticks rather than across every price,
+// so their tokens are only used, and only earn fees, while the price is in their range.
+// Initialized ticks form a linked list, bounded by sentinels at the lowest and highest tick,
+// which swaps walk as the price crosses position bounds.
+
+////////////////////////////////////////
+// Constants
+////////////////////////////////////////
+const TOKEN_0_SLOT = 0x0000000000000000000000000000000000000000000000000000000000000000;
+const TOKEN_1_SLOT = 0x0000000000000000000000000000000000000000000000000000000000000001;
+
+/// LP fees are in basis points
+const BASIS_POINTS = 10_000;
+
+/// LP fee charged on every swap, 0.3%
+const LP_FEE = 30;
+
+/// Fee growth is tracked per unit of liquidity with 18 decimals
+const FIXED_ONE = U128::from((0, 1_000_000_000_000_000_000));
+
+////////////////////////////////////////
+// Storage declarations
+////////////////////////////////////////
+storage {
+    sqrt_price: U128 = U128 { upper: 0, lower: 0 },
+    tick: u64 = 0,
+    // Highest initialized tick at or below `tick`, where swaps that lower the price stop next
+    lower_tick: u64 = 0,
+    // Liquidity of the positions in range of the current price
+    liquidity: u64 = 0,
+    fee_growth_global_0: U128 = U128 { upper: 0, lower: 0 },
+    fee_growth_global_1: U128 = U128 { upper: 0, lower: 0 },
+    token0_reserve: u64 = 0,
+    token1_reserve: u64 = 0,
+    ticks: StorageMap<u64, TickInfo> = StorageMap {},
+    positions: StorageMap<(b256, u64), Position> = StorageMap {},
+    // the most-recently updated index of the TWAP buffer
+    twap_buffer_current_element: u64 = 0,
+    // the current maximum number of observations that are being stored
+    twap_buffer_size: u64 = 0,
+    // the next maximum number of observations to store, triggered in observations.write
+    twap_next_buffer_size: u64 = 0,
+    twap_buffer: StorageMap<u64, TickObservation> = StorageMap {},
+}
+
+/// Where a swap would leave the pool
+struct SwapResult {
+    amount_out: u64,
+    sqrt_price: U128,
+    tick: u64,
+    lower_tick: u64,
+    liquidity: u64,
+    fee_growth_global_0: U128,
+    fee_growth_global_1: U128,
+}
+
+/// A tick a swap crosses, with the fee growth at the time it's crossed
+struct CrossedTick {
+    tick: u64,
+    fee_growth_global_0: U128,
+    fee_growth_global_1: U128,
+}
+
+////////////////////////////////////////
+// Helper functions
+////////////////////////////////////////
+#[storage(read)]
+fn get_tokens() -> (b256, b256) {
+    (get::<b256>(TOKEN_0_SLOT), get::<b256>(TOKEN_1_SLOT))
+}
+
+#[storage(read)]
+fn is_initialized() -> bool {
+    storage.sqrt_price != U128::min()
+}
+
+/// Add a new observation of the current tick to the TWAP buffer, before the price moves
+#[storage(read, write)]
+fn store_observation() {
+    let buffer_size = storage.twap_buffer_size;
+    let next_buffer_size = storage.twap_next_buffer_size;
+    let current_element = storage.twap_buffer_current_element;
+
+    let next_observation_slot = (current_element + 1) % next_buffer_size;
+    let last_observation = storage.twap_buffer.get(current_element);
+
+    let time_elapsed = timestamp() - last_observation.timestamp;
+    if (time_elapsed != 0) {
+        storage.twap_buffer.insert(next_observation_slot, TickObservation {
+            timestamp: timestamp(),
+            tick_cumulative: last_observation.tick_cumulative + storage.tick * time_elapsed,
+        });
+
+        storage.twap_buffer_current_element = next_observation_slot;
+        if (next_observation_slot >= buffer_size) {
+            storage.twap_buffer_size = buffer_size + 1;
+        }
+    }
+}
+
+/// Fee growth per unit of liquidity between two ticks
+#[storage(read)]
+fn get_fee_growth_inside(tick_lower: u64, tick_upper: u64) -> (U128, U128) {
+    let current_tick = storage.tick;
+    let global_0 = storage.fee_growth_global_0;
+    let global_1 = storage.fee_growth_global_1;
+    let lower = storage.ticks.get(tick_lower);
+    let upper = storage.ticks.get(tick_upper);
+
+    let (below_0, below_1) = if current_tick >= tick_lower {
+        (lower.fee_growth_outside_0, lower.fee_growth_outside_1)
+    } else {
+        (global_0 - lower.fee_growth_outside_0, global_1 - lower.fee_growth_outside_1)
+    };
+    let (above_0, above_1) = if current_tick < tick_upper {
+        (upper.fee_growth_outside_0, upper.fee_growth_outside_1)
+    } else {
+        (global_0 - upper.fee_growth_outside_0, global_1 - upper.fee_growth_outside_1)
+    };
+
+    (
+        wrapping_sub(wrapping_sub(global_0, below_0), above_0),
+        wrapping_sub(wrapping_sub(global_1, below_1), above_1),
+    )
+}
+
+/// A position with the fees it has earned since it was last updated moved into `tokens_owed`
+#[storage(read)]
+fn get_updated_position(owner: b256, position_id: u64) -> Position {
+    let mut position = storage.positions.get((owner, position_id));
+    if position.liquidity > 0 {
+        let (inside_0, inside_1) = get_fee_growth_inside(position.tick_lower, position.tick_upper);
+        position.tokens_owed_0 = position.tokens_owed_0 + get_fees_earned(position.liquidity, inside_0, position.fee_growth_inside_0_last);
+        position.tokens_owed_1 = position.tokens_owed_1 + get_fees_earned(position.liquidity, inside_1, position.fee_growth_inside_1_last);
+        position.fee_growth_inside_0_last = inside_0;
+        position.fee_growth_inside_1_last = inside_1;
+    }
+    position
+}
+
+/// Link a new tick into the list of initialized ticks
+#[storage(read, write)]
+fn insert_tick(tick: u64) -> TickInfo {
+    // The lower tick is initialized and below the current price, so start there when we can
+    let lower_tick = storage.lower_tick;
+    let mut prev = if tick > lower_tick { lower_tick } else { 0 };
+    let mut next = storage.ticks.get(prev).next;
+    while next < tick {
+        prev = next;
+        next = storage.ticks.get(next).next;
+    }
+
+    let mut prev_info = storage.ticks.get(prev);
+    prev_info.next = tick;
+    storage.ticks.insert(prev, prev_info);
+    let mut next_info = storage.ticks.get(next);
+    next_info.prev = tick;
+    storage.ticks.insert(next, next_info);
+
+    // By convention, all fee growth so far happened below ticks at or under the current price
+    let current_tick = storage.tick;
+    let (outside_0, outside_1) = if tick <= current_tick {
+        (storage.fee_growth_global_0, storage.fee_growth_global_1)
+    } else {
+        (U128::min(), U128::min())
+    };
+    if tick <= current_tick && tick > lower_tick {
+        storage.lower_tick = tick;
+    }
+
+    TickInfo {
+        liquidity_gross: 0,
+        liquidity_added: 0,
+        liquidity_removed: 0,
+        fee_growth_outside_0: outside_0,
+        fee_growth_outside_1: outside_1,
+        prev: prev,
+        next: next,
+    }
+}
+
+#[storage(read, write)]
+fn add_to_tick(tick: u64, liquidity: u64, is_lower: bool) {
+    let mut info = storage.ticks.get(tick);
+    if info.liquidity_gross == 0 {
+        info = insert_tick(tick);
+    }
+
+    info.liquidity_gross = info.liquidity_gross + liquidity;
+    if is_lower {
+        info.liquidity_added = info.liquidity_added + liquidity;
+    } else {
+        info.liquidity_removed = info.liquidity_removed + liquidity;
+    }
+    storage.ticks.insert(tick, info);
+}
+
+#[storage(read, write)]
+fn remove_from_tick(tick: u64, liquidity: u64, is_lower: bool) {
+    let mut info = storage.ticks.get(tick);
+    info.liquidity_gross = info.liquidity_gross - liquidity;
+    if is_lower {
+        info.liquidity_added = info.liquidity_added - liquidity;
+    } else {
+        info.liquidity_removed = info.liquidity_removed - liquidity;
+    }
+
+    if info.liquidity_gross > 0 {
+        storage.ticks.insert(tick, info);
+        return;
+    }
+
+    // No positions use the tick anymore, so unlink it
+    let mut prev_info = storage.ticks.get(info.prev);
+    prev_info.next = info.next;
+    storage.ticks.insert(info.prev, prev_info);
+    let mut next_info = storage.ticks.get(info.next);
+    next_info.prev = info.prev;
+    storage.ticks.insert(info.next, next_info);
+    if storage.lower_tick == tick {
+        storage.lower_tick = info.prev;
+    }
+
+    storage.ticks.insert(tick, TickInfo {
+        liquidity_gross: 0,
+        liquidity_added: 0,
+        liquidity_removed: 0,
+        fee_growth_outside_0: U128::min(),
+        fee_growth_outside_1: U128::min(),
+        prev: 0,
+        next: 0,
+    });
+}
+
+/// Walk a swap of `amount_in` across the initialized ticks, without changing any state
+#[storage(read)]
+fn compute_swap(amount_in: u64, token_0_in: bool) -> (SwapResult, Vec<CrossedTick>) {
+    let mut result = SwapResult {
+        amount_out: 0,
+        sqrt_price: storage.sqrt_price,
+        tick: storage.tick,
+        lower_tick: storage.lower_tick,
+        liquidity: storage.liquidity,
+        fee_growth_global_0: storage.fee_growth_global_0,
+        fee_growth_global_1: storage.fee_growth_global_1,
+    };
+    let mut crossed = Vec::new();
+
+    let mut remaining = amount_in;
+    while remaining > 0 {
+        let next_tick = storage.ticks.get(result.lower_tick).next;
+        let target_tick = if token_0_in { result.lower_tick } else { next_tick };
+        let target_price = get_sqrt_price_at_tick(target_tick);
+        let remaining_less_fee = mutiply_div(remaining, BASIS_POINTS - LP_FEE, BASIS_POINTS);
+
+        // Input needed to move the price to the target tick
+        let needed = if token_0_in {
+            get_amount_0(target_price, result.sqrt_price, result.liquidity, true)
+        } else {
+            get_amount_1(result.sqrt_price, target_price, result.liquidity, true)
+        };
+
+        let mut fee_amount = 0;
+        if remaining_less_fee < needed {
+            // The swap finishes before the target tick
+            let next_price = if token_0_in {
+                get_next_sqrt_price_from_amount_0(result.sqrt_price, result.liquidity, remaining_less_fee)
+            } else {
+                get_next_sqrt_price_from_amount_1(result.sqrt_price, result.liquidity, remaining_less_fee)
+            };
+            let amount_out = if token_0_in {
+                get_amount_1(next_price, result.sqrt_price, result.liquidity, false)
+            } else {
+                get_amount_0(result.sqrt_price, next_price, result.liquidity, false)
+            };
+            result.amount_out = result.amount_out + amount_out;
+            fee_amount = remaining - remaining_less_fee;
+            remaining = 0;
+
+            // Stay between the initialized ticks, as the tick math is approximate
+            let tick = get_tick_at_sqrt_price(next_price);
+            result.tick = if tick < result.lower_tick {
+                result.lower_tick
+            } else if tick >= next_tick {
+                next_tick - 1
+            } else {
+                tick
+            };
+            result.sqrt_price = next_price;
+        } else {
+            // The swap reaches the target tick
+            let amount_out = if token_0_in {
+                get_amount_1(target_price, result.sqrt_price, result.liquidity, false)
+            } else {
+                get_amount_0(result.sqrt_price, target_price, result.liquidity, false)
+            };
+            result.amount_out = result.amount_out + amount_out;
+            // Crossing a range without liquidity is free
+            if needed > 0 {
+                fee_amount = mutiply_div(needed, LP_FEE, BASIS_POINTS - LP_FEE) + 1;
+                if needed + fee_amount > remaining {
+                    fee_amount = remaining - needed;
+                }
+            }
+            remaining = remaining - needed - fee_amount;
+            result.sqrt_price = target_price;
+        }
+
+        if result.liquidity > 0 {
+            let fee_growth = U128::from((0, fee_amount)) * FIXED_ONE / U128::from((0, result.liquidity));
+            if token_0_in {
+                result.fee_growth_global_0 = result.fee_growth_global_0 + fee_growth;
+            } else {
+                result.fee_growth_global_1 = result.fee_growth_global_1 + fee_growth;
+            }
+        }
+
+        if remaining > 0 || result.sqrt_price == target_price {
+            // The sentinels bound the range, and have no liquidity beyond them
+            require(target_tick != 0 && target_tick != MAX_TICK * 2, Error::InsufficentLiquidity);
+
+            let info = storage.ticks.get(target_tick);
+            crossed.push(CrossedTick {
+                tick: target_tick,
+                fee_growth_global_0: result.fee_growth_global_0,
+                fee_growth_global_1: result.fee_growth_global_1,
+            });
+            if token_0_in {
+                result.liquidity = result.liquidity + info.liquidity_removed - info.liquidity_added;
+                result.tick = target_tick - 1;
+                result.lower_tick = info.prev;
+            } else {
+                result.liquidity = result.liquidity + info.liquidity_added - info.liquidity_removed;
+                result.tick = target_tick;
+                result.lower_tick = target_tick;
+            }
+        }
+    }
+
+    (result, crossed)
+}
+
+// ////////////////////////////////////////
+// // ABI definitions
+// ////////////////////////////////////////
+impl ConcentratedExchange for Contract {
+    #[storage(read)]
+    fn get_pool_state() -> PoolState {
+        PoolState {
+            sqrt_price: storage.sqrt_price,
+            tick: storage.tick,
+            liquidity: storage.liquidity,
+            fee_growth_global_0: storage.fee_growth_global_0,
+            fee_growth_global_1: storage.fee_growth_global_1,
+            token_0_reserve: storage.token0_reserve,
+            token_1_reserve: storage.token1_reserve,
+        }
+    }
+
+    #[storage(read)]
+    fn get_tick(tick: u64) -> TickInfo {
+        storage.ticks.get(tick)
+    }
+
+    #[storage(read)]
+    fn get_position(owner: b256, position_id: u64) -> Position {
+        storage.positions.get((owner, position_id))
+    }
+
+    #[storage(read)]
+    fn get_position_fees(owner: b256, position_id: u64) -> (u64, u64) {
+        let position = get_updated_position(owner, position_id);
+        (position.tokens_owed_0, position.tokens_owed_1)
+    }
+
+    #[storage(read)]
+    fn get_amount_out(amount_in: u64, token_0_in: bool) -> u64 {
+        require(is_initialized(), Error::NotInitialized);
+        require(amount_in > 0, Error::InsufficentInput);
+        let (result, _crossed) = compute_swap(amount_in, token_0_in);
+        result.amount_out
+    }
+
+    #[storage(read)]
+    fn get_tokens() -> (b256, b256) {
+        get_tokens()
+    }
+
+    #[storage(read)]
+    fn get_twap_info() -> TickTWAPInfo {
+        TickTWAPInfo {
+            current_element: storage.twap_buffer_current_element,
+            buffer_size: storage.twap_buffer_size,
+            next_buffer_size: storage.twap_next_buffer_size,
+        }
+    }
+
+    #[storage(read)]
+    fn get_observation(slot: u64) -> TickObservation {
+        require(slot < storage.twap_buffer_size, Error::TWAPOutOfRange);
+        storage.twap_buffer.get(slot)
+    }
+
+    #[storage(read)]
+    fn get_twap_tick() -> u64 {
+        require(is_initialized(), Error::NotInitialized);
+        let buffer_size = storage.twap_buffer_size;
+        // A single observation would just be the current tick
+        require(buffer_size > 1, Error::TWAPPeriodTooShort);
+        let newest_slot = storage.twap_buffer_current_element;
+        let oldest = storage.twap_buffer.get((newest_slot + 1) % buffer_size);
+        let newest = storage.twap_buffer.get(newest_slot);
+        require(newest.timestamp > oldest.timestamp, Error::TWAPPeriodTooShort);
+        (newest.tick_cumulative - oldest.tick_cumulative) / (newest.timestamp - oldest.timestamp)
+    }
+
+    #[storage(read, write)]
+    fn initialize(tick: u64) {
+        require(!is_initialized(), Error::AlreadyInitialized);
+        require(tick > 0 && tick < MAX_TICK * 2, Error::InvalidRange);
+
+        storage.sqrt_price = get_sqrt_price_at_tick(tick);
+        storage.tick = tick;
+        storage.lower_tick = 0;
+
+        // Sentinels at either end of the list of initialized ticks
+        storage.ticks.insert(0, TickInfo {
+            liquidity_gross: 0,
+            liquidity_added: 0,
+            liquidity_removed: 0,
+            fee_growth_outside_0: U128::min(),
+            fee_growth_outside_1: U128::min(),
+            prev: 0,
+            next: MAX_TICK * 2,
+        });
+        storage.ticks.insert(MAX_TICK * 2, TickInfo {
+            liquidity_gross: 0,
+            liquidity_added: 0,
+            liquidity_removed: 0,
+            fee_growth_outside_0: U128::min(),
+            fee_growth_outside_1: U128::min(),
+            prev: 0,
+            next: MAX_TICK * 2,
+        });
+
+        // Ensure there's at least 1 TWAP slot
+        storage.twap_buffer_size = 1;
+        storage.twap_next_buffer_size = 1;
+        storage.twap_buffer.insert(0, TickObservation {
+            timestamp: timestamp(),
+            tick_cumulative: 0,
+        });
+    }
+
+    #[storage(read, write)]
+    fn add_liquidity(position_id: u64, tick_lower: u64, tick_upper: u64, refund: Identity) -> LiquidityChange {
+        require(is_initialized(), Error::NotInitialized);
+        require(tick_lower > 0 && tick_lower < tick_upper && tick_upper < MAX_TICK * 2, Error::InvalidRange);
+
+        let owner = identity_to_b256(msg_sender().unwrap());
+        let (token0, token1) = get_tokens();
+        let token_0_sent = this_balance(ContractId::from(token0)) - storage.token0_reserve;
+        let token_1_sent = this_balance(ContractId::from(token1)) - storage.token1_reserve;
+
+        let sqrt_price = storage.sqrt_price;
+        let sqrt_price_lower = get_sqrt_price_at_tick(tick_lower);
+        let sqrt_price_upper = get_sqrt_price_at_tick(tick_upper);
+        let liquidity = get_liquidity_for_amounts(sqrt_price, sqrt_price_lower, sqrt_price_upper, token_0_sent, token_1_sent);
+        require(liquidity > 0, Error::InsufficentLiquidityMinted);
+        let (amount_0, amount_1) = get_amounts_for_liquidity(sqrt_price, sqrt_price_lower, sqrt_price_upper, liquidity, true);
+
+        let mut position = get_updated_position(owner, position_id);
+        let is_new = position.liquidity == 0;
+        if is_new {
+            position.tick_lower = tick_lower;
+            position.tick_upper = tick_upper;
+        } else {
+            require(position.tick_lower == tick_lower && position.tick_upper == tick_upper, Error::InvalidRange);
+        }
+
+        add_to_tick(tick_lower, liquidity, true);
+        add_to_tick(tick_upper, liquidity, false);
+        if is_new {
+            // Only fees from here on belong to the position
+            let (inside_0, inside_1) = get_fee_growth_inside(tick_lower, tick_upper);
+            position.fee_growth_inside_0_last = inside_0;
+            position.fee_growth_inside_1_last = inside_1;
+        }
+        position.liquidity = position.liquidity + liquidity;
+        storage.positions.insert((owner, position_id), position);
+
+        let current_tick = storage.tick;
+        if tick_lower <= current_tick && current_tick < tick_upper {
+            storage.liquidity = storage.liquidity + liquidity;
+        }
+
+        storage.token0_reserve = storage.token0_reserve + amount_0;
+        storage.token1_reserve = storage.token1_reserve + amount_1;
+
+        if token_0_sent > amount_0 {
+            transfer(token_0_sent - amount_0, ContractId::from(token0), refund);
+        }
+        if token_1_sent > amount_1 {
+            transfer(token_1_sent - amount_1, ContractId::from(token1), refund);
+        }
+
+        log(PositionIncreased {
+            owner: owner,
+            position_id: position_id,
+            tick_lower: tick_lower,
+            tick_upper: tick_upper,
+            liquidity: liquidity,
+            amount_0: amount_0,
+            amount_1: amount_1,
+        });
+
+        LiquidityChange {
+            liquidity: liquidity,
+            amount_0: amount_0,
+            amount_1: amount_1,
+        }
+    }
+
+    #[storage(read, write)]
+    fn remove_liquidity(position_id: u64, liquidity: u64, recipient: Identity) -> LiquidityChange {
+        let owner = identity_to_b256(msg_sender().unwrap());
+        let mut position = get_updated_position(owner, position_id);
+        require(liquidity > 0 && liquidity <= position.liquidity, Error::InsufficentLiquidityBurned);
+
+        let tick_lower = position.tick_lower;
+        let tick_upper = position.tick_upper;
+        let (amount_0, amount_1) = get_amounts_for_liquidity(
+            storage.sqrt_price,
+            get_sqrt_price_at_tick(tick_lower),
+            get_sqrt_price_at_tick(tick_upper),
+            liquidity,
+            false,
+        );
+
+        position.liquidity = position.liquidity - liquidity;
+        storage.positions.insert((owner, position_id), position);
+        remove_from_tick(tick_lower, liquidity, true);
+        remove_from_tick(tick_upper, liquidity, false);
+
+        let current_tick = storage.tick;
+        if tick_lower <= current_tick && current_tick < tick_upper {
+            storage.liquidity = storage.liquidity - liquidity;
+        }
+
+        let (token0, token1) = get_tokens();
+        storage.token0_reserve = storage.token0_reserve - amount_0;
+        storage.token1_reserve = storage.token1_reserve - amount_1;
+        if amount_0 > 0 {
+            transfer(amount_0, ContractId::from(token0), recipient);
+        }
+        if amount_1 > 0 {
+            transfer(amount_1, ContractId::from(token1), recipient);
+        }
+
+        log(PositionDecreased {
+            owner: owner,
+            position_id: position_id,
+            tick_lower: tick_lower,
+            tick_upper: tick_upper,
+            liquidity: liquidity,
+            amount_0: amount_0,
+            amount_1: amount_1,
+        });
+
+        LiquidityChange {
+            liquidity: liquidity,
+            amount_0: amount_0,
+            amount_1: amount_1,
+        }
+    }
+
+    #[storage(read, write)]
+    fn collect_fees(position_id: u64, recipient: Identity) -> (u64, u64) {
+        let owner = identity_to_b256(msg_sender().unwrap());
+        let mut position = get_updated_position(owner, position_id);
+        let amount_0 = position.tokens_owed_0;
+        let amount_1 = position.tokens_owed_1;
+        position.tokens_owed_0 = 0;
+        position.tokens_owed_1 = 0;
+        storage.positions.insert((owner, position_id), position);
+
+        let (token0, token1) = get_tokens();
+        storage.token0_reserve = storage.token0_reserve - amount_0;
+        storage.token1_reserve = storage.token1_reserve - amount_1;
+        if amount_0 > 0 {
+            transfer(amount_0, ContractId::from(token0), recipient);
+        }
+        if amount_1 > 0 {
+            transfer(amount_1, ContractId::from(token1), recipient);
+        }
+
+        log(FeesCollected {
+            owner: owner,
+            position_id: position_id,
+            amount_0: amount_0,
+            amount_1: amount_1,
+            recipient: identity_to_b256(recipient),
+        });
+        (amount_0, amount_1)
+    }
+
+    #[storage(read, write)]
+    fn swap(min_amount_out: u64, recipient: Identity) -> u64 {
+        require(is_initialized(), Error::NotInitialized);
+        let (token0, token1) = get_tokens();
+        let token_0_reserve = storage.token0_reserve;
+        let token_1_reserve = storage.token1_reserve;
+        let amount_0_in = this_balance(ContractId::from(token0)) - token_0_reserve;
+        let amount_1_in = this_balance(ContractId::from(token1)) - token_1_reserve;
+        require((amount_0_in > 0 && amount_1_in == 0) || (amount_0_in == 0 && amount_1_in > 0), Error::InsufficentInput);
+
+        let token_0_in = amount_0_in > 0;
+        let amount_in = if token_0_in { amount_0_in } else { amount_1_in };
+
+        store_observation();
+        let (result, crossed) = compute_swap(amount_in, token_0_in);
+        require(result.amount_out > 0 && result.amount_out >= min_amount_out, Error::InsufficentOutput);
+
+        // Fee growth outside each crossed tick flips to the other side
+        let mut i = 0;
+        while i < crossed.len() {
+            let crossing = crossed.get(i).unwrap();
+            let mut info = storage.ticks.get(crossing.tick);
+            info.fee_growth_outside_0 = crossing.fee_growth_global_0 - info.fee_growth_outside_0;
+            info.fee_growth_outside_1 = crossing.fee_growth_global_1 - info.fee_growth_outside_1;
+            storage.ticks.insert(crossing.tick, info);
+            i += 1;
+        }
+
+        storage.sqrt_price = result.sqrt_price;
+        storage.tick = result.tick;
+        storage.lower_tick = result.lower_tick;
+        storage.liquidity = result.liquidity;
+        storage.fee_growth_global_0 = result.fee_growth_global_0;
+        storage.fee_growth_global_1 = result.fee_growth_global_1;
+
+        let (amount_0_out, amount_1_out) = if token_0_in {
+            storage.token0_reserve = token_0_reserve + amount_in;
+            storage.token1_reserve = token_1_reserve - result.amount_out;
+            transfer(result.amount_out, ContractId::from(token1), recipient);
+            (0, result.amount_out)
+        } else {
+            storage.token0_reserve = token_0_reserve - result.amount_out;
+            storage.token1_reserve = token_1_reserve + amount_in;
+            transfer(result.amount_out, ContractId::from(token0), recipient);
+            (result.amount_out, 0)
+        };
+
+        log(ConcentratedSwap {
+            sender: identity_to_b256(msg_sender().unwrap()),
+            amount_0_in: amount_0_in,
+            amount_1_in: amount_1_in,
+            amount_0_out: amount_0_out,
+            amount_1_out: amount_1_out,
+            recipient: identity_to_b256(recipient),
+            sqrt_price: result.sqrt_price,
+            tick: result.tick,
+        });
+
+        result.amount_out
+    }
+
+    #[storage(read, write)]
+    fn expand_twap_buffer(new_total_slots: u64) {
+        let mut i = storage.twap_next_buffer_size;
+
+        while i < new_total_slots {
+            // The goal here is to initialize these slots (write a value), without
+            // providing actual data. The slots are only read once they've been written.
+            storage.twap_buffer.insert(i, TickObservation {
+                timestamp: 1,
+                tick_cumulative: 0,
+            });
+            i += 1;
+        }
+
+        storage.twap_next_buffer_size = new_total_slots;
+    }
+}
@@ -0,0 +1,447 @@
+use chrono::Utc;
+use std::str::FromStr;
+use fuels::{
+    prelude::*,
+    fuels_abigen::abigen,
+    signers::WalletUnlocked,
+    tx::{AssetId, Bytes32, StorageSlot},
+};
+use test_helpers::{constant_product_amount_out, get_logs, get_wallets, produce_block_at, CONCENTRATED_EXCHANGE_ABI};
+
+///////////////////////////////
+// Load the Concentrated Exchange Contract abi
+///////////////////////////////
+abigen!(ConcentratedExchange, "./out/debug/concentrated_exchange_contract-abi.json");
+
+abigen!(
+    TestToken,
+    "../token_contract/out/debug/token_contract-abi.json"
+);
+
+/// The tick of a 1:1 price
+const MAX_TICK: u64 = 443_636;
+
+struct Fixture {
+    wallet: WalletUnlocked,
+    exchange_contract_id: Bech32ContractId,
+    exchange_instance: ConcentratedExchange,
+    token_0_asset_id: AssetId,
+    token_1_asset_id: AssetId,
+}
+
+fn to_9_decimal(num: u64) -> u64 {
+    num * 1_000_000_000
+}
+
+/// Deploy an ETH/token pool, initialized at a 1:1 price
+async fn setup() -> Fixture {
+    let wallets = get_wallets().await;
+    let wallet = wallets.get(0).unwrap().clone();
+
+    let token_contract_id = Contract::deploy(
+        "../token_contract/out/debug/token_contract.bin",
+        &wallet,
+        TxParameters::default(),
+        StorageConfiguration::new(None, None),
+    )
+    .await
+    .unwrap();
+
+    let token_instance = TestToken::new(token_contract_id.clone(), wallet.clone());
+    token_instance
+        .methods()
+        .initialize(to_9_decimal(20000), wallet.address().into())
+        .call()
+        .await
+        .unwrap();
+    token_instance
+        .methods()
+        .mint()
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .unwrap();
+
+    // ETH is always token 0
+    let token_0_asset_id = BASE_ASSET_ID;
+    let token_1_asset_id = AssetId::new(*token_contract_id.hash());
+
+    let token0_slot = Bytes32::from_str("0x0000000000000000000000000000000000000000000000000000000000000000").unwrap();
+    let token1_slot = Bytes32::from_str("0x0000000000000000000000000000000000000000000000000000000000000001").unwrap();
+    let storage_vec = vec![
+        StorageSlot::new(token0_slot, Bytes32::new(*token_0_asset_id)),
+        StorageSlot::new(token1_slot, Bytes32::new(*token_1_asset_id)),
+    ];
+
+    let exchange_contract_id = Contract::deploy(
+        "./out/debug/concentrated_exchange_contract.bin",
+        &wallet,
+        TxParameters::default(),
+        StorageConfiguration::with_manual_storage(Some(storage_vec)),
+    )
+    .await
+    .unwrap();
+
+    let exchange_instance = ConcentratedExchange::new(exchange_contract_id.clone(), wallet.clone());
+    exchange_instance
+        .methods()
+        .initialize(MAX_TICK)
+        .call()
+        .await
+        .unwrap();
+
+    Fixture {
+        wallet: wallet,
+        exchange_contract_id: exchange_contract_id,
+        exchange_instance: exchange_instance,
+        token_0_asset_id: token_0_asset_id,
+        token_1_asset_id: token_1_asset_id,
+    }
+}
+
+fn wallet_b256(fixture: &Fixture) -> Bits256 {
+    Bits256(fixture.wallet.address().hash().into())
+}
+
+async fn add_position(
+    fixture: &Fixture,
+    position_id: u64,
+    tick_lower: u64,
+    tick_upper: u64,
+    token_0_amount: u64,
+    token_1_amount: u64,
+) -> LiquidityChange {
+    if token_0_amount > 0 {
+        let _receipts = fixture.wallet
+            .force_transfer_to_contract(&fixture.exchange_contract_id, token_0_amount, fixture.token_0_asset_id, TxParameters::default())
+            .await
+            .unwrap();
+    }
+    if token_1_amount > 0 {
+        let _receipts = fixture.wallet
+            .force_transfer_to_contract(&fixture.exchange_contract_id, token_1_amount, fixture.token_1_asset_id, TxParameters::default())
+            .await
+            .unwrap();
+    }
+
+    fixture.exchange_instance
+        .methods()
+        .add_liquidity(position_id, tick_lower, tick_upper, Identity::Address(fixture.wallet.address().into()))
+        .append_variable_outputs(2)
+        .tx_params(TxParameters {
+            gas_price: 0,
+            gas_limit: 100_000_000,
+            maturity: 0,
+        })
+        .call_params(CallParameters::new(None, None, Some(100_000_000)))
+        .call()
+        .await
+        .unwrap()
+        .value
+}
+
+async fn swap(fixture: &Fixture, amount_in: u64, token_0_in: bool, min_amount_out: u64) -> Result<FuelCallResponse<u64>, Error> {
+    let asset_id = if token_0_in { fixture.token_0_asset_id } else { fixture.token_1_asset_id };
+
+    fixture.exchange_instance
+        .methods()
+        .swap(min_amount_out, Identity::Address(fixture.wallet.address().into()))
+        .call_params(CallParameters::new(Some(amount_in), Some(asset_id), Some(100_000_000)))
+        .tx_params(TxParameters {
+            gas_price: 0,
+            gas_limit: 100_000_000,
+            maturity: 0,
+        })
+        .append_variable_outputs(1)
+        .call()
+        .await
+}
+
+async fn get_amount_out(fixture: &Fixture, amount_in: u64, token_0_in: bool) -> u64 {
+    fixture.exchange_instance
+        .methods()
+        .get_amount_out(amount_in, token_0_in)
+        .call()
+        .await
+        .unwrap()
+        .value
+}
+
+#[tokio::test]
+async fn in_range_deposit_refunds_excess() {
+    let fixture = setup().await;
+
+    let starting_balance_0 = fixture.wallet.get_asset_balance(&fixture.token_0_asset_id).await.unwrap();
+    let starting_balance_1 = fixture.wallet.get_asset_balance(&fixture.token_1_asset_id).await.unwrap();
+
+    // At a 1:1 price, a symmetric range takes equal amounts, so half the ETH comes back
+    let result = add_position(&fixture, 1, MAX_TICK - 1000, MAX_TICK + 1000, to_9_decimal(100), to_9_decimal(50)).await;
+    assert!(result.liquidity > 0);
+    assert!(result.amount_1 <= to_9_decimal(50) && result.amount_1 > to_9_decimal(50) * 999 / 1000);
+    assert!(result.amount_0 > to_9_decimal(50) * 999 / 1000 && result.amount_0 < to_9_decimal(51));
+
+    let end_balance_0 = fixture.wallet.get_asset_balance(&fixture.token_0_asset_id).await.unwrap();
+    let end_balance_1 = fixture.wallet.get_asset_balance(&fixture.token_1_asset_id).await.unwrap();
+    assert_eq!(starting_balance_0 - end_balance_0, result.amount_0);
+    assert_eq!(starting_balance_1 - end_balance_1, result.amount_1);
+
+    let state = fixture.exchange_instance.methods().get_pool_state().call().await.unwrap().value;
+    assert_eq!(state.liquidity, result.liquidity);
+    assert_eq!(state.token_0_reserve, result.amount_0);
+    assert_eq!(state.token_1_reserve, result.amount_1);
+
+    let position = fixture.exchange_instance.methods().get_position(wallet_b256(&fixture), 1).call().await.unwrap().value;
+    assert_eq!(position.tick_lower, MAX_TICK - 1000);
+    assert_eq!(position.tick_upper, MAX_TICK + 1000);
+    assert_eq!(position.liquidity, result.liquidity);
+
+    let lower = fixture.exchange_instance.methods().get_tick(MAX_TICK - 1000).call().await.unwrap().value;
+    assert_eq!(lower.liquidity_gross, result.liquidity);
+    assert_eq!(lower.next, MAX_TICK + 1000);
+}
+
+#[tokio::test]
+async fn out_of_range_deposit_uses_one_token() {
+    let fixture = setup().await;
+
+    // Above the price, a position is all token 0
+    let above = add_position(&fixture, 1, MAX_TICK + 1000, MAX_TICK + 2000, to_9_decimal(10), to_9_decimal(10)).await;
+    assert!(above.amount_0 > 0);
+    assert_eq!(above.amount_1, 0);
+
+    // Below the price, a position is all token 1
+    let below = add_position(&fixture, 2, MAX_TICK - 2000, MAX_TICK - 1000, to_9_decimal(10), to_9_decimal(10)).await;
+    assert_eq!(below.amount_0, 0);
+    assert!(below.amount_1 > 0);
+
+    // Neither is in range, so there's nothing to swap against
+    let state = fixture.exchange_instance.methods().get_pool_state().call().await.unwrap().value;
+    assert_eq!(state.liquidity, 0);
+}
+
+#[tokio::test]
+async fn invalid_ranges_should_fail() {
+    let fixture = setup().await;
+
+    let _receipts = fixture.wallet
+        .force_transfer_to_contract(&fixture.exchange_contract_id, to_9_decimal(1), fixture.token_1_asset_id, TxParameters::default())
+        .await
+        .unwrap();
+
+    let is_err = fixture.exchange_instance
+        .methods()
+        .add_liquidity(1, MAX_TICK + 10, MAX_TICK - 10, Identity::Address(fixture.wallet.address().into()))
+        .append_variable_outputs(2)
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Lower tick must be below the upper tick");
+
+    let is_err = fixture.exchange_instance
+        .methods()
+        .add_liquidity(1, 0, MAX_TICK + 10, Identity::Address(fixture.wallet.address().into()))
+        .append_variable_outputs(2)
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Sentinel ticks can't bound a position");
+
+    let is_err = fixture.exchange_instance
+        .methods()
+        .initialize(MAX_TICK)
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Pool can't be initialized twice");
+}
+
+#[tokio::test]
+async fn concentrated_swap_beats_constant_product() {
+    let fixture = setup().await;
+    add_position(&fixture, 1, MAX_TICK - 1000, MAX_TICK + 1000, to_9_decimal(100), to_9_decimal(100)).await;
+
+    let amount_in = to_9_decimal(1);
+    let amount_out = get_amount_out(&fixture, amount_in, true).await;
+    assert!(amount_out > constant_product_amount_out(amount_in, to_9_decimal(100), to_9_decimal(100)));
+    assert!(amount_out < amount_in * 997 / 1000);
+
+    let is_err = swap(&fixture, amount_in, true, amount_out + 1).await.is_err();
+    assert!(is_err, "Pool shouldn't pay more than quoted");
+
+    let starting_balance = fixture.wallet.get_asset_balance(&fixture.token_1_asset_id).await.unwrap();
+    let response = swap(&fixture, amount_in, true, amount_out).await.unwrap();
+    assert_eq!(response.value, amount_out);
+    let end_balance = fixture.wallet.get_asset_balance(&fixture.token_1_asset_id).await.unwrap();
+    assert_eq!(end_balance - starting_balance, amount_out);
+
+    let logs = get_logs::<ConcentratedSwap, _>(&response, &fixture.exchange_contract_id, CONCENTRATED_EXCHANGE_ABI);
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs.get(0).unwrap().amount_0_in, amount_in);
+    assert_eq!(logs.get(0).unwrap().amount_1_out, amount_out);
+
+    // Selling token 0 moves the price down
+    let state = fixture.exchange_instance.methods().get_pool_state().call().await.unwrap().value;
+    assert!(state.tick < MAX_TICK);
+    assert_eq!(logs.get(0).unwrap().tick, state.tick);
+}
+
+#[tokio::test]
+async fn swaps_cross_ticks() {
+    let fixture = setup().await;
+    let wide = add_position(&fixture, 1, MAX_TICK - 2000, MAX_TICK + 2000, to_9_decimal(100), to_9_decimal(100)).await;
+    let narrow = add_position(&fixture, 2, MAX_TICK - 100, MAX_TICK + 100, to_9_decimal(10), to_9_decimal(10)).await;
+
+    let state = fixture.exchange_instance.methods().get_pool_state().call().await.unwrap().value;
+    assert_eq!(state.liquidity, wide.liquidity + narrow.liquidity);
+
+    // Enough to push the price out of the narrow range
+    let amount_in = to_9_decimal(20);
+    let amount_out = get_amount_out(&fixture, amount_in, true).await;
+    swap(&fixture, amount_in, true, amount_out).await.unwrap();
+
+    let state = fixture.exchange_instance.methods().get_pool_state().call().await.unwrap().value;
+    assert!(state.tick < MAX_TICK - 100, "Tick {} should be below the narrow range", state.tick);
+    assert_eq!(state.liquidity, wide.liquidity);
+
+    // And back into it
+    let amount_in = to_9_decimal(10);
+    let amount_out = get_amount_out(&fixture, amount_in, false).await;
+    let response = swap(&fixture, amount_in, false, amount_out).await.unwrap();
+    assert_eq!(response.value, amount_out);
+
+    let state = fixture.exchange_instance.methods().get_pool_state().call().await.unwrap().value;
+    assert!(state.tick >= MAX_TICK - 100 && state.tick < MAX_TICK + 100, "Tick {} should be in the narrow range", state.tick);
+    assert_eq!(state.liquidity, wide.liquidity + narrow.liquidity);
+
+    // Swaps can't run past the last initialized tick
+    let is_err = swap(&fixture, to_9_decimal(1000), true, 1).await.is_err();
+    assert!(is_err, "Swap should fail once liquidity runs out");
+}
+
+#[tokio::test]
+async fn fees_are_shared_by_in_range_positions() {
+    let fixture = setup().await;
+    add_position(&fixture, 1, MAX_TICK - 1000, MAX_TICK + 1000, to_9_decimal(10), to_9_decimal(10)).await;
+    add_position(&fixture, 2, MAX_TICK - 1000, MAX_TICK + 1000, to_9_decimal(30), to_9_decimal(30)).await;
+    add_position(&fixture, 3, MAX_TICK + 2000, MAX_TICK + 3000, to_9_decimal(10), 0).await;
+
+    let amount_in = to_9_decimal(5);
+    let amount_out = get_amount_out(&fixture, amount_in, true).await;
+    swap(&fixture, amount_in, true, amount_out).await.unwrap();
+    let amount_out = get_amount_out(&fixture, amount_in, false).await;
+    swap(&fixture, amount_in, false, amount_out).await.unwrap();
+
+    let owner = wallet_b256(&fixture);
+    let (fees_a_0, fees_a_1) = fixture.exchange_instance.methods().get_position_fees(owner, 1).call().await.unwrap().value;
+    let (fees_b_0, fees_b_1) = fixture.exchange_instance.methods().get_position_fees(owner, 2).call().await.unwrap().value;
+    let (fees_c_0, fees_c_1) = fixture.exchange_instance.methods().get_position_fees(owner, 3).call().await.unwrap().value;
+
+    // 0.3% of each swap, split by liquidity
+    let total_fee = amount_in * 3 / 1000;
+    assert!(fees_a_0 + fees_b_0 <= total_fee + 1 && fees_a_0 + fees_b_0 > total_fee * 99 / 100);
+    assert!(fees_a_1 + fees_b_1 <= total_fee + 1 && fees_a_1 + fees_b_1 > total_fee * 99 / 100);
+    assert!(fees_b_0 > fees_a_0 * 3 * 99 / 100 && fees_b_0 < fees_a_0 * 3 * 101 / 100);
+    assert!(fees_b_1 > fees_a_1 * 3 * 99 / 100 && fees_b_1 < fees_a_1 * 3 * 101 / 100);
+
+    // Out of range the whole time
+    assert_eq!(fees_c_0, 0);
+    assert_eq!(fees_c_1, 0);
+
+    let starting_balance_1 = fixture.wallet.get_asset_balance(&fixture.token_1_asset_id).await.unwrap();
+    let response = fixture.exchange_instance
+        .methods()
+        .collect_fees(1, Identity::Address(fixture.wallet.address().into()))
+        .append_variable_outputs(2)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(response.value, (fees_a_0, fees_a_1));
+    let end_balance_1 = fixture.wallet.get_asset_balance(&fixture.token_1_asset_id).await.unwrap();
+    assert_eq!(end_balance_1 - starting_balance_1, fees_a_1);
+
+    let logs = get_logs::<FeesCollected, _>(&response, &fixture.exchange_contract_id, CONCENTRATED_EXCHANGE_ABI);
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs.get(0).unwrap().amount_0, fees_a_0);
+
+    // Fees can only be collected once
+    let (fees_0, fees_1) = fixture.exchange_instance.methods().get_position_fees(owner, 1).call().await.unwrap().value;
+    assert_eq!(fees_0, 0);
+    assert_eq!(fees_1, 0);
+}
+
+#[tokio::test]
+async fn remove_liquidity() {
+    let fixture = setup().await;
+    let added = add_position(&fixture, 1, MAX_TICK - 1000, MAX_TICK + 1000, to_9_decimal(10), to_9_decimal(10)).await;
+
+    let is_err = fixture.exchange_instance
+        .methods()
+        .remove_liquidity(1, added.liquidity + 1, Identity::Address(fixture.wallet.address().into()))
+        .append_variable_outputs(2)
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Can't remove more liquidity than the position holds");
+
+    let half = added.liquidity / 2;
+    let removed = fixture.exchange_instance
+        .methods()
+        .remove_liquidity(1, half, Identity::Address(fixture.wallet.address().into()))
+        .append_variable_outputs(2)
+        .call()
+        .await
+        .unwrap()
+        .value;
+    assert!(removed.amount_0 <= added.amount_0 / 2 && removed.amount_0 + 2 >= added.amount_0 / 2);
+    assert!(removed.amount_1 <= added.amount_1 / 2 && removed.amount_1 + 2 >= added.amount_1 / 2);
+
+    let removed = fixture.exchange_instance
+        .methods()
+        .remove_liquidity(1, added.liquidity - half, Identity::Address(fixture.wallet.address().into()))
+        .append_variable_outputs(2)
+        .call()
+        .await
+        .unwrap()
+        .value;
+    assert_eq!(removed.liquidity, added.liquidity - half);
+
+    // With no positions left, the ticks are uninitialized and the pool is empty
+    let state = fixture.exchange_instance.methods().get_pool_state().call().await.unwrap().value;
+    assert_eq!(state.liquidity, 0);
+    assert!(state.token_0_reserve <= 2 && state.token_1_reserve <= 2);
+
+    let lower = fixture.exchange_instance.methods().get_tick(MAX_TICK - 1000).call().await.unwrap().value;
+    assert_eq!(lower.liquidity_gross, 0);
+    let sentinel = fixture.exchange_instance.methods().get_tick(0).call().await.unwrap().value;
+    assert_eq!(sentinel.next, MAX_TICK * 2);
+}
+
+#[tokio::test]
+async fn twap_tick_tracks_price() {
+    let fixture = setup().await;
+    add_position(&fixture, 1, MAX_TICK - 2000, MAX_TICK + 2000, to_9_decimal(100), to_9_decimal(100)).await;
+    fixture.exchange_instance
+        .methods()
+        .expand_twap_buffer(4)
+        .call()
+        .await
+        .unwrap();
+
+    let is_err = fixture.exchange_instance.methods().get_twap_tick().call().await.is_err();
+    assert!(is_err, "TWAP tick needs at least two observations");
+
+    let now = Utc::now().timestamp();
+    produce_block_at(&fixture.wallet, now + 60).await;
+
+    let amount_in = to_9_decimal(5);
+    let amount_out = get_amount_out(&fixture, amount_in, true).await;
+    swap(&fixture, amount_in, true, amount_out).await.unwrap();
+    let moved_tick = fixture.exchange_instance.methods().get_pool_state().call().await.unwrap().value.tick;
+
+    produce_block_at(&fixture.wallet, now + 120).await;
+    swap(&fixture, to_9_decimal(1) / 1000, true, 1).await.unwrap();
+
+    // Averaged between the starting price and the moved price
+    let twap_tick = fixture.exchange_instance.methods().get_twap_tick().call().await.unwrap().value;
+    assert!(twap_tick > moved_tick && twap_tick < MAX_TICK, "TWAP tick {} should be between {} and {}", twap_tick, moved_tick, MAX_TICK);
+}
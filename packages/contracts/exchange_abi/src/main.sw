@@ -47,6 +47,28 @@ pub struct ProtocolFeeWithdrawn {
     amount_1: u64,
 }
 
+pub struct LongTermOrderPlaced {
+    order_id: u64,
+    owner: b256,
+    token_0_in: bool,
+    amount: u64,
+    sale_rate: u64,
+    expiry: u64,
+}
+
+pub struct LongTermOrderCancelled {
+    order_id: u64,
+    unsold_amount: u64,
+    proceeds: u64,
+    recipient: b256,
+}
+
+pub struct LongTermProceedsWithdrawn {
+    order_id: u64,
+    proceeds: u64,
+    recipient: b256,
+}
+
 
 // Returns
 
@@ -83,8 +105,66 @@ pub struct VaultInfo {
 
 pub struct FeeInfo {
     current_fee: u16,
+    /// The vault's fee schedule, which the current fee is computed from
+    start_fee: u16,
     change_rate: u16,
+    start_time: u32,
+    /// When the schedule was cached from the vault
+    update_time: u32,
+}
+
+pub struct FeeCacheInfo {
     update_time: u32,
+    /// Seconds since the vault's fees were cached
+    age: u64,
+    max_age: u64,
+    /// When stale, the next swap re-reads the fees from the vault
+    is_stale: bool,
+}
+
+/// Bounds for a dynamic LP fee, which scales with the price volatility seen in the TWAP buffer.
+pub struct DynamicFeeConfig {
+    enabled: bool,
+    /// LP fee in basis points while the price is stable
+    min_fee: u64,
+    /// LP fee in basis points once volatility reaches `max_volatility`
+    max_fee: u64,
+    /// Volatility in basis points at which the fee reaches `max_fee`
+    max_volatility: u64,
+    /// Number of the newest observation intervals volatility is measured over
+    lookback: u64,
+}
+
+pub struct LPFeeInfo {
+    /// LP fee in basis points the next swap will charge
+    current_fee: u64,
+    /// Largest deviation of an observation interval's average price from the TWAP over the newest
+    /// `lookback` intervals, in basis points. Only measured while the dynamic fee is enabled.
+    volatility: u64,
+    config: DynamicFeeConfig,
+}
+
+/// An order selling one of the pool's tokens at a constant rate until it expires
+pub struct LongTermOrder {
+    owner: Identity,
+    /// Whether token 0 is sold for token 1
+    token_0_in: bool,
+    /// Tokens sold per second, zero once cancelled
+    sale_rate: u64,
+    /// Time the order sells its last tokens, on an order interval boundary
+    expiry: u64,
+}
+
+pub struct TWAMMInfo {
+    /// Token 0 sold per second by all open orders
+    sale_rate_0: u64,
+    /// Token 1 sold per second by all open orders
+    sale_rate_1: u64,
+    /// Time orders were last executed against the reserves
+    last_virtual_order_time: u64,
+    /// Tokens held for long-term orders, both unsold and proceeds, which aren't part of the reserves
+    token_0_held: u64,
+    token_1_held: u64,
 }
 
 pub struct Observation {
@@ -100,7 +180,16 @@ abi Exchange {
     /// Get information on the liquidity pool.
     #[storage(read)]fn get_pool_info() -> PoolInfo;
     #[storage(read)]fn get_vault_info() -> VaultInfo;
+    /// Get the protocol fee the next swap will use.
     #[storage(read)]fn get_fee_info() -> FeeInfo;
+    #[storage(read)]fn get_fee_cache_info() -> FeeCacheInfo;
+    /// Get the LP fee the next swap will charge, along with the dynamic fee settings.
+    #[storage(read)]fn get_lp_fee_info() -> LPFeeInfo;
+    /// Output a swap of `amount_in` would receive, after protocol and LP fees.
+    /// Each pool prices with its own curve, so callers shouldn't assume constant product.
+    #[storage(read)]fn get_amount_out(amount_in: u64, token_0_in: bool) -> u64;
+    /// Input, including protocol and LP fees, needed to receive `amount_out`.
+    #[storage(read)]fn get_amount_in(amount_out: u64, token_0_out: bool) -> u64;
     #[storage(read)]fn get_twap_info() -> TWAPInfo;
     /// Get the two tokens held in the pool
     #[storage(read)]fn get_tokens() -> (b256, b256);
@@ -110,6 +199,10 @@ abi Exchange {
     ////////////////////
     #[storage(read, write)]fn initialize(new_vault: b256);
     #[storage(read, write)]fn cache_vault_fees();
+    /// Set how long cached vault fees are used before swaps refresh them. Can only be called by the vault.
+    #[storage(read, write)]fn set_max_fee_cache_age(max_age: u64);
+    /// Enable, disable or tune the dynamic LP fee. Can only be called by the vault.
+    #[storage(read, write)]fn set_dynamic_fee(config: DynamicFeeConfig);
     /// Deposit ETH and Tokens at current ratio to mint SWAYSWAP tokens.
     #[storage(read, write)]fn add_liquidity(recipient: Identity) -> u64;
     /// Burn SWAYSWAP tokens to withdraw ETH and Tokens at current ratio.
@@ -119,3 +212,36 @@ abi Exchange {
     /// Increase the size of the TWAP buffer to the given size
     #[storage(read, write)]fn expand_twap_buffer(new_slots: u64);
 }
+
+/// Long-term orders, selling large amounts gradually against the pool's reserves.
+/// Orders are executed virtually, before every interaction with the pool, up to the current time.
+/// Pools left idle for over a day must first be caught up in pieces with `execute_virtual_orders`.
+abi LongTermOrders {
+    ////////////////////
+    // Read only
+    ////////////////////
+    /// Get the state of the open orders, as of the last interaction with the pool
+    #[storage(read)]fn get_twamm_info() -> TWAMMInfo;
+    #[storage(read)]fn get_long_term_order(order_id: u64) -> LongTermOrder;
+    /// Proceeds an order can withdraw, including orders executed since the last interaction
+    #[storage(read)]fn get_order_proceeds(order_id: u64) -> u64;
+    ////////////////////
+    // Actions
+    ////////////////////
+    /// Sell the forwarded tokens evenly until the end of the `intervals`th order interval, returning the order ID.
+    /// Tokens that don't divide into a whole sale rate are returned to the sender.
+    #[storage(read, write)]fn place_long_term_order(intervals: u64, owner: Identity) -> u64;
+    /// Stop an order, returning its unsold tokens and proceeds. Can only be called by the order's owner.
+    #[storage(read, write)]fn cancel_long_term_order(order_id: u64, recipient: Identity) -> (u64, u64);
+    /// Withdraw an order's proceeds so far. Can only be called by the order's owner.
+    #[storage(read, write)]fn withdraw_proceeds(order_id: u64, recipient: Identity) -> u64;
+    /// Execute orders up to the current time, or at most `max_intervals` order intervals, returning
+    /// whether they're up to date. Other calls only execute a day of intervals, and revert if more are pending.
+    #[storage(read, write)]fn execute_virtual_orders(max_intervals: u64) -> bool;
+}
+
+/// Pools whose tokens make up fixed, uneven shares of their value
+abi WeightedExchange {
+    /// Weights of token 0 and token 1, in basis points of the pool
+    #[storage(read)]fn get_weights() -> (u64, u64);
+}
@@ -1,25 +1,34 @@
 library lib;
 
-use std::u128::U128;
+use microchain_helpers::{get_input_price, mutiply_div};
 
-pub fn sqrt_by_digit(n: U128) -> U128 {
-    let mut x = n;
-    let mut c = U128::min();
-    let mut d = U128::from((0, 1)) << U128::bits() - 2;
-
-    while d > n {
-        d = d >> 2;
-    }
-
-    while d != U128::min() {
-        if x > c + d || x == c + d { // TODO: gte
-            x = x - (c + d);
-            c = (c >> 1) + d;
+/// Trade the tokens sold by long-term orders over one period, returning the new reserves and each side's proceeds.
+/// Orders on opposite sides trade with each other at the pool's price, and only the difference is swapped
+/// against the reserves. `proceeds_0` is the token 1 paid to token 0 sellers, and `proceeds_1` the token 0
+/// paid to token 1 sellers.
+pub fn execute_virtual_trade(
+    reserve_0: u64,
+    reserve_1: u64,
+    amount_0_in: u64,
+    amount_1_in: u64,
+    lp_fee: u64,
+) -> (u64, u64, u64, u64) {
+    let amount_0_value = mutiply_div(amount_0_in, reserve_1, reserve_0);
+    if amount_0_value > amount_1_in || amount_0_value == amount_1_in {
+        // All of the token 1 sold is matched
+        let matched_0 = mutiply_div(amount_1_in, reserve_0, reserve_1);
+        let remaining_0 = amount_0_in - matched_0;
+        let swapped_1 = if remaining_0 > 0 {
+            get_input_price(remaining_0, reserve_0, reserve_1, lp_fee)
         } else {
-            c = c >> 1;
-        }
-        d  = d >> 2;
+            0
+        };
+        (reserve_0 + remaining_0, reserve_1 - swapped_1, amount_1_in + swapped_1, matched_0)
+    } else {
+        // All of the token 0 sold is matched
+        let matched_1 = amount_0_value;
+        let remaining_1 = amount_1_in - matched_1;
+        let swapped_0 = get_input_price(remaining_1, reserve_1, reserve_0, lp_fee);
+        (reserve_0 - swapped_0, reserve_1 + remaining_1, matched_1, amount_0_in + swapped_0)
     }
-
-    c
 }
@@ -4,123 +4,155 @@ dep lib;
 
 use std::{
     auth::msg_sender,
-    assert::assert,
     block::timestamp,
-    call_frames::contract_id,
-    constants::ZERO_B256,
+    call_frames::msg_asset_id,
     context::*,
     contract_id::ContractId,
     identity::Identity,
-    // logging::log,
-    revert::revert,
-    storage::get,
-    token::{burn, mint, transfer},
+    logging::log,
+    token::transfer,
     u128::U128,
     u256::U256,
+    vec::Vec,
 };
 
 use exchange_abi::{
+    DynamicFeeConfig,
     Exchange,
+    FeeCacheInfo,
     FeeInfo,
-    LiquidityAdded,
-    LiquidityRemoved,
+    LPFeeInfo,
     Observation,
     PoolInfo,
-    ProtocolFeeCollected,
-    ProtocolFeeWithdrawn,
     RemoveLiquidityInfo,
     Swap,
+    LongTermOrder,
+    LongTermOrderCancelled,
+    LongTermOrderPlaced,
+    LongTermOrders,
+    LongTermProceedsWithdrawn,
+    TWAMMInfo,
     TWAPInfo,
-    UpdateReserves,
     VaultInfo,
 };
-use microchain_helpers::{identity_to_b256};
-use vault_abi::Vault;
-use lib::sqrt_by_digit;
+use exchange_lib::{
+    add_protocol_fee,
+    burn_liquidity,
+    cache_vault_fees,
+    collect_protocol_fees,
+    expand_twap_buffer,
+    get_current_fee,
+    get_current_lp_fee,
+    get_fee_cache_info,
+    get_fee_info,
+    get_lp_fee_info,
+    get_lp_token_supply,
+    get_next_protocol_fee,
+    get_observation,
+    get_pool_balance,
+    get_protocol_fee_amount,
+    get_reserves,
+    get_tokens,
+    get_twap_info,
+    get_vault_info,
+    initialize,
+    mint_liquidity,
+    set_dynamic_fee,
+    set_max_fee_cache_age,
+    set_reserves,
+    settle_swap,
+    withdraw_protocol_fees,
+    write_observation,
+};
+use microchain_helpers::{get_input_price, get_output_price, identity_to_b256};
+use lib::execute_virtual_trade;
 
 enum Error {
-    AlreadyInitialized: (),
-    InsufficentOutput: (),
     InsufficentLiquidity: (),
     InsufficentInput: (),
     Invariant: (),
-    InsufficentLiquidityMinted: (),
-    InsufficentLiquidityBurned: (),
-    MustBeCalledByVault: (),
-    TWAPOutOfRange: (),
+    InvalidToken: (),
+    InvalidOrder: (),
+    OrderExpired: (),
+    MustBeCalledByOwner: (),
+    VirtualOrdersPending: (),
 }
 
-// Disable logging
-fn log<T>(event: T) {}
-
 ////////////////////////////////////////
 // Constants
 ////////////////////////////////////////
-const TOKEN_0_SLOT = 0x0000000000000000000000000000000000000000000000000000000000000000;
-const TOKEN_1_SLOT = 0x0000000000000000000000000000000000000000000000000000000000000001;
+const TWAP_PERCISION = U256::from((0, 0, 0, 1000000000));
 
-/// Minimum ETH liquidity to open a pool.
-const MINIMUM_LIQUIDITY = 1000;
+/// LP fees are in basis points
+const BASIS_POINTS = 10_000;
 
-const TWAP_PERCISION = U256::from((0, 0, 0, 1000000000));
+/// Long-term orders expire on multiples of this many seconds, so expiries can be processed together
+const ORDER_INTERVAL = 3600;
+
+/// Longest a long-term order can run, in order intervals
+const MAX_ORDER_INTERVALS = 8_760;
+
+/// Most order intervals executed by any other call, so an idle pool can't run out of gas catching up.
+/// Further intervals must first be executed in pieces with `execute_virtual_orders`.
+const MAX_VIRTUAL_ORDER_INTERVALS = 24;
+
+/// Long-term order proceeds per unit of sale rate have 18 decimals
+const REWARD_FACTOR_PRECISION = U128::from((0, 1_000_000_000_000_000_000));
 
 ////////////////////////////////////////
 // Storage declarations
 ////////////////////////////////////////
-// Packed into a single 8-byte slot
-struct VaultFee {
-    // These values should be divided by 1,000,000 to get the rate. So 10,000 = 1%
-    stored_fee: u16, // 1 byte
-    change_rate: u16, // 1 byte
-    // TODO: directional changes
-    // fee_increasing: bool, // 1 byte
-    update_time: u32, // 4 bytes
+// Reward factors when orders expiring at `expiry` stopped selling
+struct ExpiryRewardFactors {
+    expiry: u64,
+    reward_factor_0: U128,
+    reward_factor_1: U128,
+}
+
+// Long-term order state after executing orders up to some time
+struct VirtualOrders {
+    info: TWAMMInfo,
+    reward_factor_0: U128,
+    reward_factor_1: U128,
+    reserve_0: u64,
+    reserve_1: u64,
+    // Protocol fees taken from the tokens sold, owed to the vault
+    protocol_fee_0: u64,
+    protocol_fee_1: u64,
 }
 
 storage {
-    token0_reserve: u64 = 0,
-    token1_reserve: u64 = 0,
-    token0_vault_fees_collected: u64 = 0,
-    token1_vault_fees_collected: u64 = 0,
-    lp_token_supply: u64 = 0,
-    vault: b256 = ZERO_B256,
-    vault_fee: VaultFee = VaultFee {
-        stored_fee: 0u16,
-        change_rate: 0u16,
-        update_time: 0u32,
+    // Long-term orders, executed gradually against the reserves
+    twamm: TWAMMInfo = TWAMMInfo {
+        sale_rate_0: 0,
+        sale_rate_1: 0,
+        last_virtual_order_time: 0,
+        token_0_held: 0,
+        token_1_held: 0,
     },
-    // the most-recently updated index of the TWAP buffer
-    twap_buffer_current_element: u64 = 0,
-    // the current maximum number of observations that are being stored
-    twap_buffer_size: u64 = 0,
-    // the next maximum number of observations to store, triggered in observations.write
-    twap_next_buffer_size: u64 = 0,
-    twap_buffer: StorageMap<u64, Observation> = StorageMap {},
+    // Proceeds per unit of sale rate paid to each side since the first order
+    reward_factor_0: U128 = U128 { upper: 0, lower: 0 },
+    reward_factor_1: U128 = U128 { upper: 0, lower: 0 },
+    // Sale rates that end, and the reward factors reached, at each order expiry
+    sale_rate_0_ending: StorageMap<u64, u64> = StorageMap {},
+    sale_rate_1_ending: StorageMap<u64, u64> = StorageMap {},
+    expiry_reward_factors: StorageMap<u64, ExpiryRewardFactors> = StorageMap {},
+    // Order IDs start at 1
+    next_order_id: u64 = 1,
+    long_term_orders: StorageMap<u64, LongTermOrder> = StorageMap {},
+    // Reward factor each order was last paid up to
+    order_reward_factors: StorageMap<u64, U128> = StorageMap {},
 }
 
 ////////////////////////////////////////
 // Helper functions
 ////////////////////////////////////////
+/// The pool's balances, less the tokens held for long-term orders
 #[storage(read)]
-fn get_input_amount(asset_id: b256, token_0_reserve: u64, token_1_reserve: u64) -> u64 {
-    let (token0, token1) = get_tokens();
-    let mut amount = 0;
-    if (asset_id == token0) {
-        amount = this_balance(ContractId::from(token0)) - token_0_reserve - storage.token0_vault_fees_collected;
-    } else if (asset_id == token1) {
-        amount = this_balance(ContractId::from(token1)) - token_1_reserve - storage.token1_vault_fees_collected;
-    } else {
-        revert(0);
-    }
-    amount
-}
-
-#[storage(read)]
-fn get_pool_balance() -> (u64, u64) {
-    let (token0, token1) = get_tokens();
-    let balance_0 = this_balance(ContractId::from(token0)) - storage.token0_vault_fees_collected;
-    let balance_1 = this_balance(ContractId::from(token1)) - storage.token1_vault_fees_collected;
-    (balance_0, balance_1)
+fn get_reserve_balances() -> (u64, u64) {
+    let (balance_0, balance_1) = get_pool_balance();
+    let twamm = storage.twamm;
+    (balance_0 - twamm.token_0_held, balance_1 - twamm.token_1_held)
 }
 
 #[storage(read, write)]
@@ -130,104 +162,171 @@ fn store_reserves(
     prev_reserve0: u64,
     prev_reserve1: u64,
 ) {
-    let buffer_size = storage.twap_buffer_size;
-    let next_buffer_size = storage.twap_next_buffer_size;
-    let current_element = storage.twap_buffer_current_element;
-
-    let mut next_observation_slot = (current_element + 1) % next_buffer_size;
-    let mut last_observation = storage.twap_buffer.get(current_element);
-    if (last_observation.timestamp <= 1) {
-        next_observation_slot = current_element;
-        last_observation = Observation {
-            timestamp: timestamp(),
-            price_0_cumulative_last: U256::min(),
-            price_1_cumulative_last: U256::min(),
-        };
+    if (prev_reserve0 != 0 && prev_reserve1 != 0) {
+        write_observation(
+            U256::from((0, 0, 0, prev_reserve1)) * TWAP_PERCISION / U256::from((0, 0, 0, prev_reserve0)),
+            U256::from((0, 0, 0, prev_reserve0)) * TWAP_PERCISION / U256::from((0, 0, 0, prev_reserve1)),
+        );
     }
+    set_reserves(reserve0, reserve1);
+}
 
-    let time_elapsed = timestamp() - last_observation.timestamp;
-    if (time_elapsed != 0
-        && prev_reserve0 != 0
-        && prev_reserve1 != 0)
-    {
-        let time_elapsed_u256 = U256::from((0, 0, 0, time_elapsed));
-        let price_0_cumulative = U256::from((0, 0, 0, prev_reserve1)) * TWAP_PERCISION * time_elapsed_u256 / U256::from((0, 0, 0, prev_reserve0));
-        let price_1_cumulative = U256::from((0, 0, 0, prev_reserve0)) * TWAP_PERCISION * time_elapsed_u256 / U256::from((0, 0, 0, prev_reserve1));
+/// Execute long-term orders against the reserves up to `now`, or until `max_intervals` order intervals
+/// have been executed, returning the new state and the reward factors reached at each expiry passed on the way
+#[storage(read)]
+fn compute_virtual_orders(now: u64, max_intervals: u64) -> (VirtualOrders, Vec<ExpiryRewardFactors>) {
+    let (reserve_0, reserve_1) = get_reserves();
+    let mut state = VirtualOrders {
+        info: storage.twamm,
+        reward_factor_0: storage.reward_factor_0,
+        reward_factor_1: storage.reward_factor_1,
+        reserve_0: reserve_0,
+        reserve_1: reserve_1,
+        protocol_fee_0: 0,
+        protocol_fee_1: 0,
+    };
+    let mut expiries: Vec<ExpiryRewardFactors> = Vec::new();
 
-        storage.twap_buffer.insert(next_observation_slot, Observation {
-            timestamp: timestamp(),
-            price_0_cumulative_last: last_observation.price_0_cumulative_last + price_0_cumulative,
-            price_1_cumulative_last: last_observation.price_1_cumulative_last + price_1_cumulative,
-        });
+    let mut last_time = state.info.last_virtual_order_time;
+    if last_time == 0 {
+        // No orders yet, start executing from now
+        last_time = now;
+    }
+    let lp_fee = get_current_lp_fee();
+    let protocol_fee = get_current_fee();
+
+    let mut intervals = 0;
+    while last_time < now {
+        // No open orders, so nothing to execute until the next order is placed
+        if state.info.sale_rate_0 == 0 && state.info.sale_rate_1 == 0 {
+            last_time = now;
+            break;
+        }
+        if intervals == max_intervals {
+            break;
+        }
+        intervals += 1;
+
+        let next_expiry = (last_time / ORDER_INTERVAL + 1) * ORDER_INTERVAL;
+        let period_end = if next_expiry < now { next_expiry } else { now };
+        let elapsed = period_end - last_time;
+
+        let amount_0_in = state.info.sale_rate_0 * elapsed;
+        let amount_1_in = state.info.sale_rate_1 * elapsed;
+        // Everything sold pays the protocol fee, like a swap's input, including flow matched between orders
+        let protocol_fee_0 = get_protocol_fee_amount(amount_0_in, protocol_fee);
+        let protocol_fee_1 = get_protocol_fee_amount(amount_1_in, protocol_fee);
+        let (reserve_0, reserve_1, proceeds_0, proceeds_1) = execute_virtual_trade(
+            state.reserve_0,
+            state.reserve_1,
+            amount_0_in - protocol_fee_0,
+            amount_1_in - protocol_fee_1,
+            lp_fee,
+        );
+        state.reserve_0 = reserve_0;
+        state.reserve_1 = reserve_1;
+        state.protocol_fee_0 = state.protocol_fee_0 + protocol_fee_0;
+        state.protocol_fee_1 = state.protocol_fee_1 + protocol_fee_1;
 
-        storage.twap_buffer_current_element = next_observation_slot;
-        if (next_observation_slot >= buffer_size) {
-            storage.twap_buffer_size = buffer_size + 1;
+        if state.info.sale_rate_0 > 0 {
+            state.reward_factor_0 = state.reward_factor_0 + U128::from((0, proceeds_0)) * REWARD_FACTOR_PRECISION / U128::from((0, state.info.sale_rate_0));
+        }
+        if state.info.sale_rate_1 > 0 {
+            state.reward_factor_1 = state.reward_factor_1 + U128::from((0, proceeds_1)) * REWARD_FACTOR_PRECISION / U128::from((0, state.info.sale_rate_1));
         }
+        // Sold tokens leave the orders, and the proceeds are held until withdrawn
+        state.info.token_0_held = state.info.token_0_held - amount_0_in + proceeds_1;
+        state.info.token_1_held = state.info.token_1_held - amount_1_in + proceeds_0;
+
+        if period_end == next_expiry {
+            state.info.sale_rate_0 = state.info.sale_rate_0 - storage.sale_rate_0_ending.get(next_expiry);
+            state.info.sale_rate_1 = state.info.sale_rate_1 - storage.sale_rate_1_ending.get(next_expiry);
+            expiries.push(ExpiryRewardFactors {
+                expiry: next_expiry,
+                reward_factor_0: state.reward_factor_0,
+                reward_factor_1: state.reward_factor_1,
+            });
+        }
+        last_time = period_end;
     }
+    state.info.last_virtual_order_time = last_time;
 
-    storage.token0_reserve = reserve0;
-    storage.token1_reserve = reserve1;
-
-    log(UpdateReserves {
-        amount_0: reserve0,
-        amount_1: reserve1,
-    })
+    (state, expiries)
 }
 
+/// Reserves after executing long-term orders up to now, or as far as other calls would execute them
 #[storage(read)]
-fn get_tokens() -> (b256, b256) {
-    (get::<b256>(TOKEN_0_SLOT), get::<b256>(TOKEN_1_SLOT))
+fn get_virtual_reserves() -> (u64, u64) {
+    let (state, _expiries) = compute_virtual_orders(timestamp(), MAX_VIRTUAL_ORDER_INTERVALS);
+    (state.reserve_0, state.reserve_1)
 }
 
-#[storage(read)]
-fn get_current_fee() -> u64 {
-    let fee_info = storage.vault_fee;
-    let decrease_since_storage = fee_info.change_rate * (timestamp() - fee_info.update_time);
-    if decrease_since_storage > fee_info.stored_fee {
-        0
-    } else {
-        fee_info.stored_fee - decrease_since_storage
+/// Execute long-term orders up to now, or at most `max_intervals` order intervals, returning whether they're up to date
+#[storage(read, write)]
+fn execute_virtual_orders(max_intervals: u64) -> bool {
+    let now = timestamp();
+    let (state, expiries) = compute_virtual_orders(now, max_intervals);
+
+    let mut i = 0;
+    while i < expiries.len() {
+        let factors = expiries.get(i).unwrap();
+        storage.expiry_reward_factors.insert(factors.expiry, factors);
+        i += 1;
+    }
+
+    storage.twamm = state.info;
+    storage.reward_factor_0 = state.reward_factor_0;
+    storage.reward_factor_1 = state.reward_factor_1;
+
+    // The fees leave the orders' held tokens, so the pool's balances are unchanged
+    if state.protocol_fee_0 > 0 || state.protocol_fee_1 > 0 {
+        collect_protocol_fees(state.protocol_fee_0, state.protocol_fee_1);
+    }
+
+    let (token_0_reserve, token_1_reserve) = get_reserves();
+    if state.reserve_0 != token_0_reserve || state.reserve_1 != token_1_reserve {
+        store_reserves(state.reserve_0, state.reserve_1, token_0_reserve, token_1_reserve);
     }
+
+    state.info.last_virtual_order_time == now
 }
 
+/// Execute long-term orders up to now, before anything else reads the reserves
 #[storage(read, write)]
-fn process_protocol_fee(amount: u64, is_token0: bool) -> (u64, u64) {
-    let current_fee_rate = get_current_fee();
-    let mut fee = 0;
-
-    if (current_fee_rate > 0) {
-        fee = (U128::from((0, amount)) * U128::from((0, current_fee_rate)) / U128::from((0, 1_000_000))).as_u64().unwrap();
-        let sender: b256 = identity_to_b256(msg_sender().unwrap());
-
-        let mut amount_0 = 0;
-        let mut amount_1 = 0;
-        if (is_token0) {
-            storage.token0_vault_fees_collected = storage.token0_vault_fees_collected + fee;
-            amount_0 = fee;
-        } else {
-            storage.token1_vault_fees_collected = storage.token1_vault_fees_collected + fee;
-            amount_1 = fee;
+fn require_virtual_orders_executed() {
+    require(execute_virtual_orders(MAX_VIRTUAL_ORDER_INTERVALS), Error::VirtualOrdersPending);
+}
+
+/// Proceeds an order has earned, and the reward factor they're paid up to
+#[storage(read)]
+fn get_order_proceeds(order_id: u64, order: LongTermOrder, state: VirtualOrders, expiries: Vec<ExpiryRewardFactors>) -> (u64, U128) {
+    let reward_factor = if order.expiry > state.info.last_virtual_order_time {
+        if order.token_0_in { state.reward_factor_0 } else { state.reward_factor_1 }
+    } else {
+        // The order stopped selling at its expiry, which may only just have been executed
+        let mut factors = storage.expiry_reward_factors.get(order.expiry);
+        let mut i = 0;
+        while i < expiries.len() {
+            let expiry_factors = expiries.get(i).unwrap();
+            if expiry_factors.expiry == order.expiry {
+                factors = expiry_factors;
+            }
+            i += 1;
         }
+        if order.token_0_in { factors.reward_factor_0 } else { factors.reward_factor_1 }
+    };
 
-        log(ProtocolFeeCollected {
-            sender: sender,
-            amount_0: amount_0,
-            amount_1: amount_1,
-        });
-    }
-    (amount - fee, fee)
+    let reward_factor_last = storage.order_reward_factors.get(order_id);
+    let proceeds = (U128::from((0, order.sale_rate)) * (reward_factor - reward_factor_last) / REWARD_FACTOR_PRECISION).as_u64().unwrap();
+    (proceeds, reward_factor)
 }
 
-#[storage(write)]
-fn cache_vault_fees(vault: b256) {
-    let vault = abi(Vault, vault);
-    let vault_fees = vault.get_fees();
-    storage.vault_fee = VaultFee {
-        stored_fee: vault_fees.current_fee,
-        change_rate: vault_fees.change_rate,
-        update_time: timestamp(),
-    };
+#[storage(read)]
+fn get_owned_order(order_id: u64) -> LongTermOrder {
+    let order = storage.long_term_orders.get(order_id);
+    require(order.expiry > 0, Error::InvalidOrder);
+    require(msg_sender().unwrap() == order.owner, Error::MustBeCalledByOwner);
+    order
 }
 
 // ////////////////////////////////////////
@@ -236,281 +335,321 @@ fn cache_vault_fees(vault: b256) {
 impl Exchange for Contract {
     #[storage(read, write)]
     fn initialize(new_vault: b256) {
-        require(storage.vault == ZERO_B256, Error::AlreadyInitialized);
-        storage.vault = new_vault;
-        cache_vault_fees(new_vault);
+        initialize(new_vault);
     }
 
     #[storage(read)]
     fn get_pool_info() -> PoolInfo {
+        let (token_0_reserve, token_1_reserve) = get_virtual_reserves();
         PoolInfo {
-            token_0_reserve: storage.token0_reserve,
-            token_1_reserve: storage.token1_reserve,
-            lp_token_supply: storage.lp_token_supply,
+            token_0_reserve: token_0_reserve,
+            token_1_reserve: token_1_reserve,
+            lp_token_supply: get_lp_token_supply(),
         }
     }
 
     #[storage(read)]
     fn get_vault_info() -> VaultInfo {
-        let fees = storage.vault_fee;
-        VaultInfo {
-            vault: storage.vault,
-            token0_protocol_fees_collected: storage.token0_vault_fees_collected,
-            token1_protocol_fees_collected: storage.token1_vault_fees_collected,
-            current_fee: get_current_fee(),
-            change_rate: fees.change_rate,
-            update_time: fees.update_time,
-        }
+        get_vault_info()
     }
 
     #[storage(read)]
     fn get_fee_info() -> FeeInfo {
-        let fees = storage.vault_fee;
-        FeeInfo {
-            current_fee: get_current_fee(),
-            change_rate: fees.change_rate,
-            update_time: fees.update_time,
-        }
+        get_fee_info()
     }
 
     #[storage(read)]
-    fn get_twap_info() -> TWAPInfo {
-        TWAPInfo {
-            current_element: storage.twap_buffer_current_element,
-            buffer_size: storage.twap_buffer_size,
-            next_buffer_size: storage.twap_next_buffer_size,
-        }
+    fn get_fee_cache_info() -> FeeCacheInfo {
+        get_fee_cache_info()
     }
 
-    #[storage(read, write)]
-    fn cache_vault_fees() {
-        cache_vault_fees(storage.vault);
+    #[storage(read)]
+    fn get_lp_fee_info() -> LPFeeInfo {
+        get_lp_fee_info()
     }
 
-    #[storage(read, write)]
-    fn add_liquidity(recipient: Identity) -> u64 {
-        let (token0, token1) = get_tokens();
+    #[storage(read)]
+    fn get_amount_out(amount_in: u64, token_0_in: bool) -> u64 {
+        let (token_0_reserve, token_1_reserve) = get_virtual_reserves();
+        let (reserve_in, reserve_out) = if token_0_in {
+            (token_0_reserve, token_1_reserve)
+        } else {
+            (token_1_reserve, token_0_reserve)
+        };
 
-        let total_liquidity = storage.lp_token_supply;
+        let input = amount_in - get_protocol_fee_amount(amount_in, get_next_protocol_fee());
+        get_input_price(input, reserve_in, reserve_out, get_current_lp_fee())
+    }
 
-        let token_0_reserve = storage.token0_reserve;
-        let token_1_reserve = storage.token1_reserve;
+    #[storage(read)]
+    fn get_amount_in(amount_out: u64, token_0_out: bool) -> u64 {
+        let (token_0_reserve, token_1_reserve) = get_virtual_reserves();
+        let (reserve_in, reserve_out) = if token_0_out {
+            (token_1_reserve, token_0_reserve)
+        } else {
+            (token_0_reserve, token_1_reserve)
+        };
 
-        let (current_token_0_amount, current_token_1_amount) = get_pool_balance();
-        let token_0_sent = current_token_0_amount - token_0_reserve;
-        let token_1_sent = current_token_1_amount - token_1_reserve;
+        let input = get_output_price(amount_out, reserve_in, reserve_out, get_current_lp_fee());
+        add_protocol_fee(input, get_next_protocol_fee())
+    }
 
-        require(token_0_sent > 0 && token_1_sent > 0, Error::InsufficentInput);
+    #[storage(read)]
+    fn get_twap_info() -> TWAPInfo {
+        get_twap_info()
+    }
 
-        let mut minted: u64 = 0;
-        if total_liquidity > 0 {
-            let token0_liquidity = token_0_sent * total_liquidity / token_0_reserve;
-            let token1_liquidity = token_1_sent * total_liquidity / token_1_reserve;
+    #[storage(read, write)]
+    fn cache_vault_fees() {
+        cache_vault_fees();
+    }
 
-            minted = if (token0_liquidity < token1_liquidity) {
-                token0_liquidity
-            } else {
-                token1_liquidity
-            };
+    #[storage(read, write)]
+    fn set_max_fee_cache_age(max_age: u64) {
+        set_max_fee_cache_age(max_age);
+    }
 
-            store_reserves(current_token_0_amount, current_token_1_amount, token_0_reserve, token_1_reserve);
+    #[storage(read, write)]
+    fn set_dynamic_fee(config: DynamicFeeConfig) {
+        set_dynamic_fee(config);
+    }
 
-            mint(minted);
-            storage.lp_token_supply = total_liquidity + minted;
-        } else {
-            let big_amount_0 = U128::from((0, current_token_0_amount));
-            let big_amount_1 = U128::from((0, current_token_1_amount));
-            let initial_liquidity = sqrt_by_digit(big_amount_0 * big_amount_1).as_u64().unwrap() - MINIMUM_LIQUIDITY;
-
-            // Ensure there's at least 1 TWAP slot
-            storage.twap_buffer_size = 1;
-            storage.twap_next_buffer_size = 1;
-            storage.twap_buffer.insert(0, Observation {
-                timestamp: timestamp(),
-                price_0_cumulative_last: U256::min(),
-                price_1_cumulative_last: U256::min(),
-            });
+    #[storage(read, write)]
+    fn add_liquidity(recipient: Identity) -> u64 {
+        require_virtual_orders_executed();
+        let (token_0_reserve, token_1_reserve) = get_reserves();
+        let (current_token_0_amount, current_token_1_amount) = get_reserve_balances();
 
-            // Add fund to the reserves
-            store_reserves(current_token_0_amount, current_token_1_amount, 0, 0);
+        let minted = mint_liquidity(current_token_0_amount, current_token_1_amount, recipient);
+        store_reserves(current_token_0_amount, current_token_1_amount, token_0_reserve, token_1_reserve);
+        minted
+    }
 
-            // Mint LP token
-            mint(initial_liquidity);
-            storage.lp_token_supply = initial_liquidity + MINIMUM_LIQUIDITY;
+    #[storage(read, write)]
+    fn remove_liquidity(recipient: Identity) -> RemoveLiquidityInfo {
+        require_virtual_orders_executed();
+        let (token_0_reserve, token_1_reserve) = get_reserves();
+        let (current_token_0_amount, current_token_1_amount) = get_reserve_balances();
 
-            minted = initial_liquidity;
+        let removed = burn_liquidity(current_token_0_amount, current_token_1_amount, recipient);
+        store_reserves(current_token_0_amount - removed.token_0_amount, current_token_1_amount - removed.token_1_amount, token_0_reserve, token_1_reserve);
+        removed
+    }
 
-            // Log the liquidity that's burned
-            log(LiquidityAdded {
-                sender: identity_to_b256(msg_sender().unwrap()),
-                amount_0: 0,
-                amount_1: 0,
-                lp_tokens: MINIMUM_LIQUIDITY,
-                recipient: b256::min(),
-            });
-        };
-        require(minted > 0, Error::InsufficentLiquidityMinted);
+    #[storage(read, write)]
+    fn swap(amount_0_out: u64, amount_1_out: u64, recipient: Identity) {
+        require_virtual_orders_executed();
+        let (token_0_reserve, token_1_reserve) = get_reserves();
+        let twamm = storage.twamm;
+        let (balance_0, balance_1, amount0_in, amount1_in) = settle_swap(amount_0_out, amount_1_out, recipient, twamm.token_0_held, twamm.token_1_held);
+
+        // Measured before this swap's observation is written, so it matches get_lp_fee_info
+        let lp_fee = get_current_lp_fee();
+        let basis_points = U256::from((0, 0, 0, BASIS_POINTS));
+        let lp_fee_u256 = U256::from((0, 0, 0, lp_fee));
+        let balance0_adjusted = U256::from((0, 0, 0, balance_0)) * basis_points - (U256::from((0, 0, 0, amount0_in)) * lp_fee_u256);
+        let balance1_adjusted = U256::from((0, 0, 0, balance_1)) * basis_points - (U256::from((0, 0, 0, amount1_in)) * lp_fee_u256);
 
-        transfer(minted, contract_id(), recipient);
+        let left = balance0_adjusted * balance1_adjusted;
+        let right = U256::from((0, 0, 0, token_0_reserve)) * U256::from((0, 0, 0, token_1_reserve)) * basis_points * basis_points;
+        require(left > right || left == right, Error::Invariant); // U256 doesn't have >= yet
+        store_reserves(balance_0, balance_1, token_0_reserve, token_1_reserve);
 
-        log(LiquidityAdded {
+        log(Swap {
             sender: identity_to_b256(msg_sender().unwrap()),
-            amount_0: current_token_0_amount - token_0_reserve,
-            amount_1: current_token_1_amount - token_1_reserve,
-            lp_tokens: minted,
+            amount_0_in: amount0_in,
+            amount_1_in: amount1_in,
+            amount_0_out: amount_0_out,
+            amount_1_out: amount_1_out,
             recipient: identity_to_b256(recipient),
         });
-
-        minted
     }
 
     #[storage(read, write)]
-    fn remove_liquidity(recipient: Identity) -> RemoveLiquidityInfo {
-        let (token0, token1) = get_tokens();
+    fn expand_twap_buffer(new_total_slots: u64) {
+        expand_twap_buffer(new_total_slots);
+    }
 
-        let lp_tokens = this_balance(contract_id());
-        require(lp_tokens > 0, Error::InsufficentInput);
+    #[storage(read, write)]
+    fn withdraw_protocol_fees(recipient: Identity) -> (u64, u64) {
+        withdraw_protocol_fees(recipient)
+    }
 
-        let token_0_reserve = storage.token0_reserve;
-        let token_1_reserve = storage.token1_reserve;
-        let total_liquidity = storage.lp_token_supply;
-        let (current_token_0_amount, current_token_1_amount) = get_pool_balance();
+    #[storage(read)]
+    fn get_tokens() -> (b256, b256) {
+        get_tokens()
+    }
 
-        let amount0 = lp_tokens * current_token_0_amount / total_liquidity; // using balances ensures pro-rata distribution
-        let amount1 = lp_tokens * current_token_1_amount / total_liquidity; // using balances ensures pro-rata distribution
-        require(amount0 > 0 && amount1 > 0, Error::InsufficentLiquidityBurned);
+    #[storage(read)]
+    fn get_observation(slot: u64) -> Observation {
+        get_observation(slot)
+    }
+}
 
-        burn(lp_tokens);
-        storage.lp_token_supply = total_liquidity - lp_tokens;
+impl LongTermOrders for Contract {
+    #[storage(read)]
+    fn get_twamm_info() -> TWAMMInfo {
+        storage.twamm
+    }
 
-        transfer(amount0, ContractId::from(token0), recipient);
-        transfer(amount1, ContractId::from(token1), recipient);
+    #[storage(read)]
+    fn get_long_term_order(order_id: u64) -> LongTermOrder {
+        let order = storage.long_term_orders.get(order_id);
+        require(order.expiry > 0, Error::InvalidOrder);
+        order
+    }
 
-        store_reserves(current_token_0_amount - amount0, current_token_1_amount - amount1, token_0_reserve, token_1_reserve);
+    #[storage(read)]
+    fn get_order_proceeds(order_id: u64) -> u64 {
+        let order = storage.long_term_orders.get(order_id);
+        require(order.expiry > 0, Error::InvalidOrder);
+        let (state, expiries) = compute_virtual_orders(timestamp(), MAX_VIRTUAL_ORDER_INTERVALS);
+        let (proceeds, _reward_factor) = get_order_proceeds(order_id, order, state, expiries);
+        proceeds
+    }
 
-        log(LiquidityRemoved {
-            sender: identity_to_b256(msg_sender().unwrap()),
-            amount_0: amount0,
-            amount_1: amount1,
-            lp_tokens: lp_tokens,
-            recipient: identity_to_b256(recipient),
+    #[storage(read, write)]
+    fn place_long_term_order(intervals: u64, owner: Identity) -> u64 {
+        require(intervals > 0 && intervals <= MAX_ORDER_INTERVALS, Error::InvalidOrder);
+        let (token0, token1) = get_tokens();
+        let input_asset: b256 = msg_asset_id().into();
+        require(input_asset == token0 || input_asset == token1, Error::InvalidToken);
+
+        require_virtual_orders_executed();
+        let (token_0_reserve, token_1_reserve) = get_reserves();
+        require(token_0_reserve > 0 && token_1_reserve > 0, Error::InsufficentLiquidity);
+
+        let now = timestamp();
+        let expiry = (now / ORDER_INTERVAL + intervals) * ORDER_INTERVAL;
+        let sale_rate = msg_amount() / (expiry - now);
+        require(sale_rate > 0, Error::InsufficentInput);
+        let amount = sale_rate * (expiry - now);
+
+        let token_0_in = input_asset == token0;
+        let mut info = storage.twamm;
+        let reward_factor = if token_0_in {
+            info.sale_rate_0 = info.sale_rate_0 + sale_rate;
+            info.token_0_held = info.token_0_held + amount;
+            storage.sale_rate_0_ending.insert(expiry, storage.sale_rate_0_ending.get(expiry) + sale_rate);
+            storage.reward_factor_0
+        } else {
+            info.sale_rate_1 = info.sale_rate_1 + sale_rate;
+            info.token_1_held = info.token_1_held + amount;
+            storage.sale_rate_1_ending.insert(expiry, storage.sale_rate_1_ending.get(expiry) + sale_rate);
+            storage.reward_factor_1
+        };
+        storage.twamm = info;
+
+        let order_id = storage.next_order_id;
+        storage.next_order_id = order_id + 1;
+        storage.long_term_orders.insert(order_id, LongTermOrder {
+            owner: owner,
+            token_0_in: token_0_in,
+            sale_rate: sale_rate,
+            expiry: expiry,
         });
+        storage.order_reward_factors.insert(order_id, reward_factor);
 
-        RemoveLiquidityInfo {
-            token_0_amount: amount0,
-            token_1_amount: amount1,
+        // Return what doesn't divide into the sale rate
+        if msg_amount() > amount {
+            transfer(msg_amount() - amount, msg_asset_id(), msg_sender().unwrap());
         }
-    }
 
-    #[storage(read, write)]
-    fn swap(amount_0_out: u64, amount_1_out: u64, recipient: Identity) {
-        require(amount_0_out > 0 || amount_1_out > 0, Error::InsufficentOutput);
-        let (token0, token1) = get_tokens();
+        log(LongTermOrderPlaced {
+            order_id: order_id,
+            owner: identity_to_b256(owner),
+            token_0_in: token_0_in,
+            amount: amount,
+            sale_rate: sale_rate,
+            expiry: expiry,
+        });
 
-        let token_0_reserve = storage.token0_reserve;
-        let token_1_reserve = storage.token1_reserve;
+        order_id
+    }
 
-        require(amount_0_out < token_0_reserve && amount_1_out < token_1_reserve, Error::InsufficentLiquidity);
+    #[storage(read, write)]
+    fn cancel_long_term_order(order_id: u64, recipient: Identity) -> (u64, u64) {
+        let mut order = get_owned_order(order_id);
+        require_virtual_orders_executed();
+        let now = timestamp();
+        require(order.sale_rate > 0 && order.expiry > now, Error::OrderExpired);
 
-        if (amount_0_out > 0) {
-            transfer(amount_0_out, ContractId::from(token0), recipient);
-        }
-        if (amount_1_out > 0) {
-            transfer(amount_1_out, ContractId::from(token1), recipient);
-        }
-        // Should be the following line, but `let mut` doesn't work with destructuring
-        // let (balance_0, balance_1) = get_pool_balance();
-        let mut balance_0 = this_balance(ContractId::from(token0)) - storage.token0_vault_fees_collected;
-        let mut balance_1 = this_balance(ContractId::from(token1)) - storage.token1_vault_fees_collected;
+        // Orders are executed up to now, so this only reads the current state
+        let (state, expiries) = compute_virtual_orders(timestamp(), MAX_VIRTUAL_ORDER_INTERVALS);
+        let (proceeds, reward_factor) = get_order_proceeds(order_id, order, state, expiries);
+        let unsold_amount = order.sale_rate * (order.expiry - now);
 
-        let (amount0_in, amount0_protocol_fee) = if balance_0 > token_0_reserve - amount_0_out {
-            process_protocol_fee(balance_0 - (token_0_reserve - amount_0_out), true)
-        } else {
-            (0, 0)
-        };
-        let (amount1_in, amount1_protocol_fee) = if balance_1 > token_1_reserve - amount_1_out {
-            process_protocol_fee(balance_1 - (token_1_reserve - amount_1_out), false)
+        let (token0, token1) = get_tokens();
+        let mut info = storage.twamm;
+        let (input_asset, output_asset) = if order.token_0_in {
+            info.sale_rate_0 = info.sale_rate_0 - order.sale_rate;
+            info.token_0_held = info.token_0_held - unsold_amount;
+            info.token_1_held = info.token_1_held - proceeds;
+            storage.sale_rate_0_ending.insert(order.expiry, storage.sale_rate_0_ending.get(order.expiry) - order.sale_rate);
+            (token0, token1)
         } else {
-            (0, 0)
+            info.sale_rate_1 = info.sale_rate_1 - order.sale_rate;
+            info.token_1_held = info.token_1_held - unsold_amount;
+            info.token_0_held = info.token_0_held - proceeds;
+            storage.sale_rate_1_ending.insert(order.expiry, storage.sale_rate_1_ending.get(order.expiry) - order.sale_rate);
+            (token1, token0)
         };
+        storage.twamm = info;
 
-        require(amount0_in > 0 || amount1_in > 0, Error::InsufficentInput);
-
-        balance_0 = balance_0 - amount0_protocol_fee;
-        balance_1 = balance_1 - amount1_protocol_fee;
-
-        let balance0_adjusted = U128::from((0, balance_0)) * U128::from((0, 1000)) - (U128::from((0, amount0_in)) * U128::from((0, 3)));
-        let balance1_adjusted = U128::from((0, balance_1)) * U128::from((0, 1000)) - (U128::from((0, amount1_in)) * U128::from((0, 3)));
+        order.sale_rate = 0;
+        storage.long_term_orders.insert(order_id, order);
+        storage.order_reward_factors.insert(order_id, reward_factor);
 
-        let left = balance0_adjusted * balance1_adjusted;
-        let right = U128::from((0, token_0_reserve)) * U128::from((0, token_1_reserve)) * U128::from((0, 1000 * 1000));
-        require(left > right || left == right, Error::Invariant); // U128 doesn't have >= yet
-        store_reserves(balance_0, balance_1, token_0_reserve, token_1_reserve);
+        transfer(unsold_amount, ContractId::from(input_asset), recipient);
+        if proceeds > 0 {
+            transfer(proceeds, ContractId::from(output_asset), recipient);
+        }
 
-        log(Swap {
-            sender: identity_to_b256(msg_sender().unwrap()),
-            amount_0_in: amount0_in,
-            amount_1_in: amount1_in,
-            amount_0_out: amount_0_out,
-            amount_1_out: amount_1_out,
+        log(LongTermOrderCancelled {
+            order_id: order_id,
+            unsold_amount: unsold_amount,
+            proceeds: proceeds,
             recipient: identity_to_b256(recipient),
         });
-    }
 
-    #[storage(read, write)]
-    fn expand_twap_buffer(new_total_slots: u64) {
-        let mut i = storage.twap_next_buffer_size;
-
-        while i < new_total_slots {
-            // The goal here is to initialize these slots (write a value), without
-            // providing actual data. Observations will be aware that timestamp=1 means
-            // initialized, but not yet used
-            storage.twap_buffer.insert(i, Observation {
-                timestamp: 1,
-                price_0_cumulative_last: U256::max(),
-                price_1_cumulative_last: U256::max(),
-            });
-            i += 1;
-        }
-
-        storage.twap_next_buffer_size = new_total_slots;
+        (unsold_amount, proceeds)
     }
 
     #[storage(read, write)]
-    fn withdraw_protocol_fees(recipient: Identity) -> (u64, u64) {
-        let sender: Identity = msg_sender().unwrap();
-        require(identity_to_b256(sender) == storage.vault, Error::MustBeCalledByVault);
-
-        let (token0, token1) = get_tokens();
-        let (token0_vault_fees_collected, token1_vault_fees_collected) = (
-            storage.token0_vault_fees_collected,
-            storage.token1_vault_fees_collected,
-        );
-
-        if (token0_vault_fees_collected > 0) {
-            transfer(token0_vault_fees_collected, ContractId::from(token0), recipient);
-            storage.token0_vault_fees_collected = 0;
-        }
-        if (token1_vault_fees_collected > 0) {
-            transfer(token1_vault_fees_collected, ContractId::from(token1), recipient);
-            storage.token1_vault_fees_collected = 0;
+    fn withdraw_proceeds(order_id: u64, recipient: Identity) -> u64 {
+        let order = get_owned_order(order_id);
+        require_virtual_orders_executed();
+
+        // Orders are executed up to now, so this only reads the current state
+        let (state, expiries) = compute_virtual_orders(timestamp(), MAX_VIRTUAL_ORDER_INTERVALS);
+        let (proceeds, reward_factor) = get_order_proceeds(order_id, order, state, expiries);
+        storage.order_reward_factors.insert(order_id, reward_factor);
+
+        if proceeds > 0 {
+            let (token0, token1) = get_tokens();
+            let mut info = storage.twamm;
+            let output_asset = if order.token_0_in {
+                info.token_1_held = info.token_1_held - proceeds;
+                token1
+            } else {
+                info.token_0_held = info.token_0_held - proceeds;
+                token0
+            };
+            storage.twamm = info;
+            transfer(proceeds, ContractId::from(output_asset), recipient);
         }
 
-        log(ProtocolFeeWithdrawn {
-            amount_0: token0_vault_fees_collected,
-            amount_1: token1_vault_fees_collected,
+        log(LongTermProceedsWithdrawn {
+            order_id: order_id,
+            proceeds: proceeds,
+            recipient: identity_to_b256(recipient),
         });
-        (token0_vault_fees_collected, token1_vault_fees_collected)
-    }
 
-    #[storage(read)]
-    fn get_tokens() -> (b256, b256) {
-        get_tokens()
+        proceeds
     }
 
-    #[storage(read)]
-    fn get_observation(slot: u64) -> Observation {
-        require(slot < storage.twap_buffer_size, Error::TWAPOutOfRange);
-        storage.twap_buffer.get(slot)
+    #[storage(read, write)]
+    fn execute_virtual_orders(max_intervals: u64) -> bool {
+        execute_virtual_orders(max_intervals)
     }
 }
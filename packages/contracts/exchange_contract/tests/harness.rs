@@ -4,6 +4,7 @@ use std::{
     vec,
     str::FromStr,
 };
+use chrono::Utc;
 use fuels::{
     prelude::*,
     fuels_abigen::abigen,
@@ -11,7 +12,17 @@ use fuels::{
     tx::{AssetId, Bytes32, StorageSlot},
 };
 use tokio::time::{sleep, Duration};
-use test_helpers::{get_timestamp_and_call, get_wallets};
+use test_helpers::{
+    deployer_storage,
+    get_logs,
+    get_timestamp_and_call,
+    get_wallets,
+    produce_block_at,
+    run_fee_change,
+    unix_to_tai64,
+    EXCHANGE_ABI,
+    MIN_FEE_CHANGE_DELAY,
+};
 
 ///////////////////////////////
 // Load the Exchange Contract abi
@@ -45,8 +56,6 @@ fn to_9_decimal(num: u64) -> u64 {
     num * 1_000_000_000
 }
 
-const LOGS_ENABLED: bool = false;
-
 async fn setup() -> Fixture {
     let wallets = get_wallets().await;
     let wallet = wallets.get(0).unwrap().clone();
@@ -83,7 +92,7 @@ async fn setup() -> Fixture {
         "../vault_contract/out/debug/vault_contract.bin",
         &wallet,
         TxParameters::default(),
-        StorageConfiguration::new(None, None),
+        deployer_storage(&wallet),
     )
     .await
     .unwrap();
@@ -111,6 +120,13 @@ async fn setup() -> Fixture {
         .await
         .unwrap();
 
+    vault_instance
+        .methods()
+        .initialize(Identity::Address(wallet.address().into()), MIN_FEE_CHANGE_DELAY)
+        .call()
+        .await
+        .unwrap();
+
     exchange_instance
         .methods()
         .initialize(Bits256(vault_contract_id.hash().into()))
@@ -132,6 +148,18 @@ async fn setup() -> Fixture {
     }
 }
 
+/// Change the vault's global fees through the timelock, returning the unix time it was executed at
+async fn set_vault_fees(fixture: &Fixture, start_fee: u16, change_rate: u16, now: i64) -> i64 {
+    let methods = fixture.vault_instance.methods();
+    run_fee_change(
+        &fixture.wallet,
+        now,
+        |eta| methods.queue_fee_change(start_fee, change_rate, eta),
+        methods.execute_fee_change(),
+    )
+    .await
+}
+
 async fn add_liquidity(fixture: &Fixture, token_0_amount: u64, token_1_amount: u64) -> u64 {
     let starting_token_0_balance = fixture.wallet.get_asset_balance(&BASE_ASSET_ID).await.unwrap();
     assert!(starting_token_0_balance >= token_0_amount, "Insufficient token 0");
@@ -233,30 +261,28 @@ async fn mint() {
     let lp_balance = fixture.wallet.get_asset_balance(&fixture.exchange_asset_id).await.unwrap();
     assert_eq!(lp_balance, expected_liquidity - MINIMUM_LIQUIDITY);
 
-    if LOGS_ENABLED {
-        // let liquidity_logs = response.get_logs_with_type::<LiquidityAdded>().unwrap();
-        // assert_eq!(liquidity_logs.len(), 2);
-
-        // let liquidity_event = liquidity_logs.get(0).unwrap();
-        // assert_eq!(liquidity_event.sender, Bits256(fixture.wallet.address().hash().into()));
-        // assert_eq!(liquidity_event.recipient, Bits256([0; 32]));
-        // assert_eq!(liquidity_event.amount_0, 0);
-        // assert_eq!(liquidity_event.amount_1, 0);
-        // assert_eq!(liquidity_event.lp_tokens, 1000);
-
-        // let liquidity_event = liquidity_logs.get(1).unwrap();
-        // assert_eq!(liquidity_event.sender, Bits256(fixture.wallet.address().hash().into()));
-        // assert_eq!(liquidity_event.recipient, Bits256(fixture.wallet.address().hash().into()));
-        // assert_eq!(liquidity_event.amount_0, token_0_amount);
-        // assert_eq!(liquidity_event.amount_1, token_1_amount);
-        // assert_eq!(liquidity_event.lp_tokens, expected_liquidity - 1000);
-
-        // let reserve_logs = response.get_logs_with_type::<UpdateReserves>().unwrap();
-        // assert_eq!(reserve_logs.len(), 1);
-        // let reserve_event = reserve_logs.get(0).unwrap();
-        // assert_eq!(reserve_event.amount_0, 1000000000);
-        // assert_eq!(reserve_event.amount_1, 4000000000);
-    }
+    let liquidity_logs = get_logs::<LiquidityAdded, _>(&response, &fixture.exchange_contract_id, EXCHANGE_ABI);
+    assert_eq!(liquidity_logs.len(), 2);
+
+    let liquidity_event = liquidity_logs.get(0).unwrap();
+    assert_eq!(liquidity_event.sender, Bits256(fixture.wallet.address().hash().into()));
+    assert_eq!(liquidity_event.recipient, Bits256([0; 32]));
+    assert_eq!(liquidity_event.amount_0, 0);
+    assert_eq!(liquidity_event.amount_1, 0);
+    assert_eq!(liquidity_event.lp_tokens, MINIMUM_LIQUIDITY);
+
+    let liquidity_event = liquidity_logs.get(1).unwrap();
+    assert_eq!(liquidity_event.sender, Bits256(fixture.wallet.address().hash().into()));
+    assert_eq!(liquidity_event.recipient, Bits256(fixture.wallet.address().hash().into()));
+    assert_eq!(liquidity_event.amount_0, token_0_amount);
+    assert_eq!(liquidity_event.amount_1, token_1_amount);
+    assert_eq!(liquidity_event.lp_tokens, expected_liquidity - MINIMUM_LIQUIDITY);
+
+    let reserve_logs = get_logs::<UpdateReserves, _>(&response, &fixture.exchange_contract_id, EXCHANGE_ABI);
+    assert_eq!(reserve_logs.len(), 1);
+    let reserve_event = reserve_logs.get(0).unwrap();
+    assert_eq!(reserve_event.amount_0, token_0_amount);
+    assert_eq!(reserve_event.amount_1, token_1_amount);
 
     // expect(await pair.balanceOf(wallet.address)).to.eq(expectedLiquidity.sub(MINIMUM_LIQUIDITY))
     // expect(await token0.balanceOf(pair.address)).to.eq(token0Amount)
@@ -314,30 +340,22 @@ async fn mint() {
     let lp_balance = fixture.wallet.get_asset_balance(&fixture.exchange_asset_id).await.unwrap();
     assert_eq!(lp_balance, expected_liquidity * 2 - MINIMUM_LIQUIDITY);
 
-    if LOGS_ENABLED {
-        // let liquidity_logs = response.get_logs_with_type::<LiquidityAdded>().unwrap();
-        // assert_eq!(liquidity_logs.len(), 2);
-
-        // let liquidity_event = liquidity_logs.get(0).unwrap();
-        // assert_eq!(liquidity_event.sender, Bits256(fixture.wallet.address().hash().into()));
-        // assert_eq!(liquidity_event.recipient, Bits256([0; 32]));
-        // assert_eq!(liquidity_event.amount_0, 0);
-        // assert_eq!(liquidity_event.amount_1, 0);
-        // assert_eq!(liquidity_event.lp_tokens, 1000);
-
-        // let liquidity_event = liquidity_logs.get(1).unwrap();
-        // assert_eq!(liquidity_event.sender, Bits256(fixture.wallet.address().hash().into()));
-        // assert_eq!(liquidity_event.recipient, Bits256(fixture.wallet.address().hash().into()));
-        // assert_eq!(liquidity_event.amount_0, token_0_amount);
-        // assert_eq!(liquidity_event.amount_1, token_1_amount);
-        // assert_eq!(liquidity_event.lp_tokens, expected_liquidity - 1000);
-
-        // let reserve_logs = response.get_logs_with_type::<UpdateReserves>().unwrap();
-        // assert_eq!(reserve_logs.len(), 1);
-        // let reserve_event = reserve_logs.get(0).unwrap();
-        // assert_eq!(reserve_event.amount_0, 1000000000);
-        // assert_eq!(reserve_event.amount_1, 4000000000);
-    }
+    // No minimum liquidity is locked the second time
+    let liquidity_logs = get_logs::<LiquidityAdded, _>(&response, &fixture.exchange_contract_id, EXCHANGE_ABI);
+    assert_eq!(liquidity_logs.len(), 1);
+
+    let liquidity_event = liquidity_logs.get(0).unwrap();
+    assert_eq!(liquidity_event.sender, Bits256(fixture.wallet.address().hash().into()));
+    assert_eq!(liquidity_event.recipient, Bits256(fixture.wallet.address().hash().into()));
+    assert_eq!(liquidity_event.amount_0, token_0_amount);
+    assert_eq!(liquidity_event.amount_1, token_1_amount + unbalanced_extra_coins);
+    assert_eq!(liquidity_event.lp_tokens, expected_liquidity);
+
+    let reserve_logs = get_logs::<UpdateReserves, _>(&response, &fixture.exchange_contract_id, EXCHANGE_ABI);
+    assert_eq!(reserve_logs.len(), 1);
+    let reserve_event = reserve_logs.get(0).unwrap();
+    assert_eq!(reserve_event.amount_0, token_0_amount * 2);
+    assert_eq!(reserve_event.amount_1, token_1_amount * 2 + unbalanced_extra_coins);
 
     // expect(await pair.balanceOf(wallet.address)).to.eq(expectedLiquidity.sub(MINIMUM_LIQUIDITY))
     // expect(await token0.balanceOf(pair.address)).to.eq(token0Amount)
@@ -470,24 +488,26 @@ async fn swap_token_0() {
         .await
         .unwrap();
 
-    if LOGS_ENABLED {
-        // let swap_logs = response.get_logs_with_type::<Swap>().unwrap();
-        // assert_eq!(swap_logs.len(), 1);
-
-        // let swap_event = swap_logs.get(0).unwrap();
-        // assert_eq!(swap_event.sender, Bits256(fixture.wallet.address().hash().into()));
-        // assert_eq!(swap_event.recipient, Bits256(fixture.wallet.address().hash().into()));
-        // assert_eq!(swap_event.amount_0_in, swap_amount);
-        // assert_eq!(swap_event.amount_1_in, 0);
-        // assert_eq!(swap_event.amount_0_out, 0);
-        // assert_eq!(swap_event.amount_1_out, expected_output);
-
-        // let reserve_logs = response.get_logs_with_type::<UpdateReserves>().unwrap();
-        // assert_eq!(reserve_logs.len(), 1);
-        // let reserve_event = reserve_logs.get(0).unwrap();
-        // assert_eq!(reserve_event.amount_0, token_0_amount + swap_amount);
-        // assert_eq!(reserve_event.amount_1, token_1_amount - expected_output);
-    }
+    let swap_logs = get_logs::<Swap, _>(&response, &fixture.exchange_contract_id, EXCHANGE_ABI);
+    assert_eq!(swap_logs.len(), 1);
+
+    let swap_event = swap_logs.get(0).unwrap();
+    assert_eq!(swap_event.sender, Bits256(fixture.wallet.address().hash().into()));
+    assert_eq!(swap_event.recipient, Bits256(fixture.wallet.address().hash().into()));
+    assert_eq!(swap_event.amount_0_in, swap_amount);
+    assert_eq!(swap_event.amount_1_in, 0);
+    assert_eq!(swap_event.amount_0_out, 0);
+    assert_eq!(swap_event.amount_1_out, expected_output);
+
+    let reserve_logs = get_logs::<UpdateReserves, _>(&response, &fixture.exchange_contract_id, EXCHANGE_ABI);
+    assert_eq!(reserve_logs.len(), 1);
+    let reserve_event = reserve_logs.get(0).unwrap();
+    assert_eq!(reserve_event.amount_0, token_0_amount + swap_amount);
+    assert_eq!(reserve_event.amount_1, token_1_amount - expected_output);
+
+    // No protocol fee has been set
+    let fee_logs = get_logs::<ProtocolFeeCollected, _>(&response, &fixture.exchange_contract_id, EXCHANGE_ABI);
+    assert_eq!(fee_logs.len(), 0);
 
     let pool_info = fixture.exchange_instance.methods().get_pool_info().call().await.unwrap();
     assert_eq!(pool_info.value.token_0_reserve, token_0_amount + swap_amount);
@@ -548,24 +568,22 @@ async fn swap_token_1() {
         .await
         .unwrap();
 
-    if LOGS_ENABLED {
-        // let swap_logs = response.get_logs_with_type::<Swap>().unwrap();
-        // assert_eq!(swap_logs.len(), 1);
-
-        // let swap_event = swap_logs.get(0).unwrap();
-        // assert_eq!(swap_event.sender, Bits256(fixture.wallet.address().hash().into()));
-        // assert_eq!(swap_event.recipient, Bits256(fixture.wallet.address().hash().into()));
-        // assert_eq!(swap_event.amount_1_in, swap_amount);
-        // assert_eq!(swap_event.amount_0_in, 0);
-        // assert_eq!(swap_event.amount_1_out, 0);
-        // assert_eq!(swap_event.amount_0_out, expected_output);
-
-        // let reserve_logs = response.get_logs_with_type::<UpdateReserves>().unwrap();
-        // assert_eq!(reserve_logs.len(), 1);
-        // let reserve_event = reserve_logs.get(0).unwrap();
-        // assert_eq!(reserve_event.amount_1, token_1_amount + swap_amount);
-        // assert_eq!(reserve_event.amount_0, token_0_amount - expected_output);
-    }
+    let swap_logs = get_logs::<Swap, _>(&response, &fixture.exchange_contract_id, EXCHANGE_ABI);
+    assert_eq!(swap_logs.len(), 1);
+
+    let swap_event = swap_logs.get(0).unwrap();
+    assert_eq!(swap_event.sender, Bits256(fixture.wallet.address().hash().into()));
+    assert_eq!(swap_event.recipient, Bits256(fixture.wallet.address().hash().into()));
+    assert_eq!(swap_event.amount_1_in, swap_amount);
+    assert_eq!(swap_event.amount_0_in, 0);
+    assert_eq!(swap_event.amount_1_out, 0);
+    assert_eq!(swap_event.amount_0_out, expected_output);
+
+    let reserve_logs = get_logs::<UpdateReserves, _>(&response, &fixture.exchange_contract_id, EXCHANGE_ABI);
+    assert_eq!(reserve_logs.len(), 1);
+    let reserve_event = reserve_logs.get(0).unwrap();
+    assert_eq!(reserve_event.amount_1, token_1_amount + swap_amount);
+    assert_eq!(reserve_event.amount_0, token_0_amount - expected_output);
 
     let pool_info = fixture.exchange_instance.methods().get_pool_info().call().await.unwrap();
     assert_eq!(pool_info.value.token_1_reserve, token_1_amount + swap_amount);
@@ -629,24 +647,22 @@ async fn burn() {
         .await
         .unwrap();
 
-    if LOGS_ENABLED {
-        // let burn_logs = response.get_logs_with_type::<LiquidityRemoved>().unwrap();
-        // assert_eq!(burn_logs.len(), 1);
+    let burn_logs = get_logs::<LiquidityRemoved, _>(&response, &fixture.exchange_contract_id, EXCHANGE_ABI);
+    assert_eq!(burn_logs.len(), 1);
 
-        // let burn_event = burn_logs.get(0).unwrap();
-        // assert_eq!(burn_event.sender, Bits256(fixture.wallet.address().hash().into()));
-        // assert_eq!(burn_event.recipient, Bits256(fixture.wallet.address().hash().into()));
-        // assert_eq!(burn_event.amount_0, token_0_amount - 1000);
-        // assert_eq!(burn_event.amount_1, token_1_amount - 1000);
-        // assert_eq!(burn_event.lp_tokens, expected_liquidity);
+    let burn_event = burn_logs.get(0).unwrap();
+    assert_eq!(burn_event.sender, Bits256(fixture.wallet.address().hash().into()));
+    assert_eq!(burn_event.recipient, Bits256(fixture.wallet.address().hash().into()));
+    assert_eq!(burn_event.amount_0, token_0_amount - 1000);
+    assert_eq!(burn_event.amount_1, token_1_amount - 1000);
+    assert_eq!(burn_event.lp_tokens, expected_liquidity);
 
-        // let reserve_logs = response.get_logs_with_type::<UpdateReserves>().unwrap();
-        // assert_eq!(reserve_logs.len(), 1);
+    let reserve_logs = get_logs::<UpdateReserves, _>(&response, &fixture.exchange_contract_id, EXCHANGE_ABI);
+    assert_eq!(reserve_logs.len(), 1);
 
-        // let reserve_event = reserve_logs.get(0).unwrap();
-        // assert_eq!(reserve_event.amount_1, 1000);
-        // assert_eq!(reserve_event.amount_0, 1000);
-    }
+    let reserve_event = reserve_logs.get(0).unwrap();
+    assert_eq!(reserve_event.amount_1, 1000);
+    assert_eq!(reserve_event.amount_0, 1000);
 
     let lp_balance = fixture.wallet.get_asset_balance(&fixture.exchange_asset_id.clone()).await.unwrap();
     assert_eq!(lp_balance, 0);
@@ -688,13 +704,12 @@ async fn accrue_protocol_fees() {
     add_liquidity(&fixture, token_0_amount, token_1_amount)
         .await;
 
+    let fees_set_at = set_vault_fees(&fixture, 10000, 1000, Utc::now().timestamp()).await;
     let (_result, set_fee_timestamp) = get_timestamp_and_call(
-        fixture.vault_instance
-            .methods()
-            .set_fees(10000, 1000)
+        fixture.vault_instance.methods().get_fees()
     )
         .await;
-    
+
     fixture.exchange_instance
         .methods()
         .cache_vault_fees()
@@ -718,7 +733,11 @@ async fn accrue_protocol_fees() {
     let input = to_9_decimal(1);
     let expected_output = 1648613753;
 
-    let (response, swap_timestamp) = get_timestamp_and_call(
+    // Pin the swap two seconds into the fee schedule, when the protocol fee is 0.8%
+    let swap_time = fees_set_at + 2;
+    produce_block_at(&fixture.wallet, swap_time).await;
+
+    let (response, _swap_timestamp) = get_timestamp_and_call(
         fixture.exchange_instance
             .methods()
             .swap(0, expected_output, Identity::Address(fixture.wallet.address().into()))
@@ -736,18 +755,15 @@ async fn accrue_protocol_fees() {
     )
         .await;
 
-    let expected_fee_rate = 10000 - (1000 * (swap_timestamp - set_fee_timestamp));
+    let expected_fee_rate = 10000 - 1000 * (swap_time - fees_set_at) as u64;
     let expected_fee_0 = input * expected_fee_rate / 1_000_000;
 
-    if LOGS_ENABLED {
-        // let logs = response.get_logs_with_type::<ProtocolFeeCollected>().unwrap();
-        // assert_eq!(logs.len(), 1);
-        // let fee_event = logs.get(0).unwrap();
-        // assert_eq!(fee_event.sender, Bits256(fixture.wallet.address().hash().into()));
-        // // TODO: Uncomment once timestamps working
-        // // assert_eq!(fee_event.amount_0, expected_fee_0);
-        // assert_eq!(fee_event.amount_1, 0);
-    }
+    let logs = get_logs::<ProtocolFeeCollected, _>(&response, &fixture.exchange_contract_id, EXCHANGE_ABI);
+    assert_eq!(logs.len(), 1);
+    let fee_event = logs.get(0).unwrap();
+    assert_eq!(fee_event.sender, Bits256(fixture.wallet.address().hash().into()));
+    assert_eq!(fee_event.amount_0, expected_fee_0);
+    assert_eq!(fee_event.amount_1, 0);
 
     let (fee_info, fee_info_timestamp) = get_timestamp_and_call(
         fixture.exchange_instance.methods().get_vault_info()
@@ -787,7 +803,11 @@ async fn accrue_protocol_fees() {
 
     let expected_output = 633116959;
 
-    let (response, swap_timestamp) = get_timestamp_and_call(
+    // Pin the second swap four seconds into the schedule, when the protocol fee is 0.6%
+    let swap_time = fees_set_at + 4;
+    produce_block_at(&fixture.wallet, swap_time).await;
+
+    let (response, _swap_timestamp) = get_timestamp_and_call(
         fixture.exchange_instance
             .methods()
             .swap(expected_output, 0, Identity::Address(fixture.wallet.address().into()))
@@ -805,23 +825,22 @@ async fn accrue_protocol_fees() {
     )
         .await;
 
-    let expected_fee_rate = 10000 - (1000 * (swap_timestamp - set_fee_timestamp));
+    let expected_fee_rate = 10000 - 1000 * (swap_time - fees_set_at) as u64;
     let expected_fee_1 = input * expected_fee_rate / 1_000_000;
 
-    if LOGS_ENABLED {
-        // let logs = response.get_logs_with_type::<ProtocolFeeCollected>().unwrap();
-        // assert_eq!(logs.len(), 1);
-        // let fee_event = logs.get(0).unwrap();
-        // assert_eq!(fee_event.sender, Bits256(fixture.wallet.address().hash().into()));
-        // assert_eq!(fee_event.amount_0, 0);
-        // // TODO: Uncomment once timestamps working
-        // // assert_eq!(fee_event.amount_1, expected_fee_1);
-    }
+    let logs = get_logs::<ProtocolFeeCollected, _>(&response, &fixture.exchange_contract_id, EXCHANGE_ABI);
+    assert_eq!(logs.len(), 1);
+    let fee_event = logs.get(0).unwrap();
+    assert_eq!(fee_event.sender, Bits256(fixture.wallet.address().hash().into()));
+    assert_eq!(fee_event.amount_0, 0);
+    assert_eq!(fee_event.amount_1, expected_fee_1);
 
     let fee_info = fixture.exchange_instance.methods().get_vault_info().call().await.unwrap();
     // TODO: Uncomment once timestamps working
     // assert_eq!(fee_info.value.token_0_protocol_fees_collected, expected_fee_0);
     // assert_eq!(fee_info.value.token_1_protocol_fees_collected, expected_fee_1);
+    let fees_collected_0 = fee_info.value.token_0_protocol_fees_collected;
+    let fees_collected_1 = fee_info.value.token_1_protocol_fees_collected;
 
     let end_token_balance = fixture.wallet.get_asset_balance(&BASE_ASSET_ID).await.unwrap();
 
@@ -837,12 +856,12 @@ async fn accrue_protocol_fees() {
         .await
         .unwrap();
 
-    // TODO: how do we parse a log from a different contract than the one called?
-    // let logs = fixture.exchange_instance.get_logs_with_type::<ProtocolFeeWithdrawn>(&response.receipts).unwrap();
-    // assert_eq!(logs.len(), 1);
-    // let fee_withdraw_event = logs.get(0).unwrap();
-    // assert_eq!(fee_withdraw_event.amount_0, expected_fee_0);
-    // assert_eq!(fee_withdraw_event.amount_1, expected_fee_1);
+    // Logged by the exchange, not the vault that was called
+    let logs = get_logs::<ProtocolFeeWithdrawn, _>(&response, &fixture.exchange_contract_id, EXCHANGE_ABI);
+    assert_eq!(logs.len(), 1);
+    let fee_withdraw_event = logs.get(0).unwrap();
+    assert_eq!(fee_withdraw_event.amount_0, fees_collected_0);
+    assert_eq!(fee_withdraw_event.amount_1, fees_collected_1);
 
     let fee_info = fixture.exchange_instance.methods().get_vault_info().call().await.unwrap();
     assert_eq!(fee_info.value.token_0_protocol_fees_collected, 0);
@@ -885,12 +904,7 @@ async fn non_vault_claim_will_fail() {
 async fn protocol_fees_minimum_zero() {
     let fixture = setup().await;
 
-    fixture.vault_instance
-        .methods()
-        .set_fees(1_000, 10_000)
-        .call()
-        .await
-        .unwrap();
+    set_vault_fees(&fixture, 1_000, 10_000, Utc::now().timestamp()).await;
 
     fixture.exchange_instance
         .methods()
@@ -1051,3 +1065,529 @@ async fn observe_price_changes() {
     // TODO: Uncomment once timestamps working
     // assert_eq!(observation_2.value.timestamp, remove_timestamp);
 }
+
+/// Mirror of the pool's pricing, with the LP fee in basis points
+fn get_amount_out(amount_in: u64, reserve_in: u64, reserve_out: u64, lp_fee: u64) -> u64 {
+    let amount_in_with_fee = amount_in as u128 * (10_000 - lp_fee) as u128;
+    let numerator = amount_in_with_fee * reserve_out as u128;
+    let denominator = reserve_in as u128 * 10_000 + amount_in_with_fee;
+    (numerator / denominator) as u64
+}
+
+/// Swap at the quoted LP fee, checking the pool won't give any more. Returns the fee used.
+async fn swap_at_quoted_fee(fixture: &Fixture, amount_in: u64, token_0_in: bool) -> u64 {
+    let lp_fee = fixture.exchange_instance.methods().get_lp_fee_info().call().await.unwrap().value.current_fee;
+    let pool_info = fixture.exchange_instance.methods().get_pool_info().call().await.unwrap().value;
+
+    let (amount_out, asset_id) = if token_0_in {
+        (get_amount_out(amount_in, pool_info.token_0_reserve, pool_info.token_1_reserve, lp_fee), BASE_ASSET_ID)
+    } else {
+        (get_amount_out(amount_in, pool_info.token_1_reserve, pool_info.token_0_reserve, lp_fee), fixture.token_asset_id)
+    };
+    let outputs = |amount_out| if token_0_in { (0, amount_out) } else { (amount_out, 0) };
+
+    let (out0, out1) = outputs(amount_out + 1);
+    let is_err = fixture.exchange_instance
+        .methods()
+        .swap(out0, out1, Identity::Address(fixture.wallet.address().into()))
+        .set_contracts(&[fixture.vault_contract_id.clone()])
+        .call_params(CallParameters::new(Some(amount_in), Some(asset_id), None))
+        .tx_params(TxParameters {
+            gas_price: 0,
+            gas_limit: 100_000_000,
+            maturity: 0,
+        })
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Pool paid out more than the quoted fee allows");
+
+    let (out0, out1) = outputs(amount_out);
+    fixture.exchange_instance
+        .methods()
+        .swap(out0, out1, Identity::Address(fixture.wallet.address().into()))
+        .set_contracts(&[fixture.vault_contract_id.clone()])
+        .call_params(CallParameters::new(Some(amount_in), Some(asset_id), None))
+        .tx_params(TxParameters {
+            gas_price: 0,
+            gas_limit: 100_000_000,
+            maturity: 0,
+        })
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .unwrap();
+
+    lp_fee
+}
+
+#[tokio::test]
+async fn dynamic_fee_config() {
+    let fixture = setup().await;
+    let exchange_id = Bits256(fixture.exchange_contract_id.hash().into());
+
+    let result = fixture.exchange_instance.methods().get_lp_fee_info().call().await.unwrap();
+    assert_eq!(result.value.current_fee, 30);
+    assert!(!result.value.config.enabled);
+
+    let is_err = fixture.exchange_instance
+        .methods()
+        .set_dynamic_fee(DynamicFeeConfig {
+            enabled: true,
+            min_fee: 10,
+            max_fee: 100,
+            max_volatility: 500,
+            lookback: 5,
+        })
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Only the vault should configure the dynamic fee");
+
+    let invalid_configs = [
+        (20, 10, 500, 5),
+        (10, 1_001, 500, 5),
+        (10, 100, 0, 5),
+        (10, 100, 500, 1),
+        // Scanning more than 32 intervals could push swaps out of gas
+        (10, 100, 500, 33),
+    ];
+    for (min_fee, max_fee, max_volatility, lookback) in invalid_configs {
+        let is_err = fixture.vault_instance
+            .methods()
+            .set_pool_dynamic_fee(exchange_id, true, min_fee, max_fee, max_volatility, lookback)
+            .set_contracts(&[fixture.exchange_contract_id.clone()])
+            .call()
+            .await
+            .is_err();
+        assert!(is_err, "Invalid config {:?} should be rejected", (min_fee, max_fee, max_volatility, lookback));
+    }
+
+    fixture.vault_instance
+        .methods()
+        .set_pool_dynamic_fee(exchange_id, true, 10, 100, 500, 5)
+        .set_contracts(&[fixture.exchange_contract_id.clone()])
+        .call()
+        .await
+        .unwrap();
+
+    // Without enough history the pool charges the minimum
+    let result = fixture.exchange_instance.methods().get_lp_fee_info().call().await.unwrap();
+    assert_eq!(result.value.current_fee, 10);
+    assert_eq!(result.value.volatility, 0);
+    assert_eq!(result.value.config.max_fee, 100);
+
+    fixture.vault_instance
+        .methods()
+        .set_pool_dynamic_fee(exchange_id, false, 0, 0, 0, 0)
+        .set_contracts(&[fixture.exchange_contract_id.clone()])
+        .call()
+        .await
+        .unwrap();
+
+    let result = fixture.exchange_instance.methods().get_lp_fee_info().call().await.unwrap();
+    assert_eq!(result.value.current_fee, 30);
+}
+
+#[tokio::test]
+async fn dynamic_fee_simulation() {
+    let fixture = setup().await;
+
+    let mut now = Utc::now().timestamp();
+    produce_block_at(&fixture.wallet, now).await;
+
+    add_liquidity(&fixture, to_9_decimal(100), to_9_decimal(100)).await;
+    fixture.exchange_instance
+        .methods()
+        .expand_twap_buffer(6)
+        .call()
+        .await
+        .unwrap();
+
+    // 0.1% while stable, rising to 1% once volatility reaches 5%
+    fixture.vault_instance
+        .methods()
+        .set_pool_dynamic_fee(Bits256(fixture.exchange_contract_id.hash().into()), true, 10, 100, 500, 5)
+        .set_contracts(&[fixture.exchange_contract_id.clone()])
+        .call()
+        .await
+        .unwrap();
+
+    // Calm market: small trades back and forth every minute
+    let mut calm_fees = vec![];
+    for i in 0..8 {
+        now += 60;
+        produce_block_at(&fixture.wallet, now).await;
+        calm_fees.push(swap_at_quoted_fee(&fixture, to_9_decimal(1) / 10, i % 2 == 0).await);
+    }
+
+    let calm_info = fixture.exchange_instance.methods().get_lp_fee_info().call().await.unwrap().value;
+    assert!(calm_info.volatility < 50, "Calm volatility was {}", calm_info.volatility);
+    assert!(calm_info.current_fee < 20, "Calm fee was {}", calm_info.current_fee);
+    assert!(calm_fees.iter().all(|fee| *fee >= 10 && *fee < 20), "Calm fees were {:?}", calm_fees);
+
+    // Turbulent market: large trades swinging the price by around 20% every minute
+    let mut turbulent_fees = vec![];
+    for i in 0..8 {
+        now += 60;
+        produce_block_at(&fixture.wallet, now).await;
+        turbulent_fees.push(swap_at_quoted_fee(&fixture, to_9_decimal(10), i % 2 == 0).await);
+    }
+
+    let turbulent_info = fixture.exchange_instance.methods().get_lp_fee_info().call().await.unwrap().value;
+    assert!(turbulent_info.volatility >= 500, "Turbulent volatility was {}", turbulent_info.volatility);
+    // Capped at the maximum, however volatile
+    assert_eq!(turbulent_info.current_fee, 100);
+    assert!(turbulent_fees.first() < turbulent_fees.last(), "Fees should rise with volatility: {:?}", turbulent_fees);
+
+    // Once the turbulence leaves the buffer, fees fall back
+    for i in 0..8 {
+        now += 60;
+        produce_block_at(&fixture.wallet, now).await;
+        swap_at_quoted_fee(&fixture, to_9_decimal(1) / 10, i % 2 == 0).await;
+    }
+
+    let recovered_info = fixture.exchange_instance.methods().get_lp_fee_info().call().await.unwrap().value;
+    assert!(recovered_info.current_fee < 20, "Fee stayed at {}", recovered_info.current_fee);
+}
+
+const ORDER_INTERVAL: u64 = 3600;
+
+/// Convert a TAI64 timestamp from the contract to unix time
+fn tai64_to_unix(tai64_time: u64) -> i64 {
+    (tai64_time - unix_to_tai64(0)) as i64
+}
+
+/// Place a long-term order, returning its ID and the amount it will sell
+async fn place_long_term_order(fixture: &Fixture, wallet: &WalletUnlocked, amount: u64, token_0_in: bool, intervals: u64) -> (u64, u64) {
+    let asset_id = if token_0_in { BASE_ASSET_ID } else { fixture.token_asset_id };
+    let response = Exchange::new(fixture.exchange_contract_id.clone(), wallet.clone())
+        .methods()
+        .place_long_term_order(intervals, Identity::Address(wallet.address().into()))
+        .call_params(CallParameters::new(Some(amount), Some(asset_id), Some(100_000_000)))
+        .tx_params(TxParameters {
+            gas_price: 0,
+            gas_limit: 100_000_000,
+            maturity: 0,
+        })
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .unwrap();
+
+    let logs = get_logs::<LongTermOrderPlaced, _>(&response, &fixture.exchange_contract_id, EXCHANGE_ABI);
+    assert_eq!(logs.len(), 1);
+    (response.value, logs.get(0).unwrap().amount)
+}
+
+async fn withdraw_proceeds(fixture: &Fixture, order_id: u64) -> u64 {
+    fixture.exchange_instance
+        .methods()
+        .withdraw_proceeds(order_id, Identity::Address(fixture.wallet.address().into()))
+        .tx_params(TxParameters {
+            gas_price: 0,
+            gas_limit: 100_000_000,
+            maturity: 0,
+        })
+        .call_params(CallParameters::new(None, None, Some(100_000_000)))
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .unwrap()
+        .value
+}
+
+#[tokio::test]
+async fn long_term_order_sells_gradually() {
+    let fixture = setup().await;
+    produce_block_at(&fixture.wallet, Utc::now().timestamp()).await;
+    add_liquidity(&fixture, to_9_decimal(100), to_9_decimal(100)).await;
+
+    let is_err = fixture.exchange_instance
+        .methods()
+        .place_long_term_order(0, Identity::Address(fixture.wallet.address().into()))
+        .call_params(CallParameters::new(Some(to_9_decimal(10)), None, None))
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Orders must last at least one interval");
+
+    let (order_id, amount) = place_long_term_order(&fixture, &fixture.wallet, to_9_decimal(10), true, 2).await;
+    // Only dust is returned when the amount doesn't divide into a sale rate
+    assert!(amount <= to_9_decimal(10) && amount > to_9_decimal(10) - ORDER_INTERVAL * 2);
+
+    let order = fixture.exchange_instance.methods().get_long_term_order(order_id).call().await.unwrap().value;
+    assert!(order.token_0_in);
+    assert_eq!(order.expiry % ORDER_INTERVAL, 0);
+    let info = fixture.exchange_instance.methods().get_twamm_info().call().await.unwrap().value;
+    assert_eq!(info.sale_rate_0, order.sale_rate);
+    assert_eq!(info.token_0_held, amount);
+
+    // Partway through, some has been sold into the pool
+    produce_block_at(&fixture.wallet, tai64_to_unix(order.expiry - ORDER_INTERVAL)).await;
+    fixture.exchange_instance
+        .methods()
+        .execute_virtual_orders(24)
+        .call()
+        .await
+        .unwrap();
+    let pool_info = fixture.exchange_instance.methods().get_pool_info().call().await.unwrap().value;
+    assert!(pool_info.token_0_reserve > to_9_decimal(100) && pool_info.token_0_reserve < to_9_decimal(100) + amount);
+    assert!(pool_info.token_1_reserve < to_9_decimal(100));
+    let partial_proceeds = fixture.exchange_instance.methods().get_order_proceeds(order_id).call().await.unwrap().value;
+    assert!(partial_proceeds > 0);
+    assert_eq!(partial_proceeds, to_9_decimal(100) - pool_info.token_1_reserve);
+
+    // After the expiry, everything has been sold
+    produce_block_at(&fixture.wallet, tai64_to_unix(order.expiry) + 60).await;
+    let starting_balance = fixture.wallet.get_asset_balance(&fixture.token_asset_id).await.unwrap();
+    let proceeds = withdraw_proceeds(&fixture, order_id).await;
+    let end_balance = fixture.wallet.get_asset_balance(&fixture.token_asset_id).await.unwrap();
+    assert_eq!(end_balance - starting_balance, proceeds);
+
+    // Selling gradually does at least as well as selling at once, since the fees stay in the pool
+    let one_shot = get_amount_out(amount, to_9_decimal(100), to_9_decimal(100), 30);
+    assert!(proceeds >= one_shot && proceeds < one_shot * 1005 / 1000, "Proceeds {} vs one swap {}", proceeds, one_shot);
+
+    let pool_info = fixture.exchange_instance.methods().get_pool_info().call().await.unwrap().value;
+    assert_eq!(pool_info.token_0_reserve, to_9_decimal(100) + amount);
+
+    let info = fixture.exchange_instance.methods().get_twamm_info().call().await.unwrap().value;
+    assert_eq!(info.sale_rate_0, 0);
+    assert_eq!(info.token_0_held, 0);
+    assert!(info.token_1_held < 10, "Only rounding dust should be left, found {}", info.token_1_held);
+
+    // Proceeds can only be withdrawn once
+    assert_eq!(withdraw_proceeds(&fixture, order_id).await, 0);
+}
+
+#[tokio::test]
+async fn opposing_long_term_orders_trade_with_each_other() {
+    let fixture = setup().await;
+    produce_block_at(&fixture.wallet, Utc::now().timestamp()).await;
+    add_liquidity(&fixture, to_9_decimal(100), to_9_decimal(100)).await;
+
+    let (order_0_id, amount_0) = place_long_term_order(&fixture, &fixture.wallet, to_9_decimal(10), true, 2).await;
+    let (order_1_id, amount_1) = place_long_term_order(&fixture, &fixture.wallet, to_9_decimal(10), false, 2).await;
+
+    let order = fixture.exchange_instance.methods().get_long_term_order(order_0_id).call().await.unwrap().value;
+    produce_block_at(&fixture.wallet, tai64_to_unix(order.expiry) + 60).await;
+
+    // Matched at the pool's price, without paying the LP fee on the matched amounts
+    let proceeds_0 = withdraw_proceeds(&fixture, order_0_id).await;
+    let proceeds_1 = withdraw_proceeds(&fixture, order_1_id).await;
+    assert!(proceeds_0 > amount_0 * 999 / 1000, "Token 0 seller received {} for {}", proceeds_0, amount_0);
+    assert!(proceeds_1 > amount_1 * 999 / 1000, "Token 1 seller received {} for {}", proceeds_1, amount_1);
+
+    // The price barely moved
+    let pool_info = fixture.exchange_instance.methods().get_pool_info().call().await.unwrap().value;
+    assert!(pool_info.token_0_reserve > to_9_decimal(100) * 999 / 1000 && pool_info.token_0_reserve < to_9_decimal(100) * 1001 / 1000);
+    assert!(pool_info.token_1_reserve > to_9_decimal(100) * 999 / 1000 && pool_info.token_1_reserve < to_9_decimal(100) * 1001 / 1000);
+}
+
+#[tokio::test]
+async fn cancel_long_term_order() {
+    let fixture = setup().await;
+    let wallets = get_wallets().await;
+    let other_wallet = wallets.get(1).unwrap().clone();
+    produce_block_at(&fixture.wallet, Utc::now().timestamp()).await;
+    add_liquidity(&fixture, to_9_decimal(100), to_9_decimal(100)).await;
+
+    let (order_id, amount) = place_long_term_order(&fixture, &fixture.wallet, to_9_decimal(10), true, 4).await;
+    let order = fixture.exchange_instance.methods().get_long_term_order(order_id).call().await.unwrap().value;
+
+    let is_err = Exchange::new(fixture.exchange_contract_id.clone(), other_wallet.clone())
+        .methods()
+        .cancel_long_term_order(order_id, Identity::Address(other_wallet.address().into()))
+        .append_variable_outputs(2)
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Only the owner can cancel an order");
+
+    let is_err = Exchange::new(fixture.exchange_contract_id.clone(), other_wallet.clone())
+        .methods()
+        .withdraw_proceeds(order_id, Identity::Address(other_wallet.address().into()))
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Only the owner can withdraw proceeds");
+
+    // Cancel with two intervals left
+    produce_block_at(&fixture.wallet, tai64_to_unix(order.expiry - ORDER_INTERVAL * 2)).await;
+    let starting_balance = fixture.wallet.get_asset_balance(&BASE_ASSET_ID).await.unwrap();
+    let response = fixture.exchange_instance
+        .methods()
+        .cancel_long_term_order(order_id, Identity::Address(fixture.wallet.address().into()))
+        .tx_params(TxParameters {
+            gas_price: 0,
+            gas_limit: 100_000_000,
+            maturity: 0,
+        })
+        .call_params(CallParameters::new(None, None, Some(100_000_000)))
+        .append_variable_outputs(2)
+        .call()
+        .await
+        .unwrap();
+    let (unsold_amount, proceeds) = response.value;
+    let end_balance = fixture.wallet.get_asset_balance(&BASE_ASSET_ID).await.unwrap();
+    assert_eq!(end_balance - starting_balance, unsold_amount);
+    assert!(proceeds > 0);
+
+    // Roughly half was left to sell
+    assert!(unsold_amount < amount / 2 && unsold_amount > amount * 2 / 5, "Unsold {} of {}", unsold_amount, amount);
+    let pool_info = fixture.exchange_instance.methods().get_pool_info().call().await.unwrap().value;
+    assert_eq!(pool_info.token_0_reserve, to_9_decimal(100) + amount - unsold_amount);
+
+    let logs = get_logs::<LongTermOrderCancelled, _>(&response, &fixture.exchange_contract_id, EXCHANGE_ABI);
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs.get(0).unwrap().unsold_amount, unsold_amount);
+
+    let info = fixture.exchange_instance.methods().get_twamm_info().call().await.unwrap().value;
+    assert_eq!(info.sale_rate_0, 0);
+    assert_eq!(info.token_0_held, 0);
+
+    let is_err = fixture.exchange_instance
+        .methods()
+        .cancel_long_term_order(order_id, Identity::Address(fixture.wallet.address().into()))
+        .append_variable_outputs(2)
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Orders can only be cancelled once");
+    assert_eq!(withdraw_proceeds(&fixture, order_id).await, 0);
+
+    // Nothing more is sold after the cancellation
+    produce_block_at(&fixture.wallet, tai64_to_unix(order.expiry) + 60).await;
+    let later_pool_info = fixture.exchange_instance.methods().get_pool_info().call().await.unwrap().value;
+    assert_eq!(later_pool_info.token_0_reserve, pool_info.token_0_reserve);
+}
+
+#[tokio::test]
+async fn swaps_include_executed_long_term_orders() {
+    let fixture = setup().await;
+    produce_block_at(&fixture.wallet, Utc::now().timestamp()).await;
+    add_liquidity(&fixture, to_9_decimal(100), to_9_decimal(100)).await;
+
+    let (order_id, _amount) = place_long_term_order(&fixture, &fixture.wallet, to_9_decimal(10), true, 2).await;
+    let order = fixture.exchange_instance.methods().get_long_term_order(order_id).call().await.unwrap().value;
+    produce_block_at(&fixture.wallet, tai64_to_unix(order.expiry - ORDER_INTERVAL)).await;
+
+    // Nobody has touched the pool since the order was placed, but the quotes include its sales
+    let pool_info = fixture.exchange_instance.methods().get_pool_info().call().await.unwrap().value;
+    assert!(pool_info.token_0_reserve > to_9_decimal(100));
+    let stored = fixture.exchange_instance.methods().get_twamm_info().call().await.unwrap().value;
+    assert!(stored.last_virtual_order_time < order.expiry - ORDER_INTERVAL);
+
+    // A swap buying the cheaper token 0 at the quote settles the orders first. The orders keep
+    // selling token 0 until the swap lands, so it can only get cheaper.
+    let amount_out = get_amount_out(to_9_decimal(1), pool_info.token_1_reserve, pool_info.token_0_reserve, 30);
+    fixture.exchange_instance
+        .methods()
+        .swap(amount_out, 0, Identity::Address(fixture.wallet.address().into()))
+        .set_contracts(&[fixture.vault_contract_id.clone()])
+        .call_params(CallParameters::new(Some(to_9_decimal(1)), Some(fixture.token_asset_id), None))
+        .tx_params(TxParameters {
+            gas_price: 0,
+            gas_limit: 100_000_000,
+            maturity: 0,
+        })
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .unwrap();
+    let info = fixture.exchange_instance.methods().get_twamm_info().call().await.unwrap().value;
+    assert!(info.last_virtual_order_time >= order.expiry - ORDER_INTERVAL);
+    assert_eq!(info.sale_rate_0, order.sale_rate);
+}
+
+/// Swap token 1 for a small amount of token 0, returning whether it succeeded
+async fn try_small_swap(fixture: &Fixture) -> bool {
+    fixture.exchange_instance
+        .methods()
+        .swap(1_000, 0, Identity::Address(fixture.wallet.address().into()))
+        .set_contracts(&[fixture.vault_contract_id.clone()])
+        .call_params(CallParameters::new(Some(to_9_decimal(1) / 100), Some(fixture.token_asset_id), None))
+        .tx_params(TxParameters {
+            gas_price: 0,
+            gas_limit: 100_000_000,
+            maturity: 0,
+        })
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .is_ok()
+}
+
+async fn execute_virtual_orders(fixture: &Fixture, max_intervals: u64) -> bool {
+    fixture.exchange_instance
+        .methods()
+        .execute_virtual_orders(max_intervals)
+        .tx_params(TxParameters {
+            gas_price: 0,
+            gas_limit: 100_000_000,
+            maturity: 0,
+        })
+        .call()
+        .await
+        .unwrap()
+        .value
+}
+
+#[tokio::test]
+async fn idle_pools_catch_up_in_pieces() {
+    let fixture = setup().await;
+    produce_block_at(&fixture.wallet, Utc::now().timestamp()).await;
+    add_liquidity(&fixture, to_9_decimal(100), to_9_decimal(100)).await;
+
+    let (order_id, _amount) = place_long_term_order(&fixture, &fixture.wallet, to_9_decimal(10), true, 48).await;
+    let order = fixture.exchange_instance.methods().get_long_term_order(order_id).call().await.unwrap().value;
+
+    // Idle for over a day of intervals, more than other calls will execute
+    produce_block_at(&fixture.wallet, tai64_to_unix(order.expiry - ORDER_INTERVAL * 10)).await;
+    assert!(!try_small_swap(&fixture).await, "Swaps can't skip pending intervals");
+
+    // Each piece only executes up to its limit
+    assert!(!execute_virtual_orders(&fixture, 10).await);
+    let info = fixture.exchange_instance.methods().get_twamm_info().call().await.unwrap().value;
+    assert!(info.last_virtual_order_time < order.expiry - ORDER_INTERVAL * 10);
+    assert_eq!(info.last_virtual_order_time % ORDER_INTERVAL, 0);
+
+    assert!(execute_virtual_orders(&fixture, 30).await);
+    let info = fixture.exchange_instance.methods().get_twamm_info().call().await.unwrap().value;
+    assert!(info.last_virtual_order_time >= order.expiry - ORDER_INTERVAL * 10);
+
+    // Once caught up, the pool works as normal
+    assert!(try_small_swap(&fixture).await);
+}
+
+#[tokio::test]
+async fn long_term_orders_pay_protocol_fees() {
+    let fixture = setup().await;
+
+    // A constant 1% protocol fee
+    set_vault_fees(&fixture, 10000, 0, Utc::now().timestamp()).await;
+    add_liquidity(&fixture, to_9_decimal(100), to_9_decimal(100)).await;
+    fixture.exchange_instance
+        .methods()
+        .cache_vault_fees()
+        .set_contracts(&[fixture.vault_contract_id.clone()])
+        .call()
+        .await
+        .unwrap();
+
+    // Opposing orders, so part of each side is matched rather than swapped
+    let (order_0_id, amount_0) = place_long_term_order(&fixture, &fixture.wallet, to_9_decimal(10), true, 2).await;
+    let (_order_1_id, amount_1) = place_long_term_order(&fixture, &fixture.wallet, to_9_decimal(5), false, 2).await;
+
+    let order = fixture.exchange_instance.methods().get_long_term_order(order_0_id).call().await.unwrap().value;
+    produce_block_at(&fixture.wallet, tai64_to_unix(order.expiry) + 60).await;
+    execute_virtual_orders(&fixture, 24).await;
+
+    // Both sides paid 1% of everything they sold
+    let vault_info = fixture.exchange_instance.methods().get_vault_info().call().await.unwrap().value;
+    assert!(vault_info.token0_protocol_fees_collected <= amount_0 / 100 && vault_info.token0_protocol_fees_collected > amount_0 / 100 - 10);
+    assert!(vault_info.token1_protocol_fees_collected <= amount_1 / 100 && vault_info.token1_protocol_fees_collected > amount_1 / 100 - 10);
+}
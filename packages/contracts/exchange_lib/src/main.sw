@@ -0,0 +1,687 @@
+library exchange_lib;
+
+use std::{
+    auth::msg_sender,
+    block::timestamp,
+    call_frames::contract_id,
+    constants::ZERO_B256,
+    context::this_balance,
+    contract_id::ContractId,
+    hash::sha256,
+    identity::Identity,
+    logging::log,
+    revert::require,
+    storage::{get, store},
+    token::{burn, mint, transfer},
+    u128::U128,
+    u256::U256,
+};
+
+use exchange_abi::{
+    DynamicFeeConfig,
+    FeeCacheInfo,
+    FeeInfo,
+    LiquidityAdded,
+    LiquidityRemoved,
+    LPFeeInfo,
+    Observation,
+    ProtocolFeeCollected,
+    ProtocolFeeWithdrawn,
+    RemoveLiquidityInfo,
+    TWAPInfo,
+    UpdateReserves,
+    VaultInfo,
+};
+use microchain_helpers::{calculate_vault_fee, identity_to_b256, mutiply_div};
+use vault_abi::Vault;
+
+// State and logic shared by every pool implementing the Exchange interface: reserves and LP
+// supply, the vault's fee cache and protocol fees, the dynamic LP fee and the TWAP buffer.
+// Pools only add their curve's pricing and invariant on top.
+
+enum Error {
+    AlreadyInitialized: (),
+    InsufficentOutput: (),
+    InsufficentLiquidity: (),
+    InsufficentInput: (),
+    InsufficentLiquidityMinted: (),
+    InsufficentLiquidityBurned: (),
+    MustBeCalledByVault: (),
+    TWAPOutOfRange: (),
+    InvalidFeeConfig: (),
+}
+
+////////////////////////////////////////
+// Constants
+////////////////////////////////////////
+const TOKEN_0_SLOT = 0x0000000000000000000000000000000000000000000000000000000000000000;
+const TOKEN_1_SLOT = 0x0000000000000000000000000000000000000000000000000000000000000001;
+
+/// Minimum ETH liquidity to open a pool.
+const MINIMUM_LIQUIDITY = 1000;
+
+/// LP fees are in basis points
+const BASIS_POINTS = 10_000;
+
+/// Protocol fees are out of 1,000,000, so 10,000 = 1%
+const PROTOCOL_FEE_PRECISION = 1_000_000;
+
+/// LP fee charged unless the dynamic fee is enabled, 0.3%
+const LP_FEE = 30;
+
+/// Highest LP fee the dynamic fee can be configured to, 10%
+const MAX_LP_FEE = 1_000;
+
+/// Most observation intervals the dynamic fee reads on each swap, however large the TWAP buffer grows
+const MAX_VOLATILITY_LOOKBACK = 32;
+
+/// Cached vault fees older than this are refreshed on swap, until the vault sets another age
+const DEFAULT_MAX_FEE_CACHE_AGE = 3600;
+
+////////////////////////////////////////
+// Storage keys
+////////////////////////////////////////
+// Libraries can't declare storage, so the shared state lives at fixed keys, each the sha256 of
+// "exchange_lib.<name>". Pools can still use their own storage block alongside these.
+const TOKEN_0_RESERVE_KEY = 0x300dec8b836d26d28de69976d1d81b3cd9b5adaf6e0fa957e8af9ef39b397bd2;
+const TOKEN_1_RESERVE_KEY = 0x9265494f990f77b95b141d41e5384495a01dc2c475e37099db7f308470ada4ec;
+const LP_TOKEN_SUPPLY_KEY = 0x18a7af0c976821fa6fac7ecd566b8388990501fb0c4af5fb685083f8050f80c1;
+const TOKEN_0_PROTOCOL_FEES_KEY = 0xfa6a420ed31c79556cf16a759da65422a9afdc37756ad9afb34962d432f20360;
+const TOKEN_1_PROTOCOL_FEES_KEY = 0x40f99b0c30b239c88ef75423cb849c47864607c8a21f1b1646f438aaa7900a31;
+const VAULT_KEY = 0x6fbaa761d5b19c0c6f2a28b43405e8f83c0ce7631bb3be9de2f80e6c9a42537b;
+const VAULT_FEE_KEY = 0xd2281d55e3a45ade231146257a33bf67394959c21d08312e9bf7056aa88cb9fc;
+const MAX_FEE_CACHE_AGE_KEY = 0xbdf87a2cd4e9fdcfc3b8be68fc95b97cb8d2b9c602ffc2ee63a6bd3bd3fac4ef;
+const DYNAMIC_FEE_KEY = 0xc8712a3b4ba0ec450bee5d10015b4b7d0eeca48fb95cc7a00610012fb2ed3fe0;
+// the most-recently updated index of the TWAP buffer
+const TWAP_CURRENT_ELEMENT_KEY = 0xa1ee632349ee08d438b7b4d8f3303b9ed210a155fa63033f3e8ca8dda29d650e;
+// the current maximum number of observations that are being stored
+const TWAP_BUFFER_SIZE_KEY = 0x06b062cf46404aa046b6aa77140a9da82171cfb38a3f6191a92408c9a324f98d;
+// the next maximum number of observations to store, triggered in observations.write
+const TWAP_NEXT_BUFFER_SIZE_KEY = 0xd2323ed2793ac0ab5b02faa5b8fe8d21ed12aa0d31e0bc7f152fd0e034f90cc2;
+// Observations are stored at sha256((TWAP_BUFFER_KEY, slot)), like a StorageMap
+const TWAP_BUFFER_KEY = 0xb96e2a1f762aa9ba996489fdddfd0fe28e9d05b2b5367955339e336c5e0b6e6f;
+
+// A copy of the vault's fee schedule for this pool
+struct VaultFee {
+    // These values should be divided by 1,000,000 to get the rate. So 10,000 = 1%
+    start_fee: u16,
+    change_rate: u16,
+    // TODO: directional changes
+    // fee_increasing: bool,
+    // When the vault started the schedule
+    start_time: u32,
+    // When the schedule was cached, to detect stale caches
+    update_time: u32,
+}
+
+////////////////////////////////////////
+// Reserves and balances
+////////////////////////////////////////
+#[storage(read)]
+pub fn get_tokens() -> (b256, b256) {
+    (get::<b256>(TOKEN_0_SLOT), get::<b256>(TOKEN_1_SLOT))
+}
+
+#[storage(read)]
+pub fn get_reserves() -> (u64, u64) {
+    (get::<u64>(TOKEN_0_RESERVE_KEY), get::<u64>(TOKEN_1_RESERVE_KEY))
+}
+
+#[storage(read)]
+pub fn get_lp_token_supply() -> u64 {
+    get::<u64>(LP_TOKEN_SUPPLY_KEY)
+}
+
+/// The pool's token balances, less the protocol fees held for the vault
+#[storage(read)]
+pub fn get_pool_balance() -> (u64, u64) {
+    let (token0, token1) = get_tokens();
+    let balance_0 = this_balance(ContractId::from(token0)) - get::<u64>(TOKEN_0_PROTOCOL_FEES_KEY);
+    let balance_1 = this_balance(ContractId::from(token1)) - get::<u64>(TOKEN_1_PROTOCOL_FEES_KEY);
+    (balance_0, balance_1)
+}
+
+/// Store new reserves. Pools record the TWAP observation for the old reserves first, with `write_observation`.
+#[storage(write)]
+pub fn set_reserves(reserve0: u64, reserve1: u64) {
+    store(TOKEN_0_RESERVE_KEY, reserve0);
+    store(TOKEN_1_RESERVE_KEY, reserve1);
+
+    log(UpdateReserves {
+        amount_0: reserve0,
+        amount_1: reserve1,
+    })
+}
+
+/// Mint LP tokens to `recipient` for the tokens sent since the reserves were stored, given the pool's
+/// balances. The first deposit opens the TWAP buffer and burns MINIMUM_LIQUIDITY. Pools store the
+/// balances as the new reserves afterwards.
+#[storage(read, write)]
+pub fn mint_liquidity(balance_0: u64, balance_1: u64, recipient: Identity) -> u64 {
+    let total_liquidity = get_lp_token_supply();
+    let (token_0_reserve, token_1_reserve) = get_reserves();
+    let token_0_sent = balance_0 - token_0_reserve;
+    let token_1_sent = balance_1 - token_1_reserve;
+
+    require(token_0_sent > 0 && token_1_sent > 0, Error::InsufficentInput);
+
+    let mut minted: u64 = 0;
+    if total_liquidity > 0 {
+        let token0_liquidity = mutiply_div(token_0_sent, total_liquidity, token_0_reserve);
+        let token1_liquidity = mutiply_div(token_1_sent, total_liquidity, token_1_reserve);
+
+        minted = if (token0_liquidity < token1_liquidity) {
+            token0_liquidity
+        } else {
+            token1_liquidity
+        };
+
+        mint(minted);
+        store(LP_TOKEN_SUPPLY_KEY, total_liquidity + minted);
+    } else {
+        let big_amount_0 = U128::from((0, balance_0));
+        let big_amount_1 = U128::from((0, balance_1));
+        let initial_liquidity = sqrt_by_digit(big_amount_0 * big_amount_1).as_u64().unwrap() - MINIMUM_LIQUIDITY;
+
+        // Ensure there's at least 1 TWAP slot
+        store(TWAP_BUFFER_SIZE_KEY, 1);
+        store(TWAP_NEXT_BUFFER_SIZE_KEY, 1);
+        store(get_observation_key(0), Observation {
+            timestamp: timestamp(),
+            price_0_cumulative_last: U256::min(),
+            price_1_cumulative_last: U256::min(),
+        });
+
+        // Mint LP token
+        mint(initial_liquidity);
+        store(LP_TOKEN_SUPPLY_KEY, initial_liquidity + MINIMUM_LIQUIDITY);
+
+        minted = initial_liquidity;
+
+        // Log the liquidity that's burned
+        log(LiquidityAdded {
+            sender: identity_to_b256(msg_sender().unwrap()),
+            amount_0: 0,
+            amount_1: 0,
+            lp_tokens: MINIMUM_LIQUIDITY,
+            recipient: b256::min(),
+        });
+    };
+    require(minted > 0, Error::InsufficentLiquidityMinted);
+
+    transfer(minted, contract_id(), recipient);
+
+    log(LiquidityAdded {
+        sender: identity_to_b256(msg_sender().unwrap()),
+        amount_0: token_0_sent,
+        amount_1: token_1_sent,
+        lp_tokens: minted,
+        recipient: identity_to_b256(recipient),
+    });
+
+    minted
+}
+
+/// Burn the LP tokens sent to the pool, paying `recipient` their share of the pool's balances.
+/// Pools store the remaining balances as the new reserves afterwards.
+#[storage(read, write)]
+pub fn burn_liquidity(balance_0: u64, balance_1: u64, recipient: Identity) -> RemoveLiquidityInfo {
+    let (token0, token1) = get_tokens();
+
+    let lp_tokens = this_balance(contract_id());
+    require(lp_tokens > 0, Error::InsufficentInput);
+
+    let total_liquidity = get_lp_token_supply();
+
+    // using balances ensures pro-rata distribution
+    let amount0 = mutiply_div(lp_tokens, balance_0, total_liquidity);
+    let amount1 = mutiply_div(lp_tokens, balance_1, total_liquidity);
+    require(amount0 > 0 && amount1 > 0, Error::InsufficentLiquidityBurned);
+
+    burn(lp_tokens);
+    store(LP_TOKEN_SUPPLY_KEY, total_liquidity - lp_tokens);
+
+    transfer(amount0, ContractId::from(token0), recipient);
+    transfer(amount1, ContractId::from(token1), recipient);
+
+    log(LiquidityRemoved {
+        sender: identity_to_b256(msg_sender().unwrap()),
+        amount_0: amount0,
+        amount_1: amount1,
+        lp_tokens: lp_tokens,
+        recipient: identity_to_b256(recipient),
+    });
+
+    RemoveLiquidityInfo {
+        token_0_amount: amount0,
+        token_1_amount: amount1,
+    }
+}
+
+/// Send a swap's outputs to `recipient`, then measure its inputs from the pool's balances, less `held_0`
+/// and `held_1` which belong to neither the reserves nor the vault. Takes the protocol fee from the inputs,
+/// returning the balances and the inputs left for the pool to check its invariant against.
+#[storage(read, write)]
+pub fn settle_swap(
+    amount_0_out: u64,
+    amount_1_out: u64,
+    recipient: Identity,
+    held_0: u64,
+    held_1: u64,
+) -> (u64, u64, u64, u64) {
+    require(amount_0_out > 0 || amount_1_out > 0, Error::InsufficentOutput);
+    let (token0, token1) = get_tokens();
+    let (token_0_reserve, token_1_reserve) = get_reserves();
+
+    require(amount_0_out < token_0_reserve && amount_1_out < token_1_reserve, Error::InsufficentLiquidity);
+
+    if is_fee_cache_stale() {
+        cache_vault_fees();
+    }
+
+    if (amount_0_out > 0) {
+        transfer(amount_0_out, ContractId::from(token0), recipient);
+    }
+    if (amount_1_out > 0) {
+        transfer(amount_1_out, ContractId::from(token1), recipient);
+    }
+    let (pool_balance_0, pool_balance_1) = get_pool_balance();
+    let balance_0 = pool_balance_0 - held_0;
+    let balance_1 = pool_balance_1 - held_1;
+
+    let (amount0_in, amount0_protocol_fee) = if balance_0 > token_0_reserve - amount_0_out {
+        process_protocol_fee(balance_0 - (token_0_reserve - amount_0_out), true)
+    } else {
+        (0, 0)
+    };
+    let (amount1_in, amount1_protocol_fee) = if balance_1 > token_1_reserve - amount_1_out {
+        process_protocol_fee(balance_1 - (token_1_reserve - amount_1_out), false)
+    } else {
+        (0, 0)
+    };
+
+    require(amount0_in > 0 || amount1_in > 0, Error::InsufficentInput);
+
+    (balance_0 - amount0_protocol_fee, balance_1 - amount1_protocol_fee, amount0_in, amount1_in)
+}
+
+pub fn sqrt_by_digit(n: U128) -> U128 {
+    let mut x = n;
+    let mut c = U128::min();
+    let mut d = U128::from((0, 1)) << U128::bits() - 2;
+
+    while d > n {
+        d = d >> 2;
+    }
+
+    while d != U128::min() {
+        if x > c + d || x == c + d { // TODO: gte
+            x = x - (c + d);
+            c = (c >> 1) + d;
+        } else {
+            c = c >> 1;
+        }
+        d  = d >> 2;
+    }
+
+    c
+}
+
+////////////////////////////////////////
+// Vault and protocol fees
+////////////////////////////////////////
+#[storage(read, write)]
+pub fn initialize(new_vault: b256) {
+    require(get::<b256>(VAULT_KEY) == ZERO_B256, Error::AlreadyInitialized);
+    store(VAULT_KEY, new_vault);
+    store(MAX_FEE_CACHE_AGE_KEY, DEFAULT_MAX_FEE_CACHE_AGE);
+    store(DYNAMIC_FEE_KEY, DynamicFeeConfig {
+        enabled: false,
+        min_fee: LP_FEE,
+        max_fee: LP_FEE,
+        max_volatility: 0,
+        lookback: 0,
+    });
+    cache_vault_fees();
+}
+
+#[storage(read)]
+fn require_vault() {
+    let sender: Identity = msg_sender().unwrap();
+    require(identity_to_b256(sender) == get::<b256>(VAULT_KEY), Error::MustBeCalledByVault);
+}
+
+#[storage(read, write)]
+pub fn cache_vault_fees() {
+    let vault = abi(Vault, get::<b256>(VAULT_KEY));
+    let vault_fees = vault.get_fees_for(contract_id().value);
+    store(VAULT_FEE_KEY, VaultFee {
+        start_fee: vault_fees.start_fee,
+        change_rate: vault_fees.change_rate,
+        start_time: vault_fees.start_time,
+        update_time: timestamp(),
+    });
+}
+
+#[storage(read)]
+pub fn is_fee_cache_stale() -> bool {
+    get::<b256>(VAULT_KEY) != ZERO_B256 && timestamp() - get::<VaultFee>(VAULT_FEE_KEY).update_time > get::<u64>(MAX_FEE_CACHE_AGE_KEY)
+}
+
+#[storage(read)]
+pub fn get_current_fee() -> u64 {
+    let fee_info = get::<VaultFee>(VAULT_FEE_KEY);
+    calculate_vault_fee(fee_info.start_fee, fee_info.change_rate, fee_info.start_time, timestamp())
+}
+
+/// Protocol fee the next swap will charge, which re-reads stale fees from the vault
+#[storage(read)]
+pub fn get_next_protocol_fee() -> u64 {
+    if is_fee_cache_stale() {
+        abi(Vault, get::<b256>(VAULT_KEY)).get_fees_for(contract_id().value).current_fee
+    } else {
+        get_current_fee()
+    }
+}
+
+/// Protocol fee owed on `amount`, with `fee_rate` out of 1,000,000
+pub fn get_protocol_fee_amount(amount: u64, fee_rate: u64) -> u64 {
+    mutiply_div(amount, fee_rate, PROTOCOL_FEE_PRECISION)
+}
+
+/// Smallest input that leaves at least `amount` once the protocol fee at `fee_rate` is taken
+pub fn add_protocol_fee(amount: u64, fee_rate: u64) -> u64 {
+    if fee_rate == 0 {
+        return amount;
+    }
+    let precision = U128::from((0, PROTOCOL_FEE_PRECISION));
+    let divisor = precision - U128::from((0, fee_rate));
+    ((U128::from((0, amount)) * precision + divisor - U128::from((0, 1))) / divisor).as_u64().unwrap()
+}
+
+/// Hold protocol fees for the vault, which leave the pool's balances
+#[storage(read, write)]
+pub fn collect_protocol_fees(amount_0: u64, amount_1: u64) {
+    store(TOKEN_0_PROTOCOL_FEES_KEY, get::<u64>(TOKEN_0_PROTOCOL_FEES_KEY) + amount_0);
+    store(TOKEN_1_PROTOCOL_FEES_KEY, get::<u64>(TOKEN_1_PROTOCOL_FEES_KEY) + amount_1);
+
+    log(ProtocolFeeCollected {
+        sender: identity_to_b256(msg_sender().unwrap()),
+        amount_0: amount_0,
+        amount_1: amount_1,
+    });
+}
+
+#[storage(read, write)]
+fn process_protocol_fee(amount: u64, is_token0: bool) -> (u64, u64) {
+    let current_fee_rate = get_current_fee();
+    let mut fee = 0;
+
+    if (current_fee_rate > 0) {
+        fee = get_protocol_fee_amount(amount, current_fee_rate);
+        if (is_token0) {
+            collect_protocol_fees(fee, 0);
+        } else {
+            collect_protocol_fees(0, fee);
+        }
+    }
+    (amount - fee, fee)
+}
+
+#[storage(read, write)]
+pub fn withdraw_protocol_fees(recipient: Identity) -> (u64, u64) {
+    require_vault();
+
+    let (token0, token1) = get_tokens();
+    let (token0_vault_fees_collected, token1_vault_fees_collected) = (
+        get::<u64>(TOKEN_0_PROTOCOL_FEES_KEY),
+        get::<u64>(TOKEN_1_PROTOCOL_FEES_KEY),
+    );
+
+    if (token0_vault_fees_collected > 0) {
+        transfer(token0_vault_fees_collected, ContractId::from(token0), recipient);
+        store(TOKEN_0_PROTOCOL_FEES_KEY, 0);
+    }
+    if (token1_vault_fees_collected > 0) {
+        transfer(token1_vault_fees_collected, ContractId::from(token1), recipient);
+        store(TOKEN_1_PROTOCOL_FEES_KEY, 0);
+    }
+
+    log(ProtocolFeeWithdrawn {
+        amount_0: token0_vault_fees_collected,
+        amount_1: token1_vault_fees_collected,
+    });
+    (token0_vault_fees_collected, token1_vault_fees_collected)
+}
+
+#[storage(read)]
+pub fn get_vault_info() -> VaultInfo {
+    let fees = get::<VaultFee>(VAULT_FEE_KEY);
+    VaultInfo {
+        vault: get::<b256>(VAULT_KEY),
+        token0_protocol_fees_collected: get::<u64>(TOKEN_0_PROTOCOL_FEES_KEY),
+        token1_protocol_fees_collected: get::<u64>(TOKEN_1_PROTOCOL_FEES_KEY),
+        current_fee: get_current_fee(),
+        change_rate: fees.change_rate,
+        update_time: fees.update_time,
+    }
+}
+
+#[storage(read)]
+pub fn get_fee_info() -> FeeInfo {
+    // Report what the next swap will use, which re-reads stale fees from the vault
+    if is_fee_cache_stale() {
+        let vault_fees = abi(Vault, get::<b256>(VAULT_KEY)).get_fees_for(contract_id().value);
+        return FeeInfo {
+            current_fee: vault_fees.current_fee,
+            start_fee: vault_fees.start_fee,
+            change_rate: vault_fees.change_rate,
+            start_time: vault_fees.start_time,
+            update_time: timestamp(),
+        };
+    }
+
+    let fees = get::<VaultFee>(VAULT_FEE_KEY);
+    FeeInfo {
+        current_fee: get_current_fee(),
+        start_fee: fees.start_fee,
+        change_rate: fees.change_rate,
+        start_time: fees.start_time,
+        update_time: fees.update_time,
+    }
+}
+
+#[storage(read)]
+pub fn get_fee_cache_info() -> FeeCacheInfo {
+    let update_time = get::<VaultFee>(VAULT_FEE_KEY).update_time;
+    FeeCacheInfo {
+        update_time: update_time,
+        age: timestamp() - update_time,
+        max_age: get::<u64>(MAX_FEE_CACHE_AGE_KEY),
+        is_stale: is_fee_cache_stale(),
+    }
+}
+
+#[storage(read, write)]
+pub fn set_max_fee_cache_age(max_age: u64) {
+    require_vault();
+    store(MAX_FEE_CACHE_AGE_KEY, max_age);
+}
+
+////////////////////////////////////////
+// LP fees
+////////////////////////////////////////
+/// LP fee taken from an input amount, rounded up
+pub fn get_lp_fee_amount(amount_in: u64, lp_fee: u64) -> u64 {
+    let basis_points = U128::from((0, BASIS_POINTS));
+    ((U128::from((0, amount_in)) * U128::from((0, lp_fee)) + basis_points - U128::from((0, 1))) / basis_points).as_u64().unwrap()
+}
+
+/// Smallest input that leaves at least `amount` once the LP fee is taken
+pub fn add_lp_fee(amount: u64, lp_fee: u64) -> u64 {
+    let basis_points = U128::from((0, BASIS_POINTS));
+    let divisor = basis_points - U128::from((0, lp_fee));
+    ((U128::from((0, amount)) * basis_points + divisor - U128::from((0, 1))) / divisor).as_u64().unwrap()
+}
+
+/// Scale the LP fee linearly between the configured bounds, reaching the maximum at `max_volatility`
+#[storage(read)]
+fn get_lp_fee(volatility: u64) -> u64 {
+    let config = get::<DynamicFeeConfig>(DYNAMIC_FEE_KEY);
+    if !config.enabled {
+        return LP_FEE;
+    }
+
+    let capped_volatility = if volatility > config.max_volatility {
+        config.max_volatility
+    } else {
+        volatility
+    };
+    config.min_fee + (config.max_fee - config.min_fee) * capped_volatility / config.max_volatility
+}
+
+#[storage(read)]
+pub fn get_current_lp_fee() -> u64 {
+    let config = get::<DynamicFeeConfig>(DYNAMIC_FEE_KEY);
+    if config.enabled {
+        get_lp_fee(get_volatility(config.lookback))
+    } else {
+        LP_FEE
+    }
+}
+
+#[storage(read)]
+pub fn get_lp_fee_info() -> LPFeeInfo {
+    let config = get::<DynamicFeeConfig>(DYNAMIC_FEE_KEY);
+    let volatility = if config.enabled { get_volatility(config.lookback) } else { 0 };
+    LPFeeInfo {
+        current_fee: get_lp_fee(volatility),
+        volatility: volatility,
+        config: config,
+    }
+}
+
+#[storage(read, write)]
+pub fn set_dynamic_fee(config: DynamicFeeConfig) {
+    require_vault();
+    if config.enabled {
+        require(config.min_fee <= config.max_fee && config.max_fee <= MAX_LP_FEE, Error::InvalidFeeConfig);
+        require(config.max_volatility > 0, Error::InvalidFeeConfig);
+        require(config.lookback > 1 && config.lookback <= MAX_VOLATILITY_LOOKBACK, Error::InvalidFeeConfig);
+    }
+    store(DYNAMIC_FEE_KEY, config);
+}
+
+////////////////////////////////////////
+// TWAP
+////////////////////////////////////////
+fn get_observation_key(slot: u64) -> b256 {
+    sha256((TWAP_BUFFER_KEY, slot))
+}
+
+/// Accumulate the prices in place since the newest observation into a new one. Each price is
+/// the other token's amount per token, scaled by the pool's TWAP precision.
+#[storage(read, write)]
+pub fn write_observation(price_0: U256, price_1: U256) {
+    let buffer_size = get::<u64>(TWAP_BUFFER_SIZE_KEY);
+    let next_buffer_size = get::<u64>(TWAP_NEXT_BUFFER_SIZE_KEY);
+    let current_element = get::<u64>(TWAP_CURRENT_ELEMENT_KEY);
+
+    let mut next_observation_slot = (current_element + 1) % next_buffer_size;
+    let mut last_observation = get::<Observation>(get_observation_key(current_element));
+    if (last_observation.timestamp <= 1) {
+        next_observation_slot = current_element;
+        last_observation = Observation {
+            timestamp: timestamp(),
+            price_0_cumulative_last: U256::min(),
+            price_1_cumulative_last: U256::min(),
+        };
+    }
+
+    let time_elapsed = timestamp() - last_observation.timestamp;
+    if (time_elapsed != 0) {
+        let time_elapsed_u256 = U256::from((0, 0, 0, time_elapsed));
+        store(get_observation_key(next_observation_slot), Observation {
+            timestamp: timestamp(),
+            price_0_cumulative_last: last_observation.price_0_cumulative_last + price_0 * time_elapsed_u256,
+            price_1_cumulative_last: last_observation.price_1_cumulative_last + price_1 * time_elapsed_u256,
+        });
+
+        store(TWAP_CURRENT_ELEMENT_KEY, next_observation_slot);
+        if (next_observation_slot >= buffer_size) {
+            store(TWAP_BUFFER_SIZE_KEY, buffer_size + 1);
+        }
+    }
+}
+
+/// Largest deviation of any of the newest `lookback` intervals between observations from their TWAP, in basis points
+#[storage(read)]
+fn get_volatility(lookback: u64) -> u64 {
+    let buffer_size = get::<u64>(TWAP_BUFFER_SIZE_KEY);
+    // Needs at least two intervals to compare
+    if buffer_size < 3 {
+        return 0;
+    }
+
+    // Only the newest observations are read, so growing the buffer doesn't make swaps more expensive
+    let intervals = if lookback < buffer_size - 1 { lookback } else { buffer_size - 1 };
+    let newest_slot = get::<u64>(TWAP_CURRENT_ELEMENT_KEY);
+    let oldest_slot = (newest_slot + buffer_size - intervals) % buffer_size;
+    let oldest = get::<Observation>(get_observation_key(oldest_slot));
+    let newest = get::<Observation>(get_observation_key(newest_slot));
+
+    let twap = (newest.price_0_cumulative_last - oldest.price_0_cumulative_last) / U256::from((0, 0, 0, newest.timestamp - oldest.timestamp));
+    if twap == U256::min() {
+        return 0;
+    }
+
+    let mut max_deviation = U256::min();
+    let mut previous = oldest;
+    let mut i = 1;
+    while i <= intervals {
+        let observation = get::<Observation>(get_observation_key((oldest_slot + i) % buffer_size));
+        let price = (observation.price_0_cumulative_last - previous.price_0_cumulative_last) / U256::from((0, 0, 0, observation.timestamp - previous.timestamp));
+        let deviation = if price > twap { price - twap } else { twap - price };
+        if deviation > max_deviation {
+            max_deviation = deviation;
+        }
+        previous = observation;
+        i += 1;
+    }
+
+    (max_deviation * U256::from((0, 0, 0, BASIS_POINTS)) / twap).as_u64().unwrap()
+}
+
+#[storage(read)]
+pub fn get_twap_info() -> TWAPInfo {
+    TWAPInfo {
+        current_element: get::<u64>(TWAP_CURRENT_ELEMENT_KEY),
+        buffer_size: get::<u64>(TWAP_BUFFER_SIZE_KEY),
+        next_buffer_size: get::<u64>(TWAP_NEXT_BUFFER_SIZE_KEY),
+    }
+}
+
+#[storage(read, write)]
+pub fn expand_twap_buffer(new_total_slots: u64) {
+    let mut i = get::<u64>(TWAP_NEXT_BUFFER_SIZE_KEY);
+
+    while i < new_total_slots {
+        // The goal here is to initialize these slots (write a value), without
+        // providing actual data. Observations will be aware that timestamp=1 means
+        // initialized, but not yet used
+        store(get_observation_key(i), Observation {
+            timestamp: 1,
+            price_0_cumulative_last: U256::max(),
+            price_1_cumulative_last: U256::max(),
+        });
+        i += 1;
+    }
+
+    store(TWAP_NEXT_BUFFER_SIZE_KEY, new_total_slots);
+}
+
+#[storage(read)]
+pub fn get_observation(slot: u64) -> Observation {
+    require(slot < get::<u64>(TWAP_BUFFER_SIZE_KEY), Error::TWAPOutOfRange);
+    get::<Observation>(get_observation_key(slot))
+}
@@ -0,0 +1,94 @@
+library farm_abi;
+
+use std::identity::Identity;
+
+// Events
+
+pub struct Staked {
+    pool: b256,
+    owner: b256,
+    amount: u64,
+}
+
+pub struct Unstaked {
+    pool: b256,
+    owner: b256,
+    amount: u64,
+    recipient: b256,
+}
+
+pub struct RewardsClaimed {
+    pool: b256,
+    owner: b256,
+    amount: u64,
+    recipient: b256,
+}
+
+pub struct EmergencyWithdrawn {
+    pool: b256,
+    owner: b256,
+    amount: u64,
+    recipient: b256,
+}
+
+pub struct RewardScheduleSet {
+    pool: b256,
+    reward_asset: b256,
+    reward_rate: u64,
+    start_time: u64,
+    end_time: u64,
+}
+
+// Returns
+
+/// A pool's farm, staking the pool's LP token. Times are TAI64.
+pub struct FarmInfo {
+    reward_asset: b256,
+    /// Rewards emitted each second between the start and end times, shared between stakers
+    reward_rate: u64,
+    start_time: u64,
+    end_time: u64,
+    total_staked: u64,
+}
+
+pub struct StakeInfo {
+    amount: u64,
+    /// Rewards earned and not yet claimed
+    pending_rewards: u64,
+}
+
+abi Farm {
+    ////////////////////
+    // Read only
+    ////////////////////
+    #[storage(read)]fn get_owner() -> Identity;
+    #[storage(read)]fn get_farm(pool: b256) -> FarmInfo;
+    #[storage(read)]fn get_stake(pool: b256, owner: Identity) -> StakeInfo;
+    /// Reward tokens held by the farm and not promised to any schedule
+    #[storage(read)]fn get_available_rewards(asset_id: b256) -> u64;
+    ////////////////////
+    // Owner only
+    ////////////////////
+    /// Set the owner, can only be called once by the deployer stored at deploy
+    #[storage(read, write)]fn initialize(owner: Identity);
+    /// Replace a pool's reward schedule, from `start_time` (no earlier than now) to `end_time`.
+    /// Rewards not yet emitted by the old schedule are returned to the available rewards, and the
+    /// whole new schedule must be covered by them. A pool's reward asset can't be changed once set.
+    #[storage(read, write)]fn set_reward_schedule(pool: b256, reward_asset: b256, reward_rate: u64, start_time: u64, end_time: u64);
+    /// Withdraw reward tokens that aren't promised to any schedule
+    #[storage(read, write)]fn withdraw_rewards(asset_id: b256, amount: u64, recipient: Identity);
+    ////////////////////
+    // Actions
+    ////////////////////
+    /// Add the forwarded reward tokens to the available rewards
+    #[storage(read, write)]fn fund_rewards();
+    /// Stake the forwarded LP tokens for `owner`, in the farm of the pool that issued them
+    #[storage(read, write)]fn stake(owner: Identity);
+    /// Unstake the sender's LP tokens. Rewards earned so far stay claimable.
+    #[storage(read, write)]fn unstake(pool: b256, amount: u64, recipient: Identity);
+    /// Send the sender's earned rewards to `recipient`, returning the amount claimed
+    #[storage(read, write)]fn claim(pool: b256, recipient: Identity) -> u64;
+    /// Unstake all of the sender's LP tokens without claiming, giving up their rewards.
+    /// Works even if reward accounting can't be updated.
+    #[storage(read, write)]fn emergency_withdraw(pool: b256, recipient: Identity) -> u64;
+}
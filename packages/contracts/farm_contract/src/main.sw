@@ -0,0 +1,373 @@
+contract;
+
+use std::{
+    auth::{msg_sender, AuthError},
+    block::timestamp,
+    call_frames::msg_asset_id,
+    constants::ZERO_B256,
+    context::msg_amount,
+    contract_id::ContractId,
+    identity::Identity,
+    logging::log,
+    storage::get,
+    token::transfer,
+    u128::U128,
+    u256::U256,
+};
+
+use farm_abi::{
+    EmergencyWithdrawn,
+    Farm,
+    FarmInfo,
+    RewardsClaimed,
+    RewardScheduleSet,
+    StakeInfo,
+    Staked,
+    Unstaked,
+};
+use microchain_helpers::identity_to_b256;
+
+enum Error {
+    MustBeCalledByOwner: (),
+    MustBeCalledByDeployer: (),
+    AlreadyInitialized: (),
+    FarmNotFound: (),
+    InvalidSchedule: (),
+    RewardAssetChanged: (),
+    InsufficentRewards: (),
+    InsufficentInput: (),
+    InsufficentStake: (),
+}
+
+////////////////////////////////////////
+// Constants
+////////////////////////////////////////
+
+/// Address allowed to initialize the farm, set at deploy so the first owner can't be claimed by anyone else
+const DEPLOYER_SLOT = 0x0000000000000000000000000000000000000000000000000000000000000000;
+
+/// Scale of each farm's reward per staked token
+const REWARD_PRECISION: u64 = 1_000_000_000_000;
+
+////////////////////////////////////////
+// Storage declarations
+////////////////////////////////////////
+// Each farm tracks the rewards earned by a single staked LP token since the farm started, and
+// each stake records that amount when it last changed. A stake has earned the growth since then,
+// times its size, so rewards are shared by stake size and time without touching every stake.
+
+struct FarmState {
+    configured: bool,
+    reward_asset: b256,
+    reward_rate: u64,
+    start_time: u64,
+    end_time: u64,
+    total_staked: u64,
+    last_update_time: u64,
+    /// Rewards per staked token, scaled by REWARD_PRECISION
+    reward_per_share: U128,
+}
+
+struct StakeState {
+    amount: u64,
+    /// `amount` times the farm's reward per share when the stake last changed
+    reward_debt: U256,
+    /// Rewards earned before the stake last changed
+    unclaimed: u64,
+}
+
+storage {
+    // Kept separately from the owner, so the farm can't be initialized again
+    initialized: bool = false,
+    owner: Identity = Identity::Address(Address::from(ZERO_B256)),
+    // Keyed by the pool, which is also the LP token's asset ID
+    farms: StorageMap<b256, FarmState> = StorageMap {},
+    // Keyed by pool and owner
+    stakes: StorageMap<(b256, b256), StakeState> = StorageMap {},
+    // Reward tokens not promised to any schedule, by asset
+    available_rewards: StorageMap<b256, u64> = StorageMap {},
+}
+
+////////////////////////////////////////
+// Helper functions
+////////////////////////////////////////
+
+#[storage(read)]
+fn validate_deployer() {
+    let sender: Result<Identity, AuthError> = msg_sender();
+    require(
+        sender.unwrap() == Identity::Address(Address::from(get::<b256>(DEPLOYER_SLOT))),
+        Error::MustBeCalledByDeployer
+    );
+}
+
+#[storage(read)]
+fn validate_owner() {
+    let sender: Result<Identity, AuthError> = msg_sender();
+    let owner = storage.owner;
+    require(
+        sender.unwrap() == owner && owner != Identity::Address(Address::from(ZERO_B256)),
+        Error::MustBeCalledByOwner
+    );
+}
+
+fn min(a: u64, b: u64) -> u64 {
+    if a < b { a } else { b }
+}
+
+fn max(a: u64, b: u64) -> u64 {
+    if a > b { a } else { b }
+}
+
+/// A stake's rewards since the farm started, scaled by REWARD_PRECISION
+fn accrued(amount: u64, reward_per_share: U128) -> U256 {
+    U256::from((0, 0, reward_per_share.upper, reward_per_share.lower)) * U256::from((0, 0, 0, amount))
+}
+
+/// The farm after emitting rewards up to `now`, and the rewards emitted while nothing was staked
+fn farm_at(farm: FarmState, now: u64) -> (FarmState, u64) {
+    let mut farm = farm;
+    let mut unallocated = 0;
+
+    let from = max(farm.last_update_time, farm.start_time);
+    let to = min(now, farm.end_time);
+    if to > from {
+        let rewards = farm.reward_rate * (to - from);
+        if farm.total_staked > 0 {
+            farm.reward_per_share = farm.reward_per_share + U128::from((0, rewards)) * U128::from((0, REWARD_PRECISION)) / U128::from((0, farm.total_staked));
+        } else {
+            unallocated = rewards;
+        }
+    }
+    farm.last_update_time = max(farm.last_update_time, now);
+
+    (farm, unallocated)
+}
+
+/// Emit a farm's rewards up to now. Rewards emitted with nothing staked become available again.
+#[storage(read, write)]
+fn update_farm(pool: b256) -> FarmState {
+    let (farm, unallocated) = farm_at(storage.farms.get(pool), timestamp());
+    if unallocated > 0 {
+        storage.available_rewards.insert(farm.reward_asset, storage.available_rewards.get(farm.reward_asset) + unallocated);
+    }
+    storage.farms.insert(pool, farm);
+    farm
+}
+
+/// A stake's unclaimed rewards, including those earned since it last changed
+fn pending_rewards(stake: StakeState, farm: FarmState) -> u64 {
+    let earned = (accrued(stake.amount, farm.reward_per_share) - stake.reward_debt) / U256::from((0, 0, 0, REWARD_PRECISION));
+    stake.unclaimed + earned.as_u64().unwrap()
+}
+
+/// Load a stake, moving the rewards it has earned into `unclaimed`
+#[storage(read)]
+fn get_settled_stake(pool: b256, owner: b256, farm: FarmState) -> StakeState {
+    let mut stake = storage.stakes.get((pool, owner));
+    stake.unclaimed = pending_rewards(stake, farm);
+    stake
+}
+
+/// Store a stake, starting its rewards from the farm's current reward per share
+#[storage(write)]
+fn store_stake(pool: b256, owner: b256, stake: StakeState, farm: FarmState) {
+    let mut stake = stake;
+    stake.reward_debt = accrued(stake.amount, farm.reward_per_share);
+    storage.stakes.insert((pool, owner), stake);
+}
+
+// ////////////////////////////////////////
+// // ABI definitions
+// ////////////////////////////////////////
+impl Farm for Contract {
+    #[storage(read)]
+    fn get_owner() -> Identity {
+        storage.owner
+    }
+
+    #[storage(read)]
+    fn get_farm(pool: b256) -> FarmInfo {
+        let farm = storage.farms.get(pool);
+        FarmInfo {
+            reward_asset: farm.reward_asset,
+            reward_rate: farm.reward_rate,
+            start_time: farm.start_time,
+            end_time: farm.end_time,
+            total_staked: farm.total_staked,
+        }
+    }
+
+    #[storage(read)]
+    fn get_stake(pool: b256, owner: Identity) -> StakeInfo {
+        let (farm, _unallocated) = farm_at(storage.farms.get(pool), timestamp());
+        let stake = storage.stakes.get((pool, identity_to_b256(owner)));
+        StakeInfo {
+            amount: stake.amount,
+            pending_rewards: pending_rewards(stake, farm),
+        }
+    }
+
+    #[storage(read)]
+    fn get_available_rewards(asset_id: b256) -> u64 {
+        storage.available_rewards.get(asset_id)
+    }
+
+    #[storage(read, write)]
+    fn initialize(owner: Identity) {
+        validate_deployer();
+        require(!storage.initialized, Error::AlreadyInitialized);
+        storage.initialized = true;
+        storage.owner = owner;
+    }
+
+    #[storage(read, write)]
+    fn set_reward_schedule(pool: b256, reward_asset: b256, reward_rate: u64, start_time: u64, end_time: u64) {
+        validate_owner();
+        let now = timestamp();
+        require(start_time >= now && end_time > start_time, Error::InvalidSchedule);
+
+        let mut farm = update_farm(pool);
+        require(!farm.configured || farm.reward_asset == reward_asset, Error::RewardAssetChanged);
+
+        // Rewards the old schedule hasn't emitted yet can fund the new one
+        let mut available = storage.available_rewards.get(reward_asset);
+        if farm.end_time > now {
+            available = available + farm.reward_rate * (farm.end_time - max(now, farm.start_time));
+        }
+        let required = reward_rate * (end_time - start_time);
+        require(available >= required, Error::InsufficentRewards);
+        storage.available_rewards.insert(reward_asset, available - required);
+
+        farm.configured = true;
+        farm.reward_asset = reward_asset;
+        farm.reward_rate = reward_rate;
+        farm.start_time = start_time;
+        farm.end_time = end_time;
+        storage.farms.insert(pool, farm);
+
+        log(RewardScheduleSet {
+            pool: pool,
+            reward_asset: reward_asset,
+            reward_rate: reward_rate,
+            start_time: start_time,
+            end_time: end_time,
+        });
+    }
+
+    #[storage(read, write)]
+    fn withdraw_rewards(asset_id: b256, amount: u64, recipient: Identity) {
+        validate_owner();
+        let available = storage.available_rewards.get(asset_id);
+        require(available >= amount, Error::InsufficentRewards);
+        storage.available_rewards.insert(asset_id, available - amount);
+        transfer(amount, ContractId::from(asset_id), recipient);
+    }
+
+    #[storage(read, write)]
+    fn fund_rewards() {
+        let asset_id: b256 = msg_asset_id().into();
+        storage.available_rewards.insert(asset_id, storage.available_rewards.get(asset_id) + msg_amount());
+    }
+
+    #[storage(read, write)]
+    fn stake(owner: Identity) {
+        // An exchange's LP token has the exchange's ID
+        let pool: b256 = msg_asset_id().into();
+        let amount = msg_amount();
+        require(amount > 0, Error::InsufficentInput);
+
+        let mut farm = update_farm(pool);
+        require(farm.configured, Error::FarmNotFound);
+
+        let owner = identity_to_b256(owner);
+        let mut stake = get_settled_stake(pool, owner, farm);
+        stake.amount = stake.amount + amount;
+        store_stake(pool, owner, stake, farm);
+
+        farm.total_staked = farm.total_staked + amount;
+        storage.farms.insert(pool, farm);
+
+        log(Staked {
+            pool: pool,
+            owner: owner,
+            amount: amount,
+        });
+    }
+
+    #[storage(read, write)]
+    fn unstake(pool: b256, amount: u64, recipient: Identity) {
+        let sender = identity_to_b256(msg_sender().unwrap());
+        let mut farm = update_farm(pool);
+        let mut stake = get_settled_stake(pool, sender, farm);
+        require(amount > 0 && stake.amount >= amount, Error::InsufficentStake);
+
+        stake.amount = stake.amount - amount;
+        store_stake(pool, sender, stake, farm);
+
+        farm.total_staked = farm.total_staked - amount;
+        storage.farms.insert(pool, farm);
+
+        transfer(amount, ContractId::from(pool), recipient);
+
+        log(Unstaked {
+            pool: pool,
+            owner: sender,
+            amount: amount,
+            recipient: identity_to_b256(recipient),
+        });
+    }
+
+    #[storage(read, write)]
+    fn claim(pool: b256, recipient: Identity) -> u64 {
+        let sender = identity_to_b256(msg_sender().unwrap());
+        let farm = update_farm(pool);
+        let mut stake = get_settled_stake(pool, sender, farm);
+
+        let amount = stake.unclaimed;
+        stake.unclaimed = 0;
+        store_stake(pool, sender, stake, farm);
+
+        if amount > 0 {
+            transfer(amount, ContractId::from(farm.reward_asset), recipient);
+        }
+
+        log(RewardsClaimed {
+            pool: pool,
+            owner: sender,
+            amount: amount,
+            recipient: identity_to_b256(recipient),
+        });
+
+        amount
+    }
+
+    #[storage(read, write)]
+    fn emergency_withdraw(pool: b256, recipient: Identity) -> u64 {
+        let sender = identity_to_b256(msg_sender().unwrap());
+        let amount = storage.stakes.get((pool, sender)).amount;
+        require(amount > 0, Error::InsufficentStake);
+
+        // Skip the reward accounting entirely, so a broken schedule can't lock stakes. The forfeited
+        // rewards stay in the farm without being allocated to anyone.
+        storage.stakes.insert((pool, sender), StakeState {
+            amount: 0,
+            reward_debt: U256::from((0, 0, 0, 0)),
+            unclaimed: 0,
+        });
+        let mut farm = storage.farms.get(pool);
+        farm.total_staked = farm.total_staked - amount;
+        storage.farms.insert(pool, farm);
+
+        transfer(amount, ContractId::from(pool), recipient);
+
+        log(EmergencyWithdrawn {
+            pool: pool,
+            owner: sender,
+            amount: amount,
+            recipient: identity_to_b256(recipient),
+        });
+
+        amount
+    }
+}
@@ -0,0 +1,475 @@
+use std::str::FromStr;
+use chrono::Utc;
+use fuels::{
+    prelude::*,
+    fuels_abigen::abigen,
+    signers::WalletUnlocked,
+    tx::{AssetId, Bytes32, StorageSlot},
+};
+use test_helpers::{
+    deployer_storage,
+    get_logs,
+    get_wallets,
+    produce_block_at,
+    unix_to_tai64,
+    FARM_ABI,
+    MIN_FEE_CHANGE_DELAY,
+};
+
+///////////////////////////////
+// Load the Farm Contract abi
+///////////////////////////////
+abigen!(Farm, "./out/debug/farm_contract-abi.json");
+
+abigen!(Exchange, "../exchange_contract/out/debug/exchange_contract-abi.json");
+abigen!(TestToken, "../token_contract/out/debug/token_contract-abi.json");
+abigen!(Vault, "../vault_contract/out/debug/vault_contract-abi.json");
+
+struct Fixture {
+    wallet: WalletUnlocked,
+    other_wallet: WalletUnlocked,
+    token_asset_id: AssetId,
+    lp_asset_id: AssetId,
+    farm_contract_id: Bech32ContractId,
+    farm_instance: Farm,
+    other_farm_instance: Farm,
+}
+
+fn to_9_decimal(num: u64) -> u64 {
+    num * 1_000_000_000
+}
+
+/// Deploy an ETH/token pool with 10 of each, giving the other wallet 2.5 of the LP tokens,
+/// and a farm owned by the first wallet. Rewards are paid in the pool's token.
+async fn setup() -> Fixture {
+    let wallets = get_wallets().await;
+    let wallet = wallets.get(0).unwrap().clone();
+    let other_wallet = wallets.get(1).unwrap().clone();
+    produce_block_at(&wallet, Utc::now().timestamp()).await;
+
+    let token_contract_id = Contract::deploy(
+        "../token_contract/out/debug/token_contract.bin",
+        &wallet,
+        TxParameters::default(),
+        StorageConfiguration::new(None, None),
+    )
+    .await
+    .unwrap();
+
+    let token_instance = TestToken::new(token_contract_id.clone(), wallet.clone());
+    token_instance
+        .methods()
+        .initialize(to_9_decimal(20000), wallet.address().into())
+        .call()
+        .await
+        .unwrap();
+    token_instance
+        .methods()
+        .mint()
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .unwrap();
+    let token_asset_id = AssetId::new(*token_contract_id.hash());
+
+    let token1_slot = Bytes32::from_str("0x0000000000000000000000000000000000000000000000000000000000000001").unwrap();
+    let storage_vec = vec![
+        StorageSlot::new(token1_slot, token_contract_id.hash()),
+    ];
+    let exchange_contract_id = Contract::deploy_with_parameters(
+        "../exchange_contract/out/debug/exchange_contract.bin",
+        &wallet,
+        TxParameters::default(),
+        StorageConfiguration::with_manual_storage(Some(storage_vec)),
+        Salt::from([0u8; 32]),
+    )
+    .await
+    .unwrap();
+
+    let vault_contract_id = Contract::deploy(
+        "../vault_contract/out/debug/vault_contract.bin",
+        &wallet,
+        TxParameters::default(),
+        deployer_storage(&wallet),
+    )
+    .await
+    .unwrap();
+
+    let farm_contract_id = Contract::deploy(
+        "./out/debug/farm_contract.bin",
+        &wallet,
+        TxParameters::default(),
+        deployer_storage(&wallet),
+    )
+    .await
+    .unwrap();
+
+    Vault::new(vault_contract_id.clone(), wallet.clone())
+        .methods()
+        .initialize(Identity::Address(wallet.address().into()), MIN_FEE_CHANGE_DELAY)
+        .call()
+        .await
+        .unwrap();
+
+    let exchange_instance = Exchange::new(exchange_contract_id.clone(), wallet.clone());
+    exchange_instance
+        .methods()
+        .initialize(Bits256(vault_contract_id.hash().into()))
+        .set_contracts(&[vault_contract_id.clone()])
+        .call()
+        .await
+        .unwrap();
+
+    let _receipts = wallet
+        .force_transfer_to_contract(&exchange_contract_id, to_9_decimal(10), BASE_ASSET_ID, TxParameters::default())
+        .await;
+    let _receipts = wallet
+        .force_transfer_to_contract(&exchange_contract_id, to_9_decimal(10), token_asset_id, TxParameters::default())
+        .await;
+    exchange_instance
+        .methods()
+        .add_liquidity(Identity::Address(wallet.address().into()))
+        .tx_params(TxParameters {
+            gas_price: 0,
+            gas_limit: 100_000_000,
+            maturity: 0,
+        })
+        .call_params(CallParameters::new(None, None, Some(100_000_000)))
+        .append_variable_outputs(2)
+        .call()
+        .await
+        .unwrap();
+
+    let lp_asset_id = AssetId::new(*exchange_contract_id.hash());
+    wallet
+        .transfer(other_wallet.address(), to_9_decimal(25) / 10, lp_asset_id, TxParameters::default())
+        .await
+        .unwrap();
+
+    let is_err = Farm::new(farm_contract_id.clone(), other_wallet.clone())
+        .methods()
+        .initialize(Identity::Address(other_wallet.address().into()))
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "The farm should only be initialized by its deployer");
+
+    let farm_instance = Farm::new(farm_contract_id.clone(), wallet.clone());
+    farm_instance
+        .methods()
+        .initialize(Identity::Address(wallet.address().into()))
+        .call()
+        .await
+        .unwrap();
+
+    Fixture {
+        wallet: wallet,
+        other_farm_instance: Farm::new(farm_contract_id.clone(), other_wallet.clone()),
+        other_wallet: other_wallet,
+        token_asset_id: token_asset_id,
+        lp_asset_id: lp_asset_id,
+        farm_contract_id: farm_contract_id,
+        farm_instance: farm_instance,
+    }
+}
+
+fn in_seconds(seconds: i64) -> u64 {
+    unix_to_tai64((Utc::now().timestamp() + seconds) as u64)
+}
+
+async fn fund_rewards(fixture: &Fixture, amount: u64) {
+    fixture.farm_instance
+        .methods()
+        .fund_rewards()
+        .call_params(CallParameters::new(Some(amount), Some(fixture.token_asset_id), None))
+        .call()
+        .await
+        .unwrap();
+}
+
+/// Fund and schedule the pool's rewards from an hour to two hours from now, returning the total
+async fn schedule_rewards(fixture: &Fixture, reward_rate: u64) -> u64 {
+    let total = reward_rate * 3600;
+    fund_rewards(fixture, total).await;
+
+    let response = fixture.farm_instance
+        .methods()
+        .set_reward_schedule(
+            Bits256(*fixture.lp_asset_id),
+            Bits256(*fixture.token_asset_id),
+            reward_rate,
+            in_seconds(3600),
+            in_seconds(7200),
+        )
+        .call()
+        .await
+        .unwrap();
+
+    let logs = get_logs::<RewardScheduleSet, _>(&response, &fixture.farm_contract_id, FARM_ABI);
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs.get(0).unwrap().reward_rate, reward_rate);
+    total
+}
+
+async fn stake(farm_instance: &Farm, wallet: &WalletUnlocked, lp_asset_id: AssetId, amount: u64) {
+    farm_instance
+        .methods()
+        .stake(Identity::Address(wallet.address().into()))
+        .call_params(CallParameters::new(Some(amount), Some(lp_asset_id), None))
+        .call()
+        .await
+        .unwrap();
+}
+
+async fn claim(farm_instance: &Farm, wallet: &WalletUnlocked, lp_asset_id: AssetId) -> u64 {
+    farm_instance
+        .methods()
+        .claim(Bits256(*lp_asset_id), Identity::Address(wallet.address().into()))
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .unwrap()
+        .value
+}
+
+#[tokio::test]
+async fn stakers_share_rewards_by_stake() {
+    let fixture = setup().await;
+    let reward_rate = to_9_decimal(1) / 1000;
+    let total = schedule_rewards(&fixture, reward_rate).await;
+    assert_eq!(fixture.farm_instance.methods().get_available_rewards(Bits256(*fixture.token_asset_id)).call().await.unwrap().value, 0);
+
+    let farm = fixture.farm_instance.methods().get_farm(Bits256(*fixture.lp_asset_id)).call().await.unwrap().value;
+    assert_eq!(farm.reward_asset, Bits256(*fixture.token_asset_id));
+    assert_eq!(farm.end_time - farm.start_time, 3600);
+
+    // Staked 3:1 before the rewards start
+    stake(&fixture.farm_instance, &fixture.wallet, fixture.lp_asset_id, to_9_decimal(75) / 10).await;
+    stake(&fixture.other_farm_instance, &fixture.other_wallet, fixture.lp_asset_id, to_9_decimal(25) / 10).await;
+    let farm = fixture.farm_instance.methods().get_farm(Bits256(*fixture.lp_asset_id)).call().await.unwrap().value;
+    assert_eq!(farm.total_staked, to_9_decimal(10));
+
+    // Nothing is earned before the start
+    let stake_info = fixture.farm_instance
+        .methods()
+        .get_stake(Bits256(*fixture.lp_asset_id), Identity::Address(fixture.wallet.address().into()))
+        .call()
+        .await
+        .unwrap()
+        .value;
+    assert_eq!(stake_info.amount, to_9_decimal(75) / 10);
+    assert_eq!(stake_info.pending_rewards, 0);
+
+    // Nothing is emitted after the end either
+    produce_block_at(&fixture.wallet, Utc::now().timestamp() + 10800).await;
+    let stake_info = fixture.farm_instance
+        .methods()
+        .get_stake(Bits256(*fixture.lp_asset_id), Identity::Address(fixture.wallet.address().into()))
+        .call()
+        .await
+        .unwrap()
+        .value;
+    assert!(stake_info.pending_rewards <= total * 3 / 4 && stake_info.pending_rewards > total * 3 / 4 - 10);
+
+    let starting_balance = fixture.wallet.get_asset_balance(&fixture.token_asset_id).await.unwrap();
+    let claimed = claim(&fixture.farm_instance, &fixture.wallet, fixture.lp_asset_id).await;
+    let end_balance = fixture.wallet.get_asset_balance(&fixture.token_asset_id).await.unwrap();
+    assert_eq!(claimed, stake_info.pending_rewards);
+    assert_eq!(end_balance - starting_balance, claimed);
+
+    let other_claimed = claim(&fixture.other_farm_instance, &fixture.other_wallet, fixture.lp_asset_id).await;
+    assert!(other_claimed <= total / 4 && other_claimed > total / 4 - 10);
+
+    // Rewards can only be claimed once
+    assert_eq!(claim(&fixture.farm_instance, &fixture.wallet, fixture.lp_asset_id).await, 0);
+}
+
+#[tokio::test]
+async fn unstake_keeps_earned_rewards() {
+    let fixture = setup().await;
+    let reward_rate = to_9_decimal(1) / 1000;
+    let total = schedule_rewards(&fixture, reward_rate).await;
+    stake(&fixture.farm_instance, &fixture.wallet, fixture.lp_asset_id, to_9_decimal(5)).await;
+
+    let is_err = fixture.other_farm_instance
+        .methods()
+        .unstake(Bits256(*fixture.lp_asset_id), 1, Identity::Address(fixture.other_wallet.address().into()))
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Only the staker can unstake");
+
+    // Halfway through, the only staker has earned about half of the rewards
+    produce_block_at(&fixture.wallet, Utc::now().timestamp() + 5400).await;
+    let starting_balance = fixture.wallet.get_asset_balance(&fixture.lp_asset_id).await.unwrap();
+    let response = fixture.farm_instance
+        .methods()
+        .unstake(Bits256(*fixture.lp_asset_id), to_9_decimal(5), Identity::Address(fixture.wallet.address().into()))
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .unwrap();
+    let end_balance = fixture.wallet.get_asset_balance(&fixture.lp_asset_id).await.unwrap();
+    assert_eq!(end_balance - starting_balance, to_9_decimal(5));
+
+    let logs = get_logs::<Unstaked, _>(&response, &fixture.farm_contract_id, FARM_ABI);
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs.get(0).unwrap().amount, to_9_decimal(5));
+
+    let is_err = fixture.farm_instance
+        .methods()
+        .unstake(Bits256(*fixture.lp_asset_id), 1, Identity::Address(fixture.wallet.address().into()))
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Can't unstake more than was staked");
+
+    // Nothing more is earned once unstaked
+    produce_block_at(&fixture.wallet, Utc::now().timestamp() + 10800).await;
+    let claimed = claim(&fixture.farm_instance, &fixture.wallet, fixture.lp_asset_id).await;
+    assert!(claimed > total / 2 - 10 && claimed < total * 3 / 4, "Claimed {} of {}", claimed, total);
+
+    // Rewards emitted with nothing staked can be withdrawn by the owner
+    let available = fixture.farm_instance.methods().get_available_rewards(Bits256(*fixture.token_asset_id)).call().await.unwrap().value;
+    // Less any rounding dust
+    assert!(available <= total - claimed && available > total - claimed - 10);
+
+    let is_err = fixture.other_farm_instance
+        .methods()
+        .withdraw_rewards(Bits256(*fixture.token_asset_id), available, Identity::Address(fixture.other_wallet.address().into()))
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Only the owner can withdraw rewards");
+
+    fixture.farm_instance
+        .methods()
+        .withdraw_rewards(Bits256(*fixture.token_asset_id), available, Identity::Address(fixture.wallet.address().into()))
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn emergency_withdraw_forfeits_rewards() {
+    let fixture = setup().await;
+    let reward_rate = to_9_decimal(1) / 1000;
+    let total = schedule_rewards(&fixture, reward_rate).await;
+    stake(&fixture.farm_instance, &fixture.wallet, fixture.lp_asset_id, to_9_decimal(25) / 10).await;
+    stake(&fixture.other_farm_instance, &fixture.other_wallet, fixture.lp_asset_id, to_9_decimal(25) / 10).await;
+
+    produce_block_at(&fixture.wallet, Utc::now().timestamp() + 5400).await;
+    let starting_balance = fixture.other_wallet.get_asset_balance(&fixture.lp_asset_id).await.unwrap();
+    let response = fixture.other_farm_instance
+        .methods()
+        .emergency_withdraw(Bits256(*fixture.lp_asset_id), Identity::Address(fixture.other_wallet.address().into()))
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .unwrap();
+    let end_balance = fixture.other_wallet.get_asset_balance(&fixture.lp_asset_id).await.unwrap();
+    assert_eq!(response.value, to_9_decimal(25) / 10);
+    assert_eq!(end_balance - starting_balance, to_9_decimal(25) / 10);
+
+    let logs = get_logs::<EmergencyWithdrawn, _>(&response, &fixture.farm_contract_id, FARM_ABI);
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs.get(0).unwrap().amount, to_9_decimal(25) / 10);
+
+    let stake_info = fixture.farm_instance
+        .methods()
+        .get_stake(Bits256(*fixture.lp_asset_id), Identity::Address(fixture.other_wallet.address().into()))
+        .call()
+        .await
+        .unwrap()
+        .value;
+    assert_eq!(stake_info.amount, 0);
+    assert_eq!(stake_info.pending_rewards, 0);
+    assert_eq!(claim(&fixture.other_farm_instance, &fixture.other_wallet, fixture.lp_asset_id).await, 0);
+
+    // The forfeited rewards aren't handed back for new schedules
+    let available = fixture.farm_instance.methods().get_available_rewards(Bits256(*fixture.token_asset_id)).call().await.unwrap().value;
+    assert_eq!(available, 0);
+
+    // The remaining staker earns all of the rewards from then on
+    produce_block_at(&fixture.wallet, Utc::now().timestamp() + 10800).await;
+    let claimed = claim(&fixture.farm_instance, &fixture.wallet, fixture.lp_asset_id).await;
+    assert!(claimed > total / 2 && claimed < total, "Claimed {} of {}", claimed, total);
+}
+
+#[tokio::test]
+async fn reward_schedules_are_owner_only_and_funded() {
+    let fixture = setup().await;
+    let reward_rate = to_9_decimal(1) / 1000;
+
+    let is_err = fixture.farm_instance
+        .methods()
+        .initialize(Identity::Address(fixture.other_wallet.address().into()))
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "The farm can only be initialized once");
+
+    let is_err = fixture.farm_instance
+        .methods()
+        .stake(Identity::Address(fixture.wallet.address().into()))
+        .call_params(CallParameters::new(Some(1), Some(fixture.lp_asset_id), None))
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Can't stake before the pool has a farm");
+
+    fund_rewards(&fixture, reward_rate * 3600).await;
+
+    let is_err = fixture.other_farm_instance
+        .methods()
+        .set_reward_schedule(Bits256(*fixture.lp_asset_id), Bits256(*fixture.token_asset_id), reward_rate, in_seconds(3600), in_seconds(7200))
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Only the owner can set reward schedules");
+
+    let is_err = fixture.farm_instance
+        .methods()
+        .set_reward_schedule(Bits256(*fixture.lp_asset_id), Bits256(*fixture.token_asset_id), reward_rate * 2, in_seconds(3600), in_seconds(7200))
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Schedules must be covered by the available rewards");
+
+    let is_err = fixture.farm_instance
+        .methods()
+        .set_reward_schedule(Bits256(*fixture.lp_asset_id), Bits256(*fixture.token_asset_id), reward_rate, in_seconds(-3600), in_seconds(0))
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Schedules can't start in the past");
+
+    fixture.farm_instance
+        .methods()
+        .set_reward_schedule(Bits256(*fixture.lp_asset_id), Bits256(*fixture.token_asset_id), reward_rate, in_seconds(3600), in_seconds(7200))
+        .call()
+        .await
+        .unwrap();
+
+    // Replacing a schedule before it starts returns its rewards
+    fixture.farm_instance
+        .methods()
+        .set_reward_schedule(Bits256(*fixture.lp_asset_id), Bits256(*fixture.token_asset_id), reward_rate / 2, in_seconds(3600), in_seconds(7200))
+        .call()
+        .await
+        .unwrap();
+    let available = fixture.farm_instance.methods().get_available_rewards(Bits256(*fixture.token_asset_id)).call().await.unwrap().value;
+    assert_eq!(available, reward_rate * 3600 / 2);
+
+    let is_err = fixture.farm_instance
+        .methods()
+        .set_reward_schedule(Bits256(*fixture.lp_asset_id), Bits256(*BASE_ASSET_ID), 0, in_seconds(3600), in_seconds(7200))
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "A farm's reward asset can't change");
+}
@@ -0,0 +1,77 @@
+library limit_order_abi;
+
+use std::identity::Identity;
+
+// Events
+
+pub struct LimitOrderPlaced {
+    order_id: u64,
+    owner: b256,
+    pool: b256,
+    asset_in: b256,
+    amount_in: u64,
+    min_amount_out: u64,
+    keeper_tip: u64,
+    expiry: u64,
+}
+
+pub struct LimitOrderFilled {
+    order_id: u64,
+    filler: b256,
+    amount_in: u64,
+    amount_out: u64,
+    keeper_tip: u64,
+}
+
+pub struct LimitOrderCancelled {
+    order_id: u64,
+    sender: b256,
+    refund: u64,
+}
+
+// Returns
+
+pub struct LimitOrder {
+    owner: Identity,
+    pool: b256,
+    asset_in: b256,
+    asset_out: b256,
+    /// Amount sold when the order is filled, zero once it's filled or cancelled
+    amount_in: u64,
+    /// Sets the target price, the order fills once `amount_in` buys at least this much
+    min_amount_out: u64,
+    /// Paid to whoever fills the order, in the input asset and on top of `amount_in`
+    keeper_tip: u64,
+    /// TAI64 time after which the order can no longer be filled
+    expiry: u64,
+}
+
+abi LimitOrders {
+    ////////////////////
+    // Read only
+    ////////////////////
+    #[storage(read)]fn get_router() -> b256;
+    #[storage(read)]fn get_order(order_id: u64) -> LimitOrder;
+    /// Whether the pool's price has crossed an open order's target, so it can be filled
+    #[storage(read)]fn can_fill(order_id: u64) -> bool;
+    ////////////////////
+    // Actions
+    ////////////////////
+    /// Set the router orders are filled through, can only be called once by the deployer stored at deploy
+    #[storage(read, write)]fn initialize(router: b256);
+    /// Deposit the forwarded coins as an order selling them in a pair pool.
+    /// The keeper tip is taken from the deposit, and the rest is sold.
+    #[storage(read, write)]fn place_order(
+        pool: b256,
+        min_amount_out: u64,
+        keeper_tip: u64,
+        expiry: u64,
+        owner: Identity,
+    ) -> u64;
+    /// Fill an order once the pool's price crosses its target. The proceeds go to the order's
+    /// owner, and the keeper tip to `tip_recipient`. Returns the amount bought.
+    #[storage(read, write)]fn fill_order(order_id: u64, tip_recipient: Identity) -> u64;
+    /// Refund an open order, including its keeper tip, to its owner.
+    /// Owners can cancel at any time, and anyone can cancel an expired order.
+    #[storage(read, write)]fn cancel_order(order_id: u64) -> u64;
+}
@@ -0,0 +1,219 @@
+contract;
+
+use std::{
+    auth::{msg_sender, AuthError},
+    block::timestamp,
+    call_frames::msg_asset_id,
+    constants::ZERO_B256,
+    context::msg_amount,
+    contract_id::ContractId,
+    identity::Identity,
+    logging::log,
+    storage::get,
+    token::transfer,
+    u128::U128,
+};
+use exchange_abi::Exchange;
+use limit_order_abi::{
+    LimitOrder,
+    LimitOrderCancelled,
+    LimitOrderFilled,
+    LimitOrderPlaced,
+    LimitOrders,
+};
+use router_abi::Router;
+use microchain_helpers::identity_to_b256;
+
+enum Error {
+    AlreadyInitialized: (),
+    NotInitialized: (),
+    InvalidToken: (),
+    InsufficentInput: (),
+    InsufficentOutput: (),
+    OrderNotFound: (),
+    OrderClosed: (),
+    OrderExpired: (),
+    PriceNotReached: (),
+    MustBeCalledByOwner: (),
+    MustBeCalledByDeployer: (),
+}
+
+// Holds the input of each order until a keeper fills it through the router, once the pool's
+// price has crossed the order's target. The router's minimum output protects the owner from
+// the fill moving the price back past the target.
+
+////////////////////////////////////////
+// Constants
+////////////////////////////////////////
+
+/// Address allowed to initialize the contract, set at deploy so the router can't be picked by anyone else
+const DEPLOYER_SLOT = 0x0000000000000000000000000000000000000000000000000000000000000000;
+
+storage {
+    router: b256 = 0x0000000000000000000000000000000000000000000000000000000000000000,
+    // Order IDs start at 1
+    next_order_id: u64 = 1,
+    orders: StorageMap<u64, LimitOrder> = StorageMap {},
+}
+
+////////////////////////////////////////
+// Helper functions
+////////////////////////////////////////
+#[storage(read)]
+fn validate_deployer() {
+    let sender: Result<Identity, AuthError> = msg_sender();
+    require(
+        sender.unwrap() == Identity::Address(Address::from(get::<b256>(DEPLOYER_SLOT))),
+        Error::MustBeCalledByDeployer
+    );
+}
+
+#[storage(read)]
+fn get_open_order(order_id: u64) -> LimitOrder {
+    let order = storage.orders.get(order_id);
+    require(order.pool != ZERO_B256, Error::OrderNotFound);
+    require(order.amount_in > 0, Error::OrderClosed);
+    order
+}
+
+/// Whether the pool's spot price is at least the order's target price
+fn price_reached(order: LimitOrder) -> bool {
+    let exchange = abi(Exchange, order.pool);
+    let (token0, _token1) = exchange.get_tokens();
+    let pool_info = exchange.get_pool_info();
+    let (reserve_in, reserve_out) = if order.asset_in == token0 {
+        (pool_info.token_0_reserve, pool_info.token_1_reserve)
+    } else {
+        (pool_info.token_1_reserve, pool_info.token_0_reserve)
+    };
+
+    // reserve_out / reserve_in >= min_amount_out / amount_in
+    let available = U128::from((0, reserve_out)) * U128::from((0, order.amount_in));
+    let target = U128::from((0, order.min_amount_out)) * U128::from((0, reserve_in));
+    reserve_in > 0 && (available > target || available == target) // U128 doesn't have >= yet
+}
+
+impl LimitOrders for Contract {
+    #[storage(read)]
+    fn get_router() -> b256 {
+        storage.router
+    }
+
+    #[storage(read)]
+    fn get_order(order_id: u64) -> LimitOrder {
+        let order = storage.orders.get(order_id);
+        require(order.pool != ZERO_B256, Error::OrderNotFound);
+        order
+    }
+
+    #[storage(read)]
+    fn can_fill(order_id: u64) -> bool {
+        let order = storage.orders.get(order_id);
+        order.amount_in > 0 && timestamp() < order.expiry && price_reached(order)
+    }
+
+    #[storage(read, write)]
+    fn initialize(router: b256) {
+        validate_deployer();
+        require(storage.router == ZERO_B256, Error::AlreadyInitialized);
+        storage.router = router;
+    }
+
+    #[storage(read, write)]
+    fn place_order(
+        pool: b256,
+        min_amount_out: u64,
+        keeper_tip: u64,
+        expiry: u64,
+        owner: Identity,
+    ) -> u64 {
+        require(storage.router != ZERO_B256, Error::NotInitialized);
+        require(expiry > timestamp(), Error::OrderExpired);
+        require(min_amount_out > 0, Error::InsufficentOutput);
+
+        let asset_in: b256 = msg_asset_id().into();
+        let (token0, token1) = abi(Exchange, pool).get_tokens();
+        require(asset_in == token0 || asset_in == token1, Error::InvalidToken);
+        let asset_out = if asset_in == token0 { token1 } else { token0 };
+
+        require(msg_amount() > keeper_tip, Error::InsufficentInput);
+        let amount_in = msg_amount() - keeper_tip;
+
+        let order_id = storage.next_order_id;
+        storage.next_order_id = order_id + 1;
+        storage.orders.insert(order_id, LimitOrder {
+            owner: owner,
+            pool: pool,
+            asset_in: asset_in,
+            asset_out: asset_out,
+            amount_in: amount_in,
+            min_amount_out: min_amount_out,
+            keeper_tip: keeper_tip,
+            expiry: expiry,
+        });
+
+        log(LimitOrderPlaced {
+            order_id: order_id,
+            owner: identity_to_b256(owner),
+            pool: pool,
+            asset_in: asset_in,
+            amount_in: amount_in,
+            min_amount_out: min_amount_out,
+            keeper_tip: keeper_tip,
+            expiry: expiry,
+        });
+
+        order_id
+    }
+
+    #[storage(read, write)]
+    fn fill_order(order_id: u64, tip_recipient: Identity) -> u64 {
+        let mut order = get_open_order(order_id);
+        require(timestamp() < order.expiry, Error::OrderExpired);
+        require(price_reached(order), Error::PriceNotReached);
+
+        let amount_in = order.amount_in;
+        order.amount_in = 0;
+        storage.orders.insert(order_id, order);
+
+        let result = abi(Router, storage.router).swap_exact_input {
+            asset_id: order.asset_in,
+            coins: amount_in,
+        }(order.pool, order.min_amount_out, order.owner);
+
+        if order.keeper_tip > 0 {
+            transfer(order.keeper_tip, ContractId::from(order.asset_in), tip_recipient);
+        }
+
+        log(LimitOrderFilled {
+            order_id: order_id,
+            filler: identity_to_b256(msg_sender().unwrap()),
+            amount_in: amount_in,
+            amount_out: result.output_amount,
+            keeper_tip: order.keeper_tip,
+        });
+
+        result.output_amount
+    }
+
+    #[storage(read, write)]
+    fn cancel_order(order_id: u64) -> u64 {
+        let mut order = get_open_order(order_id);
+        let sender = msg_sender().unwrap();
+        require(sender == order.owner || timestamp() >= order.expiry, Error::MustBeCalledByOwner);
+
+        let refund = order.amount_in + order.keeper_tip;
+        order.amount_in = 0;
+        storage.orders.insert(order_id, order);
+
+        transfer(refund, ContractId::from(order.asset_in), order.owner);
+
+        log(LimitOrderCancelled {
+            order_id: order_id,
+            sender: identity_to_b256(sender),
+            refund: refund,
+        });
+
+        refund
+    }
+}
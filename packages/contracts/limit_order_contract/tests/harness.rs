@@ -0,0 +1,414 @@
+use std::str::FromStr;
+use chrono::Utc;
+use fuels::{
+    prelude::*,
+    fuels_abigen::abigen,
+    signers::WalletUnlocked,
+    tx::{AssetId, Bytes32, StorageSlot},
+};
+use test_helpers::{
+    deployer_storage,
+    get_logs,
+    get_wallets,
+    produce_block_at,
+    unix_to_tai64,
+    LIMIT_ORDER_ABI,
+    MIN_FEE_CHANGE_DELAY,
+};
+
+///////////////////////////////
+// Load the Limit Order Contract abi
+///////////////////////////////
+abigen!(LimitOrders, "./out/debug/limit_order_contract-abi.json");
+
+abigen!(Exchange, "../exchange_contract/out/debug/exchange_contract-abi.json");
+abigen!(Router, "../router_contract/out/debug/router_contract-abi.json");
+abigen!(TestToken, "../token_contract/out/debug/token_contract-abi.json");
+abigen!(Vault, "../vault_contract/out/debug/vault_contract-abi.json");
+
+struct Fixture {
+    wallet: WalletUnlocked,
+    keeper_wallet: WalletUnlocked,
+    token_asset_id: AssetId,
+    exchange_contract_id: Bech32ContractId,
+    exchange_instance: Exchange,
+    router_contract_id: Bech32ContractId,
+    vault_contract_id: Bech32ContractId,
+    orders_contract_id: Bech32ContractId,
+    orders_instance: LimitOrders,
+}
+
+fn to_9_decimal(num: u64) -> u64 {
+    num * 1_000_000_000
+}
+
+/// Deploy an ETH/token pool with 10 of each, a router, and the limit order contract
+async fn setup() -> Fixture {
+    let wallets = get_wallets().await;
+    let wallet = wallets.get(0).unwrap().clone();
+    let keeper_wallet = wallets.get(1).unwrap().clone();
+    produce_block_at(&wallet, Utc::now().timestamp()).await;
+
+    let token_contract_id = Contract::deploy(
+        "../token_contract/out/debug/token_contract.bin",
+        &wallet,
+        TxParameters::default(),
+        StorageConfiguration::new(None, None),
+    )
+    .await
+    .unwrap();
+
+    let token_instance = TestToken::new(token_contract_id.clone(), wallet.clone());
+    token_instance
+        .methods()
+        .initialize(to_9_decimal(20000), wallet.address().into())
+        .call()
+        .await
+        .unwrap();
+    token_instance
+        .methods()
+        .mint()
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .unwrap();
+    let token_asset_id = AssetId::new(*token_contract_id.hash());
+
+    let token1_slot = Bytes32::from_str("0x0000000000000000000000000000000000000000000000000000000000000001").unwrap();
+    let storage_vec = vec![
+        StorageSlot::new(token1_slot, token_contract_id.hash()),
+    ];
+    let exchange_contract_id = Contract::deploy_with_parameters(
+        "../exchange_contract/out/debug/exchange_contract.bin",
+        &wallet,
+        TxParameters::default(),
+        StorageConfiguration::with_manual_storage(Some(storage_vec)),
+        Salt::from([0u8; 32]),
+    )
+    .await
+    .unwrap();
+
+    let vault_contract_id = Contract::deploy(
+        "../vault_contract/out/debug/vault_contract.bin",
+        &wallet,
+        TxParameters::default(),
+        deployer_storage(&wallet),
+    )
+    .await
+    .unwrap();
+
+    let router_contract_id = Contract::deploy(
+        "../router_contract/out/debug/router_contract.bin",
+        &wallet,
+        TxParameters::default(),
+        StorageConfiguration::new(None, None),
+    )
+    .await
+    .unwrap();
+
+    let orders_contract_id = Contract::deploy(
+        "./out/debug/limit_order_contract.bin",
+        &wallet,
+        TxParameters::default(),
+        deployer_storage(&wallet),
+    )
+    .await
+    .unwrap();
+
+    Vault::new(vault_contract_id.clone(), wallet.clone())
+        .methods()
+        .initialize(Identity::Address(wallet.address().into()), MIN_FEE_CHANGE_DELAY)
+        .call()
+        .await
+        .unwrap();
+
+    let exchange_instance = Exchange::new(exchange_contract_id.clone(), wallet.clone());
+    exchange_instance
+        .methods()
+        .initialize(Bits256(vault_contract_id.hash().into()))
+        .set_contracts(&[vault_contract_id.clone()])
+        .call()
+        .await
+        .unwrap();
+
+    let is_err = LimitOrders::new(orders_contract_id.clone(), keeper_wallet.clone())
+        .methods()
+        .initialize(Bits256(router_contract_id.hash().into()))
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "The limit order contract should only be initialized by its deployer");
+
+    let orders_instance = LimitOrders::new(orders_contract_id.clone(), wallet.clone());
+    orders_instance
+        .methods()
+        .initialize(Bits256(router_contract_id.hash().into()))
+        .call()
+        .await
+        .unwrap();
+
+    let _receipts = wallet
+        .force_transfer_to_contract(&exchange_contract_id, to_9_decimal(10), BASE_ASSET_ID, TxParameters::default())
+        .await;
+    let _receipts = wallet
+        .force_transfer_to_contract(&exchange_contract_id, to_9_decimal(10), token_asset_id, TxParameters::default())
+        .await;
+    exchange_instance
+        .methods()
+        .add_liquidity(Identity::Address(wallet.address().into()))
+        .tx_params(TxParameters {
+            gas_price: 0,
+            gas_limit: 100_000_000,
+            maturity: 0,
+        })
+        .call_params(CallParameters::new(None, None, Some(100_000_000)))
+        .append_variable_outputs(2)
+        .call()
+        .await
+        .unwrap();
+
+    Fixture {
+        wallet: wallet,
+        keeper_wallet: keeper_wallet,
+        token_asset_id: token_asset_id,
+        exchange_contract_id: exchange_contract_id,
+        exchange_instance: exchange_instance,
+        router_contract_id: router_contract_id,
+        vault_contract_id: vault_contract_id,
+        orders_contract_id: orders_contract_id,
+        orders_instance: orders_instance,
+    }
+}
+
+/// Place an order selling `amount` ETH, plus the keeper tip, for at least `min_amount_out` tokens
+async fn place_order(fixture: &Fixture, amount: u64, min_amount_out: u64, keeper_tip: u64, expiry: u64) -> u64 {
+    let response = fixture.orders_instance
+        .methods()
+        .place_order(
+            Bits256(fixture.exchange_contract_id.hash().into()),
+            min_amount_out,
+            keeper_tip,
+            expiry,
+            Identity::Address(fixture.wallet.address().into()),
+        )
+        .set_contracts(&[fixture.exchange_contract_id.clone()])
+        .call_params(CallParameters::new(Some(amount + keeper_tip), Some(BASE_ASSET_ID), None))
+        .call()
+        .await
+        .unwrap();
+
+    let logs = get_logs::<LimitOrderPlaced, _>(&response, &fixture.orders_contract_id, LIMIT_ORDER_ABI);
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs.get(0).unwrap().amount_in, amount);
+    response.value
+}
+
+/// Fill an order from the keeper wallet
+async fn fill_order(fixture: &Fixture, order_id: u64) -> Option<u64> {
+    LimitOrders::new(fixture.orders_contract_id.clone(), fixture.keeper_wallet.clone())
+        .methods()
+        .fill_order(order_id, Identity::Address(fixture.keeper_wallet.address().into()))
+        .set_contracts(&[
+            fixture.router_contract_id.clone(),
+            fixture.exchange_contract_id.clone(),
+            fixture.vault_contract_id.clone(),
+        ])
+        .tx_params(TxParameters {
+            gas_price: 0,
+            gas_limit: 100_000_000,
+            maturity: 0,
+        })
+        .call_params(CallParameters::new(None, None, Some(100_000_000)))
+        .append_variable_outputs(2)
+        .call()
+        .await
+        .ok()
+        .map(|response| response.value)
+}
+
+/// Buy ETH from the pool with tokens, raising the price of ETH
+async fn buy_eth(fixture: &Fixture, token_amount: u64) {
+    let amount_out = fixture.exchange_instance
+        .methods()
+        .get_amount_out(token_amount, false)
+        .call()
+        .await
+        .unwrap()
+        .value;
+
+    fixture.exchange_instance
+        .methods()
+        .swap(amount_out, 0, Identity::Address(fixture.wallet.address().into()))
+        .set_contracts(&[fixture.vault_contract_id.clone()])
+        .call_params(CallParameters::new(Some(token_amount), Some(fixture.token_asset_id), None))
+        .tx_params(TxParameters {
+            gas_price: 0,
+            gas_limit: 100_000_000,
+            maturity: 0,
+        })
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .unwrap();
+}
+
+fn expiry_in(seconds: i64) -> u64 {
+    unix_to_tai64((Utc::now().timestamp() + seconds) as u64)
+}
+
+#[tokio::test]
+async fn fill_order_once_price_crosses() {
+    let fixture = setup().await;
+    let amount = to_9_decimal(1);
+    let min_amount_out = to_9_decimal(12) / 10;
+    let keeper_tip = to_9_decimal(1) / 100;
+
+    let is_err = fixture.orders_instance
+        .methods()
+        .place_order(
+            Bits256(fixture.exchange_contract_id.hash().into()),
+            min_amount_out,
+            amount,
+            expiry_in(3600),
+            Identity::Address(fixture.wallet.address().into()),
+        )
+        .set_contracts(&[fixture.exchange_contract_id.clone()])
+        .call_params(CallParameters::new(Some(amount), Some(BASE_ASSET_ID), None))
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "The keeper tip must leave something to sell");
+
+    let order_id = place_order(&fixture, amount, min_amount_out, keeper_tip, expiry_in(3600)).await;
+    let order = fixture.orders_instance.methods().get_order(order_id).call().await.unwrap().value;
+    assert_eq!(order.amount_in, amount);
+    assert_eq!(order.asset_out, Bits256(*fixture.token_asset_id));
+
+    // The pool's price of 1 token per ETH is below the target of 1.2
+    let can_fill = fixture.orders_instance
+        .methods()
+        .can_fill(order_id)
+        .set_contracts(&[fixture.exchange_contract_id.clone()])
+        .call()
+        .await
+        .unwrap()
+        .value;
+    assert!(!can_fill);
+    assert!(fill_order(&fixture, order_id).await.is_none(), "Orders can't fill below their target");
+
+    buy_eth(&fixture, to_9_decimal(5)).await;
+    let can_fill = fixture.orders_instance
+        .methods()
+        .can_fill(order_id)
+        .set_contracts(&[fixture.exchange_contract_id.clone()])
+        .call()
+        .await
+        .unwrap()
+        .value;
+    assert!(can_fill);
+
+    let expected_amount_out = fixture.exchange_instance
+        .methods()
+        .get_amount_out(amount, true)
+        .call()
+        .await
+        .unwrap()
+        .value;
+    let starting_token_balance = fixture.wallet.get_asset_balance(&fixture.token_asset_id).await.unwrap();
+    let starting_keeper_balance = fixture.keeper_wallet.get_asset_balance(&BASE_ASSET_ID).await.unwrap();
+
+    let amount_out = fill_order(&fixture, order_id).await.unwrap();
+    assert_eq!(amount_out, expected_amount_out);
+    assert!(amount_out >= min_amount_out);
+
+    // The owner receives the proceeds, and the keeper the tip
+    let end_token_balance = fixture.wallet.get_asset_balance(&fixture.token_asset_id).await.unwrap();
+    let end_keeper_balance = fixture.keeper_wallet.get_asset_balance(&BASE_ASSET_ID).await.unwrap();
+    assert_eq!(end_token_balance - starting_token_balance, amount_out);
+    assert_eq!(end_keeper_balance - starting_keeper_balance, keeper_tip);
+
+    let order = fixture.orders_instance.methods().get_order(order_id).call().await.unwrap().value;
+    assert_eq!(order.amount_in, 0);
+    assert!(fill_order(&fixture, order_id).await.is_none(), "Orders can only be filled once");
+}
+
+#[tokio::test]
+async fn cancel_and_expire_orders() {
+    let fixture = setup().await;
+    let amount = to_9_decimal(1);
+    let keeper_tip = to_9_decimal(1) / 100;
+    let keeper_orders = LimitOrders::new(fixture.orders_contract_id.clone(), fixture.keeper_wallet.clone());
+
+    let order_id = place_order(&fixture, amount, amount * 2, keeper_tip, expiry_in(3600)).await;
+
+    let is_err = keeper_orders
+        .methods()
+        .cancel_order(order_id)
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Only the owner can cancel an open order");
+
+    let starting_balance = fixture.wallet.get_asset_balance(&BASE_ASSET_ID).await.unwrap();
+    let response = fixture.orders_instance
+        .methods()
+        .cancel_order(order_id)
+        .tx_params(TxParameters {
+            gas_price: 0,
+            gas_limit: 100_000_000,
+            maturity: 0,
+        })
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .unwrap();
+    let end_balance = fixture.wallet.get_asset_balance(&BASE_ASSET_ID).await.unwrap();
+    assert_eq!(response.value, amount + keeper_tip);
+    assert_eq!(end_balance - starting_balance, amount + keeper_tip);
+
+    let logs = get_logs::<LimitOrderCancelled, _>(&response, &fixture.orders_contract_id, LIMIT_ORDER_ABI);
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs.get(0).unwrap().refund, amount + keeper_tip);
+
+    let is_err = fixture.orders_instance
+        .methods()
+        .cancel_order(order_id)
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Orders can only be cancelled once");
+
+    // Expired orders can't be filled, even once the price crosses, and anyone can return them to their owner
+    let order_id = place_order(&fixture, amount, amount / 2, keeper_tip, expiry_in(3600)).await;
+    produce_block_at(&fixture.wallet, Utc::now().timestamp() + 7200).await;
+    assert!(fill_order(&fixture, order_id).await.is_none(), "Expired orders can't be filled");
+
+    let starting_balance = fixture.wallet.get_asset_balance(&BASE_ASSET_ID).await.unwrap();
+    keeper_orders
+        .methods()
+        .cancel_order(order_id)
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .unwrap();
+    let end_balance = fixture.wallet.get_asset_balance(&BASE_ASSET_ID).await.unwrap();
+    assert_eq!(end_balance - starting_balance, amount + keeper_tip);
+
+    let is_err = fixture.orders_instance
+        .methods()
+        .place_order(
+            Bits256(fixture.exchange_contract_id.hash().into()),
+            amount,
+            0,
+            expiry_in(3600),
+            Identity::Address(fixture.wallet.address().into()),
+        )
+        .set_contracts(&[fixture.exchange_contract_id.clone()])
+        .call_params(CallParameters::new(Some(amount), Some(BASE_ASSET_ID), None))
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Orders can't be placed already expired");
+}
@@ -18,11 +18,27 @@ use std::{
 enum Error {
     InsufficentReserves: (),
     InsufficentAmount: (),
+    MaxRatioExceeded: (),
+    InvalidFixedPoint: (),
+    FixedPointOverflow: (),
 }
 
 // Liquidity miner fee apply to all swaps
 const LIQUIDITY_MINER_FEE = 333;
 
+/// Fixed-point math uses 18 decimals
+const FIXED_ONE = U128::from((0, 1_000_000_000_000_000_000));
+
+/// ln(2) with 18 decimals
+const LN_2 = 693_147_180_559_945_309;
+
+/// Relative error allowed for `pow_fixed`, with 18 decimals. Weighted prices
+/// are rounded by this much in the pool's favor.
+const MAX_POW_RELATIVE_ERROR = 10_000;
+
+/// Largest share of a reserve a weighted swap can move, in percent, keeping the powers in range
+const MAX_WEIGHTED_RATIO = 30;
+
 // Calculate 0.3% fee
 pub fn calculate_amount_with_fee(amount: u64) -> u64 {
     let fee: u64 = (amount / LIQUIDITY_MINER_FEE);
@@ -40,28 +56,163 @@ pub fn mutiply_div(a: u64, b: u64, c: u64) -> u64 {
     }
 }
 
-/// Pricing function for converting between tokens.
-pub fn get_input_price(input_amount: u64, input_reserve: u64, output_reserve: u64) -> u64 {
+/// Pricing function for converting between tokens, with the pool's LP fee in basis points.
+pub fn get_input_price(input_amount: u64, input_reserve: u64, output_reserve: u64, lp_fee: u64) -> u64 {
     require(input_amount > 0, Error::InsufficentAmount);
     require(input_reserve > 0 && output_reserve > 0, Error::InsufficentReserves);
-    let input_amount_with_fee = U128::from((0, input_amount)) * U128::from((0, 997));
+    let input_amount_with_fee = U128::from((0, input_amount)) * U128::from((0, 10_000 - lp_fee));
     let numerator = input_amount_with_fee * U128::from((0, output_reserve));
-    let denominator = (U128::from((0, input_reserve)) * U128::from((0, 1000))) + input_amount_with_fee;
+    let denominator = (U128::from((0, input_reserve)) * U128::from((0, 10_000))) + input_amount_with_fee;
     let result_wrapped = (numerator / denominator).as_u64();
     result_wrapped.unwrap()
 }
 
-/// Pricing function for converting between tokens.
-pub fn get_output_price(output_amount: u64, input_reserve: u64, output_reserve: u64) -> u64 {
+/// Pricing function for converting between tokens, with the pool's LP fee in basis points.
+pub fn get_output_price(output_amount: u64, input_reserve: u64, output_reserve: u64, lp_fee: u64) -> u64 {
     require(output_amount > 0, Error::InsufficentAmount);
     require(input_reserve > 0 && output_reserve > 0, Error::InsufficentReserves);
 
-    let numerator = U128::from((0, input_reserve)) * U128::from((0, output_amount)) * U128::from((0, 1000));
-    let denominator = U128::from((0, output_reserve - output_amount)) * U128::from((0, 997));
+    let numerator = U128::from((0, input_reserve)) * U128::from((0, output_amount)) * U128::from((0, 10_000));
+    let denominator = U128::from((0, output_reserve - output_amount)) * U128::from((0, 10_000 - lp_fee));
     let amount_in = (numerator / denominator) + U128::from((0, 1));
     amount_in.as_u64().unwrap()
 }
 
+/// Natural log of a fixed-point `x`, which must be at least one
+pub fn ln_fixed(x: U128) -> U128 {
+    require(x > FIXED_ONE || x == FIXED_ONE, Error::InvalidFixedPoint); // U128 doesn't have >= yet
+
+    // ln(x) = k * ln(2) + ln(m), with m in [1, 2)
+    let two = FIXED_ONE * U128::from((0, 2));
+    let mut m = x;
+    let mut k = 0;
+    while m > two || m == two {
+        m = m >> 1;
+        k += 1;
+    }
+
+    // ln(m) = 2 * atanh((m - 1) / (m + 1)), which converges quickly as the ratio is at most 1/3
+    let z = (m - FIXED_ONE) * FIXED_ONE / (m + FIXED_ONE);
+    let z_squared = z * z / FIXED_ONE;
+    let mut term = z;
+    let mut sum = U128::min();
+    let mut i = 1;
+    while term != U128::min() {
+        sum = sum + term / U128::from((0, i));
+        term = term * z_squared / FIXED_ONE;
+        i += 2;
+    }
+
+    U128::from((0, k)) * U128::from((0, LN_2)) + sum * U128::from((0, 2))
+}
+
+/// e to the power of a fixed-point `x`
+pub fn exp_fixed(x: U128) -> U128 {
+    // e^x = 2^k * e^r, with r in [0, ln(2))
+    let ln_2 = U128::from((0, LN_2));
+    let k = (x / ln_2).as_u64().unwrap();
+    require(k < 64, Error::FixedPointOverflow);
+    let r = x - U128::from((0, k)) * ln_2;
+
+    let mut sum = FIXED_ONE;
+    let mut term = FIXED_ONE;
+    let mut i = 1;
+    while term != U128::min() {
+        term = term * r / FIXED_ONE / U128::from((0, i));
+        sum = sum + term;
+        i += 1;
+    }
+
+    sum << k
+}
+
+/// Fixed-point `base` to the power of a fixed-point `exponent`, as e^(exponent * ln(base))
+pub fn pow_fixed(base: U128, exponent: U128) -> U128 {
+    if base > FIXED_ONE || base == FIXED_ONE {
+        exp_fixed(exponent * ln_fixed(base) / FIXED_ONE)
+    } else {
+        // ln is only defined here from one upwards, so invert bases below one
+        require(base > U128::min(), Error::InvalidFixedPoint);
+        let inverse = FIXED_ONE * FIXED_ONE / base;
+        FIXED_ONE * FIXED_ONE / exp_fixed(exponent * ln_fixed(inverse) / FIXED_ONE)
+    }
+}
+
+fn pow_fixed_up(base: U128, exponent: U128) -> U128 {
+    let power = pow_fixed(base, exponent);
+    power + power * U128::from((0, MAX_POW_RELATIVE_ERROR)) / FIXED_ONE + U128::from((0, 1))
+}
+
+fn div_fixed_up(a: U128, b: U128) -> U128 {
+    (a * FIXED_ONE + b - U128::from((0, 1))) / b
+}
+
+/// Pricing function for a weighted pool with an exact input, with the pool's LP fee in basis points.
+/// Weights can be in any unit, as only their ratio is used.
+/// out = output_reserve * (1 - (input_reserve / (input_reserve + input))^(input_weight / output_weight))
+pub fn get_weighted_input_price(
+    input_amount: u64,
+    input_reserve: u64,
+    output_reserve: u64,
+    input_weight: u64,
+    output_weight: u64,
+    lp_fee: u64,
+) -> u64 {
+    require(input_amount > 0, Error::InsufficentAmount);
+    require(input_reserve > 0 && output_reserve > 0, Error::InsufficentReserves);
+    let input_amount_with_fee = mutiply_div(input_amount, 10_000 - lp_fee, 10_000);
+    require(input_amount_with_fee <= mutiply_div(input_reserve, MAX_WEIGHTED_RATIO, 100), Error::MaxRatioExceeded);
+
+    // Round the power up, so the output is rounded down
+    let reserve_in = U128::from((0, input_reserve));
+    let base = div_fixed_up(reserve_in, reserve_in + U128::from((0, input_amount_with_fee)));
+    let exponent = U128::from((0, input_weight)) * FIXED_ONE / U128::from((0, output_weight));
+    let power = pow_fixed_up(base, exponent);
+    if power > FIXED_ONE || power == FIXED_ONE {
+        return 0;
+    }
+
+    (U128::from((0, output_reserve)) * (FIXED_ONE - power) / FIXED_ONE).as_u64().unwrap()
+}
+
+/// Pricing function for a weighted pool with an exact output, with the pool's LP fee in basis points.
+/// in = input_reserve * ((output_reserve / (output_reserve - output))^(output_weight / input_weight) - 1)
+pub fn get_weighted_output_price(
+    output_amount: u64,
+    input_reserve: u64,
+    output_reserve: u64,
+    input_weight: u64,
+    output_weight: u64,
+    lp_fee: u64,
+) -> u64 {
+    require(output_amount > 0, Error::InsufficentAmount);
+    require(input_reserve > 0 && output_reserve > 0, Error::InsufficentReserves);
+    require(output_amount <= mutiply_div(output_reserve, MAX_WEIGHTED_RATIO, 100), Error::MaxRatioExceeded);
+
+    // Round the base, exponent and power up, so the input is rounded up
+    let reserve_out = U128::from((0, output_reserve));
+    let base = div_fixed_up(reserve_out, reserve_out - U128::from((0, output_amount)));
+    let exponent = div_fixed_up(U128::from((0, output_weight)), U128::from((0, input_weight)));
+    let power = pow_fixed_up(base, exponent);
+
+    let one = U128::from((0, 1));
+    let input_amount = (U128::from((0, input_reserve)) * (power - FIXED_ONE) + FIXED_ONE - one) / FIXED_ONE;
+    let basis_points = U128::from((0, 10_000));
+    let fee_divisor = basis_points - U128::from((0, lp_fee));
+    ((input_amount * basis_points + fee_divisor - one) / fee_divisor).as_u64().unwrap()
+}
+
+/// Fee on a vault fee schedule at `now`, falling by `change_rate` each second from `start_fee` down to zero.
+/// Shared by the vault and the exchanges' fee cache, so both always agree.
+pub fn calculate_vault_fee(start_fee: u16, change_rate: u16, start_time: u32, now: u64) -> u64 {
+    let decrease_since_start = change_rate * (now - start_time);
+    if decrease_since_start > start_fee {
+        0
+    } else {
+        start_fee - decrease_since_start
+    }
+}
+
 pub fn identity_to_b256(identity: Identity) -> b256 {
     match identity {
         Identity::Address(address) => address.into(),
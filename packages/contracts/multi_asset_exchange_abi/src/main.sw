@@ -0,0 +1,61 @@
+library multi_asset_exchange_abi;
+
+use std::identity::Identity;
+
+// Events
+
+pub struct MultiAssetLiquidityAdded {
+    sender: b256,
+    lp_tokens: u64,
+    recipient: b256,
+}
+
+pub struct MultiAssetLiquidityRemoved {
+    sender: b256,
+    lp_tokens: u64,
+    recipient: b256,
+}
+
+pub struct MultiAssetSwap {
+    sender: b256,
+    asset_in: b256,
+    amount_in: u64,
+    asset_out: b256,
+    amount_out: u64,
+    recipient: b256,
+}
+
+// Returns
+
+pub struct MultiAssetPoolInfo {
+    token_count: u64,
+    lp_token_supply: u64,
+}
+
+abi MultiAssetExchange {
+    ////////////////////
+    // Read only
+    ////////////////////
+    #[storage(read)]fn get_pool_info() -> MultiAssetPoolInfo;
+    /// Get one of the pool's tokens, with `index` below the token count
+    #[storage(read)]fn get_token(index: u64) -> b256;
+    /// Reserve of one of the pool's tokens
+    #[storage(read)]fn get_reserve(asset_id: b256) -> u64;
+    /// Output a swap of `amount_in` between two of the pool's tokens would receive, after the LP fee.
+    #[storage(read)]fn get_amount_out(asset_in: b256, asset_out: b256, amount_in: u64) -> u64;
+    /// Input, including the LP fee, needed to receive `amount_out`.
+    #[storage(read)]fn get_amount_in(asset_in: b256, asset_out: b256, amount_out: u64) -> u64;
+    ////////////////////
+    // Actions
+    ////////////////////
+    /// Check the tokens set in storage at deployment, and open the pool
+    #[storage(read, write)]fn initialize();
+    /// Deposit every token at the current ratio to mint LP tokens.
+    /// Tokens beyond the ratio of the scarcest deposit are returned to the recipient.
+    #[storage(read, write)]fn add_liquidity(recipient: Identity) -> u64;
+    /// Burn LP tokens to withdraw every token at the current ratio, returning the LP tokens burned.
+    #[storage(read, write)]fn remove_liquidity(recipient: Identity) -> u64;
+    /// Swap `asset_in`, sent beforehand or forwarded, for `amount_out` of another of the pool's tokens.
+    /// Everything of `asset_in` held beyond its reserve is taken as the input.
+    #[storage(read, write)]fn swap(asset_in: b256, asset_out: b256, amount_out: u64, recipient: Identity);
+}
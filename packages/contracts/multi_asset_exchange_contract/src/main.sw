@@ -0,0 +1,316 @@
+contract;
+
+use std::{
+    auth::msg_sender,
+    call_frames::contract_id,
+    context::*,
+    contract_id::ContractId,
+    identity::Identity,
+    logging::log,
+    storage::get,
+    token::{burn, mint, transfer},
+    u128::U128,
+    u256::U256,
+};
+
+use multi_asset_exchange_abi::{
+    MultiAssetExchange,
+    MultiAssetLiquidityAdded,
+    MultiAssetLiquidityRemoved,
+    MultiAssetPoolInfo,
+    MultiAssetSwap,
+};
+use microchain_helpers::{get_input_price, get_output_price, identity_to_b256, mutiply_div};
+
+enum Error {
+    AlreadyInitialized: (),
+    NotInitialized: (),
+    InvalidTokens: (),
+    InvalidToken: (),
+    InsufficentOutput: (),
+    InsufficentLiquidity: (),
+    InsufficentInput: (),
+    Invariant: (),
+    InsufficentLiquidityMinted: (),
+    InsufficentLiquidityBurned: (),
+}
+
+////////////////////////////////////////
+// Constants
+////////////////////////////////////////
+// Token `i` is stored in slot `i` at deployment, like the pair exchange's
+// TOKEN_0_SLOT and TOKEN_1_SLOT, with the number of tokens in its own slot.
+const TOKEN_COUNT_SLOT = 0x0000000000000000000000000000000000000000000000000000000000000008;
+
+const MIN_TOKENS = 2;
+const MAX_TOKENS = 8;
+
+/// LP tokens minted by the first deposit, whatever its size
+const INITIAL_LP_SUPPLY = 100_000_000_000;
+
+/// LP tokens locked by the first deposit, so the pool can't be emptied
+const MINIMUM_LIQUIDITY = 1000;
+
+/// LP fees are in basis points
+const BASIS_POINTS = 10_000;
+
+/// LP fee charged on every swap, 0.3%
+const LP_FEE = 30;
+
+////////////////////////////////////////
+// Storage declarations
+////////////////////////////////////////
+// Every pair of tokens is priced as a constant product pool of their two reserves. With equal
+// weights, this keeps the product of all the reserves from falling, like a pair pool does.
+
+storage {
+    // Zero until the pool is initialized
+    token_count: u64 = 0,
+    reserves: StorageMap<b256, u64> = StorageMap {},
+    lp_token_supply: u64 = 0,
+}
+
+////////////////////////////////////////
+// Helper functions
+////////////////////////////////////////
+fn token_slot(index: u64) -> b256 {
+    asm(r1: (0, 0, 0, index)) { r1: b256 }
+}
+
+#[storage(read)]
+fn get_token(index: u64) -> b256 {
+    get::<b256>(token_slot(index))
+}
+
+#[storage(read)]
+fn is_token(asset_id: b256) -> bool {
+    let token_count = storage.token_count;
+    let mut i = 0;
+    while i < token_count {
+        if get_token(i) == asset_id {
+            return true;
+        }
+        i += 1;
+    }
+    false
+}
+
+#[storage(read)]
+fn get_reserves(asset_in: b256, asset_out: b256) -> (u64, u64) {
+    require(storage.token_count > 0, Error::NotInitialized);
+    require(asset_in != asset_out && is_token(asset_in) && is_token(asset_out), Error::InvalidToken);
+    (storage.reserves.get(asset_in), storage.reserves.get(asset_out))
+}
+
+/// `a * b / c`, rounded up
+fn mutiply_div_up(a: u64, b: u64, c: u64) -> u64 {
+    let numerator = U128::from((0, a)) * U128::from((0, b)) + U128::from((0, c - 1));
+    (numerator / U128::from((0, c))).as_u64().unwrap()
+}
+
+// ////////////////////////////////////////
+// // ABI definitions
+// ////////////////////////////////////////
+impl MultiAssetExchange for Contract {
+    #[storage(read)]
+    fn get_pool_info() -> MultiAssetPoolInfo {
+        MultiAssetPoolInfo {
+            token_count: storage.token_count,
+            lp_token_supply: storage.lp_token_supply,
+        }
+    }
+
+    #[storage(read)]
+    fn get_token(index: u64) -> b256 {
+        require(index < storage.token_count, Error::InvalidToken);
+        get_token(index)
+    }
+
+    #[storage(read)]
+    fn get_reserve(asset_id: b256) -> u64 {
+        require(is_token(asset_id), Error::InvalidToken);
+        storage.reserves.get(asset_id)
+    }
+
+    #[storage(read)]
+    fn get_amount_out(asset_in: b256, asset_out: b256, amount_in: u64) -> u64 {
+        let (reserve_in, reserve_out) = get_reserves(asset_in, asset_out);
+        get_input_price(amount_in, reserve_in, reserve_out, LP_FEE)
+    }
+
+    #[storage(read)]
+    fn get_amount_in(asset_in: b256, asset_out: b256, amount_out: u64) -> u64 {
+        let (reserve_in, reserve_out) = get_reserves(asset_in, asset_out);
+        require(amount_out < reserve_out, Error::InsufficentLiquidity);
+        get_output_price(amount_out, reserve_in, reserve_out, LP_FEE)
+    }
+
+    #[storage(read, write)]
+    fn initialize() {
+        require(storage.token_count == 0, Error::AlreadyInitialized);
+
+        let token_count = get::<u64>(TOKEN_COUNT_SLOT);
+        require(token_count >= MIN_TOKENS && token_count <= MAX_TOKENS, Error::InvalidTokens);
+
+        // Every token must be different
+        let mut i = 0;
+        while i < token_count {
+            let token = get_token(i);
+            let mut j = i + 1;
+            while j < token_count {
+                require(get_token(j) != token, Error::InvalidTokens);
+                j += 1;
+            }
+            i += 1;
+        }
+
+        storage.token_count = token_count;
+    }
+
+    #[storage(read, write)]
+    fn add_liquidity(recipient: Identity) -> u64 {
+        let token_count = storage.token_count;
+        require(token_count > 0, Error::NotInitialized);
+        let total_liquidity = storage.lp_token_supply;
+
+        // Liquidity is limited by the scarcest token, relative to the pool
+        let mut minted: u64 = INITIAL_LP_SUPPLY;
+        let mut i = 0;
+        while i < token_count {
+            let token = get_token(i);
+            let sent = this_balance(ContractId::from(token)) - storage.reserves.get(token);
+            require(sent > 0, Error::InsufficentInput);
+
+            if total_liquidity > 0 {
+                let token_liquidity = mutiply_div(sent, total_liquidity, storage.reserves.get(token));
+                if i == 0 || token_liquidity < minted {
+                    minted = token_liquidity;
+                }
+            }
+            i += 1;
+        }
+
+        if total_liquidity == 0 {
+            // The first deposit sets the ratio, so takes everything sent
+            i = 0;
+            while i < token_count {
+                let token = get_token(i);
+                storage.reserves.insert(token, this_balance(ContractId::from(token)));
+                i += 1;
+            }
+
+            // The locked liquidity is never minted, so no one can redeem it
+            minted = INITIAL_LP_SUPPLY - MINIMUM_LIQUIDITY;
+            mint(minted);
+            storage.lp_token_supply = INITIAL_LP_SUPPLY;
+
+            // Log the liquidity that's locked
+            log(MultiAssetLiquidityAdded {
+                sender: identity_to_b256(msg_sender().unwrap()),
+                lp_tokens: MINIMUM_LIQUIDITY,
+                recipient: b256::min(),
+            });
+        } else {
+            require(minted > 0, Error::InsufficentLiquidityMinted);
+
+            // Take each token at the pool's ratio, and return the rest
+            i = 0;
+            while i < token_count {
+                let token = get_token(i);
+                let reserve = storage.reserves.get(token);
+                let sent = this_balance(ContractId::from(token)) - reserve;
+                let used = mutiply_div_up(minted, reserve, total_liquidity);
+                storage.reserves.insert(token, reserve + used);
+                if sent > used {
+                    transfer(sent - used, ContractId::from(token), recipient);
+                }
+                i += 1;
+            }
+
+            mint(minted);
+            storage.lp_token_supply = total_liquidity + minted;
+        }
+
+        transfer(minted, contract_id(), recipient);
+
+        log(MultiAssetLiquidityAdded {
+            sender: identity_to_b256(msg_sender().unwrap()),
+            lp_tokens: minted,
+            recipient: identity_to_b256(recipient),
+        });
+
+        minted
+    }
+
+    #[storage(read, write)]
+    fn remove_liquidity(recipient: Identity) -> u64 {
+        let token_count = storage.token_count;
+        require(token_count > 0, Error::NotInitialized);
+
+        let lp_tokens = this_balance(contract_id());
+        require(lp_tokens > 0, Error::InsufficentInput);
+        let total_liquidity = storage.lp_token_supply;
+
+        let mut i = 0;
+        while i < token_count {
+            let token = get_token(i);
+            let reserve = storage.reserves.get(token);
+            let amount = mutiply_div(lp_tokens, reserve, total_liquidity);
+            require(amount > 0, Error::InsufficentLiquidityBurned);
+
+            storage.reserves.insert(token, reserve - amount);
+            transfer(amount, ContractId::from(token), recipient);
+            i += 1;
+        }
+
+        burn(lp_tokens);
+        storage.lp_token_supply = total_liquidity - lp_tokens;
+
+        log(MultiAssetLiquidityRemoved {
+            sender: identity_to_b256(msg_sender().unwrap()),
+            lp_tokens: lp_tokens,
+            recipient: identity_to_b256(recipient),
+        });
+
+        lp_tokens
+    }
+
+    #[storage(read, write)]
+    fn swap(asset_in: b256, asset_out: b256, amount_out: u64, recipient: Identity) {
+        let token_count = storage.token_count;
+        require(token_count > 0, Error::NotInitialized);
+        require(is_token(asset_in) && is_token(asset_out) && asset_in != asset_out, Error::InvalidToken);
+        require(amount_out > 0, Error::InsufficentOutput);
+
+        let reserve_out = storage.reserves.get(asset_out);
+        require(amount_out < reserve_out, Error::InsufficentLiquidity);
+
+        // Only the input token's balance is measured, so tokens sent to the pool outside of
+        // a swap don't affect swaps between other tokens. They're added by the next deposit.
+        let reserve_in = storage.reserves.get(asset_in);
+        let amount_in = this_balance(ContractId::from(asset_in)) - reserve_in;
+        require(amount_in > 0, Error::InsufficentInput);
+
+        transfer(amount_out, ContractId::from(asset_out), recipient);
+
+        let basis_points = U256::from((0, 0, 0, BASIS_POINTS));
+        let balance_in_adjusted = U256::from((0, 0, 0, reserve_in + amount_in)) * basis_points - (U256::from((0, 0, 0, amount_in)) * U256::from((0, 0, 0, LP_FEE)));
+        let balance_out_adjusted = U256::from((0, 0, 0, reserve_out - amount_out)) * basis_points;
+
+        let left = balance_in_adjusted * balance_out_adjusted;
+        let right = U256::from((0, 0, 0, reserve_in)) * U256::from((0, 0, 0, reserve_out)) * basis_points * basis_points;
+        require(left > right || left == right, Error::Invariant); // U256 doesn't have >= yet
+
+        storage.reserves.insert(asset_in, reserve_in + amount_in);
+        storage.reserves.insert(asset_out, reserve_out - amount_out);
+
+        log(MultiAssetSwap {
+            sender: identity_to_b256(msg_sender().unwrap()),
+            asset_in: asset_in,
+            amount_in: amount_in,
+            asset_out: asset_out,
+            amount_out: amount_out,
+            recipient: identity_to_b256(recipient),
+        });
+    }
+}
@@ -0,0 +1,373 @@
+use std::str::FromStr;
+use fuels::{
+    prelude::*,
+    fuels_abigen::abigen,
+    signers::WalletUnlocked,
+    tx::{AssetId, Bytes32, StorageSlot},
+};
+use test_helpers::{constant_product_amount_out, get_logs, get_wallets, MULTI_ASSET_EXCHANGE_ABI};
+
+///////////////////////////////
+// Load the Multi-Asset Exchange Contract abi
+///////////////////////////////
+abigen!(MultiAssetExchange, "./out/debug/multi_asset_exchange_contract-abi.json");
+
+abigen!(
+    TestToken,
+    "../token_contract/out/debug/token_contract-abi.json"
+);
+
+const INITIAL_LP_SUPPLY: u64 = 100_000_000_000;
+const MINIMUM_LIQUIDITY: u64 = 1000;
+
+struct Fixture {
+    wallet: WalletUnlocked,
+    exchange_contract_id: Bech32ContractId,
+    exchange_instance: MultiAssetExchange,
+    exchange_asset_id: AssetId,
+    /// ETH and two tokens
+    token_asset_ids: Vec<AssetId>,
+}
+
+fn to_9_decimal(num: u64) -> u64 {
+    num * 1_000_000_000
+}
+
+async fn deploy_token(wallet: &WalletUnlocked, salt: u8) -> AssetId {
+    test_helpers::deploy_token(wallet, salt, |token_contract_id| {
+        let token_instance = TestToken::new(token_contract_id.clone(), wallet.clone());
+        (
+            token_instance.methods().initialize(to_9_decimal(20000), wallet.address().into()),
+            token_instance.methods().mint(),
+        )
+    })
+    .await
+}
+
+/// Storage holding each token in its own slot, followed by the number of tokens
+fn token_storage(tokens: &Vec<AssetId>) -> Vec<StorageSlot> {
+    let mut storage_vec: Vec<StorageSlot> = tokens
+        .iter()
+        .enumerate()
+        .map(|(i, token)| {
+            let mut slot = [0u8; 32];
+            slot[31] = i as u8;
+            StorageSlot::new(Bytes32::new(slot), Bytes32::new(**token))
+        })
+        .collect();
+
+    let count_slot = Bytes32::from_str("0x0000000000000000000000000000000000000000000000000000000000000008").unwrap();
+    let mut count = [0u8; 32];
+    count[7] = tokens.len() as u8;
+    storage_vec.push(StorageSlot::new(count_slot, Bytes32::new(count)));
+    storage_vec
+}
+
+async fn deploy_pool(wallet: &WalletUnlocked, tokens: &Vec<AssetId>) -> Bech32ContractId {
+    Contract::deploy(
+        "./out/debug/multi_asset_exchange_contract.bin",
+        wallet,
+        TxParameters::default(),
+        StorageConfiguration::with_manual_storage(Some(token_storage(tokens))),
+    )
+    .await
+    .unwrap()
+}
+
+async fn setup() -> Fixture {
+    let wallets = get_wallets().await;
+    let wallet = wallets.get(0).unwrap().clone();
+
+    let token_asset_ids = vec![
+        BASE_ASSET_ID,
+        deploy_token(&wallet, 1).await,
+        deploy_token(&wallet, 2).await,
+    ];
+
+    let exchange_contract_id = deploy_pool(&wallet, &token_asset_ids).await;
+    let exchange_instance = MultiAssetExchange::new(exchange_contract_id.clone(), wallet.clone());
+    exchange_instance
+        .methods()
+        .initialize()
+        .call()
+        .await
+        .unwrap();
+
+    Fixture {
+        wallet: wallet,
+        exchange_asset_id: AssetId::new(*exchange_contract_id.hash()),
+        exchange_contract_id: exchange_contract_id,
+        exchange_instance: exchange_instance,
+        token_asset_ids: token_asset_ids,
+    }
+}
+
+async fn add_liquidity(fixture: &Fixture, amounts: Vec<u64>) -> u64 {
+    for (token, amount) in fixture.token_asset_ids.iter().zip(amounts) {
+        let _receipts = fixture.wallet
+            .force_transfer_to_contract(&fixture.exchange_contract_id, amount, *token, TxParameters::default())
+            .await
+            .unwrap();
+    }
+
+    fixture.exchange_instance
+        .methods()
+        .add_liquidity(Identity::Address(fixture.wallet.address().into()))
+        .append_variable_outputs(4)
+        .tx_params(TxParameters {
+            gas_price: 0,
+            gas_limit: 100_000_000,
+            maturity: 0,
+        })
+        .call_params(CallParameters::new(None, None, Some(100_000_000)))
+        .call()
+        .await
+        .unwrap()
+        .value
+}
+
+async fn swap(fixture: &Fixture, asset_in: AssetId, amount_in: u64, asset_out: AssetId, amount_out: u64) -> Result<FuelCallResponse<()>, Error> {
+    fixture.exchange_instance
+        .methods()
+        .swap(Bits256(*asset_in), Bits256(*asset_out), amount_out, Identity::Address(fixture.wallet.address().into()))
+        .call_params(CallParameters::new(Some(amount_in), Some(asset_in), Some(100_000_000)))
+        .tx_params(TxParameters {
+            gas_price: 0,
+            gas_limit: 100_000_000,
+            maturity: 0,
+        })
+        .append_variable_outputs(1)
+        .call()
+        .await
+}
+
+async fn get_reserve(fixture: &Fixture, asset_id: AssetId) -> u64 {
+    fixture.exchange_instance.methods().get_reserve(Bits256(*asset_id)).call().await.unwrap().value
+}
+
+#[tokio::test]
+async fn initialize_checks_tokens() {
+    let fixture = setup().await;
+
+    let info = fixture.exchange_instance.methods().get_pool_info().call().await.unwrap().value;
+    assert_eq!(info.token_count, 3);
+    for (i, token) in fixture.token_asset_ids.iter().enumerate() {
+        let stored = fixture.exchange_instance.methods().get_token(i as u64).call().await.unwrap().value;
+        assert_eq!(stored, Bits256(**token));
+    }
+
+    let is_err = fixture.exchange_instance.methods().initialize().call().await.is_err();
+    assert!(is_err, "Pool can't be initialized twice");
+
+    // The same token twice
+    let tokens = vec![
+        fixture.token_asset_ids[0],
+        fixture.token_asset_ids[1],
+        fixture.token_asset_ids[1],
+    ];
+    let duplicate_pool_id = deploy_pool(&fixture.wallet, &tokens).await;
+    let is_err = MultiAssetExchange::new(duplicate_pool_id, fixture.wallet.clone())
+        .methods()
+        .initialize()
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Tokens must be unique");
+
+    // A single token isn't a pool
+    let tokens = vec![fixture.token_asset_ids[1]];
+    let single_pool_id = deploy_pool(&fixture.wallet, &tokens).await;
+    let is_err = MultiAssetExchange::new(single_pool_id, fixture.wallet.clone())
+        .methods()
+        .initialize()
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "A pool needs at least two tokens");
+}
+
+#[tokio::test]
+async fn proportional_join_and_exit() {
+    let fixture = setup().await;
+
+    let minted = add_liquidity(&fixture, vec![to_9_decimal(10), to_9_decimal(20), to_9_decimal(40)]).await;
+    assert_eq!(minted, INITIAL_LP_SUPPLY - MINIMUM_LIQUIDITY);
+
+    // Twice the second token is needed, so only a quarter of the pool can be added
+    let starting_balance = fixture.wallet.get_asset_balance(&fixture.token_asset_ids[0]).await.unwrap();
+    let minted = add_liquidity(&fixture, vec![to_9_decimal(10), to_9_decimal(5), to_9_decimal(40)]).await;
+    assert_eq!(minted, INITIAL_LP_SUPPLY / 4);
+    let end_balance = fixture.wallet.get_asset_balance(&fixture.token_asset_ids[0]).await.unwrap();
+
+    // The unused tokens are returned
+    assert_eq!(starting_balance - end_balance, to_9_decimal(10) / 4);
+    assert_eq!(get_reserve(&fixture, fixture.token_asset_ids[0]).await, to_9_decimal(10) * 5 / 4);
+    assert_eq!(get_reserve(&fixture, fixture.token_asset_ids[1]).await, to_9_decimal(20) * 5 / 4);
+    assert_eq!(get_reserve(&fixture, fixture.token_asset_ids[2]).await, to_9_decimal(40) * 5 / 4);
+
+    let info = fixture.exchange_instance.methods().get_pool_info().call().await.unwrap().value;
+    assert_eq!(info.lp_token_supply, INITIAL_LP_SUPPLY * 5 / 4);
+
+    // Missing any one token mints nothing
+    let _receipts = fixture.wallet
+        .force_transfer_to_contract(&fixture.exchange_contract_id, to_9_decimal(1), fixture.token_asset_ids[1], TxParameters::default())
+        .await
+        .unwrap();
+    let is_err = fixture.exchange_instance
+        .methods()
+        .add_liquidity(Identity::Address(fixture.wallet.address().into()))
+        .append_variable_outputs(4)
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Every token must be deposited");
+
+    // Exit with a fifth of the pool
+    let lp_tokens = INITIAL_LP_SUPPLY / 4;
+    let starting_balance = fixture.wallet.get_asset_balance(&fixture.token_asset_ids[2]).await.unwrap();
+    let response = fixture.exchange_instance
+        .methods()
+        .remove_liquidity(Identity::Address(fixture.wallet.address().into()))
+        .call_params(CallParameters::new(Some(lp_tokens), Some(fixture.exchange_asset_id), None))
+        .append_variable_outputs(3)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(response.value, lp_tokens);
+    let end_balance = fixture.wallet.get_asset_balance(&fixture.token_asset_ids[2]).await.unwrap();
+    assert_eq!(end_balance - starting_balance, to_9_decimal(10));
+
+    let logs = get_logs::<MultiAssetLiquidityRemoved, _>(&response, &fixture.exchange_contract_id, MULTI_ASSET_EXCHANGE_ABI);
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs.get(0).unwrap().lp_tokens, lp_tokens);
+
+    let info = fixture.exchange_instance.methods().get_pool_info().call().await.unwrap().value;
+    assert_eq!(info.lp_token_supply, INITIAL_LP_SUPPLY);
+}
+
+#[tokio::test]
+async fn locked_liquidity_stays_in_pool() {
+    let fixture = setup().await;
+
+    let minted = add_liquidity(&fixture, vec![to_9_decimal(10), to_9_decimal(20), to_9_decimal(40)]).await;
+
+    // Exiting with every LP token that was sent out leaves the locked share behind
+    let response = fixture.exchange_instance
+        .methods()
+        .remove_liquidity(Identity::Address(fixture.wallet.address().into()))
+        .call_params(CallParameters::new(Some(minted), Some(fixture.exchange_asset_id), None))
+        .append_variable_outputs(3)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(response.value, minted);
+
+    let info = fixture.exchange_instance.methods().get_pool_info().call().await.unwrap().value;
+    assert_eq!(info.lp_token_supply, MINIMUM_LIQUIDITY);
+    assert_eq!(get_reserve(&fixture, fixture.token_asset_ids[0]).await, to_9_decimal(10) * MINIMUM_LIQUIDITY / INITIAL_LP_SUPPLY);
+    assert_eq!(get_reserve(&fixture, fixture.token_asset_ids[1]).await, to_9_decimal(20) * MINIMUM_LIQUIDITY / INITIAL_LP_SUPPLY);
+    assert_eq!(get_reserve(&fixture, fixture.token_asset_ids[2]).await, to_9_decimal(40) * MINIMUM_LIQUIDITY / INITIAL_LP_SUPPLY);
+
+    // No LP tokens are left in the pool for the next exit to claim
+    let is_err = fixture.exchange_instance
+        .methods()
+        .remove_liquidity(Identity::Address(fixture.wallet.address().into()))
+        .append_variable_outputs(3)
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "The locked liquidity shouldn't be redeemable");
+}
+
+#[tokio::test]
+async fn swap_between_any_pair() {
+    let fixture = setup().await;
+    add_liquidity(&fixture, vec![to_9_decimal(100), to_9_decimal(200), to_9_decimal(400)]).await;
+
+    let token_1 = fixture.token_asset_ids[1];
+    let token_2 = fixture.token_asset_ids[2];
+
+    // Between two tokens, priced from their reserves alone
+    let amount_in = to_9_decimal(10);
+    let amount_out = fixture.exchange_instance
+        .methods()
+        .get_amount_out(Bits256(*token_1), Bits256(*token_2), amount_in)
+        .call()
+        .await
+        .unwrap()
+        .value;
+    assert_eq!(amount_out, constant_product_amount_out(amount_in, to_9_decimal(200), to_9_decimal(400)));
+
+    let is_err = swap(&fixture, token_1, amount_in, token_2, amount_out + 1).await.is_err();
+    assert!(is_err, "Pool shouldn't pay more than the curve allows");
+
+    let response = swap(&fixture, token_1, amount_in, token_2, amount_out).await.unwrap();
+    let logs = get_logs::<MultiAssetSwap, _>(&response, &fixture.exchange_contract_id, MULTI_ASSET_EXCHANGE_ABI);
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs.get(0).unwrap().asset_in, Bits256(*token_1));
+    assert_eq!(logs.get(0).unwrap().amount_out, amount_out);
+
+    assert_eq!(get_reserve(&fixture, token_1).await, to_9_decimal(210));
+    assert_eq!(get_reserve(&fixture, token_2).await, to_9_decimal(400) - amount_out);
+    // Untouched by the swap
+    assert_eq!(get_reserve(&fixture, BASE_ASSET_ID).await, to_9_decimal(100));
+
+    // ETH for a token, by exact output
+    let amount_out = to_9_decimal(5);
+    let amount_in = fixture.exchange_instance
+        .methods()
+        .get_amount_in(Bits256(*BASE_ASSET_ID), Bits256(*token_1), amount_out)
+        .call()
+        .await
+        .unwrap()
+        .value;
+    let starting_balance = fixture.wallet.get_asset_balance(&token_1).await.unwrap();
+    swap(&fixture, BASE_ASSET_ID, amount_in, token_1, amount_out).await.unwrap();
+    let end_balance = fixture.wallet.get_asset_balance(&token_1).await.unwrap();
+    assert_eq!(end_balance - starting_balance, amount_out);
+
+    let is_err = swap(&fixture, token_1, amount_in, token_1, 1).await.is_err();
+    assert!(is_err, "Can't swap a token for itself");
+}
+
+#[tokio::test]
+async fn swap_with_unknown_token_fails() {
+    let fixture = setup().await;
+    add_liquidity(&fixture, vec![to_9_decimal(100), to_9_decimal(100), to_9_decimal(100)]).await;
+
+    let other_token = deploy_token(&fixture.wallet, 3).await;
+    let is_err = fixture.exchange_instance
+        .methods()
+        .get_amount_out(Bits256(*other_token), Bits256(*fixture.token_asset_ids[1]), to_9_decimal(1))
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Only the pool's tokens can be priced");
+
+    let is_err = swap(&fixture, other_token, to_9_decimal(1), fixture.token_asset_ids[1], 1).await.is_err();
+    assert!(is_err, "Only the pool's tokens can be swapped in");
+}
+
+#[tokio::test]
+async fn stray_tokens_dont_block_swaps() {
+    let fixture = setup().await;
+    add_liquidity(&fixture, vec![to_9_decimal(100), to_9_decimal(100), to_9_decimal(100)]).await;
+
+    let token_1 = fixture.token_asset_ids[1];
+    let token_2 = fixture.token_asset_ids[2];
+
+    // A single unit of each of the swapped tokens sent outside of a swap
+    for token in [BASE_ASSET_ID, token_2] {
+        let _receipts = fixture.wallet
+            .force_transfer_to_contract(&fixture.exchange_contract_id, 1, token, TxParameters::default())
+            .await
+            .unwrap();
+    }
+
+    let amount_in = to_9_decimal(1);
+    let amount_out = constant_product_amount_out(amount_in, to_9_decimal(100), to_9_decimal(100));
+    swap(&fixture, token_1, amount_in, token_2, amount_out).await.unwrap();
+    assert_eq!(get_reserve(&fixture, token_1).await, to_9_decimal(101));
+    assert_eq!(get_reserve(&fixture, token_2).await, to_9_decimal(100) - amount_out);
+    assert_eq!(get_reserve(&fixture, BASE_ASSET_ID).await, to_9_decimal(100));
+}
@@ -0,0 +1,84 @@
+library position_manager_abi;
+
+use std::identity::Identity;
+
+// Events
+
+pub struct PositionMinted {
+    position_id: u64,
+    owner: b256,
+    pool: b256,
+    tick_lower: u64,
+    tick_upper: u64,
+}
+
+pub struct PositionTransferred {
+    position_id: u64,
+    from: b256,
+    to: b256,
+}
+
+// Returns
+
+pub struct ManagedPosition {
+    owner: Identity,
+    pool: b256,
+    tick_lower: u64,
+    tick_upper: u64,
+}
+
+pub struct MintOutput {
+    position_id: u64,
+    liquidity: u64,
+    amount_0: u64,
+    amount_1: u64,
+}
+
+abi PositionManager {
+    ////////////////////
+    // Read only
+    ////////////////////
+    #[storage(read)]fn get_position(position_id: u64) -> ManagedPosition;
+    /// Number of positions held by an owner
+    #[storage(read)]fn get_position_count(owner: Identity) -> u64;
+    /// ID of one of an owner's positions, with `index` below `get_position_count`
+    #[storage(read)]fn get_position_id(owner: Identity, index: u64) -> u64;
+    /// Unused tokens deposited by an owner
+    #[storage(read)]fn get_deposit(owner: Identity, asset_id: b256) -> u64;
+    ////////////////////
+    // Actions
+    ////////////////////
+    /// Hold the forwarded coins for the sender, to fund `mint` and `increase_liquidity`
+    #[storage(read, write)]fn deposit();
+    /// Return the sender's unused deposit of an asset
+    #[storage(read, write)]fn withdraw_deposit(asset_id: b256) -> u64;
+    /// Open a position in a concentrated liquidity pool from the sender's deposits.
+    /// Tokens the pool doesn't use are returned to the sender's deposits.
+    #[storage(read, write)]fn mint(
+        pool: b256,
+        tick_lower: u64,
+        tick_upper: u64,
+        amount_0_desired: u64,
+        amount_1_desired: u64,
+        amount_0_min: u64,
+        amount_1_min: u64,
+        recipient: Identity,
+    ) -> MintOutput;
+    /// Add to one of the sender's positions from their deposits
+    #[storage(read, write)]fn increase_liquidity(
+        position_id: u64,
+        amount_0_desired: u64,
+        amount_1_desired: u64,
+        amount_0_min: u64,
+        amount_1_min: u64,
+    ) -> MintOutput;
+    #[storage(read, write)]fn decrease_liquidity(
+        position_id: u64,
+        liquidity: u64,
+        amount_0_min: u64,
+        amount_1_min: u64,
+        recipient: Identity,
+    ) -> (u64, u64);
+    #[storage(read, write)]fn collect_fees(position_id: u64, recipient: Identity) -> (u64, u64);
+    #[storage(read, write)]fn transfer_position(position_id: u64, new_owner: Identity);
+}
@@ -0,0 +1,283 @@
+contract;
+
+use std::{
+    auth::msg_sender,
+    call_frames::{
+        contract_id,
+        msg_asset_id,
+    },
+    constants::ZERO_B256,
+    context::{
+        msg_amount,
+        this_balance,
+    },
+    contract_id::ContractId,
+    identity::Identity,
+    logging::log,
+    token::{
+        force_transfer_to_contract,
+        transfer,
+    },
+};
+use concentrated_exchange_abi::{ConcentratedExchange, LiquidityChange};
+use position_manager_abi::{
+    ManagedPosition,
+    MintOutput,
+    PositionManager,
+    PositionMinted,
+    PositionTransferred,
+};
+use microchain_helpers::identity_to_b256;
+
+enum Error {
+    PositionNotFound: (),
+    MustBeCalledByOwner: (),
+    InsufficentDeposit: (),
+    InsufficentToken0: (),
+    InsufficentToken1: (),
+    InsufficentOutput: (),
+    IndexOutOfRange: (),
+    InvalidPool: (),
+}
+
+// Holds concentrated liquidity positions on behalf of their owners. Pools key positions by
+// whoever added the liquidity, so every position opened here belongs to this contract in
+// the pool, and this contract tracks who owns each one.
+
+storage {
+    // Position IDs start at 1
+    next_position_id: u64 = 1,
+    positions: StorageMap<u64, ManagedPosition> = StorageMap {},
+    // Each owner's positions, so they can be enumerated
+    owner_position_count: StorageMap<b256, u64> = StorageMap {},
+    owner_positions: StorageMap<(b256, u64), u64> = StorageMap {},
+    position_index: StorageMap<u64, u64> = StorageMap {},
+    // Tokens deposited by each sender, by asset. Calls only spend the sender's own deposits.
+    deposits: StorageMap<(b256, b256), u64> = StorageMap {},
+}
+
+////////////////////////////////////////
+// Helper functions
+////////////////////////////////////////
+#[storage(read, write)]
+fn add_owned_position(owner: b256, position_id: u64) {
+    let count = storage.owner_position_count.get(owner);
+    storage.owner_positions.insert((owner, count), position_id);
+    storage.position_index.insert(position_id, count);
+    storage.owner_position_count.insert(owner, count + 1);
+}
+
+#[storage(read, write)]
+fn remove_owned_position(owner: b256, position_id: u64) {
+    // Move the owner's last position into the removed one's place
+    let last_index = storage.owner_position_count.get(owner) - 1;
+    let index = storage.position_index.get(position_id);
+    if index != last_index {
+        let last_position_id = storage.owner_positions.get((owner, last_index));
+        storage.owner_positions.insert((owner, index), last_position_id);
+        storage.position_index.insert(last_position_id, index);
+    }
+    storage.owner_positions.insert((owner, last_index), 0);
+    storage.owner_position_count.insert(owner, last_index);
+}
+
+#[storage(read)]
+fn get_owned_position(position_id: u64) -> ManagedPosition {
+    let position = storage.positions.get(position_id);
+    require(position.pool != ZERO_B256, Error::PositionNotFound);
+    require(msg_sender().unwrap() == position.owner, Error::MustBeCalledByOwner);
+    position
+}
+
+/// Send the sender's deposits to the pool as liquidity, returning what the pool didn't use to their deposits.
+/// Pools aren't trusted to report what they used, so the sender is charged by this contract's balances.
+#[storage(read, write)]
+fn add_to_position(
+    pool: b256,
+    position_id: u64,
+    tick_lower: u64,
+    tick_upper: u64,
+    amount_0_desired: u64,
+    amount_1_desired: u64,
+    amount_0_min: u64,
+    amount_1_min: u64,
+) -> LiquidityChange {
+    let sender = identity_to_b256(msg_sender().unwrap());
+    let exchange = abi(ConcentratedExchange, pool);
+    let (token0, token1) = exchange.get_tokens();
+    require(token0 != token1, Error::InvalidPool);
+
+    let deposit_0 = storage.deposits.get((sender, token0));
+    let deposit_1 = storage.deposits.get((sender, token1));
+    require(deposit_0 >= amount_0_desired && deposit_1 >= amount_1_desired, Error::InsufficentDeposit);
+
+    let balance_0_before = this_balance(ContractId::from(token0));
+    let balance_1_before = this_balance(ContractId::from(token1));
+
+    if amount_0_desired > 0 {
+        force_transfer_to_contract(amount_0_desired, ContractId::from(token0), ContractId::from(pool));
+    }
+    if amount_1_desired > 0 {
+        force_transfer_to_contract(amount_1_desired, ContractId::from(token1), ContractId::from(pool));
+    }
+
+    let result = exchange.add_liquidity(position_id, tick_lower, tick_upper, Identity::ContractId(contract_id()));
+
+    // Refunds can only lower what was sent, so these don't underflow
+    let spent_0 = balance_0_before - this_balance(ContractId::from(token0));
+    let spent_1 = balance_1_before - this_balance(ContractId::from(token1));
+    require(spent_0 >= amount_0_min, Error::InsufficentToken0);
+    require(spent_1 >= amount_1_min, Error::InsufficentToken1);
+
+    storage.deposits.insert((sender, token0), deposit_0 - spent_0);
+    storage.deposits.insert((sender, token1), deposit_1 - spent_1);
+
+    LiquidityChange {
+        liquidity: result.liquidity,
+        amount_0: spent_0,
+        amount_1: spent_1,
+    }
+}
+
+impl PositionManager for Contract {
+    #[storage(read)]
+    fn get_position(position_id: u64) -> ManagedPosition {
+        let position = storage.positions.get(position_id);
+        require(position.pool != ZERO_B256, Error::PositionNotFound);
+        position
+    }
+
+    #[storage(read)]
+    fn get_position_count(owner: Identity) -> u64 {
+        storage.owner_position_count.get(identity_to_b256(owner))
+    }
+
+    #[storage(read)]
+    fn get_position_id(owner: Identity, index: u64) -> u64 {
+        let owner = identity_to_b256(owner);
+        require(index < storage.owner_position_count.get(owner), Error::IndexOutOfRange);
+        storage.owner_positions.get((owner, index))
+    }
+
+    #[storage(read)]
+    fn get_deposit(owner: Identity, asset_id: b256) -> u64 {
+        storage.deposits.get((identity_to_b256(owner), asset_id))
+    }
+
+    #[storage(read, write)]
+    fn deposit() {
+        let sender = identity_to_b256(msg_sender().unwrap());
+        let asset_id: b256 = msg_asset_id().into();
+        storage.deposits.insert((sender, asset_id), storage.deposits.get((sender, asset_id)) + msg_amount());
+    }
+
+    #[storage(read, write)]
+    fn withdraw_deposit(asset_id: b256) -> u64 {
+        let sender_identity = msg_sender().unwrap();
+        let sender = identity_to_b256(sender_identity);
+        let amount = storage.deposits.get((sender, asset_id));
+        storage.deposits.insert((sender, asset_id), 0);
+        if amount > 0 {
+            transfer(amount, ContractId::from(asset_id), sender_identity);
+        }
+        amount
+    }
+
+    #[storage(read, write)]
+    fn mint(
+        pool: b256,
+        tick_lower: u64,
+        tick_upper: u64,
+        amount_0_desired: u64,
+        amount_1_desired: u64,
+        amount_0_min: u64,
+        amount_1_min: u64,
+        recipient: Identity,
+    ) -> MintOutput {
+        let position_id = storage.next_position_id;
+        storage.next_position_id = position_id + 1;
+
+        let result = add_to_position(pool, position_id, tick_lower, tick_upper, amount_0_desired, amount_1_desired, amount_0_min, amount_1_min);
+
+        storage.positions.insert(position_id, ManagedPosition {
+            owner: recipient,
+            pool: pool,
+            tick_lower: tick_lower,
+            tick_upper: tick_upper,
+        });
+        let owner = identity_to_b256(recipient);
+        add_owned_position(owner, position_id);
+
+        log(PositionMinted {
+            position_id: position_id,
+            owner: owner,
+            pool: pool,
+            tick_lower: tick_lower,
+            tick_upper: tick_upper,
+        });
+
+        MintOutput {
+            position_id: position_id,
+            liquidity: result.liquidity,
+            amount_0: result.amount_0,
+            amount_1: result.amount_1,
+        }
+    }
+
+    #[storage(read, write)]
+    fn increase_liquidity(
+        position_id: u64,
+        amount_0_desired: u64,
+        amount_1_desired: u64,
+        amount_0_min: u64,
+        amount_1_min: u64,
+    ) -> MintOutput {
+        let position = get_owned_position(position_id);
+        let result = add_to_position(position.pool, position_id, position.tick_lower, position.tick_upper, amount_0_desired, amount_1_desired, amount_0_min, amount_1_min);
+
+        MintOutput {
+            position_id: position_id,
+            liquidity: result.liquidity,
+            amount_0: result.amount_0,
+            amount_1: result.amount_1,
+        }
+    }
+
+    #[storage(read, write)]
+    fn decrease_liquidity(
+        position_id: u64,
+        liquidity: u64,
+        amount_0_min: u64,
+        amount_1_min: u64,
+        recipient: Identity,
+    ) -> (u64, u64) {
+        let position = get_owned_position(position_id);
+        let result = abi(ConcentratedExchange, position.pool).remove_liquidity(position_id, liquidity, recipient);
+        require(result.amount_0 >= amount_0_min && result.amount_1 >= amount_1_min, Error::InsufficentOutput);
+        (result.amount_0, result.amount_1)
+    }
+
+    #[storage(read, write)]
+    fn collect_fees(position_id: u64, recipient: Identity) -> (u64, u64) {
+        let position = get_owned_position(position_id);
+        abi(ConcentratedExchange, position.pool).collect_fees(position_id, recipient)
+    }
+
+    #[storage(read, write)]
+    fn transfer_position(position_id: u64, new_owner: Identity) {
+        let mut position = get_owned_position(position_id);
+        let from = identity_to_b256(position.owner);
+        let to = identity_to_b256(new_owner);
+
+        remove_owned_position(from, position_id);
+        add_owned_position(to, position_id);
+        position.owner = new_owner;
+        storage.positions.insert(position_id, position);
+
+        log(PositionTransferred {
+            position_id: position_id,
+            from: from,
+            to: to,
+        });
+    }
+}
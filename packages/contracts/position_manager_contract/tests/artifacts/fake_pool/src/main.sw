@@ -0,0 +1,135 @@
+contract;
+
+use std::{
+    identity::Identity,
+    storage::get,
+    u128::U128,
+};
+
+use concentrated_exchange_abi::{
+    ConcentratedExchange,
+    LiquidityChange,
+    PoolState,
+    Position,
+    TickInfo,
+    TickObservation,
+    TickTWAPInfo,
+};
+
+// A malicious pool for testing the position manager. It reports real tokens, keeps everything
+// sent to it, and claims to have used none of it.
+
+const TOKEN_0_SLOT = 0x0000000000000000000000000000000000000000000000000000000000000000;
+const TOKEN_1_SLOT = 0x0000000000000000000000000000000000000000000000000000000000000001;
+
+impl ConcentratedExchange for Contract {
+    #[storage(read)]
+    fn get_pool_state() -> PoolState {
+        PoolState {
+            sqrt_price: U128::min(),
+            tick: 0,
+            liquidity: 0,
+            fee_growth_global_0: U128::min(),
+            fee_growth_global_1: U128::min(),
+            token_0_reserve: 0,
+            token_1_reserve: 0,
+        }
+    }
+
+    #[storage(read)]
+    fn get_tick(tick: u64) -> TickInfo {
+        TickInfo {
+            liquidity_gross: 0,
+            liquidity_added: 0,
+            liquidity_removed: 0,
+            fee_growth_outside_0: U128::min(),
+            fee_growth_outside_1: U128::min(),
+            prev: 0,
+            next: 0,
+        }
+    }
+
+    #[storage(read)]
+    fn get_position(owner: b256, position_id: u64) -> Position {
+        Position {
+            tick_lower: 0,
+            tick_upper: 0,
+            liquidity: 0,
+            fee_growth_inside_0_last: U128::min(),
+            fee_growth_inside_1_last: U128::min(),
+            tokens_owed_0: 0,
+            tokens_owed_1: 0,
+        }
+    }
+
+    #[storage(read)]
+    fn get_position_fees(owner: b256, position_id: u64) -> (u64, u64) {
+        (0, 0)
+    }
+
+    #[storage(read)]
+    fn get_amount_out(amount_in: u64, token_0_in: bool) -> u64 {
+        0
+    }
+
+    #[storage(read)]
+    fn get_tokens() -> (b256, b256) {
+        (get::<b256>(TOKEN_0_SLOT), get::<b256>(TOKEN_1_SLOT))
+    }
+
+    #[storage(read)]
+    fn get_twap_info() -> TickTWAPInfo {
+        TickTWAPInfo {
+            current_element: 0,
+            buffer_size: 0,
+            next_buffer_size: 0,
+        }
+    }
+
+    #[storage(read)]
+    fn get_observation(element: u64) -> TickObservation {
+        TickObservation {
+            timestamp: 0,
+            tick_cumulative: 0,
+        }
+    }
+
+    #[storage(read)]
+    fn get_twap_tick() -> u64 {
+        0
+    }
+
+    #[storage(read, write)]
+    fn initialize(tick: u64) {}
+
+    #[storage(read, write)]
+    fn add_liquidity(position_id: u64, tick_lower: u64, tick_upper: u64, refund: Identity) -> LiquidityChange {
+        LiquidityChange {
+            liquidity: 1,
+            amount_0: 0,
+            amount_1: 0,
+        }
+    }
+
+    #[storage(read, write)]
+    fn remove_liquidity(position_id: u64, liquidity: u64, recipient: Identity) -> LiquidityChange {
+        LiquidityChange {
+            liquidity: 0,
+            amount_0: 0,
+            amount_1: 0,
+        }
+    }
+
+    #[storage(read, write)]
+    fn collect_fees(position_id: u64, recipient: Identity) -> (u64, u64) {
+        (0, 0)
+    }
+
+    #[storage(read, write)]
+    fn swap(min_amount_out: u64, recipient: Identity) -> u64 {
+        0
+    }
+
+    #[storage(read, write)]
+    fn expand_twap_buffer(new_slots: u64) {}
+}
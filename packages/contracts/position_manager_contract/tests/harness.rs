@@ -0,0 +1,516 @@
+use std::str::FromStr;
+use fuels::{
+    prelude::*,
+    fuels_abigen::abigen,
+    signers::WalletUnlocked,
+    tx::{AssetId, Bytes32, StorageSlot},
+};
+use test_helpers::{get_logs, get_wallets, POSITION_MANAGER_ABI};
+
+///////////////////////////////
+// Load the Position Manager Contract abi
+///////////////////////////////
+abigen!(PositionManager, "./out/debug/position_manager_contract-abi.json");
+
+abigen!(
+    ConcentratedExchange,
+    "../concentrated_exchange_contract/out/debug/concentrated_exchange_contract-abi.json"
+);
+abigen!(
+    TestToken,
+    "../token_contract/out/debug/token_contract-abi.json"
+);
+
+/// The tick of a 1:1 price
+const MAX_TICK: u64 = 443_636;
+
+struct Fixture {
+    wallet: WalletUnlocked,
+    other_wallet: WalletUnlocked,
+    exchange_contract_id: Bech32ContractId,
+    exchange_instance: ConcentratedExchange,
+    manager_contract_id: Bech32ContractId,
+    manager_instance: PositionManager,
+    token_0_asset_id: AssetId,
+    token_1_asset_id: AssetId,
+}
+
+fn to_9_decimal(num: u64) -> u64 {
+    num * 1_000_000_000
+}
+
+/// Deploy an ETH/token pool at a 1:1 price, and a manager for its positions
+async fn setup() -> Fixture {
+    let wallets = get_wallets().await;
+    let wallet = wallets.get(0).unwrap().clone();
+    let other_wallet = wallets.get(1).unwrap().clone();
+
+    let token_contract_id = Contract::deploy(
+        "../token_contract/out/debug/token_contract.bin",
+        &wallet,
+        TxParameters::default(),
+        StorageConfiguration::new(None, None),
+    )
+    .await
+    .unwrap();
+
+    let token_instance = TestToken::new(token_contract_id.clone(), wallet.clone());
+    token_instance
+        .methods()
+        .initialize(to_9_decimal(20000), wallet.address().into())
+        .call()
+        .await
+        .unwrap();
+    token_instance
+        .methods()
+        .mint()
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .unwrap();
+
+    let token_0_asset_id = BASE_ASSET_ID;
+    let token_1_asset_id = AssetId::new(*token_contract_id.hash());
+
+    let token0_slot = Bytes32::from_str("0x0000000000000000000000000000000000000000000000000000000000000000").unwrap();
+    let token1_slot = Bytes32::from_str("0x0000000000000000000000000000000000000000000000000000000000000001").unwrap();
+    let storage_vec = vec![
+        StorageSlot::new(token0_slot, Bytes32::new(*token_0_asset_id)),
+        StorageSlot::new(token1_slot, Bytes32::new(*token_1_asset_id)),
+    ];
+
+    let exchange_contract_id = Contract::deploy(
+        "../concentrated_exchange_contract/out/debug/concentrated_exchange_contract.bin",
+        &wallet,
+        TxParameters::default(),
+        StorageConfiguration::with_manual_storage(Some(storage_vec)),
+    )
+    .await
+    .unwrap();
+
+    let exchange_instance = ConcentratedExchange::new(exchange_contract_id.clone(), wallet.clone());
+    exchange_instance
+        .methods()
+        .initialize(MAX_TICK)
+        .call()
+        .await
+        .unwrap();
+
+    let manager_contract_id = Contract::deploy(
+        "./out/debug/position_manager_contract.bin",
+        &wallet,
+        TxParameters::default(),
+        StorageConfiguration::new(None, None),
+    )
+    .await
+    .unwrap();
+    let manager_instance = PositionManager::new(manager_contract_id.clone(), wallet.clone());
+
+    Fixture {
+        wallet: wallet,
+        other_wallet: other_wallet,
+        exchange_contract_id: exchange_contract_id,
+        exchange_instance: exchange_instance,
+        manager_contract_id: manager_contract_id,
+        manager_instance: manager_instance,
+        token_0_asset_id: token_0_asset_id,
+        token_1_asset_id: token_1_asset_id,
+    }
+}
+
+fn identity(wallet: &WalletUnlocked) -> Identity {
+    Identity::Address(wallet.address().into())
+}
+
+async fn deposit(manager: &PositionManager, amount: u64, asset_id: AssetId) {
+    manager
+        .methods()
+        .deposit()
+        .call_params(CallParameters::new(Some(amount), Some(asset_id), None))
+        .call()
+        .await
+        .unwrap();
+}
+
+async fn get_deposit(fixture: &Fixture, wallet: &WalletUnlocked, asset_id: AssetId) -> u64 {
+    fixture.manager_instance
+        .methods()
+        .get_deposit(identity(wallet), Bits256(*asset_id))
+        .call()
+        .await
+        .unwrap()
+        .value
+}
+
+async fn mint(
+    fixture: &Fixture,
+    manager: &PositionManager,
+    amount_0: u64,
+    amount_1: u64,
+    recipient: Identity,
+) -> Result<FuelCallResponse<MintOutput>, Error> {
+    manager
+        .methods()
+        .mint(
+            Bits256(fixture.exchange_contract_id.hash().into()),
+            MAX_TICK - 1000,
+            MAX_TICK + 1000,
+            amount_0,
+            amount_1,
+            0,
+            0,
+            recipient,
+        )
+        .set_contracts(&[fixture.exchange_contract_id.clone()])
+        .tx_params(TxParameters {
+            gas_price: 0,
+            gas_limit: 100_000_000,
+            maturity: 0,
+        })
+        .call_params(CallParameters::new(None, None, Some(100_000_000)))
+        .call()
+        .await
+}
+
+async fn decrease_liquidity(
+    fixture: &Fixture,
+    manager: &PositionManager,
+    position_id: u64,
+    liquidity: u64,
+    recipient: Identity,
+) -> Result<FuelCallResponse<(u64, u64)>, Error> {
+    manager
+        .methods()
+        .decrease_liquidity(position_id, liquidity, 0, 0, recipient)
+        .set_contracts(&[fixture.exchange_contract_id.clone()])
+        .append_variable_outputs(2)
+        .call()
+        .await
+}
+
+#[tokio::test]
+async fn mint_from_deposits() {
+    let fixture = setup().await;
+    deposit(&fixture.manager_instance, to_9_decimal(20), fixture.token_0_asset_id).await;
+    deposit(&fixture.manager_instance, to_9_decimal(10), fixture.token_1_asset_id).await;
+
+    let response = mint(&fixture, &fixture.manager_instance, to_9_decimal(20), to_9_decimal(10), identity(&fixture.wallet))
+        .await
+        .unwrap();
+    let output = response.value.clone();
+    assert_eq!(output.position_id, 1);
+    assert!(output.amount_0 < to_9_decimal(11) && output.amount_1 <= to_9_decimal(10));
+
+    let logs = get_logs::<PositionMinted, _>(&response, &fixture.manager_contract_id, POSITION_MANAGER_ABI);
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs.get(0).unwrap().position_id, 1);
+
+    // Only what the pool used was taken from the deposits
+    assert_eq!(get_deposit(&fixture, &fixture.wallet, fixture.token_0_asset_id).await, to_9_decimal(20) - output.amount_0);
+    assert_eq!(get_deposit(&fixture, &fixture.wallet, fixture.token_1_asset_id).await, to_9_decimal(10) - output.amount_1);
+
+    // The pool holds the position for the manager
+    let pool_position = fixture.exchange_instance
+        .methods()
+        .get_position(Bits256(fixture.manager_contract_id.hash().into()), 1)
+        .call()
+        .await
+        .unwrap()
+        .value;
+    assert_eq!(pool_position.liquidity, output.liquidity);
+
+    let position = fixture.manager_instance.methods().get_position(1).call().await.unwrap().value;
+    assert_eq!(position.owner, identity(&fixture.wallet));
+    assert_eq!(position.tick_lower, MAX_TICK - 1000);
+    assert_eq!(position.tick_upper, MAX_TICK + 1000);
+
+    let count = fixture.manager_instance.methods().get_position_count(identity(&fixture.wallet)).call().await.unwrap().value;
+    assert_eq!(count, 1);
+    let position_id = fixture.manager_instance.methods().get_position_id(identity(&fixture.wallet), 0).call().await.unwrap().value;
+    assert_eq!(position_id, 1);
+
+    // Leftovers can be withdrawn
+    let starting_balance = fixture.wallet.get_asset_balance(&fixture.token_0_asset_id).await.unwrap();
+    fixture.manager_instance
+        .methods()
+        .withdraw_deposit(Bits256(*fixture.token_0_asset_id))
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .unwrap();
+    let end_balance = fixture.wallet.get_asset_balance(&fixture.token_0_asset_id).await.unwrap();
+    assert_eq!(end_balance - starting_balance, to_9_decimal(20) - output.amount_0);
+    assert_eq!(get_deposit(&fixture, &fixture.wallet, fixture.token_0_asset_id).await, 0);
+}
+
+#[tokio::test]
+async fn increase_and_decrease_liquidity() {
+    let fixture = setup().await;
+    deposit(&fixture.manager_instance, to_9_decimal(20), fixture.token_0_asset_id).await;
+    deposit(&fixture.manager_instance, to_9_decimal(20), fixture.token_1_asset_id).await;
+
+    let minted = mint(&fixture, &fixture.manager_instance, to_9_decimal(10), to_9_decimal(10), identity(&fixture.wallet))
+        .await
+        .unwrap()
+        .value;
+
+    let increased = fixture.manager_instance
+        .methods()
+        .increase_liquidity(minted.position_id, to_9_decimal(10), to_9_decimal(10), 0, 0)
+        .set_contracts(&[fixture.exchange_contract_id.clone()])
+        .tx_params(TxParameters {
+            gas_price: 0,
+            gas_limit: 100_000_000,
+            maturity: 0,
+        })
+        .call_params(CallParameters::new(None, None, Some(100_000_000)))
+        .call()
+        .await
+        .unwrap()
+        .value;
+    assert_eq!(increased.position_id, minted.position_id);
+
+    let total_liquidity = minted.liquidity + increased.liquidity;
+    let pool_position = fixture.exchange_instance
+        .methods()
+        .get_position(Bits256(fixture.manager_contract_id.hash().into()), minted.position_id)
+        .call()
+        .await
+        .unwrap()
+        .value;
+    assert_eq!(pool_position.liquidity, total_liquidity);
+
+    // Minimums protect against the price moving
+    let is_err = fixture.manager_instance
+        .methods()
+        .decrease_liquidity(minted.position_id, total_liquidity, to_9_decimal(100), 0, identity(&fixture.wallet))
+        .set_contracts(&[fixture.exchange_contract_id.clone()])
+        .append_variable_outputs(2)
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Output below the minimum should fail");
+
+    let starting_balance = fixture.wallet.get_asset_balance(&fixture.token_1_asset_id).await.unwrap();
+    let (amount_0, amount_1) = decrease_liquidity(&fixture, &fixture.manager_instance, minted.position_id, total_liquidity, identity(&fixture.wallet))
+        .await
+        .unwrap()
+        .value;
+    let end_balance = fixture.wallet.get_asset_balance(&fixture.token_1_asset_id).await.unwrap();
+    assert_eq!(end_balance - starting_balance, amount_1);
+    assert!(amount_0 + 2 >= minted.amount_0 + increased.amount_0);
+    assert!(amount_1 + 2 >= minted.amount_1 + increased.amount_1);
+}
+
+#[tokio::test]
+async fn collect_fees_through_manager() {
+    let fixture = setup().await;
+    deposit(&fixture.manager_instance, to_9_decimal(10), fixture.token_0_asset_id).await;
+    deposit(&fixture.manager_instance, to_9_decimal(10), fixture.token_1_asset_id).await;
+    let minted = mint(&fixture, &fixture.manager_instance, to_9_decimal(10), to_9_decimal(10), identity(&fixture.wallet))
+        .await
+        .unwrap()
+        .value;
+
+    // Trade against the pool directly
+    let amount_in = to_9_decimal(1);
+    fixture.exchange_instance
+        .methods()
+        .swap(1, identity(&fixture.wallet))
+        .call_params(CallParameters::new(Some(amount_in), Some(fixture.token_1_asset_id), Some(100_000_000)))
+        .tx_params(TxParameters {
+            gas_price: 0,
+            gas_limit: 100_000_000,
+            maturity: 0,
+        })
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .unwrap();
+
+    let starting_balance = fixture.wallet.get_asset_balance(&fixture.token_1_asset_id).await.unwrap();
+    let (fees_0, fees_1) = fixture.manager_instance
+        .methods()
+        .collect_fees(minted.position_id, identity(&fixture.wallet))
+        .set_contracts(&[fixture.exchange_contract_id.clone()])
+        .append_variable_outputs(2)
+        .call()
+        .await
+        .unwrap()
+        .value;
+    let end_balance = fixture.wallet.get_asset_balance(&fixture.token_1_asset_id).await.unwrap();
+
+    // The only LP earns the whole 0.3% fee
+    assert_eq!(fees_0, 0);
+    assert!(fees_1 <= amount_in * 3 / 1000 && fees_1 > amount_in * 3 / 1000 * 99 / 100);
+    assert_eq!(end_balance - starting_balance, fees_1);
+}
+
+#[tokio::test]
+async fn deposits_are_per_sender() {
+    let fixture = setup().await;
+    let other_manager = PositionManager::new(fixture.manager_contract_id.clone(), fixture.other_wallet.clone());
+
+    deposit(&fixture.manager_instance, to_9_decimal(10), fixture.token_0_asset_id).await;
+    deposit(&fixture.manager_instance, to_9_decimal(10), fixture.token_1_asset_id).await;
+
+    // Another sender can't mint from someone else's deposits
+    let is_err = mint(&fixture, &other_manager, to_9_decimal(10), to_9_decimal(10), identity(&fixture.other_wallet))
+        .await
+        .is_err();
+    assert!(is_err, "Minting without a deposit should fail");
+
+    // Or withdraw them
+    let withdrawn = other_manager
+        .methods()
+        .withdraw_deposit(Bits256(*fixture.token_1_asset_id))
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .unwrap()
+        .value;
+    assert_eq!(withdrawn, 0);
+    assert_eq!(get_deposit(&fixture, &fixture.wallet, fixture.token_1_asset_id).await, to_9_decimal(10));
+
+    // Depositing only covers the sender's own mint
+    deposit(&other_manager, to_9_decimal(5), fixture.token_0_asset_id).await;
+    let is_err = mint(&fixture, &other_manager, to_9_decimal(5), to_9_decimal(5), identity(&fixture.other_wallet))
+        .await
+        .is_err();
+    assert!(is_err, "Minting beyond the sender's deposit should fail");
+    assert_eq!(get_deposit(&fixture, &fixture.wallet, fixture.token_0_asset_id).await, to_9_decimal(10));
+}
+
+#[tokio::test]
+async fn only_owner_manages_position() {
+    let fixture = setup().await;
+    let other_manager = PositionManager::new(fixture.manager_contract_id.clone(), fixture.other_wallet.clone());
+
+    deposit(&fixture.manager_instance, to_9_decimal(10), fixture.token_0_asset_id).await;
+    deposit(&fixture.manager_instance, to_9_decimal(10), fixture.token_1_asset_id).await;
+    let minted = mint(&fixture, &fixture.manager_instance, to_9_decimal(10), to_9_decimal(10), identity(&fixture.wallet))
+        .await
+        .unwrap()
+        .value;
+
+    let is_err = decrease_liquidity(&fixture, &other_manager, minted.position_id, minted.liquidity, identity(&fixture.other_wallet))
+        .await
+        .is_err();
+    assert!(is_err, "Only the owner can remove liquidity");
+
+    let is_err = other_manager
+        .methods()
+        .collect_fees(minted.position_id, identity(&fixture.other_wallet))
+        .set_contracts(&[fixture.exchange_contract_id.clone()])
+        .append_variable_outputs(2)
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Only the owner can collect fees");
+
+    let is_err = other_manager
+        .methods()
+        .transfer_position(minted.position_id, identity(&fixture.other_wallet))
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Only the owner can transfer a position");
+
+    let response = fixture.manager_instance
+        .methods()
+        .transfer_position(minted.position_id, identity(&fixture.other_wallet))
+        .call()
+        .await
+        .unwrap();
+    let logs = get_logs::<PositionTransferred, _>(&response, &fixture.manager_contract_id, POSITION_MANAGER_ABI);
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs.get(0).unwrap().position_id, minted.position_id);
+
+    let count = fixture.manager_instance.methods().get_position_count(identity(&fixture.wallet)).call().await.unwrap().value;
+    assert_eq!(count, 0);
+    let count = fixture.manager_instance.methods().get_position_count(identity(&fixture.other_wallet)).call().await.unwrap().value;
+    assert_eq!(count, 1);
+
+    // Control moves with the position
+    let is_err = decrease_liquidity(&fixture, &fixture.manager_instance, minted.position_id, minted.liquidity, identity(&fixture.wallet))
+        .await
+        .is_err();
+    assert!(is_err, "The previous owner can't remove liquidity");
+
+    let (amount_0, amount_1) = decrease_liquidity(&fixture, &other_manager, minted.position_id, minted.liquidity, identity(&fixture.other_wallet))
+        .await
+        .unwrap()
+        .value;
+    assert!(amount_0 > 0 && amount_1 > 0);
+}
+
+#[tokio::test]
+async fn fake_pools_cant_drain_deposits() {
+    let fixture = setup().await;
+    let other_manager = PositionManager::new(fixture.manager_contract_id.clone(), fixture.other_wallet.clone());
+
+    // A pool that keeps everything sent to it while reporting that it used nothing
+    let token0_slot = Bytes32::from_str("0x0000000000000000000000000000000000000000000000000000000000000000").unwrap();
+    let token1_slot = Bytes32::from_str("0x0000000000000000000000000000000000000000000000000000000000000001").unwrap();
+    let fake_pool_id = Contract::deploy(
+        "./tests/artifacts/fake_pool/out/debug/fake_pool.bin",
+        &fixture.other_wallet,
+        TxParameters::default(),
+        StorageConfiguration::with_manual_storage(Some(vec![
+            StorageSlot::new(token0_slot, Bytes32::new(*fixture.token_0_asset_id)),
+            StorageSlot::new(token1_slot, Bytes32::new(*fixture.token_1_asset_id)),
+        ])),
+    )
+    .await
+    .unwrap();
+
+    deposit(&fixture.manager_instance, to_9_decimal(10), fixture.token_0_asset_id).await;
+    deposit(&fixture.manager_instance, to_9_decimal(10), fixture.token_1_asset_id).await;
+    deposit(&other_manager, to_9_decimal(5), fixture.token_0_asset_id).await;
+    deposit(&other_manager, to_9_decimal(5), fixture.token_1_asset_id).await;
+
+    let minted = other_manager
+        .methods()
+        .mint(
+            Bits256(fake_pool_id.hash().into()),
+            MAX_TICK - 1000,
+            MAX_TICK + 1000,
+            to_9_decimal(5),
+            to_9_decimal(5),
+            0,
+            0,
+            identity(&fixture.other_wallet),
+        )
+        .set_contracts(&[fake_pool_id.clone()])
+        .call()
+        .await
+        .unwrap()
+        .value;
+
+    // The sender is charged for what left the manager, not what the pool reported
+    assert_eq!(minted.amount_0, to_9_decimal(5));
+    assert_eq!(minted.amount_1, to_9_decimal(5));
+    assert_eq!(get_deposit(&fixture, &fixture.other_wallet, fixture.token_0_asset_id).await, 0);
+    assert_eq!(get_deposit(&fixture, &fixture.other_wallet, fixture.token_1_asset_id).await, 0);
+
+    let withdrawn = other_manager
+        .methods()
+        .withdraw_deposit(Bits256(*fixture.token_1_asset_id))
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .unwrap()
+        .value;
+    assert_eq!(withdrawn, 0);
+
+    // Other senders' deposits are untouched, and still withdrawable in full
+    let withdrawn = fixture.manager_instance
+        .methods()
+        .withdraw_deposit(Bits256(*fixture.token_1_asset_id))
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .unwrap()
+        .value;
+    assert_eq!(withdrawn, to_9_decimal(10));
+}
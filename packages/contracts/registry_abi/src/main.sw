@@ -0,0 +1,121 @@
+library registry_abi;
+
+use std::{
+    identity::Identity,
+    option::Option,
+};
+
+/// Pricing curve of a registered pool, decided by the template its bytecode matches
+pub enum CurveType {
+    ConstantProduct: (),
+    Stable: (),
+    Weighted: (),
+}
+
+// Events
+
+pub struct RegistryInitialized {
+    template_root: b256,
+    vault: b256,
+    owner: Identity,
+}
+
+pub struct PoolRegistered {
+    token0: b256,
+    token1: b256,
+    pool: b256,
+    template_root: b256,
+    curve: CurveType,
+}
+
+pub struct StableTemplateSet {
+    template_root: b256,
+}
+
+pub struct WeightedTemplateSet {
+    template_root: b256,
+}
+
+pub struct PoolRemoved {
+    token0: b256,
+    token1: b256,
+    pool: b256,
+}
+
+pub struct OwnershipTransferStarted {
+    previous_owner: Identity,
+    new_owner: Identity,
+}
+
+pub struct OwnershipTransferred {
+    previous_owner: Identity,
+    new_owner: Identity,
+}
+
+pub struct TokenDenylistUpdated {
+    token: b256,
+    denied: bool,
+}
+
+abi PoolRegistry {
+    #[storage(write, read)]
+    fn initialize(template_exchange_id: b256, vault: b256, owner: Identity);
+    // Add exchange contract to the token
+    #[storage(write, read)]
+    fn add_exchange_contract(exchange_id: b256);
+    // Get exchange contract for desired token
+    #[storage(read)]
+    fn get_exchange_contract(token_a: b256, token_b: b256) -> Option<b256>;
+    #[storage(read)]
+    fn is_pool(addr: b256) -> bool;
+    #[storage(read)]
+    fn exchange_contract_root() -> b256;
+    // Bytecode root of the stable-curve exchange template, zero until one is set
+    #[storage(read)]
+    fn stable_contract_root() -> b256;
+    // Bytecode root of the weighted exchange template, zero until one is set
+    #[storage(read)]
+    fn weighted_contract_root() -> b256;
+    // Curve of a pool that has been registered
+    #[storage(read)]
+    fn get_curve_type(pool: b256) -> CurveType;
+    // Token weights of a registered weighted pool, in basis points
+    #[storage(read)]
+    fn get_pool_weights(pool: b256) -> (u64, u64);
+    // Vault that all registered pools must send protocol fees to
+    #[storage(read)]
+    fn get_vault() -> b256;
+    // Number of pools ever registered, including delisted ones
+    #[storage(read)]
+    fn get_pool_count() -> u64;
+    // Pool at the given index of the registration list, check is_pool to see if it's still listed
+    #[storage(read)]
+    fn get_pool_at(index: u64) -> b256;
+
+    ////////////////////
+    // Admin
+    ////////////////////
+    #[storage(read)]
+    fn get_owner() -> Identity;
+    #[storage(read)]
+    fn get_pending_owner() -> Identity;
+    // Start an ownership transfer, which only takes effect once accepted by the new owner
+    #[storage(read, write)]
+    fn transfer_ownership(new_owner: Identity);
+    #[storage(read, write)]
+    fn accept_ownership();
+    // Also accept pools deployed from this stable-curve exchange
+    #[storage(read, write)]
+    fn set_stable_template(template_exchange_id: b256);
+    // Also accept pools deployed from this weighted exchange
+    #[storage(read, write)]
+    fn set_weighted_template(template_exchange_id: b256);
+    // Delist a pool, allowing a replacement to be registered for the same tokens
+    #[storage(read, write)]
+    fn remove_exchange_contract(exchange_id: b256);
+    // Prevent (or allow) pools containing the token from being registered
+    #[storage(read, write)]
+    fn set_token_denied(token: b256, denied: bool);
+    #[storage(read)]
+    fn is_token_denied(token: b256) -> bool;
+}
@@ -1,13 +1,28 @@
 contract;
 
 use std::{
+    auth::msg_sender,
     constants::ZERO_B256,
     contract_id::ContractId,
     external::bytecode_root,
+    identity::Identity,
+    logging::log,
     option::Option,
-    storage::StorageMap,
+    storage::{get, StorageMap, StorageVec},
+};
+use exchange_abi::{Exchange, WeightedExchange};
+use registry_abi::{
+    CurveType,
+    OwnershipTransferred,
+    OwnershipTransferStarted,
+    PoolRegistered,
+    PoolRegistry,
+    PoolRemoved,
+    RegistryInitialized,
+    StableTemplateSet,
+    TokenDenylistUpdated,
+    WeightedTemplateSet,
 };
-use exchange_abi::Exchange;
 
 enum Error {
     UnorderedTokens: (),
@@ -15,35 +30,75 @@ enum Error {
     AlreadyRegistered: (),
     InvalidContractCode: (),
     PoolInitialized: (),
+    MustBeCalledByOwner: (),
+    MustBeCalledByDeployer: (),
+    MustBeCalledByPendingOwner: (),
+    NotRegistered: (),
+    TokenDenied: (),
+    InvalidVault: (),
+    IndexOutOfBounds: (),
+    NotWeighted: (),
 }
 
-abi PoolRegistry {
-    #[storage(write, read)]
-    fn initialize(template_exchange_id: b256);
-    // Add exchange contract to the token
-    #[storage(write, read)]
-    fn add_exchange_contract(exchange_id: b256);
-    // Get exchange contract for desired token
-    #[storage(read)]
-    fn get_exchange_contract(token_a: b256, token_b: b256) -> Option<b256>;
-    #[storage(read)]
-    fn is_pool(addr: b256) -> bool;
-    #[storage(read)]
-    fn exchange_contract_root() -> b256;
-}
+////////////////////////////////////////
+// Constants
+////////////////////////////////////////
+
+/// Address allowed to initialize the registry, set at deploy so the first owner can't be claimed by anyone else
+const DEPLOYER_SLOT = 0x0000000000000000000000000000000000000000000000000000000000000000;
 
 storage {
     expected_contract_root: b256 = ZERO_B256,
+    stable_contract_root: b256 = ZERO_B256,
+    weighted_contract_root: b256 = ZERO_B256,
+    vault: b256 = ZERO_B256,
+    owner: Identity = Identity::Address(Address::from(ZERO_B256)),
+    pending_owner: Identity = Identity::Address(Address::from(ZERO_B256)),
     pools: StorageMap<(b256, b256), b256> = StorageMap {},
     is_pool: StorageMap<b256, bool> = StorageMap {},
+    // Tokens of each registered pool, so pools can be removed without calling them
+    pool_tokens: StorageMap<b256, (b256, b256)> = StorageMap {},
+    denied_tokens: StorageMap<b256, bool> = StorageMap {},
+    // Every pool in registration order, so off-chain and on-chain callers can enumerate them
+    pool_list: StorageVec<b256> = StorageVec {},
+    is_listed: StorageMap<b256, bool> = StorageMap {},
+    pool_curves: StorageMap<b256, CurveType> = StorageMap {},
+    // Token weights of each weighted pool, fixed when the pool is deployed
+    pool_weights: StorageMap<b256, (u64, u64)> = StorageMap {},
+}
+
+////////////////////////////////////////
+// Helper functions
+////////////////////////////////////////
+
+#[storage(read)]
+fn validate_deployer() {
+    let sender = msg_sender().unwrap();
+    require(sender == Identity::Address(Address::from(get::<b256>(DEPLOYER_SLOT))), Error::MustBeCalledByDeployer);
+}
+
+#[storage(read)]
+fn validate_owner() {
+    let sender = msg_sender().unwrap();
+    require(sender == storage.owner, Error::MustBeCalledByOwner);
 }
 
 impl PoolRegistry for Contract {
     #[storage(write, read)]
-    fn initialize(template_exchange_id: b256) {
+    fn initialize(template_exchange_id: b256, vault: b256, owner: Identity) {
+        validate_deployer();
         require(storage.expected_contract_root == ZERO_B256, Error::AlreadyInitialized);
+        require(vault != ZERO_B256, Error::InvalidVault);
         let root = bytecode_root(ContractId::from(template_exchange_id));
         storage.expected_contract_root = root;
+        storage.vault = vault;
+        storage.owner = owner;
+
+        log(RegistryInitialized {
+            template_root: root,
+            vault: vault,
+            owner: owner,
+        });
     }
 
     #[storage(write, read)]
@@ -51,10 +106,19 @@ impl PoolRegistry for Contract {
         let exchange = abi(Exchange, exchange_id);
 
         let root = bytecode_root(ContractId::from(exchange_id));
-        require(root == storage.expected_contract_root, Error::InvalidContractCode);
+        let curve = if root == storage.expected_contract_root {
+            CurveType::ConstantProduct
+        } else if root != ZERO_B256 && root == storage.stable_contract_root {
+            CurveType::Stable
+        } else {
+            let weighted_root = storage.weighted_contract_root;
+            require(weighted_root != ZERO_B256 && root == weighted_root, Error::InvalidContractCode);
+            CurveType::Weighted
+        };
 
         let (token0, token1) = exchange.get_tokens();
         require(token0 < token1, Error::UnorderedTokens);
+        require(!storage.denied_tokens.get(token0) && !storage.denied_tokens.get(token1), Error::TokenDenied);
 
         let existing_exchange = storage.pools.get((token0, token1));
         require(existing_exchange == b256::min(), Error::AlreadyRegistered);
@@ -62,8 +126,33 @@ impl PoolRegistry for Contract {
         let pool_info = exchange.get_pool_info();
         require(pool_info.lp_token_supply == 0, Error::PoolInitialized);
 
+        // Pools are initialized with their vault by anyone, so make sure protocol fees go to ours
+        let vault_info = exchange.get_vault_info();
+        require(vault_info.vault == storage.vault, Error::InvalidVault);
+
         storage.pools.insert((token0, token1), exchange_id);
         storage.is_pool.insert(exchange_id, true);
+        storage.pool_tokens.insert(exchange_id, (token0, token1));
+        storage.pool_curves.insert(exchange_id, curve);
+        match curve {
+            CurveType::Weighted => {
+                storage.pool_weights.insert(exchange_id, abi(WeightedExchange, exchange_id).get_weights());
+            },
+            _ => {},
+        }
+        // A delisted pool that's registered again keeps its original position
+        if (!storage.is_listed.get(exchange_id)) {
+            storage.pool_list.push(exchange_id);
+            storage.is_listed.insert(exchange_id, true);
+        }
+
+        log(PoolRegistered {
+            token0: token0,
+            token1: token1,
+            pool: exchange_id,
+            template_root: root,
+            curve: curve,
+        });
     }
 
     #[storage(read)]
@@ -91,4 +180,139 @@ impl PoolRegistry for Contract {
     fn exchange_contract_root() -> b256 {
         storage.expected_contract_root
     }
+
+    #[storage(read)]
+    fn stable_contract_root() -> b256 {
+        storage.stable_contract_root
+    }
+
+    #[storage(read)]
+    fn weighted_contract_root() -> b256 {
+        storage.weighted_contract_root
+    }
+
+    #[storage(read)]
+    fn get_curve_type(pool: b256) -> CurveType {
+        require(storage.is_listed.get(pool), Error::NotRegistered);
+        storage.pool_curves.get(pool)
+    }
+
+    #[storage(read)]
+    fn get_pool_weights(pool: b256) -> (u64, u64) {
+        require(storage.is_listed.get(pool), Error::NotRegistered);
+        let weights = storage.pool_weights.get(pool);
+        require(weights.0 > 0, Error::NotWeighted);
+        weights
+    }
+
+    #[storage(read)]
+    fn get_vault() -> b256 {
+        storage.vault
+    }
+
+    #[storage(read)]
+    fn get_pool_count() -> u64 {
+        storage.pool_list.len()
+    }
+
+    #[storage(read)]
+    fn get_pool_at(index: u64) -> b256 {
+        let pool = storage.pool_list.get(index);
+        require(pool.is_some(), Error::IndexOutOfBounds);
+        pool.unwrap()
+    }
+
+    #[storage(read)]
+    fn get_owner() -> Identity {
+        storage.owner
+    }
+
+    #[storage(read)]
+    fn get_pending_owner() -> Identity {
+        storage.pending_owner
+    }
+
+    #[storage(read, write)]
+    fn transfer_ownership(new_owner: Identity) {
+        validate_owner();
+        storage.pending_owner = new_owner;
+
+        log(OwnershipTransferStarted {
+            previous_owner: storage.owner,
+            new_owner: new_owner,
+        });
+    }
+
+    #[storage(read, write)]
+    fn accept_ownership() {
+        let sender = msg_sender().unwrap();
+        let pending_owner = storage.pending_owner;
+        require(
+            sender == pending_owner && pending_owner != Identity::Address(Address::from(ZERO_B256)),
+            Error::MustBeCalledByPendingOwner
+        );
+
+        let previous_owner = storage.owner;
+        storage.owner = pending_owner;
+        storage.pending_owner = Identity::Address(Address::from(ZERO_B256));
+
+        log(OwnershipTransferred {
+            previous_owner: previous_owner,
+            new_owner: pending_owner,
+        });
+    }
+
+    #[storage(read, write)]
+    fn set_stable_template(template_exchange_id: b256) {
+        validate_owner();
+        let root = bytecode_root(ContractId::from(template_exchange_id));
+        storage.stable_contract_root = root;
+
+        log(StableTemplateSet {
+            template_root: root,
+        });
+    }
+
+    #[storage(read, write)]
+    fn set_weighted_template(template_exchange_id: b256) {
+        validate_owner();
+        let root = bytecode_root(ContractId::from(template_exchange_id));
+        storage.weighted_contract_root = root;
+
+        log(WeightedTemplateSet {
+            template_root: root,
+        });
+    }
+
+    #[storage(read, write)]
+    fn remove_exchange_contract(exchange_id: b256) {
+        validate_owner();
+        require(storage.is_pool.get(exchange_id), Error::NotRegistered);
+
+        let (token0, token1) = storage.pool_tokens.get(exchange_id);
+        storage.pools.insert((token0, token1), b256::min());
+        storage.is_pool.insert(exchange_id, false);
+
+        log(PoolRemoved {
+            token0: token0,
+            token1: token1,
+            pool: exchange_id,
+        });
+    }
+
+    #[storage(read, write)]
+    fn set_token_denied(token: b256, denied: bool) {
+        validate_owner();
+        storage.denied_tokens.insert(token, denied);
+
+        log(TokenDenylistUpdated {
+            token: token,
+            denied: denied,
+        });
+    }
+
+    #[storage(read)]
+    fn is_token_denied(token: b256) -> bool {
+        storage.denied_tokens.get(token)
+    }
 }
@@ -1,10 +1,12 @@
 use fuels::{
     prelude::*,
     fuels_abigen::abigen,
+    signers::WalletUnlocked,
     tx::{Bytes32, StorageSlot},
 };
 
 use std::str::FromStr;
+use test_helpers::{deployer_storage, get_logs, get_wallets, REGISTRY_ABI};
 
 ///////////////////////////////
 // Load the SwaySwap Contract abi
@@ -20,6 +22,31 @@ abigen!(
 
 const ZERO_B256: Bits256 = Bits256([0; 32]);
 
+async fn deploy_vault(wallet: &WalletUnlocked) -> Bech32ContractId {
+    Contract::deploy(
+        "../vault_contract/out/debug/vault_contract.bin",
+        wallet,
+        TxParameters::default(),
+        deployer_storage(wallet),
+    )
+    .await
+    .unwrap()
+}
+
+async fn initialize_exchange(
+    wallet: &WalletUnlocked,
+    exchange_contract_id: &Bech32ContractId,
+    vault_contract_id: &Bech32ContractId,
+) {
+    Exchange::new(exchange_contract_id.clone(), wallet.clone())
+        .methods()
+        .initialize(Bits256(vault_contract_id.hash().into()))
+        .set_contracts(&[vault_contract_id.clone()])
+        .call()
+        .await
+        .unwrap();
+}
+
 #[tokio::test]
 async fn register_exchange() {
     // Provider and Wallet
@@ -30,16 +57,18 @@ async fn register_exchange() {
         "out/debug/registry_contract.bin",
         &wallet,
         TxParameters::default(),
-        StorageConfiguration::new(None, None),
+        deployer_storage(&wallet),
     )
     .await
     .unwrap();
 
     let registry_instance = RegistryBuilder::new(
-        registry_contract_id,
+        registry_contract_id.clone(),
         wallet.clone(),
     );
 
+    let vault_contract_id = deploy_vault(&wallet).await;
+
     let token0_slot = Bytes32::from_str("0x0000000000000000000000000000000000000000000000000000000000000000").unwrap();
     let token1_slot = Bytes32::from_str("0x0000000000000000000000000000000000000000000000000000000000000001").unwrap();    
 
@@ -64,12 +93,18 @@ async fn register_exchange() {
     .await
     .unwrap();
 
+    initialize_exchange(&wallet, &exchange_contract_id, &vault_contract_id).await;
+
     let root = registry_instance.methods().exchange_contract_root().simulate().await.unwrap();
     assert_eq!(root.value, ZERO_B256, "Registry should be uninitialized");
 
-    registry_instance
+    let response = registry_instance
         .methods()
-        .initialize(Bits256(exchange_contract_id.hash().into()))
+        .initialize(
+            Bits256(exchange_contract_id.hash().into()),
+            Bits256(vault_contract_id.hash().into()),
+            Identity::Address(wallet.address().into()),
+        )
         .call()
         .await
         .unwrap();
@@ -77,6 +112,13 @@ async fn register_exchange() {
     let root = registry_instance.methods().exchange_contract_root().simulate().await.unwrap();
     assert_ne!(root.value, ZERO_B256, "Registry should be initialized");
 
+    let logs = get_logs::<RegistryInitialized, _>(&response, &registry_contract_id, REGISTRY_ABI);
+    assert_eq!(logs.len(), 1);
+    let initialized_event = logs.get(0).unwrap();
+    assert_eq!(initialized_event.template_root, root.value);
+    assert_eq!(initialized_event.vault, Bits256(vault_contract_id.hash().into()));
+    assert_eq!(initialized_event.owner, Identity::Address(wallet.address().into()));
+
     let result = registry_instance
         .methods()
         .is_pool(Bits256(exchange_contract_id.hash().into()))
@@ -86,7 +128,7 @@ async fn register_exchange() {
     assert!(!result.value, "is_pool shouldn't return true");
 
     // Test storage
-    registry_instance
+    let response = registry_instance
         .methods()
         .add_exchange_contract(Bits256(exchange_contract_id.hash().into()))
         .set_contracts(&[exchange_contract_id.clone()])
@@ -94,6 +136,14 @@ async fn register_exchange() {
         .await
         .unwrap();
 
+    let logs = get_logs::<PoolRegistered, _>(&response, &registry_contract_id, REGISTRY_ABI);
+    assert_eq!(logs.len(), 1);
+    let registered_event = logs.get(0).unwrap();
+    assert_eq!(registered_event.token0, Bits256(token_id_1.into()));
+    assert_eq!(registered_event.token1, Bits256(token_id_2.into()));
+    assert_eq!(registered_event.pool, Bits256(exchange_contract_id.hash().into()));
+    assert_eq!(registered_event.template_root, root.value);
+
     // Test retrieval (normal)
     let result = registry_instance
         .methods()
@@ -128,6 +178,16 @@ async fn register_exchange() {
         .await
         .unwrap();
     assert!(result.value, "is_pool should return true");
+
+    // Test pool list
+    let result = registry_instance.methods().get_pool_count().call().await.unwrap();
+    assert_eq!(result.value, 1);
+
+    let result = registry_instance.methods().get_pool_at(0).call().await.unwrap();
+    assert_eq!(result.value, Bits256(exchange_contract_id.hash().into()));
+
+    let is_err = registry_instance.methods().get_pool_at(1).call().await.is_err();
+    assert!(is_err, "Out of bounds index should fail");
 }
 
 #[tokio::test]
@@ -140,13 +200,15 @@ async fn unordered_tokens_should_fail() {
         "out/debug/registry_contract.bin",
         &wallet,
         TxParameters::default(),
-        StorageConfiguration::new(None, None),
+        deployer_storage(&wallet),
     )
     .await
     .unwrap();
 
     let registry_instance = RegistryBuilder::new(registry_contract_id, wallet.clone());
 
+    let vault_contract_id = deploy_vault(&wallet).await;
+
     let token0_slot = Bytes32::from_str("0x0000000000000000000000000000000000000000000000000000000000000000").unwrap();
     let token1_slot = Bytes32::from_str("0x0000000000000000000000000000000000000000000000000000000000000001").unwrap();    
 
@@ -170,9 +232,15 @@ async fn unordered_tokens_should_fail() {
     .await
     .unwrap();
 
+    initialize_exchange(&wallet, &exchange_contract_id, &vault_contract_id).await;
+
     registry_instance
         .methods()
-        .initialize(Bits256(exchange_contract_id.hash().into()))
+        .initialize(
+            Bits256(exchange_contract_id.hash().into()),
+            Bits256(vault_contract_id.hash().into()),
+            Identity::Address(wallet.address().into()),
+        )
         .call()
         .await
         .unwrap();
@@ -198,13 +266,15 @@ async fn test_invalid_contract() {
         "out/debug/registry_contract.bin",
         &wallet,
         TxParameters::default(),
-        StorageConfiguration::new(None, None),
+        deployer_storage(&wallet),
     )
     .await
     .unwrap();
 
     let registry_instance = RegistryBuilder::new(registry_contract_id, wallet.clone());
 
+    let vault_contract_id = deploy_vault(&wallet).await;
+
     let token0_slot = Bytes32::from_str("0x0000000000000000000000000000000000000000000000000000000000000000").unwrap();
     let token1_slot = Bytes32::from_str("0x0000000000000000000000000000000000000000000000000000000000000001").unwrap();    
 
@@ -240,7 +310,11 @@ async fn test_invalid_contract() {
 
     registry_instance
         .methods()
-        .initialize(Bits256(valid_exchange_contract_id.hash().into()))
+        .initialize(
+            Bits256(valid_exchange_contract_id.hash().into()),
+            Bits256(vault_contract_id.hash().into()),
+            Identity::Address(wallet.address().into()),
+        )
         .call()
         .await
         .unwrap();
@@ -266,13 +340,15 @@ async fn initialized_pools_should_fail() {
         "out/debug/registry_contract.bin",
         &wallet,
         TxParameters::default(),
-        StorageConfiguration::new(None, None),
+        deployer_storage(&wallet),
     )
     .await
     .unwrap();
 
     let registry_instance = RegistryBuilder::new(registry_contract_id, wallet.clone());
 
+    let vault_contract_id = deploy_vault(&wallet).await;
+
     let token_contract_id = Contract::deploy(
         "../token_contract/out/debug/token_contract.bin",
         &wallet,
@@ -315,6 +391,8 @@ async fn initialized_pools_should_fail() {
     .await
     .unwrap();
 
+    initialize_exchange(&wallet, &exchange_contract_id, &vault_contract_id).await;
+
     let exchange_instance = Exchange::new(exchange_contract_id.clone(), wallet.clone());
 
     // Add Liquidity
@@ -361,7 +439,11 @@ async fn initialized_pools_should_fail() {
 
     registry_instance
         .methods()
-        .initialize(Bits256(exchange_contract_id.hash().into()))
+        .initialize(
+            Bits256(exchange_contract_id.hash().into()),
+            Bits256(vault_contract_id.hash().into()),
+            Identity::Address(wallet.address().into()),
+        )
         .call()
         .await
         .unwrap();
@@ -387,13 +469,15 @@ async fn duplicate_pools_should_fail() {
         "out/debug/registry_contract.bin",
         &wallet,
         TxParameters::default(),
-        StorageConfiguration::new(None, None),
+        deployer_storage(&wallet),
     )
     .await
     .unwrap();
 
     let registry_instance = RegistryBuilder::new(registry_contract_id, wallet.clone());
 
+    let vault_contract_id = deploy_vault(&wallet).await;
+
     let token0_slot = Bytes32::from_str("0x0000000000000000000000000000000000000000000000000000000000000000").unwrap();
     let token1_slot = Bytes32::from_str("0x0000000000000000000000000000000000000000000000000000000000000001").unwrap();
 
@@ -417,6 +501,8 @@ async fn duplicate_pools_should_fail() {
     .await
     .unwrap();
 
+    initialize_exchange(&wallet, &exchange_contract_1_id, &vault_contract_id).await;
+
     // Deploy contract and get ID
     let exchange_contract_2_id = Contract::deploy_with_parameters(
         "../exchange_contract/out/debug/exchange_contract.bin",
@@ -428,9 +514,15 @@ async fn duplicate_pools_should_fail() {
     .await
     .unwrap();
 
+    initialize_exchange(&wallet, &exchange_contract_2_id, &vault_contract_id).await;
+
     registry_instance
         .methods()
-        .initialize(Bits256(exchange_contract_1_id.hash().into()))
+        .initialize(
+            Bits256(exchange_contract_1_id.hash().into()),
+            Bits256(vault_contract_id.hash().into()),
+            Identity::Address(wallet.address().into()),
+        )
         .call()
         .await
         .unwrap();
@@ -452,3 +544,814 @@ async fn duplicate_pools_should_fail() {
         .is_err();
     assert!(is_err);
 }
+
+#[tokio::test]
+async fn transfer_ownership() {
+    let wallets = get_wallets().await;
+    let owner_wallet = wallets.get(0).unwrap().clone();
+    let new_owner_wallet = wallets.get(1).unwrap().clone();
+    let attacker_wallet = wallets.get(2).unwrap().clone();
+
+    let registry_contract_id = Contract::deploy(
+        "out/debug/registry_contract.bin",
+        &owner_wallet,
+        TxParameters::default(),
+        deployer_storage(&owner_wallet),
+    )
+    .await
+    .unwrap();
+
+    let registry_instance = RegistryBuilder::new(registry_contract_id.clone(), owner_wallet.clone());
+    let new_owner_registry_instance = RegistryBuilder::new(registry_contract_id.clone(), new_owner_wallet.clone());
+    let attacker_registry_instance = RegistryBuilder::new(registry_contract_id.clone(), attacker_wallet.clone());
+
+    let vault_contract_id = deploy_vault(&owner_wallet).await;
+
+    let exchange_contract_id = Contract::deploy(
+        "../exchange_contract/out/debug/exchange_contract.bin",
+        &owner_wallet,
+        TxParameters::default(),
+        StorageConfiguration::new(None, None),
+    )
+    .await
+    .unwrap();
+
+    let owner = Identity::Address(owner_wallet.address().into());
+    let new_owner = Identity::Address(new_owner_wallet.address().into());
+
+    // Only the deployer can claim the registry
+    let is_err = attacker_registry_instance
+        .methods()
+        .initialize(
+            Bits256(exchange_contract_id.hash().into()),
+            Bits256(vault_contract_id.hash().into()),
+            Identity::Address(attacker_wallet.address().into()),
+        )
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Registry should only be initialized by its deployer");
+
+    registry_instance
+        .methods()
+        .initialize(
+            Bits256(exchange_contract_id.hash().into()),
+            Bits256(vault_contract_id.hash().into()),
+            owner.clone(),
+        )
+        .call()
+        .await
+        .unwrap();
+
+    let result = registry_instance.methods().get_owner().simulate().await.unwrap();
+    assert_eq!(result.value, owner);
+
+    // Can't be initialized twice
+    let is_err = attacker_registry_instance
+        .methods()
+        .initialize(
+            Bits256(exchange_contract_id.hash().into()),
+            Bits256(vault_contract_id.hash().into()),
+            Identity::Address(attacker_wallet.address().into()),
+        )
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Registry shouldn't be re-initialized");
+
+    // Only the owner can start a transfer
+    let is_err = attacker_registry_instance
+        .methods()
+        .transfer_ownership(Identity::Address(attacker_wallet.address().into()))
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Non-owner shouldn't transfer ownership");
+
+    let response = registry_instance
+        .methods()
+        .transfer_ownership(new_owner.clone())
+        .call()
+        .await
+        .unwrap();
+
+    let logs = get_logs::<OwnershipTransferStarted, _>(&response, &registry_contract_id, REGISTRY_ABI);
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs.get(0).unwrap().previous_owner, owner);
+    assert_eq!(logs.get(0).unwrap().new_owner, new_owner);
+
+    let result = registry_instance.methods().get_pending_owner().simulate().await.unwrap();
+    assert_eq!(result.value, new_owner);
+
+    // Ownership doesn't move until accepted
+    let result = registry_instance.methods().get_owner().simulate().await.unwrap();
+    assert_eq!(result.value, owner);
+
+    // Only the pending owner can accept
+    let is_err = attacker_registry_instance
+        .methods()
+        .accept_ownership()
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Only the pending owner should accept ownership");
+
+    let response = new_owner_registry_instance
+        .methods()
+        .accept_ownership()
+        .call()
+        .await
+        .unwrap();
+
+    let logs = get_logs::<OwnershipTransferred, _>(&response, &registry_contract_id, REGISTRY_ABI);
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs.get(0).unwrap().previous_owner, owner);
+    assert_eq!(logs.get(0).unwrap().new_owner, new_owner);
+
+    let result = registry_instance.methods().get_owner().simulate().await.unwrap();
+    assert_eq!(result.value, new_owner);
+
+    let result = registry_instance.methods().get_pending_owner().simulate().await.unwrap();
+    assert_eq!(result.value, Identity::Address(Address::from([0u8; 32])));
+
+    // The previous owner has lost access
+    let is_err = registry_instance
+        .methods()
+        .set_token_denied(Bits256([1; 32]), true)
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Previous owner should lose access");
+
+    new_owner_registry_instance
+        .methods()
+        .set_token_denied(Bits256([1; 32]), true)
+        .call()
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn remove_exchange() {
+    let wallets = get_wallets().await;
+    let wallet = wallets.get(0).unwrap().clone();
+    let attacker_wallet = wallets.get(1).unwrap().clone();
+
+    let registry_contract_id = Contract::deploy(
+        "out/debug/registry_contract.bin",
+        &wallet,
+        TxParameters::default(),
+        deployer_storage(&wallet),
+    )
+    .await
+    .unwrap();
+
+    let registry_instance = RegistryBuilder::new(registry_contract_id.clone(), wallet.clone());
+    let attacker_registry_instance = RegistryBuilder::new(registry_contract_id.clone(), attacker_wallet.clone());
+
+    let vault_contract_id = deploy_vault(&wallet).await;
+
+    let token0_slot = Bytes32::from_str("0x0000000000000000000000000000000000000000000000000000000000000000").unwrap();
+    let token1_slot = Bytes32::from_str("0x0000000000000000000000000000000000000000000000000000000000000001").unwrap();
+
+    // Create fake token ids
+    let token_id_1 = Bytes32::from_str("0x000005877b940cc69d7a9a71000a0cfdd79e93f783f198de893165278712a480").unwrap();
+    let token_id_2 = Bytes32::from_str("0x716c345b96f3c17234c73881c40df43d3d492b902a01a062c12e92eeae0284e9").unwrap();
+
+    let storage_vec = vec![
+        StorageSlot::new(token0_slot, token_id_1),
+        StorageSlot::new(token1_slot, token_id_2),
+    ];
+
+    let exchange_contract_1_id = Contract::deploy_with_parameters(
+        "../exchange_contract/out/debug/exchange_contract.bin",
+        &wallet,
+        TxParameters::default(),
+        StorageConfiguration::with_manual_storage(Some(storage_vec.clone())),
+        Salt::from([1u8; 32]),
+    )
+    .await
+    .unwrap();
+
+    initialize_exchange(&wallet, &exchange_contract_1_id, &vault_contract_id).await;
+
+    let exchange_contract_2_id = Contract::deploy_with_parameters(
+        "../exchange_contract/out/debug/exchange_contract.bin",
+        &wallet,
+        TxParameters::default(),
+        StorageConfiguration::with_manual_storage(Some(storage_vec)),
+        Salt::from([2u8; 32]),
+    )
+    .await
+    .unwrap();
+
+    initialize_exchange(&wallet, &exchange_contract_2_id, &vault_contract_id).await;
+
+    registry_instance
+        .methods()
+        .initialize(
+            Bits256(exchange_contract_1_id.hash().into()),
+            Bits256(vault_contract_id.hash().into()),
+            Identity::Address(wallet.address().into()),
+        )
+        .call()
+        .await
+        .unwrap();
+
+    registry_instance
+        .methods()
+        .add_exchange_contract(Bits256(exchange_contract_1_id.hash().into()))
+        .set_contracts(&[exchange_contract_1_id.clone()])
+        .call()
+        .await
+        .unwrap();
+
+    // Only the owner can remove pools
+    let is_err = attacker_registry_instance
+        .methods()
+        .remove_exchange_contract(Bits256(exchange_contract_1_id.hash().into()))
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Non-owner shouldn't remove pools");
+
+    let response = registry_instance
+        .methods()
+        .remove_exchange_contract(Bits256(exchange_contract_1_id.hash().into()))
+        .call()
+        .await
+        .unwrap();
+
+    let logs = get_logs::<PoolRemoved, _>(&response, &registry_contract_id, REGISTRY_ABI);
+    assert_eq!(logs.len(), 1);
+    let removed_event = logs.get(0).unwrap();
+    assert_eq!(removed_event.token0, Bits256(token_id_1.into()));
+    assert_eq!(removed_event.token1, Bits256(token_id_2.into()));
+    assert_eq!(removed_event.pool, Bits256(exchange_contract_1_id.hash().into()));
+
+    let result = registry_instance
+        .methods()
+        .is_pool(Bits256(exchange_contract_1_id.hash().into()))
+        .call()
+        .await
+        .unwrap();
+    assert!(!result.value, "is_pool should be cleared");
+
+    let result = registry_instance
+        .methods()
+        .get_exchange_contract(Bits256(token_id_1.into()), Bits256(token_id_2.into()))
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(result.value, None);
+
+    // Removing twice fails
+    let is_err = registry_instance
+        .methods()
+        .remove_exchange_contract(Bits256(exchange_contract_1_id.hash().into()))
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Removed pool shouldn't be removed again");
+
+    // A replacement pool can be registered for the same tokens
+    registry_instance
+        .methods()
+        .add_exchange_contract(Bits256(exchange_contract_2_id.hash().into()))
+        .set_contracts(&[exchange_contract_2_id.clone()])
+        .call()
+        .await
+        .unwrap();
+
+    let result = registry_instance
+        .methods()
+        .get_exchange_contract(Bits256(token_id_1.into()), Bits256(token_id_2.into()))
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(result.value, Some(Bits256(exchange_contract_2_id.hash().into())));
+
+    // Delisted pools stay in the pool list
+    let result = registry_instance.methods().get_pool_count().call().await.unwrap();
+    assert_eq!(result.value, 2);
+
+    let result = registry_instance.methods().get_pool_at(0).call().await.unwrap();
+    assert_eq!(result.value, Bits256(exchange_contract_1_id.hash().into()));
+
+    let result = registry_instance.methods().get_pool_at(1).call().await.unwrap();
+    assert_eq!(result.value, Bits256(exchange_contract_2_id.hash().into()));
+}
+
+#[tokio::test]
+async fn denied_tokens_should_fail() {
+    let wallets = get_wallets().await;
+    let wallet = wallets.get(0).unwrap().clone();
+    let attacker_wallet = wallets.get(1).unwrap().clone();
+
+    let registry_contract_id = Contract::deploy(
+        "out/debug/registry_contract.bin",
+        &wallet,
+        TxParameters::default(),
+        deployer_storage(&wallet),
+    )
+    .await
+    .unwrap();
+
+    let registry_instance = RegistryBuilder::new(registry_contract_id.clone(), wallet.clone());
+    let attacker_registry_instance = RegistryBuilder::new(registry_contract_id.clone(), attacker_wallet.clone());
+
+    let vault_contract_id = deploy_vault(&wallet).await;
+
+    let token0_slot = Bytes32::from_str("0x0000000000000000000000000000000000000000000000000000000000000000").unwrap();
+    let token1_slot = Bytes32::from_str("0x0000000000000000000000000000000000000000000000000000000000000001").unwrap();
+
+    // Create fake token ids
+    let token_id_1 = Bytes32::from_str("0x000005877b940cc69d7a9a71000a0cfdd79e93f783f198de893165278712a480").unwrap();
+    let token_id_2 = Bytes32::from_str("0x716c345b96f3c17234c73881c40df43d3d492b902a01a062c12e92eeae0284e9").unwrap();
+
+    let storage_vec = vec![
+        StorageSlot::new(token0_slot, token_id_1),
+        StorageSlot::new(token1_slot, token_id_2),
+    ];
+
+    let exchange_contract_id = Contract::deploy_with_parameters(
+        "../exchange_contract/out/debug/exchange_contract.bin",
+        &wallet,
+        TxParameters::default(),
+        StorageConfiguration::with_manual_storage(Some(storage_vec)),
+        Salt::from([1u8; 32]),
+    )
+    .await
+    .unwrap();
+
+    initialize_exchange(&wallet, &exchange_contract_id, &vault_contract_id).await;
+
+    registry_instance
+        .methods()
+        .initialize(
+            Bits256(exchange_contract_id.hash().into()),
+            Bits256(vault_contract_id.hash().into()),
+            Identity::Address(wallet.address().into()),
+        )
+        .call()
+        .await
+        .unwrap();
+
+    // Only the owner can manage the denylist
+    let is_err = attacker_registry_instance
+        .methods()
+        .set_token_denied(Bits256(token_id_2.into()), true)
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Non-owner shouldn't manage the denylist");
+
+    let response = registry_instance
+        .methods()
+        .set_token_denied(Bits256(token_id_2.into()), true)
+        .call()
+        .await
+        .unwrap();
+
+    let logs = get_logs::<TokenDenylistUpdated, _>(&response, &registry_contract_id, REGISTRY_ABI);
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs.get(0).unwrap().token, Bits256(token_id_2.into()));
+    assert!(logs.get(0).unwrap().denied);
+
+    let result = registry_instance
+        .methods()
+        .is_token_denied(Bits256(token_id_2.into()))
+        .call()
+        .await
+        .unwrap();
+    assert!(result.value);
+
+    let is_err = registry_instance
+        .methods()
+        .add_exchange_contract(Bits256(exchange_contract_id.hash().into()))
+        .set_contracts(&[exchange_contract_id.clone()])
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Pools with denied tokens shouldn't be registered");
+
+    // Lifting the denial allows registration
+    registry_instance
+        .methods()
+        .set_token_denied(Bits256(token_id_2.into()), false)
+        .call()
+        .await
+        .unwrap();
+
+    registry_instance
+        .methods()
+        .add_exchange_contract(Bits256(exchange_contract_id.hash().into()))
+        .set_contracts(&[exchange_contract_id.clone()])
+        .call()
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn foreign_vault_should_fail() {
+    // Provider and Wallet
+    let wallet = launch_provider_and_get_wallet().await;
+
+    // Get the contract ID and a handle to it
+    let registry_contract_id = Contract::deploy(
+        "out/debug/registry_contract.bin",
+        &wallet,
+        TxParameters::default(),
+        deployer_storage(&wallet),
+    )
+    .await
+    .unwrap();
+
+    let registry_instance = RegistryBuilder::new(registry_contract_id, wallet.clone());
+
+    let vault_contract_id = deploy_vault(&wallet).await;
+
+    // Note: a second vault, standing in for one controlled by an attacker
+    let foreign_vault_contract_id = Contract::deploy_with_parameters(
+        "../vault_contract/out/debug/vault_contract.bin",
+        &wallet,
+        TxParameters::default(),
+        deployer_storage(&wallet),
+        Salt::from([1u8; 32]),
+    )
+    .await
+    .unwrap();
+
+    let token0_slot = Bytes32::from_str("0x0000000000000000000000000000000000000000000000000000000000000000").unwrap();
+    let token1_slot = Bytes32::from_str("0x0000000000000000000000000000000000000000000000000000000000000001").unwrap();
+
+    // Create fake token ids
+    let token_id_1 = Bytes32::from_str("0x000005877b940cc69d7a9a71000a0cfdd79e93f783f198de893165278712a480").unwrap();
+    let token_id_2 = Bytes32::from_str("0x716c345b96f3c17234c73881c40df43d3d492b902a01a062c12e92eeae0284e9").unwrap();
+
+    let storage_vec = vec![
+        StorageSlot::new(token0_slot, token_id_1),
+        StorageSlot::new(token1_slot, token_id_2),
+    ];
+
+    let foreign_exchange_contract_id = Contract::deploy_with_parameters(
+        "../exchange_contract/out/debug/exchange_contract.bin",
+        &wallet,
+        TxParameters::default(),
+        StorageConfiguration::with_manual_storage(Some(storage_vec.clone())),
+        Salt::from([1u8; 32]),
+    )
+    .await
+    .unwrap();
+
+    initialize_exchange(&wallet, &foreign_exchange_contract_id, &foreign_vault_contract_id).await;
+
+    // Never initialized, so the vault is zero
+    let uninitialized_exchange_contract_id = Contract::deploy_with_parameters(
+        "../exchange_contract/out/debug/exchange_contract.bin",
+        &wallet,
+        TxParameters::default(),
+        StorageConfiguration::with_manual_storage(Some(storage_vec)),
+        Salt::from([2u8; 32]),
+    )
+    .await
+    .unwrap();
+
+    // The registry needs a vault
+    let is_err = registry_instance
+        .methods()
+        .initialize(
+            Bits256(foreign_exchange_contract_id.hash().into()),
+            ZERO_B256,
+            Identity::Address(wallet.address().into()),
+        )
+        .call()
+        .await
+        .is_err();
+    assert!(is_err);
+
+    registry_instance
+        .methods()
+        .initialize(
+            Bits256(foreign_exchange_contract_id.hash().into()),
+            Bits256(vault_contract_id.hash().into()),
+            Identity::Address(wallet.address().into()),
+        )
+        .call()
+        .await
+        .unwrap();
+
+    let result = registry_instance.methods().get_vault().simulate().await.unwrap();
+    assert_eq!(result.value, Bits256(vault_contract_id.hash().into()));
+
+    let is_err = registry_instance
+        .methods()
+        .add_exchange_contract(Bits256(foreign_exchange_contract_id.hash().into()))
+        .set_contracts(&[foreign_exchange_contract_id.clone()])
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Pools using another vault shouldn't be registered");
+
+    let is_err = registry_instance
+        .methods()
+        .add_exchange_contract(Bits256(uninitialized_exchange_contract_id.hash().into()))
+        .set_contracts(&[uninitialized_exchange_contract_id.clone()])
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Uninitialized pools shouldn't be registered");
+}
+
+#[tokio::test]
+async fn register_stable_exchange() {
+    let wallets = get_wallets().await;
+    let wallet = wallets.get(0).unwrap().clone();
+    let attacker_wallet = wallets.get(1).unwrap().clone();
+
+    let registry_contract_id = Contract::deploy(
+        "out/debug/registry_contract.bin",
+        &wallet,
+        TxParameters::default(),
+        deployer_storage(&wallet),
+    )
+    .await
+    .unwrap();
+
+    let registry_instance = RegistryBuilder::new(registry_contract_id.clone(), wallet.clone());
+    let attacker_registry_instance = RegistryBuilder::new(registry_contract_id.clone(), attacker_wallet.clone());
+
+    let vault_contract_id = deploy_vault(&wallet).await;
+
+    let token0_slot = Bytes32::from_str("0x0000000000000000000000000000000000000000000000000000000000000000").unwrap();
+    let token1_slot = Bytes32::from_str("0x0000000000000000000000000000000000000000000000000000000000000001").unwrap();
+
+    let token_id_1 = Bytes32::from_str("0x000005877b940cc69d7a9a71000a0cfdd79e93f783f198de893165278712a480").unwrap();
+    let token_id_2 = Bytes32::from_str("0x716c345b96f3c17234c73881c40df43d3d492b902a01a062c12e92eeae0284e9").unwrap();
+    let token_id_3 = Bytes32::from_str("0xdf43d3d492b90716c345b96f3c17234c73881e92eeae0284e9c402a01a062c12").unwrap();
+
+    let exchange_contract_id = Contract::deploy_with_parameters(
+        "../exchange_contract/out/debug/exchange_contract.bin",
+        &wallet,
+        TxParameters::default(),
+        StorageConfiguration::with_manual_storage(Some(vec![
+            StorageSlot::new(token0_slot, token_id_1),
+            StorageSlot::new(token1_slot, token_id_3),
+        ])),
+        Salt::from([1u8; 32]),
+    )
+    .await
+    .unwrap();
+
+    let stable_exchange_contract_id = Contract::deploy_with_parameters(
+        "../stable_exchange_contract/out/debug/stable_exchange_contract.bin",
+        &wallet,
+        TxParameters::default(),
+        StorageConfiguration::with_manual_storage(Some(vec![
+            StorageSlot::new(token0_slot, token_id_1),
+            StorageSlot::new(token1_slot, token_id_2),
+        ])),
+        Salt::from([1u8; 32]),
+    )
+    .await
+    .unwrap();
+
+    // The stable exchange implements the same ABI
+    initialize_exchange(&wallet, &exchange_contract_id, &vault_contract_id).await;
+    initialize_exchange(&wallet, &stable_exchange_contract_id, &vault_contract_id).await;
+
+    registry_instance
+        .methods()
+        .initialize(
+            Bits256(exchange_contract_id.hash().into()),
+            Bits256(vault_contract_id.hash().into()),
+            Identity::Address(wallet.address().into()),
+        )
+        .call()
+        .await
+        .unwrap();
+
+    let result = registry_instance.methods().stable_contract_root().call().await.unwrap();
+    assert_eq!(result.value, ZERO_B256);
+
+    let is_err = registry_instance
+        .methods()
+        .add_exchange_contract(Bits256(stable_exchange_contract_id.hash().into()))
+        .set_contracts(&[stable_exchange_contract_id.clone()])
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Stable pools shouldn't register before the template is set");
+
+    let is_err = attacker_registry_instance
+        .methods()
+        .set_stable_template(Bits256(stable_exchange_contract_id.hash().into()))
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Only the owner should set the stable template");
+
+    let response = registry_instance
+        .methods()
+        .set_stable_template(Bits256(stable_exchange_contract_id.hash().into()))
+        .call()
+        .await
+        .unwrap();
+
+    let stable_root = registry_instance.methods().stable_contract_root().call().await.unwrap().value;
+    assert_ne!(stable_root, ZERO_B256);
+
+    let logs = get_logs::<StableTemplateSet, _>(&response, &registry_contract_id, REGISTRY_ABI);
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs.get(0).unwrap().template_root, stable_root);
+
+    let is_err = registry_instance
+        .methods()
+        .get_curve_type(Bits256(stable_exchange_contract_id.hash().into()))
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Unregistered pools have no curve");
+
+    let response = registry_instance
+        .methods()
+        .add_exchange_contract(Bits256(stable_exchange_contract_id.hash().into()))
+        .set_contracts(&[stable_exchange_contract_id.clone()])
+        .call()
+        .await
+        .unwrap();
+
+    let logs = get_logs::<PoolRegistered, _>(&response, &registry_contract_id, REGISTRY_ABI);
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs.get(0).unwrap().template_root, stable_root);
+    assert_eq!(logs.get(0).unwrap().curve, CurveType::Stable());
+
+    registry_instance
+        .methods()
+        .add_exchange_contract(Bits256(exchange_contract_id.hash().into()))
+        .set_contracts(&[exchange_contract_id.clone()])
+        .call()
+        .await
+        .unwrap();
+
+    let result = registry_instance
+        .methods()
+        .get_curve_type(Bits256(stable_exchange_contract_id.hash().into()))
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(result.value, CurveType::Stable());
+
+    let is_err = registry_instance
+        .methods()
+        .get_pool_weights(Bits256(stable_exchange_contract_id.hash().into()))
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Only weighted pools have weights");
+
+    let result = registry_instance
+        .methods()
+        .get_curve_type(Bits256(exchange_contract_id.hash().into()))
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(result.value, CurveType::ConstantProduct());
+
+    let result = registry_instance
+        .methods()
+        .get_exchange_contract(Bits256(token_id_2.into()), Bits256(token_id_1.into()))
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(result.value, Some(Bits256(stable_exchange_contract_id.hash().into())));
+}
+
+#[tokio::test]
+async fn register_weighted_exchange() {
+    let wallets = get_wallets().await;
+    let wallet = wallets.get(0).unwrap().clone();
+    let attacker_wallet = wallets.get(1).unwrap().clone();
+
+    let registry_contract_id = Contract::deploy(
+        "out/debug/registry_contract.bin",
+        &wallet,
+        TxParameters::default(),
+        deployer_storage(&wallet),
+    )
+    .await
+    .unwrap();
+
+    let registry_instance = RegistryBuilder::new(registry_contract_id.clone(), wallet.clone());
+    let attacker_registry_instance = RegistryBuilder::new(registry_contract_id.clone(), attacker_wallet.clone());
+
+    let vault_contract_id = deploy_vault(&wallet).await;
+
+    let token0_slot = Bytes32::from_str("0x0000000000000000000000000000000000000000000000000000000000000000").unwrap();
+    let token1_slot = Bytes32::from_str("0x0000000000000000000000000000000000000000000000000000000000000001").unwrap();
+    let weight_slot = Bytes32::from_str("0x0000000000000000000000000000000000000000000000000000000000000002").unwrap();
+    // 80% token 0, stored as the slot's first word
+    let weight = Bytes32::from_str("0x0000000000001f40000000000000000000000000000000000000000000000000").unwrap();
+
+    let token_id_1 = Bytes32::from_str("0x000005877b940cc69d7a9a71000a0cfdd79e93f783f198de893165278712a480").unwrap();
+    let token_id_2 = Bytes32::from_str("0x716c345b96f3c17234c73881c40df43d3d492b902a01a062c12e92eeae0284e9").unwrap();
+
+    let exchange_contract_id = Contract::deploy(
+        "../exchange_contract/out/debug/exchange_contract.bin",
+        &wallet,
+        TxParameters::default(),
+        StorageConfiguration::with_manual_storage(Some(vec![
+            StorageSlot::new(token0_slot, token_id_1),
+            StorageSlot::new(token1_slot, token_id_2),
+        ])),
+    )
+    .await
+    .unwrap();
+
+    let weighted_exchange_contract_id = Contract::deploy(
+        "../weighted_exchange_contract/out/debug/weighted_exchange_contract.bin",
+        &wallet,
+        TxParameters::default(),
+        StorageConfiguration::with_manual_storage(Some(vec![
+            StorageSlot::new(token0_slot, token_id_1),
+            StorageSlot::new(token1_slot, token_id_2),
+            StorageSlot::new(weight_slot, weight),
+        ])),
+    )
+    .await
+    .unwrap();
+
+    // The weighted exchange implements the same ABI
+    initialize_exchange(&wallet, &weighted_exchange_contract_id, &vault_contract_id).await;
+
+    registry_instance
+        .methods()
+        .initialize(
+            Bits256(exchange_contract_id.hash().into()),
+            Bits256(vault_contract_id.hash().into()),
+            Identity::Address(wallet.address().into()),
+        )
+        .call()
+        .await
+        .unwrap();
+
+    let is_err = registry_instance
+        .methods()
+        .add_exchange_contract(Bits256(weighted_exchange_contract_id.hash().into()))
+        .set_contracts(&[weighted_exchange_contract_id.clone()])
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Weighted pools shouldn't register before the template is set");
+
+    let is_err = attacker_registry_instance
+        .methods()
+        .set_weighted_template(Bits256(weighted_exchange_contract_id.hash().into()))
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Only the owner should set the weighted template");
+
+    let response = registry_instance
+        .methods()
+        .set_weighted_template(Bits256(weighted_exchange_contract_id.hash().into()))
+        .call()
+        .await
+        .unwrap();
+
+    let weighted_root = registry_instance.methods().weighted_contract_root().call().await.unwrap().value;
+    assert_ne!(weighted_root, ZERO_B256);
+
+    let logs = get_logs::<WeightedTemplateSet, _>(&response, &registry_contract_id, REGISTRY_ABI);
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs.get(0).unwrap().template_root, weighted_root);
+
+    let response = registry_instance
+        .methods()
+        .add_exchange_contract(Bits256(weighted_exchange_contract_id.hash().into()))
+        .set_contracts(&[weighted_exchange_contract_id.clone()])
+        .call()
+        .await
+        .unwrap();
+
+    let logs = get_logs::<PoolRegistered, _>(&response, &registry_contract_id, REGISTRY_ABI);
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs.get(0).unwrap().template_root, weighted_root);
+    assert_eq!(logs.get(0).unwrap().curve, CurveType::Weighted());
+
+    let result = registry_instance
+        .methods()
+        .get_curve_type(Bits256(weighted_exchange_contract_id.hash().into()))
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(result.value, CurveType::Weighted());
+
+    let result = registry_instance
+        .methods()
+        .get_pool_weights(Bits256(weighted_exchange_contract_id.hash().into()))
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(result.value, (8000, 2000));
+}
@@ -0,0 +1,60 @@
+library router_abi;
+
+use std::{
+    identity::Identity,
+    vec::Vec,
+};
+
+pub struct LiquidityOutput {
+    amount_0: u64,
+    amount_1: u64,
+    liquidity: u64,
+}
+
+pub struct SwapOutput {
+    input_amount: u64,
+    output_amount: u64,
+}
+
+/// One swap along a route
+pub struct Hop {
+    pool: b256,
+    /// Token received from the pool, which is the next hop's input
+    asset_out: b256,
+    /// Whether the pool is a multi-asset pool, rather than a pair
+    multi_asset: bool,
+}
+
+abi Router {
+    /// Tokens deposited by an owner and not yet used by `add_liquidity`
+    #[storage(read)]
+    fn get_deposit(owner: Identity, asset_id: b256) -> u64;
+
+    /// Hold the forwarded coins for the sender, to fund `add_liquidity`.
+    /// Call once for each token, in the same transaction as `add_liquidity`.
+    #[storage(read, write)]
+    fn deposit();
+
+    /// Return the sender's deposit of an asset
+    #[storage(read, write)]
+    fn withdraw_deposit(asset_id: b256) -> u64;
+
+    /// Add liquidity from the sender's deposits, returning what the pool doesn't use to the sender
+    #[storage(read, write)]
+    fn add_liquidity(pool: b256, amount_0_desired: u64, amount_1_desired: u64, amount_0_min: u64, amount_1_min: u64, recipient: Identity) -> LiquidityOutput;
+
+    fn remove_liquidity(amount_0_min: u64, amount_1_min: u64, recipient: Identity) -> LiquidityOutput;
+
+    fn swap_exact_input(pool: b256, min_amount_out: u64, recipient: Identity) -> SwapOutput;
+
+    fn swap_exact_output(pool: b256, amount_out: u64, max_amount_in: u64, recipient: Identity) -> SwapOutput;
+
+    fn swap_exact_input_multihop(pools: Vec<b256>, min_amount_out: u64, recipient: Identity) -> SwapOutput;
+
+    fn swap_exact_output_multihop(pools: Vec<b256>, amount_out: u64, max_amount_in: u64, recipient: Identity) -> SwapOutput;
+
+    /// Like `swap_exact_input_multihop`, but each hop names its output token, so multi-asset pools can be used for any of their pairs
+    fn swap_exact_input_route(hops: Vec<Hop>, min_amount_out: u64, recipient: Identity) -> SwapOutput;
+
+    fn swap_exact_output_route(hops: Vec<Hop>, amount_out: u64, max_amount_in: u64, recipient: Identity) -> SwapOutput;
+}
@@ -8,10 +8,7 @@ use std::{
     },
     b512::B512,
     call_frames::msg_asset_id,
-    context::{
-        msg_amount,
-        this_balance,
-    },
+    context::msg_amount,
     contract_id::ContractId,
     identity::Identity,
     option::Option,
@@ -24,12 +21,13 @@ use std::{
         force_transfer_to_contract,
         transfer,
     },
-    u128::U128,
     vec::*,
 };
 use exchange_abi::Exchange;
+use multi_asset_exchange_abi::MultiAssetExchange;
+use router_abi::{Hop, LiquidityOutput, Router, SwapOutput};
 
-use microchain_helpers::{get_input_price, get_output_price, quote};
+use microchain_helpers::{identity_to_b256, quote};
 
 enum Error {
     InsufficentOutput: (),
@@ -38,37 +36,113 @@ enum Error {
     InsufficentToken1: (),
     InvalidToken: (),
     InvalidInput: (),
+    InsufficentDeposit: (),
 }
 
-struct LiquidityOutput {
-    amount_0: u64,
-    amount_1: u64,
-    liquidity: u64,
+storage {
+    // Tokens deposited by each sender, by asset. `add_liquidity` only spends the sender's own deposits.
+    deposits: StorageMap<(b256, b256), u64> = StorageMap {},
 }
 
-struct SwapOutput {
-    input_amount: u64,
-    output_amount: u64,
+////////////////////////////////////////
+// Route helpers
+////////////////////////////////////////
+/// Check a pair pool swaps `asset_in` for the hop's output, returning whether `asset_in` is token 0
+fn check_pair_hop(hop: Hop, asset_in: b256) -> bool {
+    let (token0, token1) = abi(Exchange, hop.pool).get_tokens();
+    require((token0 == asset_in && token1 == hop.asset_out) || (token1 == asset_in && token0 == hop.asset_out), Error::InvalidToken);
+    token0 == asset_in
 }
 
-abi Router {
-    fn add_liquidity(pool: b256, amount_0_desired: u64, amount_1_desired: u64, amount_0_min: u64, amount_1_min: u64, recipient: Identity) -> LiquidityOutput;
+fn get_hop_amount_out(hop: Hop, asset_in: b256, amount_in: u64) -> u64 {
+    if hop.multi_asset {
+        abi(MultiAssetExchange, hop.pool).get_amount_out(asset_in, hop.asset_out, amount_in)
+    } else {
+        let token_0_in = check_pair_hop(hop, asset_in);
+        abi(Exchange, hop.pool).get_amount_out(amount_in, token_0_in)
+    }
+}
 
-    fn remove_liquidity(amount_0_min: u64, amount_1_min: u64, recipient: Identity) -> LiquidityOutput;
+fn get_hop_amount_in(hop: Hop, asset_in: b256, amount_out: u64) -> u64 {
+    if hop.multi_asset {
+        abi(MultiAssetExchange, hop.pool).get_amount_in(asset_in, hop.asset_out, amount_out)
+    } else {
+        let token_0_in = check_pair_hop(hop, asset_in);
+        abi(Exchange, hop.pool).get_amount_in(amount_out, !token_0_in)
+    }
+}
 
-    fn swap_exact_input(pool: b256, min_amount_out: u64, recipient: Identity) -> SwapOutput;
+/// Swap along one hop. Only the first hop forwards coins, later hops were paid by the previous hop.
+fn swap_hop(
+    hop: Hop,
+    asset_in: b256,
+    amount_in: u64,
+    amount_out: u64,
+    recipient: Identity,
+    forward: bool,
+) {
+    if hop.multi_asset {
+        let exchange = abi(MultiAssetExchange, hop.pool);
+        if forward {
+            exchange.swap {
+                asset_id: asset_in,
+                coins: amount_in,
+            }(asset_in, hop.asset_out, amount_out, recipient);
+        } else {
+            exchange.swap(asset_in, hop.asset_out, amount_out, recipient);
+        }
+    } else {
+        let (out0, out1) = if check_pair_hop(hop, asset_in) {
+            (0, amount_out)
+        } else {
+            (amount_out, 0)
+        };
+        let exchange = abi(Exchange, hop.pool);
+        if forward {
+            exchange.swap {
+                asset_id: asset_in,
+                coins: amount_in,
+            }(out0, out1, recipient);
+        } else {
+            exchange.swap(out0, out1, recipient);
+        }
+    }
+}
 
-    fn swap_exact_output(pool: b256, amount_out: u64, max_amount_in: u64, recipient: Identity) -> SwapOutput;
+fn get_hop_recipient(hops: Vec<Hop>, i: u64, recipient: Identity) -> Identity {
+    if i == hops.len() - 1 {
+        recipient
+    } else {
+        Identity::ContractId(ContractId::from(hops.get(i + 1).unwrap().pool))
+    }
+}
 
-    fn swap_exact_input_multihop(pools: Vec<b256>, min_amount_out: u64, recipient: Identity) -> SwapOutput;
+impl Router for Contract {
+    #[storage(read)]
+    fn get_deposit(owner: Identity, asset_id: b256) -> u64 {
+        storage.deposits.get((identity_to_b256(owner), asset_id))
+    }
 
-    fn swap_exact_output_multihop(pools: Vec<b256>, amount_out: u64, max_amount_in: u64, recipient: Identity) -> SwapOutput;
+    #[storage(read, write)]
+    fn deposit() {
+        let sender = identity_to_b256(msg_sender().unwrap());
+        let asset_id: b256 = msg_asset_id().into();
+        storage.deposits.insert((sender, asset_id), storage.deposits.get((sender, asset_id)) + msg_amount());
+    }
 
-    // Used to allow easy token deposits, should be removed before prod
-    fn null();
-}
+    #[storage(read, write)]
+    fn withdraw_deposit(asset_id: b256) -> u64 {
+        let sender_identity = msg_sender().unwrap();
+        let sender = identity_to_b256(sender_identity);
+        let amount = storage.deposits.get((sender, asset_id));
+        storage.deposits.insert((sender, asset_id), 0);
+        if amount > 0 {
+            transfer(amount, ContractId::from(asset_id), sender_identity);
+        }
+        amount
+    }
 
-impl Router for Contract {
+    #[storage(read, write)]
     fn add_liquidity(
         pool: b256,
         amount_0_desired: u64,
@@ -81,6 +155,7 @@ impl Router for Contract {
         let (token0, token1) = exchange.get_tokens();
         let pool_info = exchange.get_pool_info();
         let sender_identity = msg_sender().unwrap(); // Only used for returning "change"
+        let sender = identity_to_b256(sender_identity);
         let mut amount_0 = 0;
         let mut amount_1 = 0;
         if (pool_info.token_0_reserve == 0
@@ -103,19 +178,23 @@ impl Router for Contract {
             }
         }
 
+        // Only the sender's own deposits can be used, the router may hold other senders' tokens
+        let deposit_0 = storage.deposits.get((sender, token0));
+        let deposit_1 = storage.deposits.get((sender, token1));
+        require(deposit_0 >= amount_0 && deposit_1 >= amount_1, Error::InsufficentDeposit);
+        storage.deposits.insert((sender, token0), 0);
+        storage.deposits.insert((sender, token1), 0);
+
         force_transfer_to_contract(amount_0, ContractId::from(token0), ContractId::from(pool));
         force_transfer_to_contract(amount_1, ContractId::from(token1), ContractId::from(pool));
 
         let liquidity = exchange.add_liquidity(recipient);
 
-        let current_token_0_amount = this_balance(ContractId::from(token0));
-        let current_token_1_amount = this_balance(ContractId::from(token1));
-
-        if (current_token_0_amount > 0) {
-            transfer(current_token_0_amount, ContractId::from(token0), sender_identity);
+        if (deposit_0 > amount_0) {
+            transfer(deposit_0 - amount_0, ContractId::from(token0), sender_identity);
         }
-        if (current_token_1_amount > 0) {
-            transfer(current_token_1_amount, ContractId::from(token1), sender_identity);
+        if (deposit_1 > amount_1) {
+            transfer(deposit_1 - amount_1, ContractId::from(token1), sender_identity);
         }
 
         LiquidityOutput {
@@ -149,30 +228,15 @@ impl Router for Contract {
         let input_asset: b256 = msg_asset_id().into();
 
         let (token0, token1) = exchange.get_tokens();
-        let pool_info = exchange.get_pool_info();
 
         require(token0 == input_asset || token1 == input_asset, Error::InvalidToken);
 
-        let fee_info = exchange.get_fee_info();
-        let mut input = msg_amount();
-        if (fee_info.current_fee > 0) {
-            let fee = (U128::from((0, input)) * U128::from((0, fee_info.current_fee)) / U128::from((0, 1_000_000))).as_u64().unwrap();
-            input = input - fee;
-        }
-
+        let output_amount = exchange.get_amount_out(msg_amount(), token0 == input_asset);
         let (out0, out1) = if token0 == input_asset {
-            (
-                0,
-                get_input_price(input, pool_info.token_0_reserve, pool_info.token_1_reserve),
-            )
+            (0, output_amount)
         } else {
-            (
-                get_input_price(input, pool_info.token_1_reserve, pool_info.token_0_reserve),
-                0,
-            )
+            (output_amount, 0)
         };
-
-        let output_amount = if token0 == input_asset { out1 } else { out0 };
         require(output_amount >= min_amount_out, Error::InsufficentOutput);
 
         exchange.swap {
@@ -196,32 +260,15 @@ impl Router for Contract {
         let input_asset: b256 = msg_asset_id().into();
 
         let (token0, token1) = exchange.get_tokens();
-        let pool_info = exchange.get_pool_info();
 
         require(token0 == input_asset || token1 == input_asset, Error::InvalidToken);
 
-        let (input_amount, out0, out1) = if token0 == input_asset {
-            (
-                get_output_price(amount_out, pool_info.token_0_reserve, pool_info.token_1_reserve),
-                0,
-                amount_out,
-            )
+        let (out0, out1) = if token0 == input_asset {
+            (0, amount_out)
         } else {
-            (
-                get_output_price(amount_out, pool_info.token_1_reserve, pool_info.token_0_reserve),
-                amount_out,
-                0,
-            )
+            (amount_out, 0)
         };
-
-        let fee_info = exchange.get_fee_info();
-        let mut input_amount_with_fee = input_amount;
-        if (fee_info.current_fee > 0) {
-            let percision = U128::from((0, 1_000_000));
-            let numerator = U128::from((0, input_amount)) * percision;
-            let denominator = percision - U128::from((0, fee_info.current_fee));
-            input_amount_with_fee = (numerator / denominator).as_u64().unwrap();
-        }
+        let input_amount_with_fee = exchange.get_amount_in(amount_out, token0 != input_asset);
 
         require(input_amount_with_fee <= max_amount_in, Error::ExcessiveInput);
 
@@ -257,27 +304,14 @@ impl Router for Contract {
             let pool_id = pools.get(i).unwrap();
             let exchange = abi(Exchange, pool_id);
             let (token0, token1) = exchange.get_tokens();
-            let pool_info = exchange.get_pool_info();
 
             require(token0 == input_asset || token1 == input_asset, Error::InvalidToken);
 
-            let fee_info = exchange.get_fee_info();
-            let mut input = output_amount;
-            if (fee_info.current_fee > 0) {
-                let fee = (U128::from((0, input)) * U128::from((0, fee_info.current_fee)) / U128::from((0, 1_000_000))).as_u64().unwrap();
-                input = input - fee;
-            }
-
+            let amount_out = exchange.get_amount_out(output_amount, token0 == input_asset);
             let (out0, out1) = if token0 == input_asset {
-                (
-                    0,
-                    get_input_price(input, pool_info.token_0_reserve, pool_info.token_1_reserve),
-                )
+                (0, amount_out)
             } else {
-                (
-                    get_input_price(input, pool_info.token_1_reserve, pool_info.token_0_reserve),
-                    0,
-                )
+                (amount_out, 0)
             };
 
             let swap_recipient = if i == pools.len() - 1 {
@@ -361,23 +395,9 @@ impl Router for Contract {
             let input_asset = input_assets.get(j).unwrap();
             let exchange = abi(Exchange, pool_id);
             let (token0, token1) = exchange.get_tokens();
-            let pool_info = exchange.get_pool_info();
             let pool_output_amount = output_amounts.get(j).unwrap();
 
-            let input_amount = if token0 == input_asset {
-                get_output_price(pool_output_amount, pool_info.token_0_reserve, pool_info.token_1_reserve)
-            } else {
-                get_output_price(pool_output_amount, pool_info.token_1_reserve, pool_info.token_0_reserve)
-            };
-
-            let fee_info = exchange.get_fee_info();
-            let mut input_amount_with_fee = input_amount;
-            if (fee_info.current_fee > 0) {
-                let percision = U128::from((0, 1_000_000));
-                let numerator = U128::from((0, input_amount)) * percision;
-                let denominator = percision - U128::from((0, fee_info.current_fee));
-                input_amount_with_fee = (numerator / denominator).as_u64().unwrap();
-            }
+            let input_amount_with_fee = exchange.get_amount_in(pool_output_amount, token0 != input_asset);
 
             input_amounts.set(j, input_amount_with_fee);
             if (j > 0) {
@@ -436,5 +456,81 @@ impl Router for Contract {
         }
     }
 
-    fn null() {}
+    fn swap_exact_input_route(hops: Vec<Hop>, min_amount_out: u64, recipient: Identity) -> SwapOutput {
+        require(hops.len() > 0, Error::InvalidInput);
+
+        let mut input_asset: b256 = msg_asset_id().into();
+        let mut amount = msg_amount();
+
+        let mut i = 0;
+        while i < hops.len() {
+            let hop = hops.get(i).unwrap();
+            let amount_out = get_hop_amount_out(hop, input_asset, amount);
+            swap_hop(hop, input_asset, amount, amount_out, get_hop_recipient(hops, i, recipient), i == 0);
+
+            amount = amount_out;
+            input_asset = hop.asset_out;
+            i += 1;
+        }
+        require(amount >= min_amount_out, Error::InsufficentOutput);
+
+        SwapOutput {
+            input_amount: msg_amount(),
+            output_amount: amount,
+        }
+    }
+
+    fn swap_exact_output_route(
+        hops: Vec<Hop>,
+        amount_out: u64,
+        max_amount_in: u64,
+        recipient: Identity,
+    ) -> SwapOutput {
+        require(hops.len() > 0, Error::InvalidInput);
+
+        let mut input_assets: Vec<b256> = Vec::with_capacity(hops.len());
+        input_assets.push(msg_asset_id().into());
+        let mut input_amounts: Vec<u64> = Vec::with_capacity(hops.len());
+
+        let mut i = 0;
+        while i < hops.len() {
+            input_assets.push(hops.get(i).unwrap().asset_out);
+            input_amounts.push(0);
+            i += 1;
+        }
+
+        // Work back from the output, each hop's input is the previous hop's output
+        let mut output_amount = amount_out;
+        i = hops.len();
+        while i > 0 {
+            let j = i - 1;
+            let input_amount = get_hop_amount_in(hops.get(j).unwrap(), input_assets.get(j).unwrap(), output_amount);
+            input_amounts.set(j, input_amount);
+            output_amount = input_amount;
+            i -= 1;
+        }
+        let input_amount = input_amounts.get(0).unwrap();
+        require(input_amount <= max_amount_in, Error::ExcessiveInput);
+
+        i = 0;
+        while i < hops.len() {
+            let hop_output = if i == hops.len() - 1 {
+                amount_out
+            } else {
+                input_amounts.get(i + 1).unwrap()
+            };
+            swap_hop(hops.get(i).unwrap(), input_assets.get(i).unwrap(), input_amounts.get(i).unwrap(), hop_output, get_hop_recipient(hops, i, recipient), i == 0);
+            i += 1;
+        }
+
+        if (msg_amount() > input_amount) {
+            let sender_identity = msg_sender().unwrap();
+            transfer(msg_amount() - input_amount, msg_asset_id(), sender_identity);
+        }
+
+        SwapOutput {
+            input_amount: input_amount,
+            output_amount: amount_out,
+        }
+    }
 }
@@ -1,11 +1,12 @@
 use std::{vec, str::FromStr};
+use chrono::Utc;
 use fuels::{
     prelude::*,
     fuels_abigen::abigen,
     signers::WalletUnlocked,
     tx::{AssetId, Bytes32, StorageSlot},
 };
-use test_helpers::{get_wallets};
+use test_helpers::{deployer_storage, get_wallets, run_fee_change, MIN_FEE_CHANGE_DELAY};
 
 ///////////////////////////////
 // Load the Router Script ABI abi
@@ -14,6 +15,10 @@ abigen!(Router, "./out/debug/router_contract-abi.json");
 abigen!(Exchange, "../exchange_contract/out/debug/exchange_contract-abi.json");
 abigen!(TestToken, "../token_contract/out/debug/token_contract-abi.json");
 abigen!(Vault, "../vault_contract/out/debug/vault_contract-abi.json");
+abigen!(
+    MultiAssetExchange,
+    "../multi_asset_exchange_contract/out/debug/multi_asset_exchange_contract-abi.json"
+);
 
 
 fn to_9_decimal(num: u64) -> u64 {
@@ -24,6 +29,7 @@ const MINIMUM_LIQUIDITY: u64 = 1000;
 
 struct Fixture {
     wallet: WalletUnlocked,
+    other_wallet: WalletUnlocked,
 
     token_a_contract_id: Bech32ContractId,
     token_a_asset_id: AssetId,
@@ -49,6 +55,7 @@ struct Fixture {
 async fn setup() -> Fixture {
     let wallets = get_wallets().await;
     let wallet = wallets.get(0).unwrap().clone();
+    let other_wallet = wallets.get(1).unwrap().clone();
 
     //////////////////////////////////////////
     // Setup contracts
@@ -121,7 +128,7 @@ async fn setup() -> Fixture {
         "../vault_contract/out/debug/vault_contract.bin",
         &wallet,
         TxParameters::default(),
-        StorageConfiguration::new(None, None),
+        deployer_storage(&wallet),
     )
     .await
     .unwrap();
@@ -169,6 +176,13 @@ async fn setup() -> Fixture {
         .await
         .unwrap();
 
+    vault_instance
+        .methods()
+        .initialize(Identity::Address(wallet.address().into()), MIN_FEE_CHANGE_DELAY)
+        .call()
+        .await
+        .unwrap();
+
     exchange_a_instance
         .methods()
         .initialize(Bits256(vault_contract_id.hash().into()))
@@ -187,6 +201,7 @@ async fn setup() -> Fixture {
 
     Fixture {
         wallet: wallet,
+        other_wallet: other_wallet,
 
         token_a_contract_id: token_a_contract_id.clone(),
         token_a_asset_id: AssetId::new(*token_a_contract_id.hash()),
@@ -211,6 +226,18 @@ async fn setup() -> Fixture {
 }
 
 
+/// Change the vault's global fees through the timelock, returning the unix time it was executed at
+async fn set_vault_fees(fixture: &Fixture, start_fee: u16, change_rate: u16, now: i64) -> i64 {
+    let methods = fixture.vault_instance.methods();
+    run_fee_change(
+        &fixture.wallet,
+        now,
+        |eta| methods.queue_fee_change(start_fee, change_rate, eta),
+        methods.execute_fee_change(),
+    )
+    .await
+}
+
 async fn add_pool_a_liquidity(fixture: &Fixture, token_0_amount: u64, token_1_amount: u64) {
     let _receipts = fixture.wallet
         .force_transfer_to_contract(
@@ -290,6 +317,17 @@ async fn add_pool_b_liquidity(fixture: &Fixture, token_0_amount: u64, token_1_am
         .unwrap();
 }
 
+/// Deposit tokens on the router for the wallet behind `router_instance`
+async fn deposit(router_instance: &Router, amount: u64, asset_id: AssetId) {
+    router_instance
+        .methods()
+        .deposit()
+        .call_params(CallParameters::new(Some(amount), Some(asset_id), None))
+        .call()
+        .await
+        .unwrap();
+}
+
 #[tokio::test]
 async fn add_liquidity() {
     let fixture = setup().await;
@@ -300,23 +338,9 @@ async fn add_liquidity() {
     let token_1_amount = to_9_decimal(4);
     let expected_liquidity = to_9_decimal(2);
 
-    let _receipts = fixture.wallet
-        .force_transfer_to_contract(
-            &fixture.router_contract_id,
-            token_0_amount + 1, // Add 1 so that there's change to return
-            BASE_ASSET_ID,
-            TxParameters::default()
-        )
-        .await;
-
-    let _receipts = fixture.wallet
-        .force_transfer_to_contract(
-            &fixture.router_contract_id,
-            token_1_amount + 1, // Add 1 so that there's change to return
-            fixture.token_a_asset_id.clone(),
-            TxParameters::default()
-        )
-        .await;
+    // Add 1 so that there's change to return
+    deposit(&fixture.router_instance, token_0_amount + 1, BASE_ASSET_ID).await;
+    deposit(&fixture.router_instance, token_1_amount + 1, fixture.token_a_asset_id).await;
 
     let result = fixture.router_instance
         .methods()
@@ -353,23 +377,9 @@ async fn add_liquidity() {
 
     // Add additional liquidity
 
-    let _receipts = fixture.wallet
-        .force_transfer_to_contract(
-            &fixture.router_contract_id,
-            token_0_amount + 1, // Add 1 so that there's change to return
-            BASE_ASSET_ID,
-            TxParameters::default()
-        )
-        .await;
-
-    let _receipts = fixture.wallet
-        .force_transfer_to_contract(
-            &fixture.router_contract_id,
-            token_1_amount + 1, // Add 1 so that there's change to return
-            fixture.token_a_asset_id.clone(),
-            TxParameters::default()
-        )
-        .await;
+    // Add 1 so that there's change to return
+    deposit(&fixture.router_instance, token_0_amount + 1, BASE_ASSET_ID).await;
+    deposit(&fixture.router_instance, token_1_amount + 1, fixture.token_a_asset_id).await;
 
     let is_err = fixture.router_instance
         .methods()
@@ -435,6 +445,140 @@ async fn add_liquidity() {
     assert_eq!(lp_tokens, expected_liquidity * 2 - MINIMUM_LIQUIDITY);
 }
 
+/// Add liquidity to pool A through the router, from the deposits of the wallet behind `router_instance`
+async fn router_add_liquidity(fixture: &Fixture, router_instance: &Router, token_0_amount: u64, token_1_amount: u64, recipient: &WalletUnlocked) -> bool {
+    router_instance
+        .methods()
+        .add_liquidity(
+            Bits256(fixture.exchange_a_contract_id.hash().into()),
+            token_0_amount,
+            token_1_amount,
+            0,
+            0,
+            Identity::Address(recipient.address().into()),
+        )
+        .tx_params(TxParameters {
+            gas_price: 0,
+            gas_limit: 100_000_000,
+            maturity: 0,
+        })
+        .call_params(CallParameters::new(None, None, Some(100_000_000)))
+        .set_contracts(&[fixture.exchange_a_contract_id.clone()])
+        .append_variable_outputs(3)
+        .call()
+        .await
+        .is_ok()
+}
+
+#[tokio::test]
+async fn add_liquidity_only_uses_senders_deposits() {
+    let fixture = setup().await;
+    let attacker_router = Router::new(fixture.router_contract_id.clone(), fixture.other_wallet.clone());
+    let token_0_amount = to_9_decimal(1);
+    let token_1_amount = to_9_decimal(4);
+
+    fixture.wallet
+        .transfer(fixture.other_wallet.address(), to_9_decimal(2), fixture.token_a_asset_id, TxParameters::default())
+        .await
+        .unwrap();
+
+    // The victim's deposits sit on the router until their add_liquidity call
+    deposit(&fixture.router_instance, token_0_amount, BASE_ASSET_ID).await;
+    deposit(&fixture.router_instance, token_1_amount, fixture.token_a_asset_id).await;
+
+    let added = router_add_liquidity(&fixture, &attacker_router, token_0_amount, token_1_amount, &fixture.other_wallet).await;
+    assert!(!added, "Other senders can't add liquidity from the victim's deposits");
+
+    // Tokens sent to the router without depositing can't be used either
+    let _receipts = fixture.other_wallet
+        .force_transfer_to_contract(&fixture.router_contract_id, to_9_decimal(1) / 4, fixture.token_a_asset_id, TxParameters::default())
+        .await;
+    deposit(&attacker_router, to_9_decimal(1) / 4, BASE_ASSET_ID).await;
+    let added = router_add_liquidity(&fixture, &attacker_router, to_9_decimal(1) / 4, to_9_decimal(1), &fixture.other_wallet).await;
+    assert!(!added, "Liquidity is limited to the sender's deposits");
+
+    let withdrawn = attacker_router
+        .methods()
+        .withdraw_deposit(Bits256(*fixture.token_a_asset_id))
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .unwrap()
+        .value;
+    assert_eq!(withdrawn, 0, "Other senders can't withdraw the victim's deposits");
+
+    // Adding from the attacker's own deposits leaves the victim's alone
+    deposit(&attacker_router, to_9_decimal(1), fixture.token_a_asset_id).await;
+    let added = router_add_liquidity(&fixture, &attacker_router, to_9_decimal(1) / 4, to_9_decimal(1), &fixture.other_wallet).await;
+    assert!(added);
+
+    let victim = Identity::Address(fixture.wallet.address().into());
+    let victim_deposit_0 = fixture.router_instance.methods().get_deposit(victim.clone(), Bits256(*BASE_ASSET_ID)).call().await.unwrap().value;
+    let victim_deposit_1 = fixture.router_instance.methods().get_deposit(victim.clone(), Bits256(*fixture.token_a_asset_id)).call().await.unwrap().value;
+    assert_eq!(victim_deposit_0, token_0_amount);
+    assert_eq!(victim_deposit_1, token_1_amount);
+
+    // The victim's own call still gets the liquidity they deposited for, at the pool's 1:4 ratio
+    let starting_token_balance = fixture.wallet.get_asset_balance(&fixture.token_a_asset_id).await.unwrap();
+    let added = router_add_liquidity(&fixture, &fixture.router_instance, token_0_amount, token_1_amount, &fixture.wallet).await;
+    assert!(added);
+    let end_token_balance = fixture.wallet.get_asset_balance(&fixture.token_a_asset_id).await.unwrap();
+    assert_eq!(end_token_balance, starting_token_balance);
+
+    let lp_tokens = fixture.wallet.get_asset_balance(&fixture.exchange_a_asset_id).await.unwrap();
+    assert_eq!(lp_tokens, to_9_decimal(2));
+    let deposit_after = fixture.router_instance.methods().get_deposit(victim, Bits256(*BASE_ASSET_ID)).call().await.unwrap().value;
+    assert_eq!(deposit_after, 0);
+}
+
+#[tokio::test]
+async fn add_liquidity_refunds_only_the_senders_change() {
+    let fixture = setup().await;
+    let other_router = Router::new(fixture.router_contract_id.clone(), fixture.other_wallet.clone());
+    add_pool_a_liquidity(&fixture, to_9_decimal(1), to_9_decimal(4)).await;
+
+    fixture.wallet
+        .transfer(fixture.other_wallet.address(), to_9_decimal(8), fixture.token_a_asset_id, TxParameters::default())
+        .await
+        .unwrap();
+
+    // The victim deposits tokens, and the other wallet deposits more tokens than it can add
+    deposit(&fixture.router_instance, to_9_decimal(4), fixture.token_a_asset_id).await;
+    deposit(&other_router, to_9_decimal(1), BASE_ASSET_ID).await;
+    deposit(&other_router, to_9_decimal(8), fixture.token_a_asset_id).await;
+
+    let starting_token_balance = fixture.other_wallet.get_asset_balance(&fixture.token_a_asset_id).await.unwrap();
+    let added = router_add_liquidity(&fixture, &other_router, to_9_decimal(1), to_9_decimal(8), &fixture.other_wallet).await;
+    assert!(added);
+
+    // Only the other wallet's own unused tokens are returned to it
+    let end_token_balance = fixture.other_wallet.get_asset_balance(&fixture.token_a_asset_id).await.unwrap();
+    assert_eq!(end_token_balance - starting_token_balance, to_9_decimal(4));
+
+    let victim_deposit = fixture.router_instance
+        .methods()
+        .get_deposit(Identity::Address(fixture.wallet.address().into()), Bits256(*fixture.token_a_asset_id))
+        .call()
+        .await
+        .unwrap()
+        .value;
+    assert_eq!(victim_deposit, to_9_decimal(4));
+
+    // And the victim can take their deposit back
+    let starting_token_balance = fixture.wallet.get_asset_balance(&fixture.token_a_asset_id).await.unwrap();
+    let withdrawn = fixture.router_instance
+        .methods()
+        .withdraw_deposit(Bits256(*fixture.token_a_asset_id))
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .unwrap()
+        .value;
+    let end_token_balance = fixture.wallet.get_asset_balance(&fixture.token_a_asset_id).await.unwrap();
+    assert_eq!(withdrawn, to_9_decimal(4));
+    assert_eq!(end_token_balance - starting_token_balance, to_9_decimal(4));
+}
+
 #[tokio::test]
 async fn remove_liquidity() {
     let fixture = setup().await;
@@ -854,12 +998,7 @@ async fn swap_exact_output_multi() {
 async fn with_protocol_fees_swap_exact_input_0() {
     let fixture = setup().await;
 
-    fixture.vault_instance
-        .methods()
-        .set_fees(10_000, 0)
-        .call()
-        .await
-        .unwrap();
+    set_vault_fees(&fixture, 10_000, 0, Utc::now().timestamp()).await;
 
     fixture.exchange_a_instance
         .methods()
@@ -874,12 +1013,7 @@ async fn with_protocol_fees_swap_exact_input_0() {
     let swap_amount = to_9_decimal(1);
     let expected_amount = 1648613753;
 
-    fixture.vault_instance
-        .methods()
-        .set_fees(10_000, 0)
-        .call()
-        .await
-        .unwrap();
+    set_vault_fees(&fixture, 10_000, 0, Utc::now().timestamp()).await;
 
     fixture.exchange_a_instance
         .methods()
@@ -931,12 +1065,7 @@ async fn with_protocol_fees_swap_exact_input_0() {
 async fn with_protocol_fees_swap_exact_output_0() {
     let fixture = setup().await;
 
-    fixture.vault_instance
-        .methods()
-        .set_fees(10_000, 0)
-        .call()
-        .await
-        .unwrap();
+    set_vault_fees(&fixture, 10_000, 0, Utc::now().timestamp()).await;
 
     fixture.exchange_a_instance
         .methods()
@@ -1005,12 +1134,7 @@ async fn with_protocol_fees_swap_exact_input_multi() {
     let swap_amount = to_9_decimal(1);
     let expected_amount = 2455371143;
 
-    fixture.vault_instance
-        .methods()
-        .set_fees(10_000, 0)
-        .call()
-        .await
-        .unwrap();
+    set_vault_fees(&fixture, 10_000, 0, Utc::now().timestamp()).await;
 
     fixture.exchange_a_instance
         .methods()
@@ -1087,12 +1211,7 @@ async fn with_protocol_fees_swap_exact_output_multi() {
     let expected_input = 1022363234;
     let output_amount = 2489685056;
 
-    fixture.vault_instance
-        .methods()
-        .set_fees(10_000, 0)
-        .call()
-        .await
-        .unwrap();
+    set_vault_fees(&fixture, 10_000, 0, Utc::now().timestamp()).await;
 
     fixture.exchange_a_instance
         .methods()
@@ -1158,3 +1277,222 @@ async fn with_protocol_fees_swap_exact_output_multi() {
     assert_eq!(end_token_balance - starting_token_balance, output_amount);
     assert_eq!(starting_eth_balance - end_eth_balance, expected_input);
 }
+
+/// Deploy a pool of ETH, token A and token B, with equal reserves of each
+async fn setup_multi_asset_pool(fixture: &Fixture, amount: u64) -> Bech32ContractId {
+    let tokens = vec![BASE_ASSET_ID, fixture.token_a_asset_id, fixture.token_b_asset_id];
+    let mut storage_vec: Vec<StorageSlot> = tokens
+        .iter()
+        .enumerate()
+        .map(|(i, token)| {
+            let mut slot = [0u8; 32];
+            slot[31] = i as u8;
+            StorageSlot::new(Bytes32::new(slot), Bytes32::new(**token))
+        })
+        .collect();
+    let count_slot = Bytes32::from_str("0x0000000000000000000000000000000000000000000000000000000000000008").unwrap();
+    let mut count = [0u8; 32];
+    count[7] = tokens.len() as u8;
+    storage_vec.push(StorageSlot::new(count_slot, Bytes32::new(count)));
+
+    let multi_asset_contract_id = Contract::deploy(
+        "../multi_asset_exchange_contract/out/debug/multi_asset_exchange_contract.bin",
+        &fixture.wallet,
+        TxParameters::default(),
+        StorageConfiguration::with_manual_storage(Some(storage_vec)),
+    )
+    .await
+    .unwrap();
+
+    let multi_asset_instance = MultiAssetExchange::new(multi_asset_contract_id.clone(), fixture.wallet.clone());
+    multi_asset_instance
+        .methods()
+        .initialize()
+        .call()
+        .await
+        .unwrap();
+
+    for token in tokens {
+        let _receipts = fixture.wallet
+            .force_transfer_to_contract(&multi_asset_contract_id, amount, token, TxParameters::default())
+            .await
+            .unwrap();
+    }
+    multi_asset_instance
+        .methods()
+        .add_liquidity(Identity::Address(fixture.wallet.address().into()))
+        .append_variable_outputs(4)
+        .tx_params(TxParameters {
+            gas_price: 0,
+            gas_limit: 100_000_000,
+            maturity: 0,
+        })
+        .call_params(CallParameters::new(None, None, Some(100_000_000)))
+        .call()
+        .await
+        .unwrap();
+
+    multi_asset_contract_id
+}
+
+#[tokio::test]
+async fn swap_exact_input_route_through_multi_asset_pool() {
+    let fixture = setup().await;
+    add_pool_a_liquidity(&fixture, to_9_decimal(5), to_9_decimal(10)).await;
+    let multi_asset_contract_id = setup_multi_asset_pool(&fixture, to_9_decimal(100)).await;
+    let multi_asset_instance = MultiAssetExchange::new(multi_asset_contract_id.clone(), fixture.wallet.clone());
+
+    // ETH -> token A through pool A, then token A -> token B through the multi-asset pool
+    let swap_amount = to_9_decimal(1);
+    let hop_1_amount = fixture.exchange_a_instance.methods().get_amount_out(swap_amount, true).call().await.unwrap().value;
+    let expected_amount = multi_asset_instance
+        .methods()
+        .get_amount_out(Bits256(*fixture.token_a_asset_id), Bits256(*fixture.token_b_asset_id), hop_1_amount)
+        .call()
+        .await
+        .unwrap()
+        .value;
+
+    let hops = vec![
+        Hop {
+            pool: Bits256(fixture.exchange_a_contract_id.hash().into()),
+            asset_out: Bits256(*fixture.token_a_asset_id),
+            multi_asset: false,
+        },
+        Hop {
+            pool: Bits256(multi_asset_contract_id.hash().into()),
+            asset_out: Bits256(*fixture.token_b_asset_id),
+            multi_asset: true,
+        },
+    ];
+
+    // A pair hop must output the pair's other token
+    let is_err = fixture.router_instance
+        .methods()
+        .swap_exact_input_route(
+            vec![Hop {
+                pool: Bits256(fixture.exchange_a_contract_id.hash().into()),
+                asset_out: Bits256(*fixture.token_b_asset_id),
+                multi_asset: false,
+            }],
+            1,
+            Identity::Address(fixture.wallet.address().into()),
+        )
+        .call_params(CallParameters::new(Some(swap_amount), None, Some(100_000_000)))
+        .set_contracts(&[fixture.exchange_a_contract_id.clone()])
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .is_err();
+    assert!(is_err);
+
+    let is_err = fixture.router_instance
+        .methods()
+        .swap_exact_input_route(hops.clone(), expected_amount + 1, Identity::Address(fixture.wallet.address().into()))
+        .tx_params(TxParameters {
+            gas_price: 0,
+            gas_limit: 100_000_000,
+            maturity: 0,
+        })
+        .call_params(CallParameters::new(Some(swap_amount), None, Some(100_000_000)))
+        .set_contracts(&[fixture.exchange_a_contract_id.clone(), multi_asset_contract_id.clone()])
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .is_err();
+    assert!(is_err);
+
+    let starting_token_balance = fixture.wallet.get_asset_balance(&fixture.token_b_asset_id).await.unwrap();
+    let result = fixture.router_instance
+        .methods()
+        .swap_exact_input_route(hops, expected_amount, Identity::Address(fixture.wallet.address().into()))
+        .tx_params(TxParameters {
+            gas_price: 0,
+            gas_limit: 100_000_000,
+            maturity: 0,
+        })
+        .call_params(CallParameters::new(Some(swap_amount), None, Some(100_000_000)))
+        .set_contracts(&[fixture.exchange_a_contract_id.clone(), multi_asset_contract_id.clone()])
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(result.value.input_amount, swap_amount);
+    assert_eq!(result.value.output_amount, expected_amount);
+
+    let end_token_balance = fixture.wallet.get_asset_balance(&fixture.token_b_asset_id).await.unwrap();
+    assert_eq!(end_token_balance - starting_token_balance, expected_amount);
+}
+
+#[tokio::test]
+async fn swap_exact_output_route_through_multi_asset_pool() {
+    let fixture = setup().await;
+    add_pool_b_liquidity(&fixture, to_9_decimal(10), to_9_decimal(10)).await;
+    let multi_asset_contract_id = setup_multi_asset_pool(&fixture, to_9_decimal(100)).await;
+    let multi_asset_instance = MultiAssetExchange::new(multi_asset_contract_id.clone(), fixture.wallet.clone());
+
+    // ETH -> token B through the multi-asset pool, then token B -> token A through pool B
+    let output_amount = to_9_decimal(1);
+    let hop_2_input = fixture.exchange_b_instance.methods().get_amount_in(output_amount, true).call().await.unwrap().value;
+    let expected_input = multi_asset_instance
+        .methods()
+        .get_amount_in(Bits256(*BASE_ASSET_ID), Bits256(*fixture.token_b_asset_id), hop_2_input)
+        .call()
+        .await
+        .unwrap()
+        .value;
+
+    let hops = vec![
+        Hop {
+            pool: Bits256(multi_asset_contract_id.hash().into()),
+            asset_out: Bits256(*fixture.token_b_asset_id),
+            multi_asset: true,
+        },
+        Hop {
+            pool: Bits256(fixture.exchange_b_contract_id.hash().into()),
+            asset_out: Bits256(*fixture.token_a_asset_id),
+            multi_asset: false,
+        },
+    ];
+
+    let is_err = fixture.router_instance
+        .methods()
+        .swap_exact_output_route(hops.clone(), output_amount, expected_input - 1, Identity::Address(fixture.wallet.address().into()))
+        .tx_params(TxParameters {
+            gas_price: 0,
+            gas_limit: 100_000_000,
+            maturity: 0,
+        })
+        .call_params(CallParameters::new(Some(expected_input), None, Some(100_000_000)))
+        .set_contracts(&[multi_asset_contract_id.clone(), fixture.exchange_b_contract_id.clone()])
+        .append_variable_outputs(2)
+        .call()
+        .await
+        .is_err();
+    assert!(is_err);
+
+    let starting_token_balance = fixture.wallet.get_asset_balance(&fixture.token_a_asset_id).await.unwrap();
+    let starting_eth_balance = fixture.wallet.get_asset_balance(&BASE_ASSET_ID).await.unwrap();
+    // Send extra, which is refunded
+    let result = fixture.router_instance
+        .methods()
+        .swap_exact_output_route(hops, output_amount, expected_input, Identity::Address(fixture.wallet.address().into()))
+        .tx_params(TxParameters {
+            gas_price: 0,
+            gas_limit: 100_000_000,
+            maturity: 0,
+        })
+        .call_params(CallParameters::new(Some(expected_input + 1000), None, Some(100_000_000)))
+        .set_contracts(&[multi_asset_contract_id.clone(), fixture.exchange_b_contract_id.clone()])
+        .append_variable_outputs(2)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(result.value.input_amount, expected_input);
+    assert_eq!(result.value.output_amount, output_amount);
+
+    let end_token_balance = fixture.wallet.get_asset_balance(&fixture.token_a_asset_id).await.unwrap();
+    let end_eth_balance = fixture.wallet.get_asset_balance(&BASE_ASSET_ID).await.unwrap();
+    assert_eq!(end_token_balance - starting_token_balance, output_amount);
+    assert_eq!(starting_eth_balance - end_eth_balance, expected_input);
+}
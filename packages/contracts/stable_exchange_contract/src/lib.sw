@@ -0,0 +1,75 @@
+library lib;
+
+use std::u256::U256;
+
+/// Curve math is done with 18 decimals of precision
+const PRECISION = U256::from((0, 0, 0, 1_000_000_000_000_000_000));
+
+/// Scales 9 decimal token amounts up to PRECISION
+const AMOUNT_SCALE = U256::from((0, 0, 0, 1_000_000_000));
+
+pub fn to_precision(amount: u64) -> U256 {
+    U256::from((0, 0, 0, amount)) * AMOUNT_SCALE
+}
+
+/// Convert back to a token amount, rounding down
+pub fn from_precision(value: U256) -> u64 {
+    (value / AMOUNT_SCALE).as_u64().unwrap()
+}
+
+/// The stable invariant, x³y + y³x
+pub fn get_k(x: U256, y: U256) -> U256 {
+    let a = x * y / PRECISION;
+    let b = x * x / PRECISION + y * y / PRECISION;
+    a * b / PRECISION
+}
+
+fn f(x0: U256, y: U256) -> U256 {
+    x0 * (y * y / PRECISION * y / PRECISION) / PRECISION + (x0 * x0 / PRECISION * x0 / PRECISION) * y / PRECISION
+}
+
+/// Derivative of `f` with respect to y
+fn d(x0: U256, y: U256) -> U256 {
+    U256::from((0, 0, 0, 3)) * x0 * (y * y / PRECISION) / PRECISION + (x0 * x0 / PRECISION * x0 / PRECISION)
+}
+
+/// Solve `get_k(x0, y) == k` for y with Newton's method, starting from the current reserve `y`.
+/// The curve is symmetric, so this also solves for x given y.
+pub fn get_y(x0: U256, k: U256, y: U256) -> U256 {
+    let one = U256::from((0, 0, 0, 1));
+    let mut y = y;
+    let mut i = 0;
+    while i < 255 {
+        let y_prev = y;
+        let current_k = f(x0, y);
+        if current_k < k {
+            let dy = (k - current_k) * PRECISION / d(x0, y);
+            y = y + dy;
+        } else {
+            let dy = (current_k - k) * PRECISION / d(x0, y);
+            y = y - dy;
+        }
+
+        if y > y_prev {
+            if y - y_prev < one || y - y_prev == one {
+                return y;
+            }
+        } else {
+            if y_prev - y < one || y_prev - y == one {
+                return y;
+            }
+        }
+        i += 1;
+    }
+    y
+}
+
+/// Marginal price of token 0 in token 1 on the curve, (3x²y + y³) / (x³ + 3xy²), scaled by `precision`
+pub fn get_marginal_price(x: u64, y: u64, precision: U256) -> U256 {
+    let three = U256::from((0, 0, 0, 3));
+    let x = U256::from((0, 0, 0, x));
+    let y = U256::from((0, 0, 0, y));
+    let numerator = three * x * x * y + y * y * y;
+    let denominator = x * x * x + three * x * y * y;
+    numerator * precision / denominator
+}
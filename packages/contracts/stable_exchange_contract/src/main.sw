@@ -0,0 +1,258 @@
+contract;
+
+dep lib;
+
+use std::{
+    auth::msg_sender,
+    identity::Identity,
+    logging::log,
+    u256::U256,
+};
+
+use exchange_abi::{
+    DynamicFeeConfig,
+    Exchange,
+    FeeCacheInfo,
+    FeeInfo,
+    LPFeeInfo,
+    Observation,
+    PoolInfo,
+    RemoveLiquidityInfo,
+    Swap,
+    TWAPInfo,
+    VaultInfo,
+};
+use exchange_lib::{
+    add_lp_fee,
+    add_protocol_fee,
+    burn_liquidity,
+    cache_vault_fees,
+    expand_twap_buffer,
+    get_current_lp_fee,
+    get_fee_cache_info,
+    get_fee_info,
+    get_lp_fee_amount,
+    get_lp_fee_info,
+    get_lp_token_supply,
+    get_next_protocol_fee,
+    get_observation,
+    get_pool_balance,
+    get_protocol_fee_amount,
+    get_reserves,
+    get_tokens,
+    get_twap_info,
+    get_vault_info,
+    initialize,
+    mint_liquidity,
+    set_dynamic_fee,
+    set_max_fee_cache_age,
+    set_reserves,
+    settle_swap,
+    withdraw_protocol_fees,
+    write_observation,
+};
+use microchain_helpers::identity_to_b256;
+use lib::{from_precision, get_k, get_marginal_price, get_y, to_precision};
+
+enum Error {
+    InsufficentOutput: (),
+    InsufficentLiquidity: (),
+    InsufficentInput: (),
+    Invariant: (),
+}
+
+// Exchange for pegged pairs such as USDC/USDT or ETH/stETH, pricing swaps on the
+// x³y + y³x = k curve instead of x * y = k. Both tokens must use 9 decimals.
+
+////////////////////////////////////////
+// Constants
+////////////////////////////////////////
+const TWAP_PERCISION = U256::from((0, 0, 0, 1000000000));
+
+////////////////////////////////////////
+// Helper functions
+////////////////////////////////////////
+#[storage(read, write)]
+fn store_reserves(
+    reserve0: u64,
+    reserve1: u64,
+    prev_reserve0: u64,
+    prev_reserve1: u64,
+) {
+    if (prev_reserve0 != 0 && prev_reserve1 != 0) {
+        // The reserve ratio isn't the price on this curve, so accumulate the marginal prices
+        write_observation(
+            get_marginal_price(prev_reserve0, prev_reserve1, TWAP_PERCISION),
+            get_marginal_price(prev_reserve1, prev_reserve0, TWAP_PERCISION),
+        );
+    }
+    set_reserves(reserve0, reserve1);
+}
+
+// ////////////////////////////////////////
+// // ABI definitions
+// ////////////////////////////////////////
+impl Exchange for Contract {
+    #[storage(read, write)]
+    fn initialize(new_vault: b256) {
+        initialize(new_vault);
+    }
+
+    #[storage(read)]
+    fn get_pool_info() -> PoolInfo {
+        let (token_0_reserve, token_1_reserve) = get_reserves();
+        PoolInfo {
+            token_0_reserve: token_0_reserve,
+            token_1_reserve: token_1_reserve,
+            lp_token_supply: get_lp_token_supply(),
+        }
+    }
+
+    #[storage(read)]
+    fn get_vault_info() -> VaultInfo {
+        get_vault_info()
+    }
+
+    #[storage(read)]
+    fn get_fee_info() -> FeeInfo {
+        get_fee_info()
+    }
+
+    #[storage(read)]
+    fn get_fee_cache_info() -> FeeCacheInfo {
+        get_fee_cache_info()
+    }
+
+    #[storage(read)]
+    fn get_lp_fee_info() -> LPFeeInfo {
+        get_lp_fee_info()
+    }
+
+    #[storage(read)]
+    fn get_amount_out(amount_in: u64, token_0_in: bool) -> u64 {
+        let (token_0_reserve, token_1_reserve) = get_reserves();
+        let (reserve_in, reserve_out) = if token_0_in {
+            (token_0_reserve, token_1_reserve)
+        } else {
+            (token_1_reserve, token_0_reserve)
+        };
+
+        require(amount_in > 0, Error::InsufficentInput);
+        require(reserve_in > 0 && reserve_out > 0, Error::InsufficentLiquidity);
+
+        let input = amount_in - get_protocol_fee_amount(amount_in, get_next_protocol_fee());
+        let input = input - get_lp_fee_amount(input, get_current_lp_fee());
+
+        let k = get_k(to_precision(reserve_in), to_precision(reserve_out));
+        let new_reserve_out = get_y(to_precision(reserve_in + input), k, to_precision(reserve_out));
+        let output = from_precision(to_precision(reserve_out) - new_reserve_out);
+        // Round against the trader, so the quote always satisfies the invariant
+        if output > 0 { output - 1 } else { 0 }
+    }
+
+    #[storage(read)]
+    fn get_amount_in(amount_out: u64, token_0_out: bool) -> u64 {
+        let (token_0_reserve, token_1_reserve) = get_reserves();
+        let (reserve_in, reserve_out) = if token_0_out {
+            (token_1_reserve, token_0_reserve)
+        } else {
+            (token_0_reserve, token_1_reserve)
+        };
+
+        require(amount_out > 0, Error::InsufficentOutput);
+        require(amount_out < reserve_out, Error::InsufficentLiquidity);
+
+        // The curve is symmetric, so get_y also solves for the input reserve
+        let k = get_k(to_precision(reserve_in), to_precision(reserve_out));
+        let new_reserve_in = get_y(to_precision(reserve_out - amount_out), k, to_precision(reserve_in));
+        let input = from_precision(new_reserve_in - to_precision(reserve_in)) + 1;
+
+        // Gross up for the LP fee, then the protocol fee, rounding up both times
+        add_protocol_fee(add_lp_fee(input, get_current_lp_fee()), get_next_protocol_fee())
+    }
+
+    #[storage(read)]
+    fn get_twap_info() -> TWAPInfo {
+        get_twap_info()
+    }
+
+    #[storage(read, write)]
+    fn cache_vault_fees() {
+        cache_vault_fees();
+    }
+
+    #[storage(read, write)]
+    fn set_max_fee_cache_age(max_age: u64) {
+        set_max_fee_cache_age(max_age);
+    }
+
+    #[storage(read, write)]
+    fn set_dynamic_fee(config: DynamicFeeConfig) {
+        set_dynamic_fee(config);
+    }
+
+    #[storage(read, write)]
+    fn add_liquidity(recipient: Identity) -> u64 {
+        let (token_0_reserve, token_1_reserve) = get_reserves();
+        let (current_token_0_amount, current_token_1_amount) = get_pool_balance();
+
+        let minted = mint_liquidity(current_token_0_amount, current_token_1_amount, recipient);
+        store_reserves(current_token_0_amount, current_token_1_amount, token_0_reserve, token_1_reserve);
+        minted
+    }
+
+    #[storage(read, write)]
+    fn remove_liquidity(recipient: Identity) -> RemoveLiquidityInfo {
+        let (token_0_reserve, token_1_reserve) = get_reserves();
+        let (current_token_0_amount, current_token_1_amount) = get_pool_balance();
+
+        let removed = burn_liquidity(current_token_0_amount, current_token_1_amount, recipient);
+        store_reserves(current_token_0_amount - removed.token_0_amount, current_token_1_amount - removed.token_1_amount, token_0_reserve, token_1_reserve);
+        removed
+    }
+
+    #[storage(read, write)]
+    fn swap(amount_0_out: u64, amount_1_out: u64, recipient: Identity) {
+        let (token_0_reserve, token_1_reserve) = get_reserves();
+        let (balance_0, balance_1, amount0_in, amount1_in) = settle_swap(amount_0_out, amount_1_out, recipient, 0, 0);
+
+        // Measured before this swap's observation is written, so it matches get_lp_fee_info
+        let lp_fee = get_current_lp_fee();
+        let balance0_adjusted = balance_0 - get_lp_fee_amount(amount0_in, lp_fee);
+        let balance1_adjusted = balance_1 - get_lp_fee_amount(amount1_in, lp_fee);
+
+        let left = get_k(to_precision(balance0_adjusted), to_precision(balance1_adjusted));
+        let right = get_k(to_precision(token_0_reserve), to_precision(token_1_reserve));
+        require(left > right || left == right, Error::Invariant); // U256 doesn't have >= yet
+        store_reserves(balance_0, balance_1, token_0_reserve, token_1_reserve);
+
+        log(Swap {
+            sender: identity_to_b256(msg_sender().unwrap()),
+            amount_0_in: amount0_in,
+            amount_1_in: amount1_in,
+            amount_0_out: amount_0_out,
+            amount_1_out: amount_1_out,
+            recipient: identity_to_b256(recipient),
+        });
+    }
+
+    #[storage(read, write)]
+    fn expand_twap_buffer(new_total_slots: u64) {
+        expand_twap_buffer(new_total_slots);
+    }
+
+    #[storage(read, write)]
+    fn withdraw_protocol_fees(recipient: Identity) -> (u64, u64) {
+        withdraw_protocol_fees(recipient)
+    }
+
+    #[storage(read)]
+    fn get_tokens() -> (b256, b256) {
+        get_tokens()
+    }
+
+    #[storage(read)]
+    fn get_observation(slot: u64) -> Observation {
+        get_observation(slot)
+    }
+}
@@ -0,0 +1,332 @@
+use std::str::FromStr;
+use fuels::{
+    prelude::*,
+    fuels_abigen::abigen,
+    signers::WalletUnlocked,
+    tx::{AssetId, Bytes32, StorageSlot},
+};
+use test_helpers::{
+    constant_product_amount_out,
+    deployer_storage,
+    get_logs,
+    get_wallets,
+    MIN_FEE_CHANGE_DELAY,
+    STABLE_EXCHANGE_ABI,
+};
+
+///////////////////////////////
+// Load the Stable Exchange Contract abi
+///////////////////////////////
+abigen!(Exchange, "./out/debug/stable_exchange_contract-abi.json");
+
+abigen!(
+    TestToken,
+    "../token_contract/out/debug/token_contract-abi.json"
+);
+abigen!(Vault, "../vault_contract/out/debug/vault_contract-abi.json");
+abigen!(Router, "../router_contract/out/debug/router_contract-abi.json");
+
+struct Fixture {
+    wallet: WalletUnlocked,
+    exchange_contract_id: Bech32ContractId,
+    exchange_instance: Exchange,
+    vault_contract_id: Bech32ContractId,
+    token_0_asset_id: AssetId,
+    token_1_asset_id: AssetId,
+}
+
+fn to_9_decimal(num: u64) -> u64 {
+    num * 1_000_000_000
+}
+
+async fn deploy_token(wallet: &WalletUnlocked, salt: u8) -> AssetId {
+    test_helpers::deploy_token(wallet, salt, |token_contract_id| {
+        let token_instance = TestToken::new(token_contract_id.clone(), wallet.clone());
+        (
+            token_instance.methods().initialize(to_9_decimal(10_000_000), wallet.address().into()),
+            token_instance.methods().mint(),
+        )
+    })
+    .await
+}
+
+/// Deploy a USDC/USDT style pool of two tokens, or an ETH/stETH style pool when `eth_pair` is set
+async fn setup(eth_pair: bool) -> Fixture {
+    let wallets = get_wallets().await;
+    let wallet = wallets.get(0).unwrap().clone();
+
+    let vault_contract_id = Contract::deploy(
+        "../vault_contract/out/debug/vault_contract.bin",
+        &wallet,
+        TxParameters::default(),
+        deployer_storage(&wallet),
+    )
+    .await
+    .unwrap();
+    Vault::new(vault_contract_id.clone(), wallet.clone())
+        .methods()
+        .initialize(Identity::Address(wallet.address().into()), MIN_FEE_CHANGE_DELAY)
+        .call()
+        .await
+        .unwrap();
+
+    let token_a = deploy_token(&wallet, 1).await;
+    let token_b = if eth_pair { BASE_ASSET_ID } else { deploy_token(&wallet, 2).await };
+    let (token_0_asset_id, token_1_asset_id) = if *token_a < *token_b {
+        (token_a, token_b)
+    } else {
+        (token_b, token_a)
+    };
+
+    let token0_slot = Bytes32::from_str("0x0000000000000000000000000000000000000000000000000000000000000000").unwrap();
+    let token1_slot = Bytes32::from_str("0x0000000000000000000000000000000000000000000000000000000000000001").unwrap();
+    let storage_vec = vec![
+        StorageSlot::new(token0_slot, Bytes32::new(*token_0_asset_id)),
+        StorageSlot::new(token1_slot, Bytes32::new(*token_1_asset_id)),
+    ];
+
+    let exchange_contract_id = Contract::deploy(
+        "./out/debug/stable_exchange_contract.bin",
+        &wallet,
+        TxParameters::default(),
+        StorageConfiguration::with_manual_storage(Some(storage_vec)),
+    )
+    .await
+    .unwrap();
+
+    let exchange_instance = Exchange::new(exchange_contract_id.clone(), wallet.clone());
+    exchange_instance
+        .methods()
+        .initialize(Bits256(vault_contract_id.hash().into()))
+        .set_contracts(&[vault_contract_id.clone()])
+        .call()
+        .await
+        .unwrap();
+
+    Fixture {
+        wallet: wallet,
+        exchange_contract_id: exchange_contract_id,
+        exchange_instance: exchange_instance,
+        vault_contract_id: vault_contract_id,
+        token_0_asset_id: token_0_asset_id,
+        token_1_asset_id: token_1_asset_id,
+    }
+}
+
+async fn add_liquidity(fixture: &Fixture, token_0_amount: u64, token_1_amount: u64) {
+    let _receipts = fixture.wallet
+        .force_transfer_to_contract(&fixture.exchange_contract_id, token_0_amount, fixture.token_0_asset_id, TxParameters::default())
+        .await
+        .unwrap();
+    let _receipts = fixture.wallet
+        .force_transfer_to_contract(&fixture.exchange_contract_id, token_1_amount, fixture.token_1_asset_id, TxParameters::default())
+        .await
+        .unwrap();
+
+    fixture.exchange_instance
+        .methods()
+        .add_liquidity(Identity::Address(fixture.wallet.address().into()))
+        .append_variable_outputs(2)
+        .tx_params(TxParameters {
+            gas_price: 0,
+            gas_limit: 100_000_000,
+            maturity: 0,
+        })
+        .call_params(CallParameters::new(None, None, Some(100_000_000)))
+        .call()
+        .await
+        .unwrap();
+}
+
+async fn swap(fixture: &Fixture, amount_in: u64, token_0_in: bool, amount_out: u64) -> Result<FuelCallResponse<()>, Error> {
+    let (asset_id, out0, out1) = if token_0_in {
+        (fixture.token_0_asset_id, 0, amount_out)
+    } else {
+        (fixture.token_1_asset_id, amount_out, 0)
+    };
+
+    fixture.exchange_instance
+        .methods()
+        .swap(out0, out1, Identity::Address(fixture.wallet.address().into()))
+        .set_contracts(&[fixture.vault_contract_id.clone()])
+        .call_params(CallParameters::new(Some(amount_in), Some(asset_id), None))
+        .tx_params(TxParameters {
+            gas_price: 0,
+            gas_limit: 100_000_000,
+            maturity: 0,
+        })
+        .append_variable_outputs(1)
+        .call()
+        .await
+}
+
+#[tokio::test]
+async fn stable_swap_near_peg() {
+    let fixture = setup(false).await;
+    add_liquidity(&fixture, to_9_decimal(1000), to_9_decimal(1000)).await;
+
+    let amount_in = to_9_decimal(10);
+    let amount_out = fixture.exchange_instance
+        .methods()
+        .get_amount_out(amount_in, true)
+        .call()
+        .await
+        .unwrap()
+        .value;
+
+    // Close to 1:1 after the 0.3% fee, and well ahead of constant product
+    assert!(amount_out > amount_in * 99 / 100, "Output {} too far from the peg", amount_out);
+    assert!(amount_out < amount_in);
+    assert!(amount_out > constant_product_amount_out(amount_in, to_9_decimal(1000), to_9_decimal(1000)));
+
+    let is_err = swap(&fixture, amount_in, true, amount_out + to_9_decimal(1) / 1000).await.is_err();
+    assert!(is_err, "Pool shouldn't pay more than the curve allows");
+
+    let response = swap(&fixture, amount_in, true, amount_out).await.unwrap();
+    let logs = get_logs::<Swap, _>(&response, &fixture.exchange_contract_id, STABLE_EXCHANGE_ABI);
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs.get(0).unwrap().amount_0_in, amount_in);
+    assert_eq!(logs.get(0).unwrap().amount_1_out, amount_out);
+
+    let pool_info = fixture.exchange_instance.methods().get_pool_info().call().await.unwrap().value;
+    assert_eq!(pool_info.token_0_reserve, to_9_decimal(1010));
+    assert_eq!(pool_info.token_1_reserve, to_9_decimal(1000) - amount_out);
+
+    // Swapping back returns nearly everything
+    let amount_back = fixture.exchange_instance
+        .methods()
+        .get_amount_out(amount_out, false)
+        .call()
+        .await
+        .unwrap()
+        .value;
+    swap(&fixture, amount_out, false, amount_back).await.unwrap();
+    assert!(amount_back > amount_in * 99 / 100 && amount_back < amount_in);
+}
+
+#[tokio::test]
+async fn stable_exact_output() {
+    let fixture = setup(false).await;
+    add_liquidity(&fixture, to_9_decimal(1000), to_9_decimal(1000)).await;
+
+    let amount_out = to_9_decimal(25);
+    let amount_in = fixture.exchange_instance
+        .methods()
+        .get_amount_in(amount_out, false)
+        .call()
+        .await
+        .unwrap()
+        .value;
+    assert!(amount_in > amount_out && amount_in < amount_out * 101 / 100, "Input {} too far from the peg", amount_in);
+
+    let starting_balance = fixture.wallet.get_asset_balance(&fixture.token_0_asset_id).await.unwrap();
+    swap(&fixture, amount_in, false, amount_out).await.unwrap();
+    let end_balance = fixture.wallet.get_asset_balance(&fixture.token_0_asset_id).await.unwrap();
+    assert_eq!(end_balance - starting_balance, amount_out);
+}
+
+#[tokio::test]
+async fn stable_pricing_steepens_off_peg() {
+    let fixture = setup(false).await;
+    add_liquidity(&fixture, to_9_decimal(1000), to_9_decimal(1000)).await;
+
+    // Drain most of token 1, so the pool is far from balanced
+    let amount_in = to_9_decimal(2000);
+    let amount_out = fixture.exchange_instance.methods().get_amount_out(amount_in, true).call().await.unwrap().value;
+    swap(&fixture, amount_in, true, amount_out).await.unwrap();
+    assert!(amount_out < to_9_decimal(1000));
+
+    // Token 1 is now scarce, so it costs well over 1 token 0
+    let small_amount = to_9_decimal(1);
+    let price = fixture.exchange_instance.methods().get_amount_out(small_amount, true).call().await.unwrap().value;
+    assert!(price < small_amount * 9 / 10, "Off-peg output {} should be discounted", price);
+}
+
+#[tokio::test]
+async fn large_trades_dont_overflow_fee_math() {
+    let fixture = setup(false).await;
+    add_liquidity(&fixture, to_9_decimal(4_000_000), to_9_decimal(4_000_000)).await;
+
+    // The input is over u64::MAX / 10_000, so grossing it up for the LP fee needs more than 64 bits
+    let amount_out = to_9_decimal(2_000_000);
+    let amount_in = fixture.exchange_instance
+        .methods()
+        .get_amount_in(amount_out, false)
+        .call()
+        .await
+        .unwrap()
+        .value;
+    assert!(amount_in > u64::MAX / 10_000);
+
+    let starting_balance = fixture.wallet.get_asset_balance(&fixture.token_0_asset_id).await.unwrap();
+    swap(&fixture, amount_in, false, amount_out).await.unwrap();
+    let end_balance = fixture.wallet.get_asset_balance(&fixture.token_0_asset_id).await.unwrap();
+    assert_eq!(end_balance - starting_balance, amount_out);
+}
+
+#[tokio::test]
+async fn eth_steth_pool_through_router() {
+    let fixture = setup(true).await;
+    assert_eq!(fixture.token_0_asset_id, BASE_ASSET_ID);
+    add_liquidity(&fixture, to_9_decimal(100), to_9_decimal(100)).await;
+
+    let router_contract_id = Contract::deploy(
+        "../router_contract/out/debug/router_contract.bin",
+        &fixture.wallet,
+        TxParameters::default(),
+        StorageConfiguration::new(None, None),
+    )
+    .await
+    .unwrap();
+    let router_instance = Router::new(router_contract_id, fixture.wallet.clone());
+
+    let amount_in = to_9_decimal(1);
+    let expected_amount = fixture.exchange_instance.methods().get_amount_out(amount_in, true).call().await.unwrap().value;
+    assert!(expected_amount > amount_in * 99 / 100);
+
+    // The router prices through the pool, without knowing its curve
+    let result = router_instance
+        .methods()
+        .swap_exact_input(
+            Bits256(fixture.exchange_contract_id.hash().into()),
+            expected_amount,
+            Identity::Address(fixture.wallet.address().into()),
+        )
+        .tx_params(TxParameters {
+            gas_price: 0,
+            gas_limit: 100_000_000,
+            maturity: 0,
+        })
+        .call_params(CallParameters::new(Some(amount_in), None, Some(100_000_000)))
+        .set_contracts(&[fixture.exchange_contract_id.clone()])
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(result.value.output_amount, expected_amount);
+
+    let amount_out = to_9_decimal(1);
+    let expected_input = fixture.exchange_instance.methods().get_amount_in(amount_out, true).call().await.unwrap().value;
+
+    let result = router_instance
+        .methods()
+        .swap_exact_output(
+            Bits256(fixture.exchange_contract_id.hash().into()),
+            amount_out,
+            expected_input,
+            Identity::Address(fixture.wallet.address().into()),
+        )
+        .tx_params(TxParameters {
+            gas_price: 0,
+            gas_limit: 100_000_000,
+            maturity: 0,
+        })
+        .call_params(CallParameters::new(Some(expected_input), Some(fixture.token_1_asset_id), Some(100_000_000)))
+        .set_contracts(&[fixture.exchange_contract_id.clone()])
+        .append_variable_outputs(2)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(result.value.input_amount, expected_input);
+    assert_eq!(result.value.output_amount, amount_out);
+}
@@ -0,0 +1,17 @@
+library swap_order_lib;
+
+/// A swap signed by a coin's owner, to be filled by anyone through the router.
+/// The fill script reads it from its script data, and the predicate holding the coin checks
+/// the owner signed it, together with the script's bytecode hash.
+pub struct SwapOrder {
+    /// The coin being sold, which must be the predicate input verifying the order
+    utxo_tx_id: b256,
+    utxo_output_index: u64,
+    asset_in: b256,
+    amount_in: u64,
+    router: b256,
+    pool: b256,
+    min_amount_out: u64,
+    /// Receives the swap's output, and anything returned from the coin if the fill reverts
+    recipient: b256,
+}
@@ -0,0 +1,94 @@
+predicate;
+
+use std::{
+    b512::B512,
+    ecr::ec_recover_address,
+    hash::sha256,
+    inputs::{
+        GTF_INPUT_COIN_AMOUNT,
+        GTF_INPUT_COIN_ASSET_ID,
+        GTF_INPUT_COIN_OUTPUT_INDEX,
+        GTF_INPUT_COIN_TX_ID,
+        input_predicate_data,
+    },
+    outputs::{
+        GTF_OUTPUT_COIN_ASSET_ID,
+        GTF_OUTPUT_COIN_TO,
+        Output,
+        output_type,
+    },
+    tx::{
+        tx_outputs_count,
+        tx_script_bytecode_hash,
+        tx_script_data,
+    },
+};
+use swap_order_lib::SwapOrder;
+
+// Coins sent to this predicate can only be spent by a transaction running a script the owner
+// signed, with script data holding a swap order the owner signed. The fill script sells the
+// order's coin through the order's router with the order's minimum output, so checking the
+// signature covers the router call. Anyone can submit the transaction and pay its gas.
+
+/// Replaced with the owner's address in the bytecode, giving each owner their own predicate
+const OWNER = 0xfefefefefefefefefefefefefefefefefefefefefefefefefefefefefefefefe;
+
+/// Index of the input whose predicate is being verified
+fn verifying_input() -> u64 {
+    asm(r1) {
+        gm r1 i3;
+        r1: u64
+    }
+}
+
+/// Whether the only outputs that can take the order's asset out of the
+/// transaction's free balance pay the order's recipient
+fn outputs_pay_recipient(order: SwapOrder) -> bool {
+    let output_count = tx_outputs_count();
+    let mut i = 0;
+    while i < output_count {
+        let pays_out = match output_type(i) {
+            Output::Coin => true,
+            Output::Change => true,
+            _ => false,
+        };
+        // Coin and change outputs share the same layout
+        if pays_out
+            && __gtf::<b256>(i, GTF_OUTPUT_COIN_ASSET_ID) == order.asset_in
+            && __gtf::<b256>(i, GTF_OUTPUT_COIN_TO) != order.recipient
+        {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+fn main() -> bool {
+    let input = verifying_input();
+    let order = tx_script_data::<SwapOrder>();
+
+    // The order must sell exactly this coin, so it can't be replayed against the owner's other coins
+    if __gtf::<b256>(input, GTF_INPUT_COIN_TX_ID) != order.utxo_tx_id
+        || __gtf::<u64>(input, GTF_INPUT_COIN_OUTPUT_INDEX) != order.utxo_output_index
+        || __gtf::<b256>(input, GTF_INPUT_COIN_ASSET_ID) != order.asset_in
+        || __gtf::<u64>(input, GTF_INPUT_COIN_AMOUNT) != order.amount_in
+    {
+        return false;
+    }
+
+    // If the fill reverts, the coin is returned through the change output
+    if !outputs_pay_recipient(order) {
+        return false;
+    }
+
+    let signature = input_predicate_data::<B512>(input);
+    let message = sha256((tx_script_bytecode_hash(), order));
+    match ec_recover_address(signature, message) {
+        Result::Ok(signer) => {
+            let signer: b256 = signer.into();
+            signer == OWNER
+        },
+        Result::Err(_) => false,
+    }
+}
@@ -0,0 +1,294 @@
+use std::str::FromStr;
+use fuels::{
+    prelude::*,
+    fuels_abigen::abigen,
+    signers::WalletUnlocked,
+    tx::{Address, AssetId, Bytes32, Output, StorageSlot},
+};
+use test_helpers::{
+    deployer_storage,
+    fill_swap_order,
+    get_swap_order,
+    get_wallets,
+    send_swap_order_fill,
+    sign_swap_order,
+    swap_order_outputs,
+    swap_order_predicate_address,
+    MIN_FEE_CHANGE_DELAY,
+};
+
+abigen!(Exchange, "../exchange_contract/out/debug/exchange_contract-abi.json");
+abigen!(TestToken, "../token_contract/out/debug/token_contract-abi.json");
+abigen!(Vault, "../vault_contract/out/debug/vault_contract-abi.json");
+
+struct Fixture {
+    wallet: WalletUnlocked,
+    relayer_wallet: WalletUnlocked,
+    token_asset_id: AssetId,
+    exchange_contract_id: Bech32ContractId,
+    exchange_instance: Exchange,
+    router_contract_id: Bech32ContractId,
+    vault_contract_id: Bech32ContractId,
+}
+
+fn to_9_decimal(num: u64) -> u64 {
+    num * 1_000_000_000
+}
+
+/// Deploy an ETH/token pool with 10 of each, and a router to fill orders through
+async fn setup() -> Fixture {
+    let wallets = get_wallets().await;
+    let wallet = wallets.get(0).unwrap().clone();
+    let relayer_wallet = wallets.get(1).unwrap().clone();
+
+    let token_contract_id = Contract::deploy(
+        "../token_contract/out/debug/token_contract.bin",
+        &wallet,
+        TxParameters::default(),
+        StorageConfiguration::new(None, None),
+    )
+    .await
+    .unwrap();
+
+    let token_instance = TestToken::new(token_contract_id.clone(), wallet.clone());
+    token_instance
+        .methods()
+        .initialize(to_9_decimal(20000), wallet.address().into())
+        .call()
+        .await
+        .unwrap();
+    token_instance
+        .methods()
+        .mint()
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .unwrap();
+    let token_asset_id = AssetId::new(*token_contract_id.hash());
+
+    let token1_slot = Bytes32::from_str("0x0000000000000000000000000000000000000000000000000000000000000001").unwrap();
+    let storage_vec = vec![
+        StorageSlot::new(token1_slot, token_contract_id.hash()),
+    ];
+    let exchange_contract_id = Contract::deploy_with_parameters(
+        "../exchange_contract/out/debug/exchange_contract.bin",
+        &wallet,
+        TxParameters::default(),
+        StorageConfiguration::with_manual_storage(Some(storage_vec)),
+        Salt::from([0u8; 32]),
+    )
+    .await
+    .unwrap();
+
+    let vault_contract_id = Contract::deploy(
+        "../vault_contract/out/debug/vault_contract.bin",
+        &wallet,
+        TxParameters::default(),
+        deployer_storage(&wallet),
+    )
+    .await
+    .unwrap();
+
+    let router_contract_id = Contract::deploy(
+        "../router_contract/out/debug/router_contract.bin",
+        &wallet,
+        TxParameters::default(),
+        StorageConfiguration::new(None, None),
+    )
+    .await
+    .unwrap();
+
+    Vault::new(vault_contract_id.clone(), wallet.clone())
+        .methods()
+        .initialize(Identity::Address(wallet.address().into()), MIN_FEE_CHANGE_DELAY)
+        .call()
+        .await
+        .unwrap();
+
+    let exchange_instance = Exchange::new(exchange_contract_id.clone(), wallet.clone());
+    exchange_instance
+        .methods()
+        .initialize(Bits256(vault_contract_id.hash().into()))
+        .set_contracts(&[vault_contract_id.clone()])
+        .call()
+        .await
+        .unwrap();
+
+    let _receipts = wallet
+        .force_transfer_to_contract(&exchange_contract_id, to_9_decimal(10), BASE_ASSET_ID, TxParameters::default())
+        .await;
+    let _receipts = wallet
+        .force_transfer_to_contract(&exchange_contract_id, to_9_decimal(10), token_asset_id, TxParameters::default())
+        .await;
+    exchange_instance
+        .methods()
+        .add_liquidity(Identity::Address(wallet.address().into()))
+        .tx_params(TxParameters {
+            gas_price: 0,
+            gas_limit: 100_000_000,
+            maturity: 0,
+        })
+        .call_params(CallParameters::new(None, None, Some(100_000_000)))
+        .append_variable_outputs(2)
+        .call()
+        .await
+        .unwrap();
+
+    Fixture {
+        wallet: wallet,
+        relayer_wallet: relayer_wallet,
+        token_asset_id: token_asset_id,
+        exchange_contract_id: exchange_contract_id,
+        exchange_instance: exchange_instance,
+        router_contract_id: router_contract_id,
+        vault_contract_id: vault_contract_id,
+    }
+}
+
+/// Contracts a fill through the router calls
+fn fill_contracts(fixture: &Fixture) -> Vec<Bech32ContractId> {
+    vec![
+        fixture.router_contract_id.clone(),
+        fixture.exchange_contract_id.clone(),
+        fixture.vault_contract_id.clone(),
+    ]
+}
+
+/// Send ETH to the wallet's predicate, to be sold by a signed order
+async fn fund_predicate(fixture: &Fixture, amount: u64) {
+    fixture.wallet
+        .transfer(&swap_order_predicate_address(fixture.wallet.address()), amount, BASE_ASSET_ID, TxParameters::default())
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn fill_signed_swap_order() {
+    let fixture = setup().await;
+    let provider = fixture.relayer_wallet.get_provider().unwrap();
+    let amount = to_9_decimal(1);
+    fund_predicate(&fixture, amount).await;
+
+    let expected_amount_out = fixture.exchange_instance
+        .methods()
+        .get_amount_out(amount, true)
+        .call()
+        .await
+        .unwrap()
+        .value;
+
+    let order = get_swap_order(
+        provider,
+        fixture.wallet.address(),
+        BASE_ASSET_ID,
+        &fixture.router_contract_id,
+        &fixture.exchange_contract_id,
+        expected_amount_out,
+        fixture.wallet.address(),
+    )
+    .await;
+    assert_eq!(order.amount_in, amount);
+    let signature = sign_swap_order(&fixture.wallet, &order).await;
+
+    let starting_token_balance = fixture.wallet.get_asset_balance(&fixture.token_asset_id).await.unwrap();
+    let starting_eth_balance = fixture.wallet.get_asset_balance(&BASE_ASSET_ID).await.unwrap();
+
+    // The relayer submits the fill, without the owner sending a transaction
+    fill_swap_order(provider, fixture.wallet.address(), &order, &signature, &fill_contracts(&fixture))
+        .await
+        .unwrap();
+
+    let end_token_balance = fixture.wallet.get_asset_balance(&fixture.token_asset_id).await.unwrap();
+    let end_eth_balance = fixture.wallet.get_asset_balance(&BASE_ASSET_ID).await.unwrap();
+    assert_eq!(end_token_balance - starting_token_balance, expected_amount_out);
+    assert_eq!(end_eth_balance, starting_eth_balance);
+
+    let predicate_balance = provider
+        .get_asset_balance(&swap_order_predicate_address(fixture.wallet.address()), BASE_ASSET_ID)
+        .await
+        .unwrap();
+    assert_eq!(predicate_balance, 0);
+
+    let pool_info = fixture.exchange_instance.methods().get_pool_info().call().await.unwrap().value;
+    assert_eq!(pool_info.token_0_reserve, to_9_decimal(10) + amount);
+}
+
+#[tokio::test]
+async fn reject_invalid_swap_order_fills() {
+    let fixture = setup().await;
+    let provider = fixture.relayer_wallet.get_provider().unwrap();
+    let amount = to_9_decimal(1);
+    fund_predicate(&fixture, amount).await;
+
+    let order = get_swap_order(
+        provider,
+        fixture.wallet.address(),
+        BASE_ASSET_ID,
+        &fixture.router_contract_id,
+        &fixture.exchange_contract_id,
+        to_9_decimal(9) / 10,
+        fixture.wallet.address(),
+    )
+    .await;
+    let signature = sign_swap_order(&fixture.wallet, &order).await;
+    let contracts = fill_contracts(&fixture);
+
+    // Signed by someone other than the predicate's owner
+    let relayer_signature = sign_swap_order(&fixture.relayer_wallet, &order).await;
+    let is_err = fill_swap_order(provider, fixture.wallet.address(), &order, &relayer_signature, &contracts).await.is_err();
+    assert!(is_err, "Only the owner can sign orders");
+
+    // The minimum output can't be lowered after signing
+    let mut lowered_order = order.clone();
+    lowered_order.min_amount_out = 1;
+    let is_err = fill_swap_order(provider, fixture.wallet.address(), &lowered_order, &signature, &contracts).await.is_err();
+    assert!(is_err, "Orders can't be changed after signing");
+
+    // Neither can the output's recipient
+    let mut redirected_order = order.clone();
+    redirected_order.recipient = Address::from(fixture.relayer_wallet.address());
+    let is_err = fill_swap_order(provider, fixture.wallet.address(), &redirected_order, &signature, &contracts).await.is_err();
+    assert!(is_err, "Orders can't be redirected after signing");
+
+    // The relayer can't take the coin through an output of its own, wherever it's placed.
+    // Coin outputs are paid even if the script reverts, so check nothing was spent.
+    let predicate_address = swap_order_predicate_address(fixture.wallet.address());
+    let relayer_address = Address::from(fixture.relayer_wallet.address());
+    let input_count = 1 + contracts.len();
+
+    let mut thief_first = swap_order_outputs(&order, &contracts);
+    thief_first.insert(0, Output::coin(relayer_address, amount, BASE_ASSET_ID));
+
+    let mut thief_last = swap_order_outputs(&order, &contracts);
+    thief_last.push(Output::coin(relayer_address, amount, BASE_ASSET_ID));
+
+    // With more outputs than inputs, the thief's output is past the last input's index
+    let mut thief_after_padding = swap_order_outputs(&order, &contracts);
+    thief_after_padding.extend((0..input_count).map(|_| Output::variable(Address::zeroed(), 0, AssetId::default())));
+    thief_after_padding.push(Output::coin(relayer_address, amount, BASE_ASSET_ID));
+    assert!(thief_after_padding.len() > input_count * 2);
+
+    for outputs in [thief_first, thief_last, thief_after_padding] {
+        let is_err = send_swap_order_fill(provider, fixture.wallet.address(), &order, &signature, &contracts, outputs).await.is_err();
+        assert!(is_err, "Only the recipient can receive the order's asset");
+        assert_eq!(provider.get_asset_balance(&predicate_address, BASE_ASSET_ID).await.unwrap(), amount);
+    }
+
+    // Orders from one owner can't spend another owner's predicate
+    let relayer_order = get_swap_order(
+        provider,
+        fixture.wallet.address(),
+        BASE_ASSET_ID,
+        &fixture.router_contract_id,
+        &fixture.exchange_contract_id,
+        1,
+        fixture.relayer_wallet.address(),
+    )
+    .await;
+    let is_err = fill_swap_order(provider, fixture.relayer_wallet.address(), &relayer_order, &sign_swap_order(&fixture.relayer_wallet, &relayer_order).await, &contracts).await.is_err();
+    assert!(is_err, "The order's coin isn't held by the signer's predicate");
+
+    // The order still fills as signed
+    fill_swap_order(provider, fixture.wallet.address(), &order, &signature, &contracts).await.unwrap();
+    assert_eq!(provider.get_asset_balance(&predicate_address, BASE_ASSET_ID).await.unwrap(), 0);
+}
@@ -0,0 +1,18 @@
+script;
+
+use std::{
+    address::Address,
+    identity::Identity,
+};
+use router_abi::Router;
+use swap_order_lib::SwapOrder;
+
+/// Fill a signed swap order, selling the order's coin through the router
+fn main(order: SwapOrder) -> u64 {
+    let result = abi(Router, order.router).swap_exact_input {
+        asset_id: order.asset_in,
+        coins: order.amount_in,
+    }(order.pool, order.min_amount_out, Identity::Address(Address::from(order.recipient)));
+
+    result.output_amount
+}
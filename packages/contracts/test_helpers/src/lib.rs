@@ -1,14 +1,50 @@
+use chrono::{Duration, TimeZone, Utc};
 use core::fmt::Debug;
+use std::{
+  any::type_name,
+  fs,
+};
 
 use fuels::{
   contract::{
     call_response::FuelCallResponse,
     contract::ContractCallHandler,
   },
+  core::abi_decoder::ABIDecoder,
   fuel_node::Config,
   prelude::*,
+  signers::{
+    fuel_crypto::{Hasher, Signature},
+    provider::TimeParameters,
+    Signer,
+  },
+  tx::{AssetId, Bytes32, ContractId, Input, Output, Receipt, StorageSlot, Transaction, TxPointer, UtxoId},
 //   tx::UniqueIdentifier,
 };
+use serde_json::Value;
+
+// ABI paths for `get_logs`, relative to any of the contract test crates
+pub const EXCHANGE_ABI: &str = "../exchange_contract/out/debug/exchange_contract-abi.json";
+pub const STABLE_EXCHANGE_ABI: &str = "../stable_exchange_contract/out/debug/stable_exchange_contract-abi.json";
+pub const WEIGHTED_EXCHANGE_ABI: &str = "../weighted_exchange_contract/out/debug/weighted_exchange_contract-abi.json";
+pub const CONCENTRATED_EXCHANGE_ABI: &str = "../concentrated_exchange_contract/out/debug/concentrated_exchange_contract-abi.json";
+pub const MULTI_ASSET_EXCHANGE_ABI: &str = "../multi_asset_exchange_contract/out/debug/multi_asset_exchange_contract-abi.json";
+pub const FARM_ABI: &str = "../farm_contract/out/debug/farm_contract-abi.json";
+pub const LIMIT_ORDER_ABI: &str = "../limit_order_contract/out/debug/limit_order_contract-abi.json";
+pub const POSITION_MANAGER_ABI: &str = "../position_manager_contract/out/debug/position_manager_contract-abi.json";
+pub const REGISTRY_ABI: &str = "../registry_contract/out/debug/registry_contract-abi.json";
+pub const VAULT_ABI: &str = "../vault_contract/out/debug/vault_contract-abi.json";
+
+// Scripts and predicates, relative to any of the contract test crates
+pub const ADD_LIQUIDITY_SCRIPT: &str = "../add_liquidity_script/out/debug/add_liquidity_script.bin";
+pub const SWAP_ORDER_PREDICATE: &str = "../swap_order_predicate/out/debug/swap_order_predicate.bin";
+pub const SWAP_ORDER_SCRIPT: &str = "../swap_order_script/out/debug/swap_order_script.bin";
+
+/// The predicate's `OWNER` constant, replaced with each owner's address
+const SWAP_ORDER_OWNER_PLACEHOLDER: [u8; 32] = [0xfe; 32];
+
+/// Notice the vault requires before fee changes, in seconds
+pub const MIN_FEE_CHANGE_DELAY: u64 = 48 * 60 * 60;
 
 static mut TIMESTAMP: u64 = 1;
 
@@ -51,3 +87,372 @@ where
 
     (call_response, time)
 }
+
+/// Convert a unix timestamp to the TAI64 format returned by Sway's `timestamp()`
+pub fn unix_to_tai64(unix_time: u64) -> u64 {
+  unix_time + (1 << 62) + 10
+}
+
+/// Produce a block at the given unix time, so following transactions run no earlier than it
+pub async fn produce_block_at(wallet: &WalletUnlocked, unix_time: i64) {
+  let time = TimeParameters {
+    start_time: Utc.timestamp_opt(unix_time, 0).unwrap(),
+    block_time_interval: Duration::seconds(1),
+  };
+
+  wallet.get_provider().unwrap().produce_blocks(1, Some(time)).await.unwrap();
+}
+
+/// Storage for deploying a contract that only `deployer` can initialize, which it reads from its first slot
+pub fn deployer_storage(deployer: &WalletUnlocked) -> StorageConfiguration {
+  let slot = StorageSlot::new(Bytes32::zeroed(), deployer.address().hash());
+  StorageConfiguration::with_manual_storage(Some(vec![slot]))
+}
+
+/// Run a fee change through the vault's timelock. Starting from the unix time `now`, queues it
+/// with `queue` for an eta just past the fee change delay, moves the chain past the eta, and
+/// executes it. The calls come from each harness's own vault bindings.
+/// Returns the unix time the change was executed at.
+pub async fn run_fee_change<Q, E>(
+  wallet: &WalletUnlocked,
+  now: i64,
+  queue: impl FnOnce(u64) -> ContractCallHandler<Q>,
+  execute: ContractCallHandler<E>,
+) -> i64
+where
+  Q: Tokenizable + Debug,
+  E: Tokenizable + Debug,
+{
+  produce_block_at(wallet, now).await;
+  queue(unix_to_tai64(now as u64 + MIN_FEE_CHANGE_DELAY + 60)).call().await.unwrap();
+
+  let executed_at = now + MIN_FEE_CHANGE_DELAY as i64 + 120;
+  produce_block_at(wallet, executed_at).await;
+  execute.call().await.unwrap();
+
+  executed_at
+}
+
+/// Deploy a token contract at the address picked by `salt` and mint its supply to the wallet.
+/// `mint_calls` builds the initialize and mint calls from the harness's own token bindings.
+pub async fn deploy_token<I, M>(
+  wallet: &WalletUnlocked,
+  salt: u8,
+  mint_calls: impl FnOnce(&Bech32ContractId) -> (ContractCallHandler<I>, ContractCallHandler<M>),
+) -> AssetId
+where
+  I: Tokenizable + Debug,
+  M: Tokenizable + Debug,
+{
+  let token_contract_id = Contract::deploy_with_parameters(
+    "../token_contract/out/debug/token_contract.bin",
+    wallet,
+    TxParameters::default(),
+    StorageConfiguration::new(None, None),
+    Salt::from([salt; 32]),
+  )
+  .await
+  .unwrap();
+
+  let (initialize, mint) = mint_calls(&token_contract_id);
+  initialize.call().await.unwrap();
+  mint.append_variable_outputs(1).call().await.unwrap();
+
+  AssetId::new(*token_contract_id.hash())
+}
+
+/// What a constant product pool with a 0.3% fee would pay
+pub fn constant_product_amount_out(amount_in: u64, reserve_in: u64, reserve_out: u64) -> u64 {
+  let amount_in_with_fee = amount_in as u128 * 9_970;
+  (amount_in_with_fee * reserve_out as u128 / (reserve_in as u128 * 10_000 + amount_in_with_fee)) as u64
+}
+
+/// Find the log IDs the compiler assigned to the Sway struct named `type_name` in the ABI file
+fn get_log_ids(abi_path: &str, type_name: &str) -> Vec<u64> {
+  let abi: Value = serde_json::from_str(&fs::read_to_string(abi_path).unwrap()).unwrap();
+  let struct_name = format!("struct {}", type_name);
+
+  let type_ids: Vec<u64> = abi["types"]
+    .as_array()
+    .unwrap()
+    .iter()
+    .filter(|declaration| declaration["type"] == struct_name.as_str())
+    .map(|declaration| declaration["typeId"].as_u64().unwrap())
+    .collect();
+
+  abi["loggedTypes"]
+    .as_array()
+    .into_iter()
+    .flatten()
+    .filter(|logged_type| type_ids.contains(&logged_type["loggedType"]["type"].as_u64().unwrap()))
+    .map(|logged_type| logged_type["logId"].as_u64().unwrap())
+    .collect()
+}
+
+/// Decode all logs of type `T` emitted by `contract_id` during a call.
+///
+/// `FuelCallResponse::get_logs_with_type` only knows about the logs of the contract that was
+/// called, so this reads the log IDs from the emitting contract's ABI instead. This lets tests
+/// decode logs from contracts reached through the router, registry or vault.
+pub fn get_logs<T, D>(
+  response: &FuelCallResponse<D>,
+  contract_id: &Bech32ContractId,
+  abi_path: &str,
+) -> Vec<T>
+where
+  T: Tokenizable + Parameterize,
+  D: Tokenizable + Debug,
+{
+  // Generated types share the name of the Sway struct
+  let type_name = type_name::<T>().rsplit("::").next().unwrap();
+  let log_ids = get_log_ids(abi_path, type_name);
+  let contract_id = ContractId::from(contract_id);
+
+  response.receipts
+    .iter()
+    .filter_map(|receipt| match receipt {
+      Receipt::LogData { id, rb, data, .. } if *id == contract_id && log_ids.contains(rb) => {
+        Some(data.clone())
+      }
+      _ => None,
+    })
+    .map(|data| {
+      let token = ABIDecoder::decode_single(&T::param_type(), &data).unwrap();
+      T::from_token(token).unwrap()
+    })
+    .collect()
+}
+
+/// Mirror of the Sway `SwapOrder`, filled by the swap order script from a coin held by the swap order predicate
+#[derive(Clone, Debug)]
+pub struct SwapOrder {
+  pub utxo_id: UtxoId,
+  pub asset_in: AssetId,
+  pub amount_in: u64,
+  pub router: ContractId,
+  pub pool: ContractId,
+  pub min_amount_out: u64,
+  pub recipient: Address,
+}
+
+impl SwapOrder {
+  /// The order as laid out in Sway, used as the script data and in the signed message
+  pub fn encode(&self) -> Vec<u8> {
+    let mut bytes = vec![];
+    bytes.extend_from_slice(self.utxo_id.tx_id().as_ref());
+    bytes.extend_from_slice(&(self.utxo_id.output_index() as u64).to_be_bytes());
+    bytes.extend_from_slice(self.asset_in.as_ref());
+    bytes.extend_from_slice(&self.amount_in.to_be_bytes());
+    bytes.extend_from_slice(self.router.as_ref());
+    bytes.extend_from_slice(self.pool.as_ref());
+    bytes.extend_from_slice(&self.min_amount_out.to_be_bytes());
+    bytes.extend_from_slice(self.recipient.as_ref());
+    bytes
+  }
+}
+
+/// The swap order predicate's bytecode for an owner. Each owner has their own predicate, and
+/// so their own predicate address to send coins to.
+pub fn swap_order_predicate(owner: &Bech32Address) -> Vec<u8> {
+  let mut code = fs::read(SWAP_ORDER_PREDICATE).unwrap();
+  let position = code
+    .windows(32)
+    .position(|window| window == SWAP_ORDER_OWNER_PLACEHOLDER)
+    .expect("Owner placeholder not found in the predicate");
+  code[position..position + 32].copy_from_slice(owner.hash().as_ref());
+  code
+}
+
+pub fn swap_order_predicate_address(owner: &Bech32Address) -> Bech32Address {
+  Predicate::new(swap_order_predicate(owner)).address().clone()
+}
+
+/// Build an order selling one of the owner's predicate coins of `asset_in` in a router pool
+pub async fn get_swap_order(
+  provider: &Provider,
+  owner: &Bech32Address,
+  asset_in: AssetId,
+  router: &Bech32ContractId,
+  pool: &Bech32ContractId,
+  min_amount_out: u64,
+  recipient: &Bech32Address,
+) -> SwapOrder {
+  let coins = provider.get_coins(&swap_order_predicate_address(owner), asset_in).await.unwrap();
+  let coin = coins.get(0).expect("No coins held by the predicate");
+
+  SwapOrder {
+    utxo_id: UtxoId::from(coin.utxo_id.clone()),
+    asset_in: asset_in,
+    amount_in: coin.amount.0,
+    router: ContractId::from(router),
+    pool: ContractId::from(pool),
+    min_amount_out: min_amount_out,
+    recipient: Address::from(recipient),
+  }
+}
+
+/// Sign an order, and the script allowed to fill it, as the predicate expects
+pub async fn sign_swap_order(wallet: &WalletUnlocked, order: &SwapOrder) -> Signature {
+  let script = fs::read(SWAP_ORDER_SCRIPT).unwrap();
+  let mut message = Hasher::hash(&script).to_vec();
+  message.extend(order.encode());
+
+  wallet.sign_message(message).await.unwrap()
+}
+
+/// Outputs for filling an order: one for each contract the fill calls, change to the order's
+/// recipient, and a variable output for the pool to pay the recipient
+pub fn swap_order_outputs(order: &SwapOrder, contracts: &[Bech32ContractId]) -> Vec<Output> {
+  let mut outputs: Vec<Output> = (0..contracts.len())
+    .map(|i| Output::contract(i as u8 + 1, Bytes32::zeroed(), Bytes32::zeroed()))
+    .collect();
+  outputs.push(Output::change(order.recipient, 0, order.asset_in));
+  outputs.push(Output::variable(Address::zeroed(), 0, AssetId::default()));
+  outputs
+}
+
+/// Submit a transaction filling an order with the given outputs. The order's coin is the only
+/// coin input and the gas price is zero, so the owner pays nothing. A relayer paying gas on a
+/// live network would add its own coin and change output for the fee.
+pub async fn send_swap_order_fill(
+  provider: &Provider,
+  owner: &Bech32Address,
+  order: &SwapOrder,
+  signature: &Signature,
+  contracts: &[Bech32ContractId],
+  outputs: Vec<Output>,
+) -> Result<Vec<Receipt>, Error> {
+  let mut inputs = vec![Input::coin_predicate(
+    order.utxo_id,
+    Address::from(&swap_order_predicate_address(owner)),
+    order.amount_in,
+    order.asset_in,
+    TxPointer::default(),
+    0,
+    swap_order_predicate(owner),
+    signature.to_vec(),
+  )];
+  inputs.extend(contracts.iter().map(|contract_id| Input::contract(
+    UtxoId::new(Bytes32::zeroed(), 0),
+    Bytes32::zeroed(),
+    Bytes32::zeroed(),
+    TxPointer::default(),
+    ContractId::from(contract_id),
+  )));
+
+  let tx = Transaction::script(
+    0,
+    100_000_000,
+    0,
+    fs::read(SWAP_ORDER_SCRIPT).unwrap(),
+    order.encode(),
+    inputs,
+    outputs,
+    vec![],
+  );
+
+  provider.send_transaction(&tx).await
+}
+
+/// Fill an order through the router, calling `contracts`
+pub async fn fill_swap_order(
+  provider: &Provider,
+  owner: &Bech32Address,
+  order: &SwapOrder,
+  signature: &Signature,
+  contracts: &[Bech32ContractId],
+) -> Result<Vec<Receipt>, Error> {
+  send_swap_order_fill(provider, owner, order, signature, contracts, swap_order_outputs(order, contracts)).await
+}
+
+/// Amounts returned by the add liquidity script, mirroring the router's `LiquidityOutput`
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScriptLiquidityOutput {
+  pub amount_0: u64,
+  pub amount_1: u64,
+  pub liquidity: u64,
+}
+
+/// An `Identity` as laid out in Sway
+fn encode_identity(identity: &Identity) -> Vec<u8> {
+  let (variant, value): (u64, [u8; 32]) = match identity {
+    Identity::Address(address) => (0, **address),
+    Identity::ContractId(contract_id) => (1, **contract_id),
+  };
+  let mut bytes = variant.to_be_bytes().to_vec();
+  bytes.extend_from_slice(&value);
+  bytes
+}
+
+/// Add liquidity to a pair pool in one transaction, sending the wallet's coins straight to the
+/// pool from the add liquidity script. `contracts` must start with the pool, followed by any
+/// contracts it calls. Coins the pool doesn't use are returned to the wallet as change.
+pub async fn add_liquidity_with_script(
+  wallet: &WalletUnlocked,
+  tokens: (AssetId, AssetId),
+  amount_0_desired: u64,
+  amount_1_desired: u64,
+  amount_0_min: u64,
+  amount_1_min: u64,
+  recipient: Identity,
+  contracts: &[Bech32ContractId],
+) -> Result<ScriptLiquidityOutput, Error> {
+  let pool = contracts.get(0).expect("The pool must be the first contract");
+  let (token0, token1) = tokens;
+
+  let mut script_data = pool.hash().to_vec();
+  for amount in [amount_0_desired, amount_1_desired, amount_0_min, amount_1_min] {
+    script_data.extend_from_slice(&amount.to_be_bytes());
+  }
+  script_data.extend(encode_identity(&recipient));
+
+  let mut inputs = wallet.get_asset_inputs_for_amount(token0, amount_0_desired, 0).await?;
+  inputs.extend(wallet.get_asset_inputs_for_amount(token1, amount_1_desired, 0).await?);
+  let coin_count = inputs.len();
+  inputs.extend(contracts.iter().map(|contract_id| Input::contract(
+    UtxoId::new(Bytes32::zeroed(), 0),
+    Bytes32::zeroed(),
+    Bytes32::zeroed(),
+    TxPointer::default(),
+    ContractId::from(contract_id),
+  )));
+
+  let mut outputs: Vec<Output> = (0..contracts.len())
+    .map(|i| Output::contract((coin_count + i) as u8, Bytes32::zeroed(), Bytes32::zeroed()))
+    .collect();
+  outputs.push(Output::change(wallet.address().into(), 0, token0));
+  outputs.push(Output::change(wallet.address().into(), 0, token1));
+  // For the LP tokens
+  outputs.push(Output::variable(Address::zeroed(), 0, AssetId::default()));
+
+  let mut tx = Transaction::script(
+    0,
+    100_000_000,
+    0,
+    fs::read(ADD_LIQUIDITY_SCRIPT).unwrap(),
+    script_data,
+    inputs,
+    outputs,
+    vec![],
+  );
+  wallet.sign_transaction(&mut tx).await?;
+
+  let receipts = wallet.get_provider()?.send_transaction(&tx).await?;
+
+  // The script's own return data has no contract ID
+  let data = receipts
+    .iter()
+    .rev()
+    .find_map(|receipt| match receipt {
+      Receipt::ReturnData { id, data, .. } if *id == ContractId::zeroed() => Some(data.clone()),
+      _ => None,
+    })
+    .expect("No return data from the script");
+  let word = |i: usize| u64::from_be_bytes(data[i * 8..(i + 1) * 8].try_into().unwrap());
+
+  Ok(ScriptLiquidityOutput {
+    amount_0: word(0),
+    amount_1: word(1),
+    liquidity: word(2),
+  })
+}
@@ -3,8 +3,10 @@ library exchange_abi;
 use std::{
     contract_id::ContractId,
     identity::Identity,
+    vec::Vec,
 };
 
+/// Fees are in millionths of the swap input (10,000 = 1%), and decrease by `change_rate` each second.
 pub struct VaultFee {
     start_time: u32,
     start_fee: u16,
@@ -12,12 +14,171 @@ pub struct VaultFee {
     change_rate: u16,
 }
 
+/// Protocol fees withdrawn from a single pool.
+pub struct ClaimedFees {
+    pool: b256,
+    token0: b256,
+    token1: b256,
+    amount_0: u64,
+    amount_1: u64,
+}
+
+/// A fee schedule waiting for its timelock to pass.
+pub struct PendingFeeChange {
+    start_fee: u16,
+    change_rate: u16,
+    /// Earliest time the change can be executed
+    eta: u64,
+    /// Removes the pool's override instead of replacing it
+    clear: bool,
+    queued: bool,
+}
+
+/// How collected fees are converted into the governance token.
+pub struct BuybackConfig {
+    router: b256,
+    /// Token that fees are converted into
+    token: b256,
+    /// Receives the bought tokens, normally the staking contract. Tokens only leave circulation if the
+    /// recipient burns them, since the vault can't burn through the token contract. Can't be the zero
+    /// address, so tokens are never sent there in place of a burn.
+    recipient: Identity,
+    /// Minimum time covered by each pool's TWAP, in seconds, which must be nonzero
+    twap_period: u64,
+    /// Maximum shortfall from the TWAP quote in basis points, which must cover swap fees and price impact
+    max_slippage: u64,
+}
+
+/// A recipient of distributed fees, with its share in basis points.
+pub struct FeeRecipient {
+    recipient: Identity,
+    share: u64,
+}
+
+// Events
+
+/// Logged once for each asset claimed in a batch, with the total across its pools.
+pub struct FeesClaimed {
+    asset: b256,
+    amount: u64,
+}
+
+pub struct PoolsClaimed {
+    pools_claimed: u64,
+    pools_skipped: u64,
+}
+
+pub struct TreasuryWithdrawal {
+    asset: b256,
+    amount: u64,
+    recipient: Identity,
+}
+
+pub struct FeeRecipientsUpdated {
+    recipient_count: u64,
+}
+
+pub struct FeesDistributed {
+    asset: b256,
+    amount: u64,
+    recipient_count: u64,
+}
+
+/// `pool` is zero for changes to the global fee schedule
+pub struct FeeChangeQueued {
+    pool: b256,
+    start_fee: u16,
+    change_rate: u16,
+    clear: bool,
+    eta: u64,
+}
+
+pub struct FeeChangeExecuted {
+    pool: b256,
+    start_fee: u16,
+    change_rate: u16,
+    clear: bool,
+}
+
+pub struct FeeChangeCancelled {
+    pool: b256,
+}
+
+pub struct Buyback {
+    asset: b256,
+    amount_in: u64,
+    token: b256,
+    amount_out: u64,
+    recipient: Identity,
+}
+
 abi Vault {
     ////////////////////
     // Read only
     ////////////////////
-    /// Get information on the liquidity pool.
+    /// Get the global fee schedule.
     #[storage(read)]fn get_fees() -> VaultFee;
-    #[storage(read, write)]fn set_fees(start_fee: u16, change_rate: u16);
+    /// Get the fee schedule used by a pool, which is its override if set, or the global schedule.
+    #[storage(read)]fn get_fees_for(pool: b256) -> VaultFee;
+    #[storage(read)]fn has_pool_fees(pool: b256) -> bool;
+    #[storage(read)]fn get_owner() -> Identity;
+    #[storage(read)]fn get_pending_owner() -> Identity;
+    #[storage(read)]fn get_registry() -> b256;
+    /// Amount of the asset held by the vault.
+    fn get_balance(asset: b256) -> u64;
+    #[storage(read)]fn get_fee_recipients() -> Vec<FeeRecipient>;
+    /// Highest start fee or change rate the vault accepts, 50,000 (5%).
+    fn get_max_protocol_fee() -> u16;
+    /// Minimum time between queueing and executing a fee change.
+    #[storage(read)]fn get_fee_change_delay() -> u64;
+    #[storage(read)]fn get_pending_fee_change() -> PendingFeeChange;
+    #[storage(read)]fn get_pending_pool_fee_change(pool: b256) -> PendingFeeChange;
+    #[storage(read)]fn get_buyback_config() -> BuybackConfig;
+
+    ////////////////////
+    // Owner only
+    ////////////////////
+    /// Set the owner and the fee change delay, can only be called once by the deployer stored at deploy.
+    /// The delay must be at least 48 hours.
+    #[storage(read, write)]fn initialize(owner: Identity, fee_change_delay: u64);
+    /// Announce a global fee change, which can be executed once `eta` has passed.
+    /// `eta` must be at least the fee change delay from now.
+    #[storage(read, write)]fn queue_fee_change(start_fee: u16, change_rate: u16, eta: u64);
+    #[storage(read, write)]fn execute_fee_change();
+    #[storage(read, write)]fn cancel_fee_change();
+    /// Announce a fee override for a single pool, replacing the global schedule for it.
+    #[storage(read, write)]fn queue_pool_fee_change(pool: b256, start_fee: u16, change_rate: u16, eta: u64);
+    /// Announce the removal of a pool's override, returning it to the global fee schedule.
+    /// Executed and cancelled like any other pool fee change.
+    #[storage(read, write)]fn queue_pool_fee_clear(pool: b256, eta: u64);
+    #[storage(read, write)]fn execute_pool_fee_change(pool: b256);
+    #[storage(read, write)]fn cancel_pool_fee_change(pool: b256);
+    #[storage(read, write)]fn set_buyback_config(config: BuybackConfig);
+    /// Swap `amount` of the vault's `asset` into the buyback token along `pools`, which must all be registered,
+    /// and send the result to the buyback recipient. Reverts if the output falls short of the TWAP quote.
+    #[storage(read)]fn buyback(asset: b256, amount: u64, pools: Vec<b256>) -> u64;
+    /// Start an ownership transfer, which only takes effect once accepted by the new owner.
+    #[storage(read, write)]fn transfer_ownership(new_owner: Identity);
+    #[storage(read, write)]fn accept_ownership();
+    /// Permanently give up ownership, freezing the current fees.
+    #[storage(read, write)]fn renounce_ownership();
+    /// Set the registry used by claim_all, which must point its pools at this vault.
+    #[storage(read, write)]fn set_registry(registry: b256);
+    #[storage(read)]fn withdraw(asset: b256, amount: u64, recipient: Identity);
+    /// Replace the recipients used by distribute, shares must add up to 10,000 basis points.
+    #[storage(read, write)]fn set_fee_recipients(recipients: Vec<FeeRecipient>);
+    /// Set how long a pool uses its cached fees before swaps refresh them from the vault.
+    #[storage(read)]fn set_pool_max_fee_cache_age(pool: b256, max_age: u64);
+    /// Configure a pool's dynamic LP fee, with fees in basis points. See the exchange's `DynamicFeeConfig`.
+    #[storage(read)]fn set_pool_dynamic_fee(pool: b256, enabled: bool, min_fee: u64, max_fee: u64, max_volatility: u64, lookback: u64);
+
     fn claim_fees(pool: b256);
+    fn claim_fees_batch(pools: Vec<b256>) -> Vec<ClaimedFees>;
+    /// Claim from `count` pools of the registry's pool list, starting at `start`.
+    /// Delisted pools are skipped.
+    #[storage(read)]fn claim_all(start: u64, count: u64) -> Vec<ClaimedFees>;
+    /// Split the vault's whole balance of the asset between the fee recipients. Only callable by the owner.
+    #[storage(read)]fn distribute(asset: b256);
+    /// Make each pool re-cache the vault's current fees, so fee changes apply immediately.
+    fn sync_pools(pools: Vec<b256>);
 }
@@ -5,22 +5,85 @@ use std::{
     block::timestamp,
     call_frames::contract_id,
     constants::ZERO_B256,
+    context::this_balance,
     contract_id::ContractId,
     identity::Identity,
+    logging::log,
+    option::Option,
+    storage::{get, StorageMap, StorageVec},
+    token::transfer,
+    u128::U128,
+    u256::U256,
+    vec::Vec,
 };
 
-use exchange_abi::{Exchange};
-use vault_abi::{Vault, VaultFee};
+use exchange_abi::{DynamicFeeConfig, Exchange};
+use microchain_helpers::{calculate_vault_fee};
+use registry_abi::{PoolRegistry};
+use router_abi::{Router};
+use vault_abi::{
+    Buyback,
+    BuybackConfig,
+    ClaimedFees,
+    FeeChangeCancelled,
+    FeeChangeExecuted,
+    FeeChangeQueued,
+    FeeRecipient,
+    FeeRecipientsUpdated,
+    FeesClaimed,
+    FeesDistributed,
+    PendingFeeChange,
+    PoolsClaimed,
+    TreasuryWithdrawal,
+    Vault,
+    VaultFee,
+};
 
 enum Error {
     MustBeCalledByOwner: (),
+    MustBeCalledByDeployer: (),
+    MustBeCalledByPendingOwner: (),
+    AlreadyInitialized: (),
+    RegistryNotSet: (),
+    InvalidRegistry: (),
+    InsufficientBalance: (),
+    InvalidShares: (),
+    NoFeeRecipients: (),
+    FeeChangeDelayTooShort: (),
+    EtaTooSoon: (),
+    NoPendingFeeChange: (),
+    FeeChangeNotReady: (),
+    FeeTooHigh: (),
+    ChangeRateTooHigh: (),
+    BuybackNotConfigured: (),
+    InvalidBuybackConfig: (),
+    InvalidPath: (),
+    PoolNotRegistered: (),
+    TWAPPeriodTooShort: (),
+    InsufficientTWAPHistory: (),
 }
 
-
 ////////////////////////////////////////
-// Helper functions
+// Constants
 ////////////////////////////////////////
 
+/// Address allowed to initialize the vault, set at deploy so the first owner can't be claimed by anyone else
+const DEPLOYER_SLOT = 0x0000000000000000000000000000000000000000000000000000000000000000;
+
+/// Fee recipient shares are in basis points
+const TOTAL_SHARES = 10_000;
+
+/// Protocol fees are in millionths of the swap input, so 10,000 = 1%.
+/// Capped at 5%, far below the 1,000,000 that would break the router's exact output math.
+/// Change rates (fee decrease per second) share the same cap.
+const MAX_PROTOCOL_FEE: u16 = 50_000;
+
+/// Fee changes must be announced at least 48 hours ahead
+const MIN_FEE_CHANGE_DELAY = 172_800;
+
+/// Must match the precision of the exchange's cumulative prices
+const TWAP_PERCISION = U256::from((0, 0, 0, 1000000000));
+
 struct StoredFees {
     start_time: u32,
     start_fee: u16,
@@ -29,49 +92,493 @@ struct StoredFees {
 
 
 storage {
+    // Kept separately from the owner, so a renounced vault can't be initialized again
+    initialized: bool = false,
+    // Set once on initialization, so the notice period can't be shortened later
+    fee_change_delay: u64 = 0,
     owner: Identity = Identity::Address(Address::from(ZERO_B256)),
+    pending_owner: Identity = Identity::Address(Address::from(ZERO_B256)),
+    registry: b256 = ZERO_B256,
+    fee_recipients: StorageVec<FeeRecipient> = StorageVec {},
     fees: StoredFees = StoredFees {
         start_time: 0,
         start_fee: 0,
         change_rate: 0,
     },
+    pool_fees: StorageMap<b256, StoredFees> = StorageMap {},
+    has_pool_fees: StorageMap<b256, bool> = StorageMap {},
+    // Keyed by pool, with ZERO_B256 for the global schedule
+    pending_fee_changes: StorageMap<b256, PendingFeeChange> = StorageMap {},
+    buyback_config: BuybackConfig = BuybackConfig {
+        router: ZERO_B256,
+        token: ZERO_B256,
+        recipient: Identity::Address(Address::from(ZERO_B256)),
+        twap_period: 0,
+        max_slippage: 0,
+    },
+}
+
+////////////////////////////////////////
+// Helper functions
+////////////////////////////////////////
+
+#[storage(read)]
+fn validate_deployer() {
+    let sender: Result<Identity, AuthError> = msg_sender();
+    require(
+        sender.unwrap() == Identity::Address(Address::from(get::<b256>(DEPLOYER_SLOT))),
+        Error::MustBeCalledByDeployer
+    );
+}
+
+#[storage(read)]
+fn validate_owner() {
+    let sender: Result<Identity, AuthError> = msg_sender();
+    let owner = storage.owner;
+    require(
+        sender.unwrap() == owner && owner != Identity::Address(Address::from(ZERO_B256)),
+        Error::MustBeCalledByOwner
+    );
+}
+
+fn validate_fees(start_fee: u16, change_rate: u16) {
+    require(start_fee <= MAX_PROTOCOL_FEE, Error::FeeTooHigh);
+    require(change_rate <= MAX_PROTOCOL_FEE, Error::ChangeRateTooHigh);
+}
+
+fn no_pending_fee_change() -> PendingFeeChange {
+    PendingFeeChange {
+        start_fee: 0,
+        change_rate: 0,
+        eta: 0,
+        clear: false,
+        queued: false,
+    }
+}
+
+#[storage(read, write)]
+fn queue_change(pool: b256, start_fee: u16, change_rate: u16, clear: bool, eta: u64) {
+    validate_fees(start_fee, change_rate);
+    require(eta >= timestamp() + storage.fee_change_delay, Error::EtaTooSoon);
+
+    storage.pending_fee_changes.insert(pool, PendingFeeChange {
+        start_fee: start_fee,
+        change_rate: change_rate,
+        eta: eta,
+        clear: clear,
+        queued: true,
+    });
+
+    log(FeeChangeQueued {
+        pool: pool,
+        start_fee: start_fee,
+        change_rate: change_rate,
+        clear: clear,
+        eta: eta,
+    });
+}
+
+#[storage(read, write)]
+fn execute_change(pool: b256) {
+    let change = storage.pending_fee_changes.get(pool);
+    require(change.queued, Error::NoPendingFeeChange);
+    require(timestamp() >= change.eta, Error::FeeChangeNotReady);
+
+    let fees = StoredFees {
+        start_time: timestamp(),
+        start_fee: change.start_fee,
+        change_rate: change.change_rate,
+    };
+    if pool == ZERO_B256 {
+        storage.fees = fees;
+    } else if change.clear {
+        storage.has_pool_fees.insert(pool, false);
+    } else {
+        storage.pool_fees.insert(pool, fees);
+        storage.has_pool_fees.insert(pool, true);
+    }
+    storage.pending_fee_changes.insert(pool, no_pending_fee_change());
+
+    log(FeeChangeExecuted {
+        pool: pool,
+        start_fee: change.start_fee,
+        change_rate: change.change_rate,
+        clear: change.clear,
+    });
+}
+
+#[storage(read, write)]
+fn cancel_change(pool: b256) {
+    require(storage.pending_fee_changes.get(pool).queued, Error::NoPendingFeeChange);
+    storage.pending_fee_changes.insert(pool, no_pending_fee_change());
+
+    log(FeeChangeCancelled {
+        pool: pool,
+    });
+}
+
+/// Average price of one input token in output tokens, scaled by TWAP_PERCISION.
+/// Averaged from the pool's oldest stored observation until now, which must be at least `min_period` ago.
+/// A single observation would only measure the current reserves, so at least two are required.
+fn get_twap_price(pool: b256, input_is_token0: bool, min_period: u64) -> U256 {
+    let exchange = abi(Exchange, pool);
+    let twap_info = exchange.get_twap_info();
+    let pool_info = exchange.get_pool_info();
+    require(twap_info.buffer_size > 1, Error::InsufficientTWAPHistory);
+
+    // The slot after the current one is the oldest, or slot 0 if the buffer hasn't wrapped yet
+    let oldest = exchange.get_observation((twap_info.current_element + 1) % twap_info.buffer_size);
+    let latest = exchange.get_observation(twap_info.current_element);
+
+    let period = timestamp() - oldest.timestamp;
+    require(period >= min_period && period > 0, Error::TWAPPeriodTooShort);
+
+    // Current reserves have applied since the latest observation
+    let since_latest = U256::from((0, 0, 0, timestamp() - latest.timestamp));
+    let reserve0 = U256::from((0, 0, 0, pool_info.token_0_reserve));
+    let reserve1 = U256::from((0, 0, 0, pool_info.token_1_reserve));
+
+    let price_cumulative_change = if input_is_token0 {
+        latest.price_0_cumulative_last + reserve1 * TWAP_PERCISION * since_latest / reserve0 - oldest.price_0_cumulative_last
+    } else {
+        latest.price_1_cumulative_last + reserve0 * TWAP_PERCISION * since_latest / reserve1 - oldest.price_1_cumulative_last
+    };
+
+    price_cumulative_change / U256::from((0, 0, 0, period))
+}
+
+fn to_vault_fee(fees: StoredFees) -> VaultFee {
+    VaultFee {
+        start_time: fees.start_time,
+        start_fee: fees.start_fee,
+        current_fee: calculate_vault_fee(fees.start_fee, fees.change_rate, fees.start_time, timestamp()),
+        change_rate: fees.change_rate,
+    }
+}
+
+fn claim_pool(pool: b256) -> ClaimedFees {
+    let exchange = abi(Exchange, pool);
+    let (token0, token1) = exchange.get_tokens();
+    let (amount_0, amount_1) = exchange.withdraw_protocol_fees(Identity::ContractId(contract_id()));
+
+    ClaimedFees {
+        pool: pool,
+        token0: token0,
+        token1: token1,
+        amount_0: amount_0,
+        amount_1: amount_1,
+    }
+}
+
+/// Index of the asset's running total, if it has been claimed already
+fn find_claimed_total(totals: Vec<(b256, u64)>, asset: b256) -> Option<u64> {
+    let mut i = 0;
+    while i < totals.len() {
+        let (total_asset, _) = totals.get(i).unwrap();
+        if total_asset == asset {
+            return Option::Some(i);
+        }
+        i += 1;
+    }
+    Option::None
+}
+
+/// Log the totals claimed in each asset, in the order the assets were first claimed
+fn log_claims(claims: Vec<ClaimedFees>, skipped: u64) {
+    // Sum each asset in a single pass over both sides of every claim
+    let mut totals: Vec<(b256, u64)> = Vec::new();
+    let mut i = 0;
+    while i < claims.len() * 2 {
+        let claim = claims.get(i / 2).unwrap();
+        let (asset, amount) = if i % 2 == 0 {
+            (claim.token0, claim.amount_0)
+        } else {
+            (claim.token1, claim.amount_1)
+        };
+
+        match find_claimed_total(totals, asset) {
+            Option::Some(index) => {
+                let (_, total) = totals.get(index).unwrap();
+                totals.set(index, (asset, total + amount));
+            },
+            Option::None => {
+                totals.push((asset, amount));
+            },
+        }
+        i += 1;
+    }
+
+    let mut j = 0;
+    while j < totals.len() {
+        let (asset, amount) = totals.get(j).unwrap();
+        if amount > 0 {
+            log(FeesClaimed {
+                asset: asset,
+                amount: amount,
+            });
+        }
+        j += 1;
+    }
+
+    log(PoolsClaimed {
+        pools_claimed: claims.len(),
+        pools_skipped: skipped,
+    });
 }
 
 impl Vault for Contract {
     #[storage(read)]fn get_fees() -> VaultFee {
-        let fees = storage.fees;
-        let decrease_since_change = fees.change_rate * (timestamp() - fees.start_time);
-        let current_fee = if decrease_since_change > fees.start_fee {
-            0
+        to_vault_fee(storage.fees)
+    }
+
+    #[storage(read)]fn get_fees_for(pool: b256) -> VaultFee {
+        if storage.has_pool_fees.get(pool) {
+            to_vault_fee(storage.pool_fees.get(pool))
         } else {
-            fees.start_fee - decrease_since_change
-        };
+            to_vault_fee(storage.fees)
+        }
+    }
+
+    #[storage(read)]fn has_pool_fees(pool: b256) -> bool {
+        storage.has_pool_fees.get(pool)
+    }
+
+    #[storage(read)]fn get_owner() -> Identity {
+        storage.owner
+    }
+
+    #[storage(read)]fn get_pending_owner() -> Identity {
+        storage.pending_owner
+    }
+
+    #[storage(read)]fn get_registry() -> b256 {
+        storage.registry
+    }
 
-        VaultFee {
-            start_time: fees.start_time,
-            start_fee: fees.start_fee,
-            current_fee: current_fee,
-            change_rate: fees.change_rate,
+    fn get_balance(asset: b256) -> u64 {
+        this_balance(ContractId::from(asset))
+    }
+
+    #[storage(read)]fn get_fee_recipients() -> Vec<FeeRecipient> {
+        let mut recipients = Vec::new();
+        let mut i = 0;
+        while i < storage.fee_recipients.len() {
+            recipients.push(storage.fee_recipients.get(i).unwrap());
+            i += 1;
         }
+        recipients
     }
 
-    #[storage(read, write)]fn set_fees(start_fee: u16, change_rate: u16) {
-        let sender: Result<Identity, AuthError> = msg_sender();
+    fn get_max_protocol_fee() -> u16 {
+        MAX_PROTOCOL_FEE
+    }
 
-        let owner = storage.owner;
+    #[storage(read)]fn get_buyback_config() -> BuybackConfig {
+        storage.buyback_config
+    }
+
+    #[storage(read)]fn get_fee_change_delay() -> u64 {
+        storage.fee_change_delay
+    }
+
+    #[storage(read)]fn get_pending_fee_change() -> PendingFeeChange {
+        storage.pending_fee_changes.get(ZERO_B256)
+    }
+
+    #[storage(read)]fn get_pending_pool_fee_change(pool: b256) -> PendingFeeChange {
+        storage.pending_fee_changes.get(pool)
+    }
+
+    #[storage(read, write)]fn initialize(owner: Identity, fee_change_delay: u64) {
+        validate_deployer();
+        require(!storage.initialized, Error::AlreadyInitialized);
+        require(fee_change_delay >= MIN_FEE_CHANGE_DELAY, Error::FeeChangeDelayTooShort);
+        storage.initialized = true;
+        storage.owner = owner;
+        storage.fee_change_delay = fee_change_delay;
+    }
+
+    #[storage(read, write)]fn queue_fee_change(start_fee: u16, change_rate: u16, eta: u64) {
+        validate_owner();
+        queue_change(ZERO_B256, start_fee, change_rate, false, eta);
+    }
+
+    #[storage(read, write)]fn execute_fee_change() {
+        validate_owner();
+        execute_change(ZERO_B256);
+    }
+
+    #[storage(read, write)]fn cancel_fee_change() {
+        validate_owner();
+        cancel_change(ZERO_B256);
+    }
+
+    #[storage(read, write)]fn queue_pool_fee_change(pool: b256, start_fee: u16, change_rate: u16, eta: u64) {
+        validate_owner();
+        queue_change(pool, start_fee, change_rate, false, eta);
+    }
+
+    #[storage(read, write)]fn queue_pool_fee_clear(pool: b256, eta: u64) {
+        validate_owner();
+        queue_change(pool, 0, 0, true, eta);
+    }
+
+    #[storage(read, write)]fn execute_pool_fee_change(pool: b256) {
+        validate_owner();
+        execute_change(pool);
+    }
+
+    #[storage(read, write)]fn cancel_pool_fee_change(pool: b256) {
+        validate_owner();
+        cancel_change(pool);
+    }
+
+    #[storage(read, write)]fn set_buyback_config(config: BuybackConfig) {
+        validate_owner();
+        require(
+            config.router != ZERO_B256
+            && config.token != ZERO_B256
+            && config.recipient != Identity::Address(Address::from(ZERO_B256))
+            && config.recipient != Identity::ContractId(ContractId::from(ZERO_B256))
+            && config.twap_period > 0
+            && config.max_slippage <= TOTAL_SHARES,
+            Error::InvalidBuybackConfig
+        );
+        storage.buyback_config = config;
+    }
+
+    #[storage(read)]fn buyback(asset: b256, amount: u64, pools: Vec<b256>) -> u64 {
+        validate_owner();
+
+        let config = storage.buyback_config;
+        require(config.router != ZERO_B256, Error::BuybackNotConfigured);
+        require(amount <= this_balance(ContractId::from(asset)), Error::InsufficientBalance);
+        require(pools.len() > 0, Error::InvalidPath);
+
+        let registry_id = storage.registry;
+        require(registry_id != ZERO_B256, Error::RegistryNotSet);
+        let registry = abi(PoolRegistry, registry_id);
+
+        // Quote the whole path at TWAP prices
+        let mut input_asset = asset;
+        let mut quote = U256::from((0, 0, 0, amount));
+        let mut i = 0;
+        while i < pools.len() {
+            let pool = pools.get(i).unwrap();
+            require(registry.is_pool(pool), Error::PoolNotRegistered);
+
+            let (token0, token1) = abi(Exchange, pool).get_tokens();
+            require(token0 == input_asset || token1 == input_asset, Error::InvalidPath);
+
+            let input_is_token0 = token0 == input_asset;
+            quote = quote * get_twap_price(pool, input_is_token0, config.twap_period) / TWAP_PERCISION;
+            input_asset = if input_is_token0 { token1 } else { token0 };
+            i += 1;
+        }
+        require(input_asset == config.token, Error::InvalidPath);
+
+        let slippage = U256::from((0, 0, 0, TOTAL_SHARES - config.max_slippage));
+        let min_amount_out = (quote * slippage / U256::from((0, 0, 0, TOTAL_SHARES))).as_u64().unwrap();
+
+        let router = abi(Router, config.router);
+        let output = router.swap_exact_input_multihop {
+            asset_id: asset,
+            coins: amount,
+        }(pools, min_amount_out, config.recipient);
+
+        log(Buyback {
+            asset: asset,
+            amount_in: amount,
+            token: config.token,
+            amount_out: output.output_amount,
+            recipient: config.recipient,
+        });
+
+        output.output_amount
+    }
+
+    #[storage(read, write)]fn transfer_ownership(new_owner: Identity) {
+        validate_owner();
+        storage.pending_owner = new_owner;
+    }
+
+    #[storage(read, write)]fn accept_ownership() {
+        let sender: Result<Identity, AuthError> = msg_sender();
+        let pending_owner = storage.pending_owner;
         require(
-            sender.unwrap() == owner || owner == Identity::Address(Address::from(ZERO_B256)),
-            Error::MustBeCalledByOwner
+            sender.unwrap() == pending_owner && pending_owner != Identity::Address(Address::from(ZERO_B256)),
+            Error::MustBeCalledByPendingOwner
         );
-        if (owner == Identity::Address(Address::from(ZERO_B256))) {
-            storage.owner = sender.unwrap();
+
+        storage.owner = pending_owner;
+        storage.pending_owner = Identity::Address(Address::from(ZERO_B256));
+    }
+
+    #[storage(read, write)]fn renounce_ownership() {
+        validate_owner();
+        storage.owner = Identity::Address(Address::from(ZERO_B256));
+        storage.pending_owner = Identity::Address(Address::from(ZERO_B256));
+    }
+
+    #[storage(read, write)]fn set_registry(registry: b256) {
+        validate_owner();
+        let registry_contract = abi(PoolRegistry, registry);
+        require(registry_contract.get_vault() == contract_id().value, Error::InvalidRegistry);
+        storage.registry = registry;
+    }
+
+    #[storage(read)]fn withdraw(asset: b256, amount: u64, recipient: Identity) {
+        validate_owner();
+        require(amount <= this_balance(ContractId::from(asset)), Error::InsufficientBalance);
+
+        transfer(amount, ContractId::from(asset), recipient);
+
+        log(TreasuryWithdrawal {
+            asset: asset,
+            amount: amount,
+            recipient: recipient,
+        });
+    }
+
+    #[storage(read, write)]fn set_fee_recipients(recipients: Vec<FeeRecipient>) {
+        validate_owner();
+
+        let mut total_shares = 0;
+        let mut i = 0;
+        while i < recipients.len() {
+            let recipient = recipients.get(i).unwrap();
+            require(recipient.share > 0, Error::InvalidShares);
+            total_shares += recipient.share;
+            i += 1;
         }
+        require(total_shares == TOTAL_SHARES, Error::InvalidShares);
 
-        storage.fees = StoredFees {
-            start_time: timestamp(),
-            start_fee: start_fee,
-            change_rate: change_rate,
+        storage.fee_recipients.clear();
+        let mut i = 0;
+        while i < recipients.len() {
+            storage.fee_recipients.push(recipients.get(i).unwrap());
+            i += 1;
         }
+
+        log(FeeRecipientsUpdated {
+            recipient_count: recipients.len(),
+        });
+    }
+
+    #[storage(read)]fn set_pool_max_fee_cache_age(pool: b256, max_age: u64) {
+        validate_owner();
+        abi(Exchange, pool).set_max_fee_cache_age(max_age);
+    }
+
+    #[storage(read)]fn set_pool_dynamic_fee(pool: b256, enabled: bool, min_fee: u64, max_fee: u64, max_volatility: u64, lookback: u64) {
+        validate_owner();
+        abi(Exchange, pool).set_dynamic_fee(DynamicFeeConfig {
+            enabled: enabled,
+            min_fee: min_fee,
+            max_fee: max_fee,
+            max_volatility: max_volatility,
+            lookback: lookback,
+        });
     }
 
     // Note: can call withdraw_protocol_fees on any contract, but there's no vulnerability to the vault
@@ -79,4 +586,90 @@ impl Vault for Contract {
         let exchange = abi(Exchange, pool);
         exchange.withdraw_protocol_fees(Identity::ContractId(contract_id()));
     }
+
+    fn sync_pools(pools: Vec<b256>) {
+        let mut i = 0;
+        while i < pools.len() {
+            let exchange = abi(Exchange, pools.get(i).unwrap());
+            exchange.cache_vault_fees();
+            i += 1;
+        }
+    }
+
+    fn claim_fees_batch(pools: Vec<b256>) -> Vec<ClaimedFees> {
+        let mut claims = Vec::new();
+        let mut i = 0;
+        while i < pools.len() {
+            claims.push(claim_pool(pools.get(i).unwrap()));
+            i += 1;
+        }
+
+        log_claims(claims, 0);
+        claims
+    }
+
+    #[storage(read)]fn claim_all(start: u64, count: u64) -> Vec<ClaimedFees> {
+        let registry_id = storage.registry;
+        require(registry_id != ZERO_B256, Error::RegistryNotSet);
+        let registry = abi(PoolRegistry, registry_id);
+
+        let pool_count = registry.get_pool_count();
+        // Compared without adding, so a huge count can't overflow
+        let end = if start > pool_count || count > pool_count - start {
+            pool_count
+        } else {
+            start + count
+        };
+
+        let mut claims = Vec::new();
+        let mut skipped = 0;
+        let mut i = start;
+        while i < end {
+            let pool = registry.get_pool_at(i);
+            if registry.is_pool(pool) {
+                claims.push(claim_pool(pool));
+            } else {
+                skipped += 1;
+            }
+            i += 1;
+        }
+
+        log_claims(claims, skipped);
+        claims
+    }
+
+    #[storage(read)]fn distribute(asset: b256) {
+        // The whole balance is split, so the owner decides when, leaving room for buybacks of the same asset
+        validate_owner();
+
+        let recipient_count = storage.fee_recipients.len();
+        require(recipient_count > 0, Error::NoFeeRecipients);
+
+        let asset_id = ContractId::from(asset);
+        let balance = this_balance(asset_id);
+        let mut remaining = balance;
+
+        let mut i = 0;
+        while i < recipient_count {
+            let fee_recipient = storage.fee_recipients.get(i).unwrap();
+            // The last recipient gets the rounding dust
+            let amount = if i == recipient_count - 1 {
+                remaining
+            } else {
+                (U128::from((0, balance)) * U128::from((0, fee_recipient.share)) / U128::from((0, TOTAL_SHARES))).as_u64().unwrap()
+            };
+
+            if amount > 0 {
+                transfer(amount, asset_id, fee_recipient.recipient);
+            }
+            remaining -= amount;
+            i += 1;
+        }
+
+        log(FeesDistributed {
+            asset: asset,
+            amount: balance,
+            recipient_count: recipient_count,
+        });
+    }
 }
@@ -1,24 +1,67 @@
+use std::str::FromStr;
+use chrono::Utc;
+use rand::{rngs::StdRng, Rng, SeedableRng};
 use fuels::{
     prelude::*,
     fuels_abigen::abigen,
     signers::WalletUnlocked,
+    tx::{AssetId, Bytes32, ContractId, StorageSlot},
 };
 use tokio::time::{sleep, Duration};
-use test_helpers::{get_timestamp_and_call, get_wallets};
+use test_helpers::{
+    deployer_storage,
+    get_logs,
+    get_timestamp_and_call,
+    get_wallets,
+    produce_block_at,
+    run_fee_change,
+    unix_to_tai64,
+    EXCHANGE_ABI,
+    MIN_FEE_CHANGE_DELAY,
+    VAULT_ABI,
+};
 
 ///////////////////////////////
 // Load the Vault Contract abi
 ///////////////////////////////
 abigen!(TestVault, "./out/debug/vault_contract-abi.json");
 
+abigen!(Exchange, "../exchange_contract/out/debug/exchange_contract-abi.json");
+abigen!(
+    TestToken,
+    "../token_contract/out/debug/token_contract-abi.json"
+);
+abigen!(Registry, "../registry_contract/out/debug/registry_contract-abi.json");
+
 struct Fixture {
     wallet: WalletUnlocked,
+    other_wallet: WalletUnlocked,
+    vault_contract_id: Bech32ContractId,
     vault_instance: TestVault,
+    other_vault_instance: TestVault,
+}
+
+fn to_9_decimal(num: u64) -> u64 {
+    num * 1_000_000_000
 }
 
 async fn setup() -> Fixture {
+    let fixture = setup_uninitialized().await;
+
+    fixture.vault_instance
+        .methods()
+        .initialize(Identity::Address(fixture.wallet.address().into()), MIN_FEE_CHANGE_DELAY)
+        .call()
+        .await
+        .unwrap();
+
+    fixture
+}
+
+async fn setup_uninitialized() -> Fixture {
     let mut wallets = get_wallets().await;
     let wallet = wallets.pop().unwrap();
+    let other_wallet = wallets.pop().unwrap();
 
     //////////////////////////////////////////
     // Setup contracts
@@ -28,29 +71,237 @@ async fn setup() -> Fixture {
         "./out/debug/vault_contract.bin",
         &wallet,
         TxParameters::default(),
-        StorageConfiguration::new(None, None),
+        deployer_storage(&wallet),
     )
     .await
     .unwrap();
 
-    let vault_instance = TestVault::new(vault_contract_id, wallet.clone());
+    let vault_instance = TestVault::new(vault_contract_id.clone(), wallet.clone());
+    let other_vault_instance = TestVault::new(vault_contract_id.clone(), other_wallet.clone());
 
     Fixture {
         wallet: wallet,
+        other_wallet: other_wallet,
+        vault_contract_id: vault_contract_id,
         vault_instance: vault_instance,
+        other_vault_instance: other_vault_instance,
     }
 }
 
+fn zero_identity() -> Identity {
+    Identity::Address(Address::from([0u8; 32]))
+}
+
+/// Change the vault's global fees through the timelock, returning the unix time it was executed at
+async fn set_vault_fees(fixture: &Fixture, start_fee: u16, change_rate: u16, now: i64) -> i64 {
+    let methods = fixture.vault_instance.methods();
+    run_fee_change(
+        &fixture.wallet,
+        now,
+        |eta| methods.queue_fee_change(start_fee, change_rate, eta),
+        methods.execute_fee_change(),
+    )
+    .await
+}
+
+/// Give a pool its own fee schedule through the timelock, returning the unix time it was executed at
+async fn set_pool_fees(fixture: &Fixture, pool: Bits256, start_fee: u16, change_rate: u16, now: i64) -> i64 {
+    produce_block_at(&fixture.wallet, now).await;
+    fixture.vault_instance
+        .methods()
+        .queue_pool_fee_change(pool, start_fee, change_rate, unix_to_tai64(now as u64 + MIN_FEE_CHANGE_DELAY + 60))
+        .call()
+        .await
+        .unwrap();
+
+    execute_pool_fee_change(fixture, pool, now).await
+}
+
+/// Return a pool to the global fee schedule through the timelock
+async fn clear_pool_fees(fixture: &Fixture, pool: Bits256, now: i64) -> i64 {
+    produce_block_at(&fixture.wallet, now).await;
+    fixture.vault_instance
+        .methods()
+        .queue_pool_fee_clear(pool, unix_to_tai64(now as u64 + MIN_FEE_CHANGE_DELAY + 60))
+        .call()
+        .await
+        .unwrap();
+
+    execute_pool_fee_change(fixture, pool, now).await
+}
+
+async fn execute_pool_fee_change(fixture: &Fixture, pool: Bits256, queued_at: i64) -> i64 {
+    let now = queued_at + MIN_FEE_CHANGE_DELAY as i64 + 120;
+    produce_block_at(&fixture.wallet, now).await;
+    fixture.vault_instance
+        .methods()
+        .execute_pool_fee_change(pool)
+        .call()
+        .await
+        .unwrap();
+
+    now
+}
+
+/// Deploy a new token and an ETH/token pool using the fixture's vault, with the vault's fees cached
+async fn deploy_pool(fixture: &Fixture, salt: u8) -> (Bech32ContractId, AssetId) {
+    let token_contract_id = Contract::deploy_with_parameters(
+        "../token_contract/out/debug/token_contract.bin",
+        &fixture.wallet,
+        TxParameters::default(),
+        StorageConfiguration::new(None, None),
+        Salt::from([salt; 32]),
+    )
+    .await
+    .unwrap();
+
+    let token_instance = TestToken::new(token_contract_id.clone(), fixture.wallet.clone());
+    token_instance
+        .methods()
+        .initialize(to_9_decimal(20000), fixture.wallet.address().into())
+        .call()
+        .await
+        .unwrap();
+    token_instance
+        .methods()
+        .mint()
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .unwrap();
+
+    let key = Bytes32::from_str("0x0000000000000000000000000000000000000000000000000000000000000001").unwrap();
+    let storage_vec = vec![StorageSlot::new(key, token_contract_id.hash())];
+
+    let exchange_contract_id = Contract::deploy_with_parameters(
+        "../exchange_contract/out/debug/exchange_contract.bin",
+        &fixture.wallet,
+        TxParameters::default(),
+        StorageConfiguration::with_manual_storage(Some(storage_vec)),
+        Salt::from([salt; 32]),
+    )
+    .await
+    .unwrap();
+
+    let exchange_instance = Exchange::new(exchange_contract_id.clone(), fixture.wallet.clone());
+    exchange_instance
+        .methods()
+        .initialize(Bits256(fixture.vault_contract_id.hash().into()))
+        .set_contracts(&[fixture.vault_contract_id.clone()])
+        .call()
+        .await
+        .unwrap();
+    exchange_instance
+        .methods()
+        .cache_vault_fees()
+        .set_contracts(&[fixture.vault_contract_id.clone()])
+        .call()
+        .await
+        .unwrap();
+
+    (exchange_contract_id, AssetId::new(*token_contract_id.hash()))
+}
+
+/// Add liquidity to the pool and swap ETH for tokens, accruing protocol fees in ETH
+async fn generate_fees(fixture: &Fixture, exchange_contract_id: &Bech32ContractId, token_asset_id: AssetId) {
+    add_pool_liquidity(fixture, exchange_contract_id, token_asset_id).await;
+    swap_eth_for_tokens(fixture, exchange_contract_id).await;
+}
+
+/// Add 5 ETH and 10 tokens to the pool
+async fn add_pool_liquidity(fixture: &Fixture, exchange_contract_id: &Bech32ContractId, token_asset_id: AssetId) {
+    let _receipts = fixture.wallet
+        .force_transfer_to_contract(exchange_contract_id, to_9_decimal(5), BASE_ASSET_ID, TxParameters::default())
+        .await
+        .unwrap();
+    let _receipts = fixture.wallet
+        .force_transfer_to_contract(exchange_contract_id, to_9_decimal(10), token_asset_id, TxParameters::default())
+        .await
+        .unwrap();
+
+    let exchange_instance = Exchange::new(exchange_contract_id.clone(), fixture.wallet.clone());
+    exchange_instance
+        .methods()
+        .add_liquidity(Identity::Address(fixture.wallet.address().into()))
+        .append_variable_outputs(2)
+        .tx_params(TxParameters {
+            gas_price: 0,
+            gas_limit: 100_000_000,
+            maturity: 0,
+        })
+        .call_params(CallParameters::new(None, None, Some(100_000_000)))
+        .call()
+        .await
+        .unwrap();
+}
+
+/// Swap 1 ETH for half a token, returning the protocol fee collected in ETH
+async fn swap_eth_for_tokens(fixture: &Fixture, exchange_contract_id: &Bech32ContractId) -> u64 {
+    let exchange_instance = Exchange::new(exchange_contract_id.clone(), fixture.wallet.clone());
+
+    // Ask for well under the tokens available, so repeated swaps succeed regardless of the fee
+    let response = exchange_instance
+        .methods()
+        .swap(0, to_9_decimal(1) / 2, Identity::Address(fixture.wallet.address().into()))
+        .set_contracts(&[fixture.vault_contract_id.clone()])
+        .call_params(CallParameters::new(Some(to_9_decimal(1)), None, None))
+        .tx_params(TxParameters {
+            gas_price: 0,
+            gas_limit: 100_000_000,
+            maturity: 0,
+        })
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .unwrap();
+
+    let logs = get_logs::<ProtocolFeeCollected, _>(&response, exchange_contract_id, EXCHANGE_ABI);
+    logs.iter().map(|log| log.amount_0).sum()
+}
+
+async fn deploy_registry(fixture: &Fixture, template_exchange_id: &Bech32ContractId, vault_contract_id: &Bech32ContractId) -> (Bech32ContractId, Registry) {
+    let registry_contract_id = Contract::deploy(
+        "../registry_contract/out/debug/registry_contract.bin",
+        &fixture.wallet,
+        TxParameters::default(),
+        deployer_storage(&fixture.wallet),
+    )
+    .await
+    .unwrap();
+
+    let registry_instance = Registry::new(registry_contract_id.clone(), fixture.wallet.clone());
+    registry_instance
+        .methods()
+        .initialize(
+            Bits256(template_exchange_id.hash().into()),
+            Bits256(vault_contract_id.hash().into()),
+            Identity::Address(fixture.wallet.address().into()),
+        )
+        .call()
+        .await
+        .unwrap();
+
+    (registry_contract_id, registry_instance)
+}
+
 #[tokio::test]
 async fn set_fee() {
     let fixture = setup().await;
 
-    // Add liquidity for the second time. Keeping the proportion 1:2
-    // It should return the same amount of LP as the amount of ETH deposited
+    let now = Utc::now().timestamp();
+    produce_block_at(&fixture.wallet, now).await;
+    fixture.vault_instance
+        .methods()
+        .queue_fee_change(100, 1, unix_to_tai64(now as u64 + MIN_FEE_CHANGE_DELAY + 60))
+        .call()
+        .await
+        .unwrap();
+    produce_block_at(&fixture.wallet, now + MIN_FEE_CHANGE_DELAY as i64 + 120).await;
+
     let (_result, set_timestamp) = get_timestamp_and_call(
         fixture.vault_instance
             .methods()
-            .set_fees(100, 1)
+            .execute_fee_change()
     ).await;
 
     let (returned_fees, returned_timestamp) = get_timestamp_and_call(fixture.vault_instance.methods().get_fees()).await;
@@ -73,15 +324,7 @@ async fn set_fee() {
 async fn protocol_fees_minimum_zero() {
     let fixture = setup().await;
 
-    // Add liquidity for the second time. Keeping the proportion 1:2
-    // It should return the same amount of LP as the amount of ETH deposited
-    let _result = fixture.vault_instance
-        .methods()
-        .set_fees(1_000, 10_000)
-        .call()
-        .await
-        .unwrap();
-
+    set_vault_fees(&fixture, 1_000, 10_000, Utc::now().timestamp()).await;
 
     sleep(Duration::from_secs(1)).await;
 
@@ -91,3 +334,1479 @@ async fn protocol_fees_minimum_zero() {
     assert_eq!(returned_fees.value.current_fee, 0);
     assert_eq!(returned_fees.value.change_rate, 10_000);
 }
+
+#[tokio::test]
+async fn initialize_only_once() {
+    let fixture = setup().await;
+
+    let result = fixture.vault_instance.methods().get_owner().simulate().await.unwrap();
+    assert_eq!(result.value, Identity::Address(fixture.wallet.address().into()));
+
+    let is_err = fixture.other_vault_instance
+        .methods()
+        .initialize(Identity::Address(fixture.other_wallet.address().into()), MIN_FEE_CHANGE_DELAY)
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Vault shouldn't be re-initialized");
+
+    let result = fixture.vault_instance.methods().get_owner().simulate().await.unwrap();
+    assert_eq!(result.value, Identity::Address(fixture.wallet.address().into()));
+}
+
+#[tokio::test]
+async fn initialize_only_by_deployer() {
+    let fixture = setup_uninitialized().await;
+
+    let is_err = fixture.other_vault_instance
+        .methods()
+        .initialize(Identity::Address(fixture.other_wallet.address().into()), MIN_FEE_CHANGE_DELAY)
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Vault should only be initialized by its deployer");
+
+    fixture.vault_instance
+        .methods()
+        .initialize(Identity::Address(fixture.wallet.address().into()), MIN_FEE_CHANGE_DELAY)
+        .call()
+        .await
+        .unwrap();
+
+    let result = fixture.vault_instance.methods().get_owner().simulate().await.unwrap();
+    assert_eq!(result.value, Identity::Address(fixture.wallet.address().into()));
+}
+
+#[tokio::test]
+async fn set_fees_only_owner() {
+    let fixture = setup().await;
+    let eta = unix_to_tai64(Utc::now().timestamp() as u64 + MIN_FEE_CHANGE_DELAY + 60);
+
+    // Calling first no longer claims ownership
+    let is_err = fixture.other_vault_instance
+        .methods()
+        .queue_fee_change(100, 1, eta)
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Non-owner shouldn't queue fee changes");
+
+    let result = fixture.vault_instance.methods().get_pending_fee_change().call().await.unwrap();
+    assert!(!result.value.queued);
+
+    let result = fixture.vault_instance.methods().get_owner().simulate().await.unwrap();
+    assert_eq!(result.value, Identity::Address(fixture.wallet.address().into()));
+}
+
+#[tokio::test]
+async fn transfer_ownership() {
+    let fixture = setup().await;
+
+    let owner = Identity::Address(fixture.wallet.address().into());
+    let new_owner = Identity::Address(fixture.other_wallet.address().into());
+
+    // Only the owner can start a transfer
+    let is_err = fixture.other_vault_instance
+        .methods()
+        .transfer_ownership(new_owner.clone())
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Non-owner shouldn't transfer ownership");
+
+    fixture.vault_instance
+        .methods()
+        .transfer_ownership(new_owner.clone())
+        .call()
+        .await
+        .unwrap();
+
+    let result = fixture.vault_instance.methods().get_pending_owner().simulate().await.unwrap();
+    assert_eq!(result.value, new_owner);
+
+    // Ownership doesn't move until accepted
+    let result = fixture.vault_instance.methods().get_owner().simulate().await.unwrap();
+    assert_eq!(result.value, owner);
+
+    // Only the pending owner can accept
+    let is_err = fixture.vault_instance
+        .methods()
+        .accept_ownership()
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Only the pending owner should accept ownership");
+
+    fixture.other_vault_instance
+        .methods()
+        .accept_ownership()
+        .call()
+        .await
+        .unwrap();
+
+    let result = fixture.vault_instance.methods().get_owner().simulate().await.unwrap();
+    assert_eq!(result.value, new_owner);
+
+    let result = fixture.vault_instance.methods().get_pending_owner().simulate().await.unwrap();
+    assert_eq!(result.value, zero_identity());
+
+    // The previous owner has lost access
+    let eta = unix_to_tai64(Utc::now().timestamp() as u64 + MIN_FEE_CHANGE_DELAY + 60);
+    let is_err = fixture.vault_instance
+        .methods()
+        .queue_fee_change(100, 1, eta)
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Previous owner shouldn't queue fee changes");
+
+    fixture.other_vault_instance
+        .methods()
+        .queue_fee_change(100, 1, eta)
+        .call()
+        .await
+        .unwrap();
+
+    let result = fixture.vault_instance.methods().get_pending_fee_change().call().await.unwrap();
+    assert_eq!(result.value.start_fee, 100);
+}
+
+#[tokio::test]
+async fn renounce_ownership() {
+    let fixture = setup().await;
+
+    let is_err = fixture.other_vault_instance
+        .methods()
+        .renounce_ownership()
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Non-owner shouldn't renounce ownership");
+
+    // A pending transfer is cancelled by renouncing
+    fixture.vault_instance
+        .methods()
+        .transfer_ownership(Identity::Address(fixture.other_wallet.address().into()))
+        .call()
+        .await
+        .unwrap();
+
+    fixture.vault_instance
+        .methods()
+        .renounce_ownership()
+        .call()
+        .await
+        .unwrap();
+
+    let result = fixture.vault_instance.methods().get_owner().simulate().await.unwrap();
+    assert_eq!(result.value, zero_identity());
+
+    let result = fixture.vault_instance.methods().get_pending_owner().simulate().await.unwrap();
+    assert_eq!(result.value, zero_identity());
+
+    let is_err = fixture.other_vault_instance
+        .methods()
+        .accept_ownership()
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Renounced transfer shouldn't be accepted");
+
+    let is_err = fixture.vault_instance
+        .methods()
+        .queue_fee_change(100, 1, unix_to_tai64(Utc::now().timestamp() as u64 + MIN_FEE_CHANGE_DELAY + 60))
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Fees shouldn't change after renouncing");
+
+    // Renouncing doesn't allow the vault to be claimed again
+    let is_err = fixture.other_vault_instance
+        .methods()
+        .initialize(Identity::Address(fixture.other_wallet.address().into()), MIN_FEE_CHANGE_DELAY)
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Renounced vault shouldn't be re-initialized");
+}
+
+#[tokio::test]
+async fn claim_fees_batch() {
+    let fixture = setup().await;
+
+    // Constant 1% fee
+    set_vault_fees(&fixture, 10_000, 0, Utc::now().timestamp()).await;
+
+    let (exchange_1_id, token_1_asset_id) = deploy_pool(&fixture, 1).await;
+    let (exchange_2_id, token_2_asset_id) = deploy_pool(&fixture, 2).await;
+    generate_fees(&fixture, &exchange_1_id, token_1_asset_id).await;
+    generate_fees(&fixture, &exchange_2_id, token_2_asset_id).await;
+
+    let expected_fee = to_9_decimal(1) * 10_000 / 1_000_000;
+
+    let response = fixture.vault_instance
+        .methods()
+        .claim_fees_batch(vec![
+            Bits256(exchange_1_id.hash().into()),
+            Bits256(exchange_2_id.hash().into()),
+        ])
+        .set_contracts(&[exchange_1_id.clone(), exchange_2_id.clone()])
+        .call()
+        .await
+        .unwrap();
+
+    let claims = response.value.clone();
+    assert_eq!(claims.len(), 2);
+    for (claim, (exchange_id, token_asset_id)) in claims.iter().zip([(&exchange_1_id, token_1_asset_id), (&exchange_2_id, token_2_asset_id)]) {
+        assert_eq!(claim.pool, Bits256(exchange_id.hash().into()));
+        assert_eq!(claim.token0, Bits256(*BASE_ASSET_ID));
+        assert_eq!(claim.token1, Bits256(*token_asset_id));
+        assert_eq!(claim.amount_0, expected_fee);
+        assert_eq!(claim.amount_1, 0);
+    }
+
+    // Both pools' ETH fees are summed, and the tokens with no fees aren't logged
+    let logs = get_logs::<FeesClaimed, _>(&response, &fixture.vault_contract_id, VAULT_ABI);
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs.get(0).unwrap().asset, Bits256(*BASE_ASSET_ID));
+    assert_eq!(logs.get(0).unwrap().amount, expected_fee * 2);
+
+    let logs = get_logs::<PoolsClaimed, _>(&response, &fixture.vault_contract_id, VAULT_ABI);
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs.get(0).unwrap().pools_claimed, 2);
+    assert_eq!(logs.get(0).unwrap().pools_skipped, 0);
+
+    let vault_balance = fixture
+        .wallet
+        .get_provider()
+        .unwrap()
+        .get_contract_asset_balance(&fixture.vault_contract_id, BASE_ASSET_ID)
+        .await
+        .unwrap();
+    assert_eq!(vault_balance, expected_fee * 2);
+
+    // Claiming again returns nothing
+    let response = fixture.vault_instance
+        .methods()
+        .claim_fees_batch(vec![Bits256(exchange_1_id.hash().into())])
+        .set_contracts(&[exchange_1_id.clone()])
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(response.value.len(), 1);
+    assert_eq!(response.value.get(0).unwrap().amount_0, 0);
+}
+
+#[tokio::test]
+async fn claim_all() {
+    let fixture = setup().await;
+
+    set_vault_fees(&fixture, 10_000, 0, Utc::now().timestamp()).await;
+
+    let (exchange_1_id, token_1_asset_id) = deploy_pool(&fixture, 1).await;
+    let (exchange_2_id, token_2_asset_id) = deploy_pool(&fixture, 2).await;
+    let (exchange_3_id, token_3_asset_id) = deploy_pool(&fixture, 3).await;
+
+    // Nothing to walk until a registry is set
+    let is_err = fixture.vault_instance
+        .methods()
+        .claim_all(0, 10)
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "claim_all shouldn't work without a registry");
+
+    let (registry_contract_id, registry_instance) = deploy_registry(&fixture, &exchange_1_id, &fixture.vault_contract_id).await;
+
+    // Pools must be registered before liquidity is added
+    for exchange_id in [&exchange_1_id, &exchange_2_id, &exchange_3_id] {
+        registry_instance
+            .methods()
+            .add_exchange_contract(Bits256(exchange_id.hash().into()))
+            .set_contracts(&[exchange_id.clone()])
+            .call()
+            .await
+            .unwrap();
+    }
+
+    generate_fees(&fixture, &exchange_1_id, token_1_asset_id).await;
+    generate_fees(&fixture, &exchange_2_id, token_2_asset_id).await;
+    generate_fees(&fixture, &exchange_3_id, token_3_asset_id).await;
+
+    registry_instance
+        .methods()
+        .remove_exchange_contract(Bits256(exchange_2_id.hash().into()))
+        .call()
+        .await
+        .unwrap();
+
+    // Only the owner can set the registry
+    let is_err = fixture.other_vault_instance
+        .methods()
+        .set_registry(Bits256(registry_contract_id.hash().into()))
+        .set_contracts(&[registry_contract_id.clone()])
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Non-owner shouldn't set the registry");
+
+    fixture.vault_instance
+        .methods()
+        .set_registry(Bits256(registry_contract_id.hash().into()))
+        .set_contracts(&[registry_contract_id.clone()])
+        .call()
+        .await
+        .unwrap();
+
+    let result = fixture.vault_instance.methods().get_registry().simulate().await.unwrap();
+    assert_eq!(result.value, Bits256(registry_contract_id.hash().into()));
+
+    let expected_fee = to_9_decimal(1) * 10_000 / 1_000_000;
+    let all_contracts = [
+        registry_contract_id.clone(),
+        exchange_1_id.clone(),
+        exchange_2_id.clone(),
+        exchange_3_id.clone(),
+    ];
+
+    // Walk the first two pools, skipping the delisted one
+    let response = fixture.vault_instance
+        .methods()
+        .claim_all(0, 2)
+        .set_contracts(&all_contracts)
+        .call()
+        .await
+        .unwrap();
+
+    assert_eq!(response.value.len(), 1);
+    assert_eq!(response.value.get(0).unwrap().pool, Bits256(exchange_1_id.hash().into()));
+    assert_eq!(response.value.get(0).unwrap().amount_0, expected_fee);
+
+    let logs = get_logs::<FeesClaimed, _>(&response, &fixture.vault_contract_id, VAULT_ABI);
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs.get(0).unwrap().asset, Bits256(*BASE_ASSET_ID));
+    assert_eq!(logs.get(0).unwrap().amount, expected_fee);
+
+    let logs = get_logs::<PoolsClaimed, _>(&response, &fixture.vault_contract_id, VAULT_ABI);
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs.get(0).unwrap().pools_claimed, 1);
+    assert_eq!(logs.get(0).unwrap().pools_skipped, 1);
+
+    // A start past the end of the list claims nothing
+    let response = fixture.vault_instance
+        .methods()
+        .claim_all(10, 1)
+        .set_contracts(&all_contracts)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(response.value.len(), 0);
+
+    // A count past the end of the list is clamped, even one that would overflow
+    let response = fixture.vault_instance
+        .methods()
+        .claim_all(2, u64::MAX)
+        .set_contracts(&all_contracts)
+        .call()
+        .await
+        .unwrap();
+
+    assert_eq!(response.value.len(), 1);
+    assert_eq!(response.value.get(0).unwrap().pool, Bits256(exchange_3_id.hash().into()));
+    assert_eq!(response.value.get(0).unwrap().amount_0, expected_fee);
+
+    // The delisted pool's fees were left alone
+    let fee_info = Exchange::new(exchange_2_id.clone(), fixture.wallet.clone())
+        .methods()
+        .get_vault_info()
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(fee_info.value.token0_protocol_fees_collected, expected_fee);
+}
+
+#[tokio::test]
+async fn foreign_registry_should_fail() {
+    let fixture = setup().await;
+
+    let (exchange_id, _token_asset_id) = deploy_pool(&fixture, 1).await;
+
+    // A registry sending its pools' fees to another vault
+    let other_vault_contract_id = Contract::deploy_with_parameters(
+        "./out/debug/vault_contract.bin",
+        &fixture.wallet,
+        TxParameters::default(),
+        deployer_storage(&fixture.wallet),
+        Salt::from([1u8; 32]),
+    )
+    .await
+    .unwrap();
+    let (registry_contract_id, _registry_instance) = deploy_registry(&fixture, &exchange_id, &other_vault_contract_id).await;
+
+    let is_err = fixture.vault_instance
+        .methods()
+        .set_registry(Bits256(registry_contract_id.hash().into()))
+        .set_contracts(&[registry_contract_id.clone()])
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Registry for another vault shouldn't be accepted");
+}
+
+#[tokio::test]
+async fn withdraw() {
+    let fixture = setup().await;
+
+    let _receipts = fixture.wallet
+        .force_transfer_to_contract(&fixture.vault_contract_id, 1_000, BASE_ASSET_ID, TxParameters::default())
+        .await
+        .unwrap();
+
+    let result = fixture.vault_instance.methods().get_balance(Bits256(*BASE_ASSET_ID)).call().await.unwrap();
+    assert_eq!(result.value, 1_000);
+
+    let recipient = Identity::Address(fixture.other_wallet.address().into());
+
+    let is_err = fixture.other_vault_instance
+        .methods()
+        .withdraw(Bits256(*BASE_ASSET_ID), 400, recipient.clone())
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Non-owner shouldn't withdraw");
+
+    let is_err = fixture.vault_instance
+        .methods()
+        .withdraw(Bits256(*BASE_ASSET_ID), 1_001, recipient.clone())
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Shouldn't withdraw more than the balance");
+
+    let starting_balance = fixture.other_wallet.get_asset_balance(&BASE_ASSET_ID).await.unwrap();
+
+    let response = fixture.vault_instance
+        .methods()
+        .withdraw(Bits256(*BASE_ASSET_ID), 400, recipient.clone())
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .unwrap();
+
+    let logs = get_logs::<TreasuryWithdrawal, _>(&response, &fixture.vault_contract_id, VAULT_ABI);
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs.get(0).unwrap().asset, Bits256(*BASE_ASSET_ID));
+    assert_eq!(logs.get(0).unwrap().amount, 400);
+    assert_eq!(logs.get(0).unwrap().recipient, recipient);
+
+    let end_balance = fixture.other_wallet.get_asset_balance(&BASE_ASSET_ID).await.unwrap();
+    assert_eq!(end_balance - starting_balance, 400);
+
+    let result = fixture.vault_instance.methods().get_balance(Bits256(*BASE_ASSET_ID)).call().await.unwrap();
+    assert_eq!(result.value, 600);
+}
+
+#[tokio::test]
+async fn distribute() {
+    let fixture = setup().await;
+
+    let treasury = Identity::Address(fixture.other_wallet.address().into());
+    let stakers_address = Address::from([9u8; 32]);
+    let stakers = Identity::Address(stakers_address);
+
+    // Nobody to distribute to yet
+    let is_err = fixture.vault_instance
+        .methods()
+        .distribute(Bits256(*BASE_ASSET_ID))
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Distribute shouldn't work without recipients");
+
+    let is_err = fixture.other_vault_instance
+        .methods()
+        .set_fee_recipients(vec![FeeRecipient { recipient: treasury.clone(), share: 10_000 }])
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Non-owner shouldn't set recipients");
+
+    let is_err = fixture.vault_instance
+        .methods()
+        .set_fee_recipients(vec![
+            FeeRecipient { recipient: treasury.clone(), share: 7_000 },
+            FeeRecipient { recipient: stakers.clone(), share: 2_999 },
+        ])
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Shares must add up to 100%");
+
+    let is_err = fixture.vault_instance
+        .methods()
+        .set_fee_recipients(vec![
+            FeeRecipient { recipient: treasury.clone(), share: 10_000 },
+            FeeRecipient { recipient: stakers.clone(), share: 0 },
+        ])
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Recipients shouldn't have a zero share");
+
+    let response = fixture.vault_instance
+        .methods()
+        .set_fee_recipients(vec![
+            FeeRecipient { recipient: treasury.clone(), share: 7_000 },
+            FeeRecipient { recipient: stakers.clone(), share: 3_000 },
+        ])
+        .call()
+        .await
+        .unwrap();
+
+    let logs = get_logs::<FeeRecipientsUpdated, _>(&response, &fixture.vault_contract_id, VAULT_ABI);
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs.get(0).unwrap().recipient_count, 2);
+
+    let result = fixture.vault_instance.methods().get_fee_recipients().call().await.unwrap();
+    assert_eq!(result.value.len(), 2);
+    assert_eq!(result.value.get(0).unwrap().recipient, treasury);
+    assert_eq!(result.value.get(0).unwrap().share, 7_000);
+    assert_eq!(result.value.get(1).unwrap().recipient, stakers);
+    assert_eq!(result.value.get(1).unwrap().share, 3_000);
+
+    let _receipts = fixture.wallet
+        .force_transfer_to_contract(&fixture.vault_contract_id, 1_000_001, BASE_ASSET_ID, TxParameters::default())
+        .await
+        .unwrap();
+
+    let is_err = fixture.other_vault_instance
+        .methods()
+        .distribute(Bits256(*BASE_ASSET_ID))
+        .append_variable_outputs(2)
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Non-owner shouldn't trigger a distribution");
+
+    let starting_treasury_balance = fixture.other_wallet.get_asset_balance(&BASE_ASSET_ID).await.unwrap();
+
+    let response = fixture.vault_instance
+        .methods()
+        .distribute(Bits256(*BASE_ASSET_ID))
+        .append_variable_outputs(2)
+        .call()
+        .await
+        .unwrap();
+
+    let logs = get_logs::<FeesDistributed, _>(&response, &fixture.vault_contract_id, VAULT_ABI);
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs.get(0).unwrap().asset, Bits256(*BASE_ASSET_ID));
+    assert_eq!(logs.get(0).unwrap().amount, 1_000_001);
+    assert_eq!(logs.get(0).unwrap().recipient_count, 2);
+
+    let end_treasury_balance = fixture.other_wallet.get_asset_balance(&BASE_ASSET_ID).await.unwrap();
+    assert_eq!(end_treasury_balance - starting_treasury_balance, 700_000);
+
+    // Rounding dust goes to the last recipient
+    let stakers_balance = fixture
+        .wallet
+        .get_provider()
+        .unwrap()
+        .get_asset_balance(&stakers_address.into(), BASE_ASSET_ID)
+        .await
+        .unwrap();
+    assert_eq!(stakers_balance, 300_001);
+
+    let result = fixture.vault_instance.methods().get_balance(Bits256(*BASE_ASSET_ID)).call().await.unwrap();
+    assert_eq!(result.value, 0);
+}
+
+#[tokio::test]
+async fn sync_pools() {
+    let fixture = setup().await;
+
+    let now = set_vault_fees(&fixture, 10_000, 0, Utc::now().timestamp()).await;
+
+    let (exchange_1_id, token_1_asset_id) = deploy_pool(&fixture, 1).await;
+    let (exchange_2_id, token_2_asset_id) = deploy_pool(&fixture, 2).await;
+    generate_fees(&fixture, &exchange_1_id, token_1_asset_id).await;
+    generate_fees(&fixture, &exchange_2_id, token_2_asset_id).await;
+
+    // Keep the caches fresh through the fee change delay
+    for exchange_id in [&exchange_1_id, &exchange_2_id] {
+        fixture.vault_instance
+            .methods()
+            .set_pool_max_fee_cache_age(Bits256(exchange_id.hash().into()), u64::MAX)
+            .set_contracts(&[exchange_id.clone()])
+            .call()
+            .await
+            .unwrap();
+    }
+
+    set_vault_fees(&fixture, 20_000, 0, now).await;
+
+    // Pools keep their cached rate until synced
+    let fee = swap_eth_for_tokens(&fixture, &exchange_1_id).await;
+    assert_eq!(fee, to_9_decimal(1) * 10_000 / 1_000_000);
+
+    // Anyone can sync
+    fixture.other_vault_instance
+        .methods()
+        .sync_pools(vec![
+            Bits256(exchange_1_id.hash().into()),
+            Bits256(exchange_2_id.hash().into()),
+        ])
+        .set_contracts(&[exchange_1_id.clone(), exchange_2_id.clone()])
+        .call()
+        .await
+        .unwrap();
+
+    for exchange_id in [&exchange_1_id, &exchange_2_id] {
+        let fee_info = Exchange::new(exchange_id.clone(), fixture.wallet.clone())
+            .methods()
+            .get_fee_info()
+            .call()
+            .await
+            .unwrap();
+        assert_eq!(fee_info.value.current_fee, 20_000);
+
+        let fee = swap_eth_for_tokens(&fixture, exchange_id).await;
+        assert_eq!(fee, to_9_decimal(1) * 20_000 / 1_000_000);
+    }
+}
+
+#[tokio::test]
+async fn stale_fees_refresh_on_swap() {
+    let fixture = setup().await;
+
+    let now = set_vault_fees(&fixture, 10_000, 0, Utc::now().timestamp()).await;
+
+    let (exchange_id, token_asset_id) = deploy_pool(&fixture, 1).await;
+    generate_fees(&fixture, &exchange_id, token_asset_id).await;
+
+    let exchange_instance = Exchange::new(exchange_id.clone(), fixture.wallet.clone());
+    let result = exchange_instance.methods().get_fee_cache_info().call().await.unwrap();
+    assert_eq!(result.value.max_age, 3600);
+    assert!(!result.value.is_stale);
+
+    let is_err = exchange_instance
+        .methods()
+        .set_max_fee_cache_age(60)
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Only the vault should set the max cache age");
+
+    let is_err = fixture.other_vault_instance
+        .methods()
+        .set_pool_max_fee_cache_age(Bits256(exchange_id.hash().into()), 60)
+        .set_contracts(&[exchange_id.clone()])
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Non-owner shouldn't set the max cache age");
+
+    fixture.vault_instance
+        .methods()
+        .set_pool_max_fee_cache_age(Bits256(exchange_id.hash().into()), 60)
+        .set_contracts(&[exchange_id.clone()])
+        .call()
+        .await
+        .unwrap();
+
+    let result = exchange_instance.methods().get_fee_cache_info().call().await.unwrap();
+    assert_eq!(result.value.max_age, 60);
+
+    fixture.vault_instance
+        .methods()
+        .queue_fee_change(20_000, 0, unix_to_tai64(now as u64 + MIN_FEE_CHANGE_DELAY + 60))
+        .call()
+        .await
+        .unwrap();
+    let now = now + MIN_FEE_CHANGE_DELAY as i64 + 120;
+    produce_block_at(&fixture.wallet, now).await;
+
+    // Refresh the cache just before the change takes effect
+    fixture.vault_instance
+        .methods()
+        .sync_pools(vec![Bits256(exchange_id.hash().into())])
+        .set_contracts(&[exchange_id.clone()])
+        .call()
+        .await
+        .unwrap();
+    fixture.vault_instance.methods().execute_fee_change().call().await.unwrap();
+
+    // Fresh cache, the old fee still applies
+    let fee = swap_eth_for_tokens(&fixture, &exchange_id).await;
+    assert_eq!(fee, to_9_decimal(1) * 10_000 / 1_000_000);
+
+    produce_block_at(&fixture.wallet, now + 120).await;
+
+    let result = exchange_instance.methods().get_fee_cache_info().call().await.unwrap();
+    assert!(result.value.is_stale);
+    assert!(result.value.age > 60);
+
+    // Quotes already reflect the fee the next swap will read from the vault
+    let result = exchange_instance
+        .methods()
+        .get_fee_info()
+        .set_contracts(&[fixture.vault_contract_id.clone()])
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(result.value.current_fee, 20_000);
+
+    let fee = swap_eth_for_tokens(&fixture, &exchange_id).await;
+    assert_eq!(fee, to_9_decimal(1) * 20_000 / 1_000_000);
+
+    let result = exchange_instance.methods().get_fee_cache_info().call().await.unwrap();
+    assert!(!result.value.is_stale);
+    let result = exchange_instance.methods().get_vault_info().call().await.unwrap();
+    assert_eq!(result.value.current_fee, 20_000);
+}
+
+#[tokio::test]
+async fn cached_fees_match_vault() {
+    let fixture = setup().await;
+    // Seeded, so a failing schedule can be replayed
+    let seed = 1_054;
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let (exchange_id, _token_asset_id) = deploy_pool(&fixture, 1).await;
+    let exchange_instance = Exchange::new(exchange_id.clone(), fixture.wallet.clone());
+
+    // Never refresh on reads, so the exchange computes every fee from its cached schedule
+    fixture.vault_instance
+        .methods()
+        .set_pool_max_fee_cache_age(Bits256(exchange_id.hash().into()), u64::MAX)
+        .set_contracts(&[exchange_id.clone()])
+        .call()
+        .await
+        .unwrap();
+
+    let mut now = Utc::now().timestamp();
+    for _ in 0..5 {
+        let start_fee = rng.gen_range(0..=50_000);
+        let change_rate = rng.gen_range(0..=100);
+        now = set_vault_fees(&fixture, start_fee, change_rate, now).await;
+
+        // Cache the schedule some time after it started
+        now += rng.gen_range(10..600);
+        produce_block_at(&fixture.wallet, now).await;
+        fixture.vault_instance
+            .methods()
+            .sync_pools(vec![Bits256(exchange_id.hash().into())])
+            .set_contracts(&[exchange_id.clone()])
+            .call()
+            .await
+            .unwrap();
+
+        for _ in 0..5 {
+            now += rng.gen_range(10..600);
+            produce_block_at(&fixture.wallet, now).await;
+
+            let vault_fees = fixture.vault_instance
+                .methods()
+                .get_fees_for(Bits256(exchange_id.hash().into()))
+                .call()
+                .await
+                .unwrap()
+                .value;
+            let fee_info = exchange_instance.methods().get_fee_info().call().await.unwrap().value;
+
+            assert_eq!(fee_info.current_fee, vault_fees.current_fee, "Fees diverged for {:?} at {} with seed {}", vault_fees, now, seed);
+            assert_eq!(fee_info.start_fee, vault_fees.start_fee, "Start fees diverged at {} with seed {}", now, seed);
+            assert_eq!(fee_info.start_time, vault_fees.start_time, "Start times diverged at {} with seed {}", now, seed);
+            assert_eq!(fee_info.change_rate, vault_fees.change_rate, "Change rates diverged at {} with seed {}", now, seed);
+        }
+    }
+}
+
+#[tokio::test]
+async fn pool_fee_overrides() {
+    let fixture = setup().await;
+
+    let now = set_vault_fees(&fixture, 10_000, 0, Utc::now().timestamp()).await;
+
+    let (exchange_1_id, token_1_asset_id) = deploy_pool(&fixture, 1).await;
+    let (exchange_2_id, token_2_asset_id) = deploy_pool(&fixture, 2).await;
+    let pool_1 = Bits256(exchange_1_id.hash().into());
+    let pool_2 = Bits256(exchange_2_id.hash().into());
+
+    let is_err = fixture.other_vault_instance
+        .methods()
+        .queue_pool_fee_change(pool_1, 20_000, 0, unix_to_tai64(now as u64 + MIN_FEE_CHANGE_DELAY + 60))
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Non-owner shouldn't set pool fees");
+
+    let now = set_pool_fees(&fixture, pool_1, 20_000, 0, now).await;
+
+    let result = fixture.vault_instance.methods().has_pool_fees(pool_1).call().await.unwrap();
+    assert!(result.value);
+    let result = fixture.vault_instance.methods().get_fees_for(pool_1).call().await.unwrap();
+    assert_eq!(result.value.current_fee, 20_000);
+
+    // Other pools fall back to the global schedule
+    let result = fixture.vault_instance.methods().has_pool_fees(pool_2).call().await.unwrap();
+    assert!(!result.value);
+    let result = fixture.vault_instance.methods().get_fees_for(pool_2).call().await.unwrap();
+    assert_eq!(result.value.current_fee, 10_000);
+
+    let result = fixture.vault_instance.methods().get_fees().call().await.unwrap();
+    assert_eq!(result.value.current_fee, 10_000);
+
+    fixture.vault_instance
+        .methods()
+        .sync_pools(vec![pool_1, pool_2])
+        .set_contracts(&[exchange_1_id.clone(), exchange_2_id.clone()])
+        .call()
+        .await
+        .unwrap();
+
+    generate_fees(&fixture, &exchange_1_id, token_1_asset_id).await;
+    generate_fees(&fixture, &exchange_2_id, token_2_asset_id).await;
+
+    let fee = swap_eth_for_tokens(&fixture, &exchange_1_id).await;
+    assert_eq!(fee, to_9_decimal(1) * 20_000 / 1_000_000);
+    let fee = swap_eth_for_tokens(&fixture, &exchange_2_id).await;
+    assert_eq!(fee, to_9_decimal(1) * 10_000 / 1_000_000);
+
+    // Clearing the override returns the pool to the global schedule
+    let is_err = fixture.other_vault_instance
+        .methods()
+        .queue_pool_fee_clear(pool_1, unix_to_tai64(now as u64 + MIN_FEE_CHANGE_DELAY + 60))
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Non-owner shouldn't clear pool fees");
+
+    clear_pool_fees(&fixture, pool_1, now).await;
+
+    let result = fixture.vault_instance.methods().has_pool_fees(pool_1).call().await.unwrap();
+    assert!(!result.value);
+    let result = fixture.vault_instance.methods().get_fees_for(pool_1).call().await.unwrap();
+    assert_eq!(result.value.current_fee, 10_000);
+
+    fixture.vault_instance
+        .methods()
+        .sync_pools(vec![pool_1])
+        .set_contracts(&[exchange_1_id.clone()])
+        .call()
+        .await
+        .unwrap();
+
+    let fee = swap_eth_for_tokens(&fixture, &exchange_1_id).await;
+    assert_eq!(fee, to_9_decimal(1) * 10_000 / 1_000_000);
+}
+
+#[tokio::test]
+async fn fee_change_delay_minimum() {
+    let fixture = setup_uninitialized().await;
+    let owner = Identity::Address(fixture.wallet.address().into());
+
+    for fee_change_delay in [0, MIN_FEE_CHANGE_DELAY - 1] {
+        let is_err = fixture.vault_instance
+            .methods()
+            .initialize(owner.clone(), fee_change_delay)
+            .call()
+            .await
+            .is_err();
+        assert!(is_err, "A {}s fee change delay should be too short", fee_change_delay);
+    }
+
+    // Longer delays are allowed, and apply to every queued change
+    let fee_change_delay = MIN_FEE_CHANGE_DELAY * 2;
+    fixture.vault_instance
+        .methods()
+        .initialize(owner.clone(), fee_change_delay)
+        .call()
+        .await
+        .unwrap();
+
+    let result = fixture.vault_instance.methods().get_fee_change_delay().call().await.unwrap();
+    assert_eq!(result.value, fee_change_delay);
+
+    let now = Utc::now().timestamp();
+    produce_block_at(&fixture.wallet, now).await;
+
+    let is_err = fixture.vault_instance
+        .methods()
+        .queue_fee_change(20_000, 0, unix_to_tai64(now as u64 + MIN_FEE_CHANGE_DELAY + 60))
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "eta must be at least the vault's own delay away");
+
+    let is_err = fixture.vault_instance
+        .methods()
+        .queue_pool_fee_clear(Bits256([1; 32]), unix_to_tai64(now as u64 + MIN_FEE_CHANGE_DELAY + 60))
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Pool fee clears share the delay");
+
+    fixture.vault_instance
+        .methods()
+        .queue_fee_change(20_000, 0, unix_to_tai64(now as u64 + fee_change_delay + 60))
+        .call()
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn fee_changes_are_timelocked() {
+    let fixture = setup().await;
+
+    let result = fixture.vault_instance.methods().get_fee_change_delay().call().await.unwrap();
+    assert_eq!(result.value, MIN_FEE_CHANGE_DELAY);
+
+    let now = Utc::now().timestamp();
+    produce_block_at(&fixture.wallet, now).await;
+
+    let is_err = fixture.vault_instance
+        .methods()
+        .queue_fee_change(20_000, 0, unix_to_tai64(now as u64))
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "eta must be at least the delay away");
+
+    let eta = unix_to_tai64(now as u64 + MIN_FEE_CHANGE_DELAY + 60);
+
+    let is_err = fixture.other_vault_instance
+        .methods()
+        .queue_fee_change(20_000, 0, eta)
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Non-owner shouldn't queue fee changes");
+
+    let response = fixture.vault_instance
+        .methods()
+        .queue_fee_change(20_000, 0, eta)
+        .call()
+        .await
+        .unwrap();
+
+    let logs = get_logs::<FeeChangeQueued, _>(&response, &fixture.vault_contract_id, VAULT_ABI);
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs.get(0).unwrap().pool, Bits256([0; 32]));
+    assert_eq!(logs.get(0).unwrap().start_fee, 20_000);
+    assert_eq!(logs.get(0).unwrap().eta, eta);
+
+    let result = fixture.vault_instance.methods().get_pending_fee_change().call().await.unwrap();
+    assert!(result.value.queued);
+    assert_eq!(result.value.start_fee, 20_000);
+    assert_eq!(result.value.change_rate, 0);
+    assert_eq!(result.value.eta, eta);
+
+    let is_err = fixture.vault_instance.methods().execute_fee_change().call().await.is_err();
+    assert!(is_err, "Fee change shouldn't execute before the eta");
+
+    let result = fixture.vault_instance.methods().get_fees().call().await.unwrap();
+    assert_eq!(result.value.start_fee, 0);
+
+    produce_block_at(&fixture.wallet, now + MIN_FEE_CHANGE_DELAY as i64 + 120).await;
+
+    let is_err = fixture.other_vault_instance.methods().execute_fee_change().call().await.is_err();
+    assert!(is_err, "Non-owner shouldn't execute fee changes");
+
+    let response = fixture.vault_instance.methods().execute_fee_change().call().await.unwrap();
+
+    let logs = get_logs::<FeeChangeExecuted, _>(&response, &fixture.vault_contract_id, VAULT_ABI);
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs.get(0).unwrap().pool, Bits256([0; 32]));
+    assert_eq!(logs.get(0).unwrap().start_fee, 20_000);
+
+    let result = fixture.vault_instance.methods().get_fees().call().await.unwrap();
+    assert_eq!(result.value.start_fee, 20_000);
+    assert_eq!(result.value.current_fee, 20_000);
+
+    let result = fixture.vault_instance.methods().get_pending_fee_change().call().await.unwrap();
+    assert!(!result.value.queued);
+
+    // Changes can only be executed once
+    let is_err = fixture.vault_instance.methods().execute_fee_change().call().await.is_err();
+    assert!(is_err, "Fee change shouldn't execute twice");
+}
+
+#[tokio::test]
+async fn cancel_fee_change() {
+    let fixture = setup().await;
+
+    let is_err = fixture.vault_instance.methods().cancel_fee_change().call().await.is_err();
+    assert!(is_err, "Nothing to cancel");
+
+    let now = Utc::now().timestamp();
+    produce_block_at(&fixture.wallet, now).await;
+
+    fixture.vault_instance
+        .methods()
+        .queue_fee_change(20_000, 0, unix_to_tai64(now as u64 + MIN_FEE_CHANGE_DELAY + 60))
+        .call()
+        .await
+        .unwrap();
+
+    let is_err = fixture.other_vault_instance.methods().cancel_fee_change().call().await.is_err();
+    assert!(is_err, "Non-owner shouldn't cancel fee changes");
+
+    let response = fixture.vault_instance.methods().cancel_fee_change().call().await.unwrap();
+
+    let logs = get_logs::<FeeChangeCancelled, _>(&response, &fixture.vault_contract_id, VAULT_ABI);
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs.get(0).unwrap().pool, Bits256([0; 32]));
+
+    let result = fixture.vault_instance.methods().get_pending_fee_change().call().await.unwrap();
+    assert!(!result.value.queued);
+
+    produce_block_at(&fixture.wallet, now + MIN_FEE_CHANGE_DELAY as i64 + 120).await;
+
+    let is_err = fixture.vault_instance.methods().execute_fee_change().call().await.is_err();
+    assert!(is_err, "Cancelled fee change shouldn't execute");
+
+    let result = fixture.vault_instance.methods().get_fees().call().await.unwrap();
+    assert_eq!(result.value.start_fee, 0);
+}
+
+#[tokio::test]
+async fn pool_fee_changes_are_timelocked() {
+    let fixture = setup().await;
+    let pool = Bits256([1; 32]);
+
+    let now = Utc::now().timestamp();
+    produce_block_at(&fixture.wallet, now).await;
+
+    let eta = unix_to_tai64(now as u64 + MIN_FEE_CHANGE_DELAY + 60);
+    fixture.vault_instance
+        .methods()
+        .queue_pool_fee_change(pool, 5_000, 0, eta)
+        .call()
+        .await
+        .unwrap();
+
+    let result = fixture.vault_instance.methods().get_pending_pool_fee_change(pool).call().await.unwrap();
+    assert!(result.value.queued);
+    assert_eq!(result.value.start_fee, 5_000);
+    assert_eq!(result.value.eta, eta);
+
+    // The global schedule has nothing pending
+    let result = fixture.vault_instance.methods().get_pending_fee_change().call().await.unwrap();
+    assert!(!result.value.queued);
+
+    let is_err = fixture.vault_instance.methods().execute_pool_fee_change(pool).call().await.is_err();
+    assert!(is_err, "Pool fee change shouldn't execute before the eta");
+
+    produce_block_at(&fixture.wallet, now + MIN_FEE_CHANGE_DELAY as i64 + 120).await;
+
+    let response = fixture.vault_instance.methods().execute_pool_fee_change(pool).call().await.unwrap();
+
+    let logs = get_logs::<FeeChangeExecuted, _>(&response, &fixture.vault_contract_id, VAULT_ABI);
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs.get(0).unwrap().pool, pool);
+
+    let result = fixture.vault_instance.methods().has_pool_fees(pool).call().await.unwrap();
+    assert!(result.value);
+    let result = fixture.vault_instance.methods().get_fees_for(pool).call().await.unwrap();
+    assert_eq!(result.value.current_fee, 5_000);
+
+    // Queue and cancel a second change
+    let eta = unix_to_tai64(now as u64 + MIN_FEE_CHANGE_DELAY * 2 + 240);
+    fixture.vault_instance
+        .methods()
+        .queue_pool_fee_change(pool, 1_000, 0, eta)
+        .call()
+        .await
+        .unwrap();
+    fixture.vault_instance
+        .methods()
+        .cancel_pool_fee_change(pool)
+        .call()
+        .await
+        .unwrap();
+
+    let result = fixture.vault_instance.methods().get_pending_pool_fee_change(pool).call().await.unwrap();
+    assert!(!result.value.queued);
+    let result = fixture.vault_instance.methods().get_fees_for(pool).call().await.unwrap();
+    assert_eq!(result.value.current_fee, 5_000);
+
+    // Removing the override waits out the same delay
+    let now = now + MIN_FEE_CHANGE_DELAY as i64 + 120;
+    let eta = unix_to_tai64(now as u64 + MIN_FEE_CHANGE_DELAY + 60);
+    let response = fixture.vault_instance
+        .methods()
+        .queue_pool_fee_clear(pool, eta)
+        .call()
+        .await
+        .unwrap();
+
+    let logs = get_logs::<FeeChangeQueued, _>(&response, &fixture.vault_contract_id, VAULT_ABI);
+    assert_eq!(logs.len(), 1);
+    assert!(logs.get(0).unwrap().clear);
+
+    let result = fixture.vault_instance.methods().get_pending_pool_fee_change(pool).call().await.unwrap();
+    assert!(result.value.queued);
+    assert!(result.value.clear);
+
+    let is_err = fixture.vault_instance.methods().execute_pool_fee_change(pool).call().await.is_err();
+    assert!(is_err, "Pool fee clear shouldn't execute before the eta");
+    let result = fixture.vault_instance.methods().has_pool_fees(pool).call().await.unwrap();
+    assert!(result.value);
+
+    produce_block_at(&fixture.wallet, now + MIN_FEE_CHANGE_DELAY as i64 + 120).await;
+
+    let response = fixture.vault_instance.methods().execute_pool_fee_change(pool).call().await.unwrap();
+
+    let logs = get_logs::<FeeChangeExecuted, _>(&response, &fixture.vault_contract_id, VAULT_ABI);
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs.get(0).unwrap().pool, pool);
+    assert!(logs.get(0).unwrap().clear);
+
+    let result = fixture.vault_instance.methods().has_pool_fees(pool).call().await.unwrap();
+    assert!(!result.value);
+    let result = fixture.vault_instance.methods().get_fees_for(pool).call().await.unwrap();
+    assert_eq!(result.value.current_fee, 0);
+}
+
+#[tokio::test]
+async fn fee_bounds() {
+    let fixture = setup().await;
+    let max_fee = 50_000;
+
+    let result = fixture.vault_instance.methods().get_max_protocol_fee().call().await.unwrap();
+    assert_eq!(result.value, max_fee);
+
+    let now = Utc::now().timestamp();
+    produce_block_at(&fixture.wallet, now).await;
+    let eta = unix_to_tai64(now as u64 + MIN_FEE_CHANGE_DELAY + 60);
+
+    // Rejected when queued, rather than when executed
+    for (start_fee, change_rate) in [(max_fee + 1, 0), (u16::MAX, 0), (0, max_fee + 1), (0, u16::MAX)] {
+        let is_err = fixture.vault_instance
+            .methods()
+            .queue_fee_change(start_fee, change_rate, eta)
+            .call()
+            .await
+            .is_err();
+        assert!(is_err, "queue_fee_change({}, {}) should be out of range", start_fee, change_rate);
+
+        let is_err = fixture.vault_instance
+            .methods()
+            .queue_pool_fee_change(Bits256([1; 32]), start_fee, change_rate, eta)
+            .call()
+            .await
+            .is_err();
+        assert!(is_err, "queue_pool_fee_change({}, {}) should be out of range", start_fee, change_rate);
+    }
+
+    fixture.vault_instance
+        .methods()
+        .queue_fee_change(max_fee, max_fee, eta)
+        .call()
+        .await
+        .unwrap();
+
+    let result = fixture.vault_instance.methods().get_pending_fee_change().call().await.unwrap();
+    assert!(result.value.queued);
+    assert_eq!(result.value.start_fee, max_fee);
+}
+
+#[tokio::test]
+async fn buyback() {
+    let fixture = setup().await;
+    let twap_period = 60 * 60;
+
+    let now = Utc::now().timestamp();
+    produce_block_at(&fixture.wallet, now).await;
+
+    let (exchange_id, gov_asset_id) = deploy_pool(&fixture, 1).await;
+    let (unregistered_exchange_id, _token_asset_id) = deploy_pool(&fixture, 2).await;
+    let (registry_contract_id, registry_instance) = deploy_registry(&fixture, &exchange_id, &fixture.vault_contract_id).await;
+
+    registry_instance
+        .methods()
+        .add_exchange_contract(Bits256(exchange_id.hash().into()))
+        .set_contracts(&[exchange_id.clone()])
+        .call()
+        .await
+        .unwrap();
+    fixture.vault_instance
+        .methods()
+        .set_registry(Bits256(registry_contract_id.hash().into()))
+        .set_contracts(&[registry_contract_id.clone()])
+        .call()
+        .await
+        .unwrap();
+
+    // 1 ETH = 2 GOV
+    add_pool_liquidity(&fixture, &exchange_id, gov_asset_id).await;
+    Exchange::new(exchange_id.clone(), fixture.wallet.clone())
+        .methods()
+        .expand_twap_buffer(10)
+        .call()
+        .await
+        .unwrap();
+
+    let router_contract_id = Contract::deploy(
+        "../router_contract/out/debug/router_contract.bin",
+        &fixture.wallet,
+        TxParameters::default(),
+        StorageConfiguration::new(None, None),
+    )
+    .await
+    .unwrap();
+
+    let recipient = Address::from([7u8; 32]);
+    let mut config = BuybackConfig {
+        router: Bits256(router_contract_id.hash().into()),
+        token: Bits256(*gov_asset_id),
+        recipient: Identity::Address(recipient),
+        twap_period: twap_period,
+        max_slippage: 100,
+    };
+
+    let is_err = fixture.other_vault_instance
+        .methods()
+        .set_buyback_config(config.clone())
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Non-owner shouldn't configure buybacks");
+
+    let is_err = fixture.vault_instance
+        .methods()
+        .set_buyback_config(BuybackConfig { router: Bits256([0; 32]), ..config.clone() })
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Buyback config needs a router");
+
+    let zero_recipients = [
+        Identity::Address(Address::from([0u8; 32])),
+        Identity::ContractId(ContractId::from([0u8; 32])),
+    ];
+    for zero_recipient in zero_recipients {
+        let is_err = fixture.vault_instance
+            .methods()
+            .set_buyback_config(BuybackConfig { recipient: zero_recipient, ..config.clone() })
+            .call()
+            .await
+            .is_err();
+        assert!(is_err, "Buyback tokens shouldn't be sent to the zero address");
+    }
+
+    let is_err = fixture.vault_instance
+        .methods()
+        .set_buyback_config(BuybackConfig { max_slippage: 10_001, ..config.clone() })
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Slippage can't exceed 100%");
+
+    let is_err = fixture.vault_instance
+        .methods()
+        .set_buyback_config(BuybackConfig { twap_period: 0, ..config.clone() })
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Buyback config needs a TWAP period");
+
+    fixture.vault_instance
+        .methods()
+        .set_buyback_config(config.clone())
+        .call()
+        .await
+        .unwrap();
+
+    let result = fixture.vault_instance.methods().get_buyback_config().call().await.unwrap();
+    assert_eq!(result.value, config);
+
+    // Fees waiting in the vault
+    let fees = to_9_decimal(1) / 100;
+    let _receipts = fixture.wallet
+        .force_transfer_to_contract(&fixture.vault_contract_id, fees * 3, BASE_ASSET_ID, TxParameters::default())
+        .await
+        .unwrap();
+
+    let contracts = [
+        registry_contract_id.clone(),
+        router_contract_id.clone(),
+        exchange_id.clone(),
+        unregistered_exchange_id.clone(),
+    ];
+
+    // The pool's history is too short for a TWAP
+    let is_err = fixture.vault_instance
+        .methods()
+        .buyback(Bits256(*BASE_ASSET_ID), fees, vec![Bits256(exchange_id.hash().into())])
+        .set_contracts(&contracts)
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Buyback shouldn't run without a full TWAP period");
+
+    let now = now + twap_period as i64 + 60;
+    produce_block_at(&fixture.wallet, now).await;
+
+    // A single observation only measures the current reserves
+    let is_err = fixture.vault_instance
+        .methods()
+        .buyback(Bits256(*BASE_ASSET_ID), fees, vec![Bits256(exchange_id.hash().into())])
+        .set_contracts(&contracts)
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Buyback shouldn't run on a single observation");
+
+    // Adding at the same 2:1 ratio writes a second observation
+    add_pool_liquidity(&fixture, &exchange_id, gov_asset_id).await;
+
+    let is_err = fixture.vault_instance
+        .methods()
+        .buyback(Bits256(*BASE_ASSET_ID), fees, vec![Bits256(unregistered_exchange_id.hash().into())])
+        .set_contracts(&contracts)
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Buyback should only use registered pools");
+
+    let is_err = fixture.other_vault_instance
+        .methods()
+        .buyback(Bits256(*BASE_ASSET_ID), fees, vec![Bits256(exchange_id.hash().into())])
+        .set_contracts(&contracts)
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Non-owner shouldn't trigger buybacks");
+
+    // 0.1% slippage can't cover the 0.3% LP fee
+    config.max_slippage = 10;
+    fixture.vault_instance.methods().set_buyback_config(config.clone()).call().await.unwrap();
+
+    let is_err = fixture.vault_instance
+        .methods()
+        .buyback(Bits256(*BASE_ASSET_ID), fees, vec![Bits256(exchange_id.hash().into())])
+        .set_contracts(&contracts)
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Buyback should respect the slippage limit");
+
+    config.max_slippage = 100;
+    fixture.vault_instance.methods().set_buyback_config(config.clone()).call().await.unwrap();
+
+    let response = fixture.vault_instance
+        .methods()
+        .buyback(Bits256(*BASE_ASSET_ID), fees, vec![Bits256(exchange_id.hash().into())])
+        .set_contracts(&contracts)
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .unwrap();
+
+    let amount_out = response.value;
+    // Within 1% of the 2:1 price
+    assert!(amount_out >= fees * 2 * 99 / 100 && amount_out < fees * 2, "Unexpected output {}", amount_out);
+
+    let logs = get_logs::<Buyback, _>(&response, &fixture.vault_contract_id, VAULT_ABI);
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs.get(0).unwrap().asset, Bits256(*BASE_ASSET_ID));
+    assert_eq!(logs.get(0).unwrap().amount_in, fees);
+    assert_eq!(logs.get(0).unwrap().token, Bits256(*gov_asset_id));
+    assert_eq!(logs.get(0).unwrap().amount_out, amount_out);
+    assert_eq!(logs.get(0).unwrap().recipient, Identity::Address(recipient));
+
+    let received = fixture
+        .wallet
+        .get_provider()
+        .unwrap()
+        .get_asset_balance(&recipient.into(), gov_asset_id)
+        .await
+        .unwrap();
+    assert_eq!(received, amount_out);
+
+    let result = fixture.vault_instance.methods().get_balance(Bits256(*BASE_ASSET_ID)).call().await.unwrap();
+    assert_eq!(result.value, fees * 2);
+
+    // Fees waiting for a buyback can't be swept out by someone else's distribution
+    let treasury = Identity::Address(fixture.other_wallet.address().into());
+    fixture.vault_instance
+        .methods()
+        .set_fee_recipients(vec![FeeRecipient { recipient: treasury, share: 10_000 }])
+        .call()
+        .await
+        .unwrap();
+
+    let is_err = fixture.other_vault_instance
+        .methods()
+        .distribute(Bits256(*BASE_ASSET_ID))
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Non-owner shouldn't distribute the buyback asset");
+
+    let result = fixture.vault_instance.methods().get_balance(Bits256(*BASE_ASSET_ID)).call().await.unwrap();
+    assert_eq!(result.value, fees * 2);
+
+    // Once the owner distributes the asset, there's nothing left to buy back with
+    let starting_treasury_balance = fixture.other_wallet.get_asset_balance(&BASE_ASSET_ID).await.unwrap();
+    fixture.vault_instance
+        .methods()
+        .distribute(Bits256(*BASE_ASSET_ID))
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .unwrap();
+
+    let end_treasury_balance = fixture.other_wallet.get_asset_balance(&BASE_ASSET_ID).await.unwrap();
+    assert_eq!(end_treasury_balance - starting_treasury_balance, fees * 2);
+
+    let is_err = fixture.vault_instance
+        .methods()
+        .buyback(Bits256(*BASE_ASSET_ID), fees, vec![Bits256(exchange_id.hash().into())])
+        .set_contracts(&contracts)
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Buyback shouldn't spend distributed fees");
+
+    let _receipts = fixture.wallet
+        .force_transfer_to_contract(&fixture.vault_contract_id, fees * 2, BASE_ASSET_ID, TxParameters::default())
+        .await
+        .unwrap();
+
+    // Dump ETH into the pool right before the buyback, tripling the GOV price
+    Exchange::new(exchange_id.clone(), fixture.other_wallet.clone())
+        .methods()
+        .swap(0, to_9_decimal(4), Identity::Address(fixture.other_wallet.address().into()))
+        .call_params(CallParameters::new(Some(to_9_decimal(5)), None, None))
+        .tx_params(TxParameters {
+            gas_price: 0,
+            gas_limit: 100_000_000,
+            maturity: 0,
+        })
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .unwrap();
+
+    let is_err = fixture.vault_instance
+        .methods()
+        .buyback(Bits256(*BASE_ASSET_ID), fees, vec![Bits256(exchange_id.hash().into())])
+        .set_contracts(&contracts)
+        .append_variable_outputs(1)
+        .call()
+        .await
+        .is_err();
+    assert!(is_err, "Buyback shouldn't trade at a manipulated price");
+}
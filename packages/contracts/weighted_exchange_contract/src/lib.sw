@@ -0,0 +1,27 @@
+library lib;
+
+use std::{u128::U128, u256::U256};
+use microchain_helpers::ln_fixed;
+
+/// Fixed-point math uses 18 decimals, matching microchain_helpers
+const FIXED_ONE = U128::from((0, 1_000_000_000_000_000_000));
+
+/// The weighted invariant b0^w0 * b1^w1, compared in log space as w0 * ln(b0) + w1 * ln(b1)
+pub fn get_log_invariant(balance_0: u64, balance_1: u64, weight_0: u64, weight_1: u64) -> U128 {
+    let ln_0 = ln_fixed(U128::from((0, balance_0)) * FIXED_ONE);
+    let ln_1 = ln_fixed(U128::from((0, balance_1)) * FIXED_ONE);
+    U128::from((0, weight_0)) * ln_0 + U128::from((0, weight_1)) * ln_1
+}
+
+/// Spot price of token 0 in token 1, (b1 / w1) / (b0 / w0), scaled by `precision`
+pub fn get_spot_price(
+    reserve_0: u64,
+    reserve_1: u64,
+    weight_0: u64,
+    weight_1: u64,
+    precision: U256,
+) -> U256 {
+    let numerator = U256::from((0, 0, 0, reserve_1)) * U256::from((0, 0, 0, weight_0));
+    let denominator = U256::from((0, 0, 0, reserve_0)) * U256::from((0, 0, 0, weight_1));
+    numerator * precision / denominator
+}
@@ -0,0 +1,270 @@
+contract;
+
+dep lib;
+
+use std::{
+    auth::msg_sender,
+    identity::Identity,
+    logging::log,
+    storage::get,
+    u256::U256,
+};
+
+use exchange_abi::{
+    DynamicFeeConfig,
+    Exchange,
+    FeeCacheInfo,
+    FeeInfo,
+    LPFeeInfo,
+    Observation,
+    PoolInfo,
+    RemoveLiquidityInfo,
+    Swap,
+    TWAPInfo,
+    VaultInfo,
+    WeightedExchange,
+};
+use exchange_lib::{
+    add_protocol_fee,
+    burn_liquidity,
+    cache_vault_fees,
+    expand_twap_buffer,
+    get_current_lp_fee,
+    get_fee_cache_info,
+    get_fee_info,
+    get_lp_fee_amount,
+    get_lp_fee_info,
+    get_lp_token_supply,
+    get_next_protocol_fee,
+    get_observation,
+    get_pool_balance,
+    get_protocol_fee_amount,
+    get_reserves,
+    get_tokens,
+    get_twap_info,
+    get_vault_info,
+    initialize,
+    mint_liquidity,
+    set_dynamic_fee,
+    set_max_fee_cache_age,
+    set_reserves,
+    settle_swap,
+    withdraw_protocol_fees,
+    write_observation,
+};
+use microchain_helpers::{get_weighted_input_price, get_weighted_output_price, identity_to_b256};
+use lib::{get_log_invariant, get_spot_price};
+
+enum Error {
+    Invariant: (),
+    InvalidWeights: (),
+}
+
+// Balancer-style exchange where each token makes up a fixed share of the pool's value,
+// such as 80/20, pricing swaps on the b0^w0 * b1^w1 = k curve. Token 0's weight is set
+// at deploy time in TOKEN_0_WEIGHT_SLOT, and token 1 holds the rest.
+
+////////////////////////////////////////
+// Constants
+////////////////////////////////////////
+const TOKEN_0_WEIGHT_SLOT = 0x0000000000000000000000000000000000000000000000000000000000000002;
+
+/// Token weights are in basis points of the pool
+const TOTAL_WEIGHT = 10_000;
+
+/// Lightest weight either token can have, 2%, which bounds the exponents in the pricing math
+const MIN_WEIGHT = 200;
+
+const TWAP_PERCISION = U256::from((0, 0, 0, 1000000000));
+
+////////////////////////////////////////
+// Helper functions
+////////////////////////////////////////
+#[storage(read)]
+fn get_weights() -> (u64, u64) {
+    let weight_0 = get::<u64>(TOKEN_0_WEIGHT_SLOT);
+    (weight_0, TOTAL_WEIGHT - weight_0)
+}
+
+#[storage(read, write)]
+fn store_reserves(
+    reserve0: u64,
+    reserve1: u64,
+    prev_reserve0: u64,
+    prev_reserve1: u64,
+) {
+    if (prev_reserve0 != 0 && prev_reserve1 != 0) {
+        // The reserve ratio isn't the price in a weighted pool, so accumulate the spot prices
+        let (weight_0, weight_1) = get_weights();
+        write_observation(
+            get_spot_price(prev_reserve0, prev_reserve1, weight_0, weight_1, TWAP_PERCISION),
+            get_spot_price(prev_reserve1, prev_reserve0, weight_1, weight_0, TWAP_PERCISION),
+        );
+    }
+    set_reserves(reserve0, reserve1);
+}
+
+// ////////////////////////////////////////
+// // ABI definitions
+// ////////////////////////////////////////
+impl Exchange for Contract {
+    #[storage(read, write)]
+    fn initialize(new_vault: b256) {
+        let weight_0 = get::<u64>(TOKEN_0_WEIGHT_SLOT);
+        require(weight_0 >= MIN_WEIGHT && weight_0 <= TOTAL_WEIGHT - MIN_WEIGHT, Error::InvalidWeights);
+        initialize(new_vault);
+    }
+
+    #[storage(read)]
+    fn get_pool_info() -> PoolInfo {
+        let (token_0_reserve, token_1_reserve) = get_reserves();
+        PoolInfo {
+            token_0_reserve: token_0_reserve,
+            token_1_reserve: token_1_reserve,
+            lp_token_supply: get_lp_token_supply(),
+        }
+    }
+
+    #[storage(read)]
+    fn get_vault_info() -> VaultInfo {
+        get_vault_info()
+    }
+
+    #[storage(read)]
+    fn get_fee_info() -> FeeInfo {
+        get_fee_info()
+    }
+
+    #[storage(read)]
+    fn get_fee_cache_info() -> FeeCacheInfo {
+        get_fee_cache_info()
+    }
+
+    #[storage(read)]
+    fn get_lp_fee_info() -> LPFeeInfo {
+        get_lp_fee_info()
+    }
+
+    #[storage(read)]
+    fn get_amount_out(amount_in: u64, token_0_in: bool) -> u64 {
+        let (token_0_reserve, token_1_reserve) = get_reserves();
+        let (weight_0, weight_1) = get_weights();
+        let (reserve_in, reserve_out, weight_in, weight_out) = if token_0_in {
+            (token_0_reserve, token_1_reserve, weight_0, weight_1)
+        } else {
+            (token_1_reserve, token_0_reserve, weight_1, weight_0)
+        };
+
+        let input = amount_in - get_protocol_fee_amount(amount_in, get_next_protocol_fee());
+        let output = get_weighted_input_price(input, reserve_in, reserve_out, weight_in, weight_out, get_current_lp_fee());
+        // Round against the trader, so the quote always satisfies the invariant
+        if output > 0 { output - 1 } else { 0 }
+    }
+
+    #[storage(read)]
+    fn get_amount_in(amount_out: u64, token_0_out: bool) -> u64 {
+        let (token_0_reserve, token_1_reserve) = get_reserves();
+        let (weight_0, weight_1) = get_weights();
+        let (reserve_in, reserve_out, weight_in, weight_out) = if token_0_out {
+            (token_1_reserve, token_0_reserve, weight_1, weight_0)
+        } else {
+            (token_0_reserve, token_1_reserve, weight_0, weight_1)
+        };
+
+        let input = get_weighted_output_price(amount_out, reserve_in, reserve_out, weight_in, weight_out, get_current_lp_fee());
+        add_protocol_fee(input, get_next_protocol_fee())
+    }
+
+    #[storage(read)]
+    fn get_twap_info() -> TWAPInfo {
+        get_twap_info()
+    }
+
+    #[storage(read, write)]
+    fn cache_vault_fees() {
+        cache_vault_fees();
+    }
+
+    #[storage(read, write)]
+    fn set_max_fee_cache_age(max_age: u64) {
+        set_max_fee_cache_age(max_age);
+    }
+
+    #[storage(read, write)]
+    fn set_dynamic_fee(config: DynamicFeeConfig) {
+        set_dynamic_fee(config);
+    }
+
+    #[storage(read, write)]
+    fn add_liquidity(recipient: Identity) -> u64 {
+        let (token_0_reserve, token_1_reserve) = get_reserves();
+        let (current_token_0_amount, current_token_1_amount) = get_pool_balance();
+
+        let minted = mint_liquidity(current_token_0_amount, current_token_1_amount, recipient);
+        store_reserves(current_token_0_amount, current_token_1_amount, token_0_reserve, token_1_reserve);
+        minted
+    }
+
+    #[storage(read, write)]
+    fn remove_liquidity(recipient: Identity) -> RemoveLiquidityInfo {
+        let (token_0_reserve, token_1_reserve) = get_reserves();
+        let (current_token_0_amount, current_token_1_amount) = get_pool_balance();
+
+        let removed = burn_liquidity(current_token_0_amount, current_token_1_amount, recipient);
+        store_reserves(current_token_0_amount - removed.token_0_amount, current_token_1_amount - removed.token_1_amount, token_0_reserve, token_1_reserve);
+        removed
+    }
+
+    #[storage(read, write)]
+    fn swap(amount_0_out: u64, amount_1_out: u64, recipient: Identity) {
+        let (token_0_reserve, token_1_reserve) = get_reserves();
+        let (balance_0, balance_1, amount0_in, amount1_in) = settle_swap(amount_0_out, amount_1_out, recipient, 0, 0);
+
+        // Measured before this swap's observation is written, so it matches get_lp_fee_info
+        let lp_fee = get_current_lp_fee();
+        let balance0_adjusted = balance_0 - get_lp_fee_amount(amount0_in, lp_fee);
+        let balance1_adjusted = balance_1 - get_lp_fee_amount(amount1_in, lp_fee);
+
+        let (weight_0, weight_1) = get_weights();
+        let left = get_log_invariant(balance0_adjusted, balance1_adjusted, weight_0, weight_1);
+        let right = get_log_invariant(token_0_reserve, token_1_reserve, weight_0, weight_1);
+        require(left > right || left == right, Error::Invariant); // U128 doesn't have >= yet
+        store_reserves(balance_0, balance_1, token_0_reserve, token_1_reserve);
+
+        log(Swap {
+            sender: identity_to_b256(msg_sender().unwrap()),
+            amount_0_in: amount0_in,
+            amount_1_in: amount1_in,
+            amount_0_out: amount_0_out,
+            amount_1_out: amount_1_out,
+            recipient: identity_to_b256(recipient),
+        });
+    }
+
+    #[storage(read, write)]
+    fn expand_twap_buffer(new_total_slots: u64) {
+        expand_twap_buffer(new_total_slots);
+    }
+
+    #[storage(read, write)]
+    fn withdraw_protocol_fees(recipient: Identity) -> (u64, u64) {
+        withdraw_protocol_fees(recipient)
+    }
+
+    #[storage(read)]
+    fn get_tokens() -> (b256, b256) {
+        get_tokens()
+    }
+
+    #[storage(read)]
+    fn get_observation(slot: u64) -> Observation {
+        get_observation(slot)
+    }
+}
+
+impl WeightedExchange for Contract {
+    #[storage(read)]
+    fn get_weights() -> (u64, u64) {
+        get_weights()
+    }
+}
@@ -0,0 +1,287 @@
+use std::str::FromStr;
+use fuels::{
+    prelude::*,
+    fuels_abigen::abigen,
+    signers::WalletUnlocked,
+    tx::{AssetId, Bytes32, StorageSlot},
+};
+use test_helpers::{deployer_storage, get_logs, get_wallets, MIN_FEE_CHANGE_DELAY, WEIGHTED_EXCHANGE_ABI};
+
+///////////////////////////////
+// Load the Weighted Exchange Contract abi
+///////////////////////////////
+abigen!(Exchange, "./out/debug/weighted_exchange_contract-abi.json");
+
+abigen!(
+    TestToken,
+    "../token_contract/out/debug/token_contract-abi.json"
+);
+abigen!(Vault, "../vault_contract/out/debug/vault_contract-abi.json");
+
+struct Fixture {
+    wallet: WalletUnlocked,
+    exchange_contract_id: Bech32ContractId,
+    exchange_instance: Exchange,
+    vault_contract_id: Bech32ContractId,
+    token_0_asset_id: AssetId,
+    token_1_asset_id: AssetId,
+}
+
+fn to_9_decimal(num: u64) -> u64 {
+    num * 1_000_000_000
+}
+
+/// The weighted pricing formula with the 0.3% fee, in floating point
+fn weighted_amount_out(amount_in: u64, reserve_in: u64, reserve_out: u64, weight_in: u64, weight_out: u64) -> f64 {
+    let amount_in_with_fee = amount_in as f64 * 0.997;
+    let base = reserve_in as f64 / (reserve_in as f64 + amount_in_with_fee);
+    reserve_out as f64 * (1.0 - base.powf(weight_in as f64 / weight_out as f64))
+}
+
+fn weighted_amount_in(amount_out: u64, reserve_in: u64, reserve_out: u64, weight_in: u64, weight_out: u64) -> f64 {
+    let base = reserve_out as f64 / (reserve_out as f64 - amount_out as f64);
+    reserve_in as f64 * (base.powf(weight_out as f64 / weight_in as f64) - 1.0) / 0.997
+}
+
+/// Token 0's weight, stored as the first word of its slot
+fn weight_slot_value(weight_0: u64) -> Bytes32 {
+    let mut value = [0u8; 32];
+    value[..8].copy_from_slice(&weight_0.to_be_bytes());
+    Bytes32::new(value)
+}
+
+async fn deploy_token(wallet: &WalletUnlocked, salt: u8) -> AssetId {
+    test_helpers::deploy_token(wallet, salt, |token_contract_id| {
+        let token_instance = TestToken::new(token_contract_id.clone(), wallet.clone());
+        (
+            token_instance.methods().initialize(to_9_decimal(5_000_000_000), wallet.address().into()),
+            token_instance.methods().mint(),
+        )
+    })
+    .await
+}
+
+/// Deploy a pool of ETH and a token, with token 0 (ETH) making up `weight_0` basis points of the pool
+async fn deploy_pool(weight_0: u64) -> Result<Fixture, Error> {
+    let wallets = get_wallets().await;
+    let wallet = wallets.get(0).unwrap().clone();
+
+    let vault_contract_id = Contract::deploy(
+        "../vault_contract/out/debug/vault_contract.bin",
+        &wallet,
+        TxParameters::default(),
+        deployer_storage(&wallet),
+    )
+    .await
+    .unwrap();
+    Vault::new(vault_contract_id.clone(), wallet.clone())
+        .methods()
+        .initialize(Identity::Address(wallet.address().into()), MIN_FEE_CHANGE_DELAY)
+        .call()
+        .await
+        .unwrap();
+
+    let token_0_asset_id = BASE_ASSET_ID;
+    let token_1_asset_id = deploy_token(&wallet, 1).await;
+
+    let token0_slot = Bytes32::from_str("0x0000000000000000000000000000000000000000000000000000000000000000").unwrap();
+    let token1_slot = Bytes32::from_str("0x0000000000000000000000000000000000000000000000000000000000000001").unwrap();
+    let weight_slot = Bytes32::from_str("0x0000000000000000000000000000000000000000000000000000000000000002").unwrap();
+    let storage_vec = vec![
+        StorageSlot::new(token0_slot, Bytes32::new(*token_0_asset_id)),
+        StorageSlot::new(token1_slot, Bytes32::new(*token_1_asset_id)),
+        StorageSlot::new(weight_slot, weight_slot_value(weight_0)),
+    ];
+
+    let exchange_contract_id = Contract::deploy(
+        "./out/debug/weighted_exchange_contract.bin",
+        &wallet,
+        TxParameters::default(),
+        StorageConfiguration::with_manual_storage(Some(storage_vec)),
+    )
+    .await
+    .unwrap();
+
+    let exchange_instance = Exchange::new(exchange_contract_id.clone(), wallet.clone());
+    exchange_instance
+        .methods()
+        .initialize(Bits256(vault_contract_id.hash().into()))
+        .set_contracts(&[vault_contract_id.clone()])
+        .call()
+        .await?;
+
+    Ok(Fixture {
+        wallet: wallet,
+        exchange_contract_id: exchange_contract_id,
+        exchange_instance: exchange_instance,
+        vault_contract_id: vault_contract_id,
+        token_0_asset_id: token_0_asset_id,
+        token_1_asset_id: token_1_asset_id,
+    })
+}
+
+async fn setup(weight_0: u64) -> Fixture {
+    deploy_pool(weight_0).await.unwrap()
+}
+
+async fn add_liquidity(fixture: &Fixture, token_0_amount: u64, token_1_amount: u64) {
+    let _receipts = fixture.wallet
+        .force_transfer_to_contract(&fixture.exchange_contract_id, token_0_amount, fixture.token_0_asset_id, TxParameters::default())
+        .await
+        .unwrap();
+    let _receipts = fixture.wallet
+        .force_transfer_to_contract(&fixture.exchange_contract_id, token_1_amount, fixture.token_1_asset_id, TxParameters::default())
+        .await
+        .unwrap();
+
+    fixture.exchange_instance
+        .methods()
+        .add_liquidity(Identity::Address(fixture.wallet.address().into()))
+        .append_variable_outputs(2)
+        .tx_params(TxParameters {
+            gas_price: 0,
+            gas_limit: 100_000_000,
+            maturity: 0,
+        })
+        .call_params(CallParameters::new(None, None, Some(100_000_000)))
+        .call()
+        .await
+        .unwrap();
+}
+
+async fn swap(fixture: &Fixture, amount_in: u64, token_0_in: bool, amount_out: u64) -> Result<FuelCallResponse<()>, Error> {
+    let (asset_id, out0, out1) = if token_0_in {
+        (fixture.token_0_asset_id, 0, amount_out)
+    } else {
+        (fixture.token_1_asset_id, amount_out, 0)
+    };
+
+    fixture.exchange_instance
+        .methods()
+        .swap(out0, out1, Identity::Address(fixture.wallet.address().into()))
+        .set_contracts(&[fixture.vault_contract_id.clone()])
+        .call_params(CallParameters::new(Some(amount_in), Some(asset_id), None))
+        .tx_params(TxParameters {
+            gas_price: 0,
+            gas_limit: 100_000_000,
+            maturity: 0,
+        })
+        .append_variable_outputs(1)
+        .call()
+        .await
+}
+
+#[tokio::test]
+async fn invalid_weights_should_fail() {
+    let is_err = deploy_pool(0).await.is_err();
+    assert!(is_err, "Pools without a weight shouldn't initialize");
+
+    let is_err = deploy_pool(100).await.is_err();
+    assert!(is_err, "Weights under 2% shouldn't initialize");
+
+    let is_err = deploy_pool(9_900).await.is_err();
+    assert!(is_err, "Weights over 98% shouldn't initialize");
+
+    let fixture = setup(2_000).await;
+    let weights = fixture.exchange_instance.methods().get_weights().call().await.unwrap().value;
+    assert_eq!(weights, (2_000, 8_000));
+}
+
+#[tokio::test]
+async fn weighted_swap_matches_formula() {
+    // An 80/20 token/ETH pool, priced at 1:1
+    let fixture = setup(2_000).await;
+    add_liquidity(&fixture, to_9_decimal(200), to_9_decimal(800)).await;
+
+    let amount_in = to_9_decimal(10);
+    let amount_out = fixture.exchange_instance
+        .methods()
+        .get_amount_out(amount_in, true)
+        .call()
+        .await
+        .unwrap()
+        .value;
+
+    let expected = weighted_amount_out(amount_in, to_9_decimal(200), to_9_decimal(800), 2_000, 8_000);
+    assert!((amount_out as f64 - expected).abs() < 10.0, "Output {} doesn't match {}", amount_out, expected);
+    assert!((amount_out as f64) < expected);
+
+    let is_err = swap(&fixture, amount_in, true, amount_out + to_9_decimal(1) / 1000).await.is_err();
+    assert!(is_err, "Pool shouldn't pay more than the curve allows");
+
+    let response = swap(&fixture, amount_in, true, amount_out).await.unwrap();
+    let logs = get_logs::<Swap, _>(&response, &fixture.exchange_contract_id, WEIGHTED_EXCHANGE_ABI);
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs.get(0).unwrap().amount_0_in, amount_in);
+    assert_eq!(logs.get(0).unwrap().amount_1_out, amount_out);
+
+    let pool_info = fixture.exchange_instance.methods().get_pool_info().call().await.unwrap().value;
+    assert_eq!(pool_info.token_0_reserve, to_9_decimal(210));
+    assert_eq!(pool_info.token_1_reserve, to_9_decimal(800) - amount_out);
+
+    // Swapping the other way uses the inverse exponent
+    let amount_in = to_9_decimal(5);
+    let amount_out = fixture.exchange_instance.methods().get_amount_out(amount_in, false).call().await.unwrap().value;
+    let expected = weighted_amount_out(amount_in, pool_info.token_1_reserve, pool_info.token_0_reserve, 8_000, 2_000);
+    assert!((amount_out as f64 - expected).abs() < 10.0, "Output {} doesn't match {}", amount_out, expected);
+    swap(&fixture, amount_in, false, amount_out).await.unwrap();
+}
+
+#[tokio::test]
+async fn weighted_exact_output() {
+    let fixture = setup(2_000).await;
+    add_liquidity(&fixture, to_9_decimal(200), to_9_decimal(800)).await;
+
+    let amount_out = to_9_decimal(3);
+    let amount_in = fixture.exchange_instance
+        .methods()
+        .get_amount_in(amount_out, true)
+        .call()
+        .await
+        .unwrap()
+        .value;
+
+    let expected = weighted_amount_in(amount_out, to_9_decimal(800), to_9_decimal(200), 8_000, 2_000);
+    assert!((amount_in as f64 - expected).abs() < 10.0, "Input {} doesn't match {}", amount_in, expected);
+    assert!((amount_in as f64) > expected);
+
+    let is_err = swap(&fixture, amount_in - to_9_decimal(1) / 1000, false, amount_out).await.is_err();
+    assert!(is_err, "Pool shouldn't accept less than the curve requires");
+
+    let starting_balance = fixture.wallet.get_asset_balance(&fixture.token_0_asset_id).await.unwrap();
+    swap(&fixture, amount_in, false, amount_out).await.unwrap();
+    let end_balance = fixture.wallet.get_asset_balance(&fixture.token_0_asset_id).await.unwrap();
+    assert_eq!(end_balance - starting_balance, amount_out);
+}
+
+#[tokio::test]
+async fn weighted_swaps_are_capped() {
+    let fixture = setup(2_000).await;
+    add_liquidity(&fixture, to_9_decimal(200), to_9_decimal(800)).await;
+
+    // Quotes are limited to 30% of the reserves
+    let is_err = fixture.exchange_instance.methods().get_amount_out(to_9_decimal(70), true).call().await.is_err();
+    assert!(is_err, "Inputs over 30% of the reserve shouldn't be quoted");
+
+    let is_err = fixture.exchange_instance.methods().get_amount_in(to_9_decimal(250), false).call().await.is_err();
+    assert!(is_err, "Outputs over 30% of the reserve shouldn't be quoted");
+
+    let amount_out = fixture.exchange_instance.methods().get_amount_out(to_9_decimal(50), true).call().await.unwrap().value;
+    swap(&fixture, to_9_decimal(50), true, amount_out).await.unwrap();
+}
+
+#[tokio::test]
+async fn large_swaps_dont_overflow_fee_math() {
+    let fixture = setup(5_000).await;
+    add_liquidity(&fixture, to_9_decimal(4_000_000_000), to_9_decimal(4_000_000_000)).await;
+
+    // The LP fee on this input is over u64::MAX before dividing by the basis points
+    let amount_in = to_9_decimal(1_000_000_000);
+    let amount_out = fixture.exchange_instance.methods().get_amount_out(amount_in, true).call().await.unwrap().value;
+    assert!(amount_out > 0);
+
+    let starting_balance = fixture.wallet.get_asset_balance(&fixture.token_1_asset_id).await.unwrap();
+    swap(&fixture, amount_in, true, amount_out).await.unwrap();
+    let end_balance = fixture.wallet.get_asset_balance(&fixture.token_1_asset_id).await.unwrap();
+    assert_eq!(end_balance - starting_balance, amount_out);
+}
@@ -15,46 +15,158 @@ import type {
 
 import type { Enum, Option } from './common';
 
-export type OptionalB256Input = Option<string>;
+export type AddressInput = { value: string };
 
-export type OptionalB256Output = Option<string>;
+export type AddressOutput = { value: string };
+
+export type ContractIdInput = { value: string };
+
+export type ContractIdOutput = { value: string };
+
+export type CurveTypeInput = Enum<{
+  ConstantProduct: [];
+  Stable: [];
+  Weighted: [];
+}>;
+
+export type CurveTypeOutput = Enum<{
+  ConstantProduct: [];
+  Stable: [];
+  Weighted: [];
+}>;
+
+export type IdentityInput = Enum<{
+  Address: AddressInput;
+  ContractId: ContractIdInput;
+}>;
+
+export type IdentityOutput = Enum<{
+  Address: AddressOutput;
+  ContractId: ContractIdOutput;
+}>;
 
 interface RegistryContractAbiInterface extends Interface {
   functions: {
+    accept_ownership: FunctionFragment;
     add_exchange_contract: FunctionFragment;
     exchange_contract_root: FunctionFragment;
+    get_curve_type: FunctionFragment;
     get_exchange_contract: FunctionFragment;
+    get_owner: FunctionFragment;
+    get_pending_owner: FunctionFragment;
+    get_pool_at: FunctionFragment;
+    get_pool_count: FunctionFragment;
+    get_pool_weights: FunctionFragment;
+    get_vault: FunctionFragment;
     initialize: FunctionFragment;
     is_pool: FunctionFragment;
+    is_token_denied: FunctionFragment;
+    remove_exchange_contract: FunctionFragment;
+    set_stable_template: FunctionFragment;
+    set_token_denied: FunctionFragment;
+    set_weighted_template: FunctionFragment;
+    stable_contract_root: FunctionFragment;
+    transfer_ownership: FunctionFragment;
+    weighted_contract_root: FunctionFragment;
   };
 
+  encodeFunctionData(functionFragment: 'accept_ownership', values?: undefined): Uint8Array;
   encodeFunctionData(functionFragment: 'add_exchange_contract', values: [string]): Uint8Array;
   encodeFunctionData(functionFragment: 'exchange_contract_root', values?: undefined): Uint8Array;
+  encodeFunctionData(functionFragment: 'get_curve_type', values: [string]): Uint8Array;
   encodeFunctionData(
     functionFragment: 'get_exchange_contract',
     values: [string, string]
   ): Uint8Array;
-  encodeFunctionData(functionFragment: 'initialize', values: [string]): Uint8Array;
+  encodeFunctionData(functionFragment: 'get_owner', values?: undefined): Uint8Array;
+  encodeFunctionData(functionFragment: 'get_pending_owner', values?: undefined): Uint8Array;
+  encodeFunctionData(functionFragment: 'get_pool_at', values: [BigNumberish]): Uint8Array;
+  encodeFunctionData(functionFragment: 'get_pool_count', values?: undefined): Uint8Array;
+  encodeFunctionData(functionFragment: 'get_pool_weights', values: [string]): Uint8Array;
+  encodeFunctionData(functionFragment: 'get_vault', values?: undefined): Uint8Array;
+  encodeFunctionData(
+    functionFragment: 'initialize',
+    values: [string, string, IdentityInput]
+  ): Uint8Array;
   encodeFunctionData(functionFragment: 'is_pool', values: [string]): Uint8Array;
+  encodeFunctionData(functionFragment: 'is_token_denied', values: [string]): Uint8Array;
+  encodeFunctionData(functionFragment: 'remove_exchange_contract', values: [string]): Uint8Array;
+  encodeFunctionData(functionFragment: 'set_stable_template', values: [string]): Uint8Array;
+  encodeFunctionData(functionFragment: 'set_token_denied', values: [string, boolean]): Uint8Array;
+  encodeFunctionData(functionFragment: 'set_weighted_template', values: [string]): Uint8Array;
+  encodeFunctionData(functionFragment: 'stable_contract_root', values?: undefined): Uint8Array;
+  encodeFunctionData(functionFragment: 'transfer_ownership', values: [IdentityInput]): Uint8Array;
+  encodeFunctionData(functionFragment: 'weighted_contract_root', values?: undefined): Uint8Array;
 
+  decodeFunctionData(functionFragment: 'accept_ownership', data: BytesLike): DecodedValue;
   decodeFunctionData(functionFragment: 'add_exchange_contract', data: BytesLike): DecodedValue;
   decodeFunctionData(functionFragment: 'exchange_contract_root', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'get_curve_type', data: BytesLike): DecodedValue;
   decodeFunctionData(functionFragment: 'get_exchange_contract', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'get_owner', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'get_pending_owner', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'get_pool_at', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'get_pool_count', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'get_pool_weights', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'get_vault', data: BytesLike): DecodedValue;
   decodeFunctionData(functionFragment: 'initialize', data: BytesLike): DecodedValue;
   decodeFunctionData(functionFragment: 'is_pool', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'is_token_denied', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'remove_exchange_contract', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'set_stable_template', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'set_token_denied', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'set_weighted_template', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'stable_contract_root', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'transfer_ownership', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'weighted_contract_root', data: BytesLike): DecodedValue;
 }
 
 export class RegistryContractAbi extends Contract {
   interface: RegistryContractAbiInterface;
   functions: {
+    accept_ownership: InvokeFunction<[], void>;
+
     add_exchange_contract: InvokeFunction<[exchange_id: string], void>;
 
     exchange_contract_root: InvokeFunction<[], string>;
 
-    get_exchange_contract: InvokeFunction<[token_a: string, token_b: string], OptionalB256Output>;
+    get_curve_type: InvokeFunction<[pool: string], CurveTypeOutput>;
+
+    get_exchange_contract: InvokeFunction<[token_a: string, token_b: string], Option<string>>;
+
+    get_owner: InvokeFunction<[], IdentityOutput>;
+
+    get_pending_owner: InvokeFunction<[], IdentityOutput>;
 
-    initialize: InvokeFunction<[template_exchange_id: string], void>;
+    get_pool_at: InvokeFunction<[index: BigNumberish], string>;
+
+    get_pool_count: InvokeFunction<[], BN>;
+
+    get_pool_weights: InvokeFunction<[pool: string], [BN, BN]>;
+
+    get_vault: InvokeFunction<[], string>;
+
+    initialize: InvokeFunction<
+      [template_exchange_id: string, vault: string, owner: IdentityInput],
+      void
+    >;
 
     is_pool: InvokeFunction<[addr: string], boolean>;
+
+    is_token_denied: InvokeFunction<[token: string], boolean>;
+
+    remove_exchange_contract: InvokeFunction<[exchange_id: string], void>;
+
+    set_stable_template: InvokeFunction<[template_exchange_id: string], void>;
+
+    set_token_denied: InvokeFunction<[token: string, denied: boolean], void>;
+
+    set_weighted_template: InvokeFunction<[template_exchange_id: string], void>;
+
+    stable_contract_root: InvokeFunction<[], string>;
+
+    transfer_ownership: InvokeFunction<[new_owner: IdentityInput], void>;
+
+    weighted_contract_root: InvokeFunction<[], string>;
   };
 }
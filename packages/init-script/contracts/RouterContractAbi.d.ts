@@ -63,7 +63,6 @@ export type IdentityOutput = Enum<{
 interface RouterContractAbiInterface extends Interface {
   functions: {
     add_liquidity: FunctionFragment;
-    null: FunctionFragment;
     remove_liquidity: FunctionFragment;
     swap_exact_input: FunctionFragment;
     swap_exact_input_multihop: FunctionFragment;
@@ -75,7 +74,6 @@ interface RouterContractAbiInterface extends Interface {
     functionFragment: 'add_liquidity',
     values: [string, BigNumberish, BigNumberish, BigNumberish, BigNumberish, IdentityInput]
   ): Uint8Array;
-  encodeFunctionData(functionFragment: 'null', values?: undefined): Uint8Array;
   encodeFunctionData(
     functionFragment: 'remove_liquidity',
     values: [BigNumberish, BigNumberish, IdentityInput]
@@ -98,7 +96,6 @@ interface RouterContractAbiInterface extends Interface {
   ): Uint8Array;
 
   decodeFunctionData(functionFragment: 'add_liquidity', data: BytesLike): DecodedValue;
-  decodeFunctionData(functionFragment: 'null', data: BytesLike): DecodedValue;
   decodeFunctionData(functionFragment: 'remove_liquidity', data: BytesLike): DecodedValue;
   decodeFunctionData(functionFragment: 'swap_exact_input', data: BytesLike): DecodedValue;
   decodeFunctionData(functionFragment: 'swap_exact_input_multihop', data: BytesLike): DecodedValue;
@@ -121,8 +118,6 @@ export class RouterContractAbi extends Contract {
       LiquidityOutputOutput
     >;
 
-    null: InvokeFunction<[], void>;
-
     remove_liquidity: InvokeFunction<
       [amount_0_min: BigNumberish, amount_1_min: BigNumberish, recipient: IdentityInput],
       LiquidityOutputOutput
@@ -13,6 +13,48 @@ import type {
   BN,
 } from 'fuels';
 
+import type { Enum, Option } from './common';
+
+export type AddressInput = { value: string };
+
+export type AddressOutput = { value: string };
+
+export type ContractIdInput = { value: string };
+
+export type ContractIdOutput = { value: string };
+
+export type BuybackConfigInput = {
+  router: string;
+  token: string;
+  recipient: IdentityInput;
+  twap_period: BigNumberish;
+  max_slippage: BigNumberish;
+};
+
+export type BuybackConfigOutput = {
+  router: string;
+  token: string;
+  recipient: IdentityOutput;
+  twap_period: BN;
+  max_slippage: BN;
+};
+
+export type PendingFeeChangeInput = {
+  start_fee: BigNumberish;
+  change_rate: BigNumberish;
+  eta: BigNumberish;
+  clear: boolean;
+  queued: boolean;
+};
+
+export type PendingFeeChangeOutput = {
+  start_fee: number;
+  change_rate: number;
+  eta: BN;
+  clear: boolean;
+  queued: boolean;
+};
+
 export type VaultFeeInput = {
   start_time: BigNumberish;
   start_fee: BigNumberish;
@@ -27,32 +69,261 @@ export type VaultFeeOutput = {
   change_rate: number;
 };
 
+export type RawVecInput = { ptr: any; cap: BigNumberish };
+
+export type RawVecOutput = { ptr: any; cap: BN };
+
+export type VecInput = { buf: RawVecInput; len: BigNumberish };
+
+export type VecOutput = { buf: RawVecOutput; len: BN };
+
+export type IdentityInput = Enum<{
+  Address: AddressInput;
+  ContractId: ContractIdInput;
+}>;
+
+export type IdentityOutput = Enum<{
+  Address: AddressOutput;
+  ContractId: ContractIdOutput;
+}>;
+
 interface VaultContractAbiInterface extends Interface {
   functions: {
+    accept_ownership: FunctionFragment;
+    buyback: FunctionFragment;
+    cancel_fee_change: FunctionFragment;
+    cancel_pool_fee_change: FunctionFragment;
+    claim_all: FunctionFragment;
     claim_fees: FunctionFragment;
+    claim_fees_batch: FunctionFragment;
+    distribute: FunctionFragment;
+    execute_fee_change: FunctionFragment;
+    execute_pool_fee_change: FunctionFragment;
+    get_balance: FunctionFragment;
+    get_buyback_config: FunctionFragment;
+    get_fee_change_delay: FunctionFragment;
+    get_fee_recipients: FunctionFragment;
     get_fees: FunctionFragment;
-    set_fees: FunctionFragment;
+    get_fees_for: FunctionFragment;
+    get_max_protocol_fee: FunctionFragment;
+    get_owner: FunctionFragment;
+    get_pending_fee_change: FunctionFragment;
+    get_pending_owner: FunctionFragment;
+    get_pending_pool_fee_change: FunctionFragment;
+    get_registry: FunctionFragment;
+    has_pool_fees: FunctionFragment;
+    initialize: FunctionFragment;
+    queue_fee_change: FunctionFragment;
+    queue_pool_fee_change: FunctionFragment;
+    queue_pool_fee_clear: FunctionFragment;
+    renounce_ownership: FunctionFragment;
+    set_buyback_config: FunctionFragment;
+    set_fee_recipients: FunctionFragment;
+    set_pool_dynamic_fee: FunctionFragment;
+    set_pool_max_fee_cache_age: FunctionFragment;
+    set_registry: FunctionFragment;
+    sync_pools: FunctionFragment;
+    transfer_ownership: FunctionFragment;
+    withdraw: FunctionFragment;
   };
 
+  encodeFunctionData(functionFragment: 'accept_ownership', values?: undefined): Uint8Array;
+  encodeFunctionData(
+    functionFragment: 'buyback',
+    values: [string, BigNumberish, VecInput]
+  ): Uint8Array;
+  encodeFunctionData(functionFragment: 'cancel_fee_change', values?: undefined): Uint8Array;
+  encodeFunctionData(functionFragment: 'cancel_pool_fee_change', values: [string]): Uint8Array;
+  encodeFunctionData(
+    functionFragment: 'claim_all',
+    values: [BigNumberish, BigNumberish]
+  ): Uint8Array;
   encodeFunctionData(functionFragment: 'claim_fees', values: [string]): Uint8Array;
+  encodeFunctionData(functionFragment: 'claim_fees_batch', values: [VecInput]): Uint8Array;
+  encodeFunctionData(functionFragment: 'distribute', values: [string]): Uint8Array;
+  encodeFunctionData(functionFragment: 'execute_fee_change', values?: undefined): Uint8Array;
+  encodeFunctionData(functionFragment: 'execute_pool_fee_change', values: [string]): Uint8Array;
+  encodeFunctionData(functionFragment: 'get_balance', values: [string]): Uint8Array;
+  encodeFunctionData(functionFragment: 'get_buyback_config', values?: undefined): Uint8Array;
+  encodeFunctionData(functionFragment: 'get_fee_change_delay', values?: undefined): Uint8Array;
+  encodeFunctionData(functionFragment: 'get_fee_recipients', values?: undefined): Uint8Array;
   encodeFunctionData(functionFragment: 'get_fees', values?: undefined): Uint8Array;
+  encodeFunctionData(functionFragment: 'get_fees_for', values: [string]): Uint8Array;
+  encodeFunctionData(functionFragment: 'get_max_protocol_fee', values?: undefined): Uint8Array;
+  encodeFunctionData(functionFragment: 'get_owner', values?: undefined): Uint8Array;
+  encodeFunctionData(functionFragment: 'get_pending_fee_change', values?: undefined): Uint8Array;
+  encodeFunctionData(functionFragment: 'get_pending_owner', values?: undefined): Uint8Array;
+  encodeFunctionData(functionFragment: 'get_pending_pool_fee_change', values: [string]): Uint8Array;
+  encodeFunctionData(functionFragment: 'get_registry', values?: undefined): Uint8Array;
+  encodeFunctionData(functionFragment: 'has_pool_fees', values: [string]): Uint8Array;
   encodeFunctionData(
-    functionFragment: 'set_fees',
-    values: [BigNumberish, BigNumberish]
+    functionFragment: 'initialize',
+    values: [IdentityInput, BigNumberish]
+  ): Uint8Array;
+  encodeFunctionData(
+    functionFragment: 'queue_fee_change',
+    values: [BigNumberish, BigNumberish, BigNumberish]
+  ): Uint8Array;
+  encodeFunctionData(
+    functionFragment: 'queue_pool_fee_change',
+    values: [string, BigNumberish, BigNumberish, BigNumberish]
+  ): Uint8Array;
+  encodeFunctionData(
+    functionFragment: 'queue_pool_fee_clear',
+    values: [string, BigNumberish]
+  ): Uint8Array;
+  encodeFunctionData(functionFragment: 'renounce_ownership', values?: undefined): Uint8Array;
+  encodeFunctionData(
+    functionFragment: 'set_buyback_config',
+    values: [BuybackConfigInput]
+  ): Uint8Array;
+  encodeFunctionData(functionFragment: 'set_fee_recipients', values: [VecInput]): Uint8Array;
+  encodeFunctionData(
+    functionFragment: 'set_pool_dynamic_fee',
+    values: [string, boolean, BigNumberish, BigNumberish, BigNumberish, BigNumberish]
+  ): Uint8Array;
+  encodeFunctionData(
+    functionFragment: 'set_pool_max_fee_cache_age',
+    values: [string, BigNumberish]
+  ): Uint8Array;
+  encodeFunctionData(functionFragment: 'set_registry', values: [string]): Uint8Array;
+  encodeFunctionData(functionFragment: 'sync_pools', values: [VecInput]): Uint8Array;
+  encodeFunctionData(functionFragment: 'transfer_ownership', values: [IdentityInput]): Uint8Array;
+  encodeFunctionData(
+    functionFragment: 'withdraw',
+    values: [string, BigNumberish, IdentityInput]
   ): Uint8Array;
 
+  decodeFunctionData(functionFragment: 'accept_ownership', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'buyback', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'cancel_fee_change', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'cancel_pool_fee_change', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'claim_all', data: BytesLike): DecodedValue;
   decodeFunctionData(functionFragment: 'claim_fees', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'claim_fees_batch', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'distribute', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'execute_fee_change', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'execute_pool_fee_change', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'get_balance', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'get_buyback_config', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'get_fee_change_delay', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'get_fee_recipients', data: BytesLike): DecodedValue;
   decodeFunctionData(functionFragment: 'get_fees', data: BytesLike): DecodedValue;
-  decodeFunctionData(functionFragment: 'set_fees', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'get_fees_for', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'get_max_protocol_fee', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'get_owner', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'get_pending_fee_change', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'get_pending_owner', data: BytesLike): DecodedValue;
+  decodeFunctionData(
+    functionFragment: 'get_pending_pool_fee_change',
+    data: BytesLike
+  ): DecodedValue;
+  decodeFunctionData(functionFragment: 'get_registry', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'has_pool_fees', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'initialize', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'queue_fee_change', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'queue_pool_fee_change', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'queue_pool_fee_clear', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'renounce_ownership', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'set_buyback_config', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'set_fee_recipients', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'set_pool_dynamic_fee', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'set_pool_max_fee_cache_age', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'set_registry', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'sync_pools', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'transfer_ownership', data: BytesLike): DecodedValue;
+  decodeFunctionData(functionFragment: 'withdraw', data: BytesLike): DecodedValue;
 }
 
 export class VaultContractAbi extends Contract {
   interface: VaultContractAbiInterface;
   functions: {
+    accept_ownership: InvokeFunction<[], void>;
+
+    buyback: InvokeFunction<[asset: string, amount: BigNumberish, pools: VecInput], BN>;
+
+    cancel_fee_change: InvokeFunction<[], void>;
+
+    cancel_pool_fee_change: InvokeFunction<[pool: string], void>;
+
+    claim_all: InvokeFunction<[start: BigNumberish, count: BigNumberish], VecOutput>;
+
     claim_fees: InvokeFunction<[pool: string], void>;
 
+    claim_fees_batch: InvokeFunction<[pools: VecInput], VecOutput>;
+
+    distribute: InvokeFunction<[asset: string], void>;
+
+    execute_fee_change: InvokeFunction<[], void>;
+
+    execute_pool_fee_change: InvokeFunction<[pool: string], void>;
+
+    get_balance: InvokeFunction<[asset: string], BN>;
+
+    get_buyback_config: InvokeFunction<[], BuybackConfigOutput>;
+
+    get_fee_change_delay: InvokeFunction<[], BN>;
+
+    get_fee_recipients: InvokeFunction<[], VecOutput>;
+
     get_fees: InvokeFunction<[], VaultFeeOutput>;
 
-    set_fees: InvokeFunction<[start_fee: BigNumberish, change_rate: BigNumberish], void>;
+    get_fees_for: InvokeFunction<[pool: string], VaultFeeOutput>;
+
+    get_max_protocol_fee: InvokeFunction<[], number>;
+
+    get_owner: InvokeFunction<[], IdentityOutput>;
+
+    get_pending_fee_change: InvokeFunction<[], PendingFeeChangeOutput>;
+
+    get_pending_owner: InvokeFunction<[], IdentityOutput>;
+
+    get_pending_pool_fee_change: InvokeFunction<[pool: string], PendingFeeChangeOutput>;
+
+    get_registry: InvokeFunction<[], string>;
+
+    has_pool_fees: InvokeFunction<[pool: string], boolean>;
+
+    initialize: InvokeFunction<[owner: IdentityInput, fee_change_delay: BigNumberish], void>;
+
+    queue_fee_change: InvokeFunction<
+      [start_fee: BigNumberish, change_rate: BigNumberish, eta: BigNumberish],
+      void
+    >;
+
+    queue_pool_fee_change: InvokeFunction<
+      [pool: string, start_fee: BigNumberish, change_rate: BigNumberish, eta: BigNumberish],
+      void
+    >;
+
+    queue_pool_fee_clear: InvokeFunction<[pool: string, eta: BigNumberish], void>;
+
+    renounce_ownership: InvokeFunction<[], void>;
+
+    set_buyback_config: InvokeFunction<[config: BuybackConfigInput], void>;
+
+    set_fee_recipients: InvokeFunction<[recipients: VecInput], void>;
+
+    set_pool_dynamic_fee: InvokeFunction<
+      [
+        pool: string,
+        enabled: boolean,
+        min_fee: BigNumberish,
+        max_fee: BigNumberish,
+        max_volatility: BigNumberish,
+        lookback: BigNumberish
+      ],
+      void
+    >;
+
+    set_pool_max_fee_cache_age: InvokeFunction<[pool: string, max_age: BigNumberish], void>;
+
+    set_registry: InvokeFunction<[registry: string], void>;
+
+    sync_pools: InvokeFunction<[pools: VecInput], void>;
+
+    transfer_ownership: InvokeFunction<[new_owner: IdentityInput], void>;
+
+    withdraw: InvokeFunction<[asset: string, amount: BigNumberish, recipient: IdentityInput], void>;
   };
 }
@@ -15,18 +15,57 @@ const _abi = {
     },
     {
       typeId: 1,
+      type: '(_, _)',
+      components: [
+        {
+          name: '__tuple_element',
+          type: 19,
+          typeArguments: null,
+        },
+        {
+          name: '__tuple_element',
+          type: 19,
+          typeArguments: null,
+        },
+      ],
+      typeParameters: null,
+    },
+    {
+      typeId: 2,
       type: 'b256',
       components: null,
       typeParameters: null,
     },
     {
-      typeId: 2,
+      typeId: 3,
       type: 'bool',
       components: null,
       typeParameters: null,
     },
     {
-      typeId: 3,
+      typeId: 4,
+      type: 'enum CurveType',
+      components: [
+        {
+          name: 'ConstantProduct',
+          type: 0,
+          typeArguments: null,
+        },
+        {
+          name: 'Stable',
+          type: 0,
+          typeArguments: null,
+        },
+        {
+          name: 'Weighted',
+          type: 0,
+          typeArguments: null,
+        },
+      ],
+      typeParameters: null,
+    },
+    {
+      typeId: 5,
       type: 'enum Error',
       components: [
         {
@@ -54,11 +93,68 @@ const _abi = {
           type: 0,
           typeArguments: null,
         },
+        {
+          name: 'MustBeCalledByOwner',
+          type: 0,
+          typeArguments: null,
+        },
+        {
+          name: 'MustBeCalledByDeployer',
+          type: 0,
+          typeArguments: null,
+        },
+        {
+          name: 'MustBeCalledByPendingOwner',
+          type: 0,
+          typeArguments: null,
+        },
+        {
+          name: 'NotRegistered',
+          type: 0,
+          typeArguments: null,
+        },
+        {
+          name: 'TokenDenied',
+          type: 0,
+          typeArguments: null,
+        },
+        {
+          name: 'InvalidVault',
+          type: 0,
+          typeArguments: null,
+        },
+        {
+          name: 'IndexOutOfBounds',
+          type: 0,
+          typeArguments: null,
+        },
+        {
+          name: 'NotWeighted',
+          type: 0,
+          typeArguments: null,
+        },
       ],
       typeParameters: null,
     },
     {
-      typeId: 4,
+      typeId: 6,
+      type: 'enum Identity',
+      components: [
+        {
+          name: 'Address',
+          type: 9,
+          typeArguments: null,
+        },
+        {
+          name: 'ContractId',
+          type: 10,
+          typeArguments: null,
+        },
+      ],
+      typeParameters: null,
+    },
+    {
+      typeId: 7,
       type: 'enum Option',
       components: [
         {
@@ -68,25 +164,215 @@ const _abi = {
         },
         {
           name: 'Some',
-          type: 5,
+          type: 8,
           typeArguments: null,
         },
       ],
-      typeParameters: [5],
+      typeParameters: [8],
     },
     {
-      typeId: 5,
+      typeId: 8,
       type: 'generic T',
       components: null,
       typeParameters: null,
     },
+    {
+      typeId: 9,
+      type: 'struct Address',
+      components: [
+        {
+          name: 'value',
+          type: 2,
+          typeArguments: null,
+        },
+      ],
+      typeParameters: null,
+    },
+    {
+      typeId: 10,
+      type: 'struct ContractId',
+      components: [
+        {
+          name: 'value',
+          type: 2,
+          typeArguments: null,
+        },
+      ],
+      typeParameters: null,
+    },
+    {
+      typeId: 11,
+      type: 'struct OwnershipTransferStarted',
+      components: [
+        {
+          name: 'previous_owner',
+          type: 6,
+          typeArguments: null,
+        },
+        {
+          name: 'new_owner',
+          type: 6,
+          typeArguments: null,
+        },
+      ],
+      typeParameters: null,
+    },
+    {
+      typeId: 12,
+      type: 'struct OwnershipTransferred',
+      components: [
+        {
+          name: 'previous_owner',
+          type: 6,
+          typeArguments: null,
+        },
+        {
+          name: 'new_owner',
+          type: 6,
+          typeArguments: null,
+        },
+      ],
+      typeParameters: null,
+    },
+    {
+      typeId: 13,
+      type: 'struct PoolRegistered',
+      components: [
+        {
+          name: 'token0',
+          type: 2,
+          typeArguments: null,
+        },
+        {
+          name: 'token1',
+          type: 2,
+          typeArguments: null,
+        },
+        {
+          name: 'pool',
+          type: 2,
+          typeArguments: null,
+        },
+        {
+          name: 'template_root',
+          type: 2,
+          typeArguments: null,
+        },
+        {
+          name: 'curve',
+          type: 4,
+          typeArguments: null,
+        },
+      ],
+      typeParameters: null,
+    },
+    {
+      typeId: 14,
+      type: 'struct PoolRemoved',
+      components: [
+        {
+          name: 'token0',
+          type: 2,
+          typeArguments: null,
+        },
+        {
+          name: 'token1',
+          type: 2,
+          typeArguments: null,
+        },
+        {
+          name: 'pool',
+          type: 2,
+          typeArguments: null,
+        },
+      ],
+      typeParameters: null,
+    },
+    {
+      typeId: 15,
+      type: 'struct RegistryInitialized',
+      components: [
+        {
+          name: 'template_root',
+          type: 2,
+          typeArguments: null,
+        },
+        {
+          name: 'vault',
+          type: 2,
+          typeArguments: null,
+        },
+        {
+          name: 'owner',
+          type: 6,
+          typeArguments: null,
+        },
+      ],
+      typeParameters: null,
+    },
+    {
+      typeId: 16,
+      type: 'struct StableTemplateSet',
+      components: [
+        {
+          name: 'template_root',
+          type: 2,
+          typeArguments: null,
+        },
+      ],
+      typeParameters: null,
+    },
+    {
+      typeId: 17,
+      type: 'struct TokenDenylistUpdated',
+      components: [
+        {
+          name: 'token',
+          type: 2,
+          typeArguments: null,
+        },
+        {
+          name: 'denied',
+          type: 3,
+          typeArguments: null,
+        },
+      ],
+      typeParameters: null,
+    },
+    {
+      typeId: 18,
+      type: 'struct WeightedTemplateSet',
+      components: [
+        {
+          name: 'template_root',
+          type: 2,
+          typeArguments: null,
+        },
+      ],
+      typeParameters: null,
+    },
+    {
+      typeId: 19,
+      type: 'u64',
+      components: null,
+      typeParameters: null,
+    },
   ],
   functions: [
+    {
+      inputs: [],
+      name: 'accept_ownership',
+      output: {
+        name: '',
+        type: 0,
+        typeArguments: null,
+      },
+    },
     {
       inputs: [
         {
           name: 'exchange_id',
-          type: 1,
+          type: 2,
           typeArguments: null,
         },
       ],
@@ -102,7 +388,22 @@ const _abi = {
       name: 'exchange_contract_root',
       output: {
         name: '',
-        type: 1,
+        type: 2,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [
+        {
+          name: 'pool',
+          type: 2,
+          typeArguments: null,
+        },
+      ],
+      name: 'get_curve_type',
+      output: {
+        name: '',
+        type: 4,
         typeArguments: null,
       },
     },
@@ -110,33 +411,109 @@ const _abi = {
       inputs: [
         {
           name: 'token_a',
-          type: 1,
+          type: 2,
           typeArguments: null,
         },
         {
           name: 'token_b',
-          type: 1,
+          type: 2,
           typeArguments: null,
         },
       ],
       name: 'get_exchange_contract',
       output: {
         name: '',
-        type: 4,
+        type: 7,
         typeArguments: [
           {
             name: '',
-            type: 1,
+            type: 2,
             typeArguments: null,
           },
         ],
       },
     },
+    {
+      inputs: [],
+      name: 'get_owner',
+      output: {
+        name: '',
+        type: 6,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [],
+      name: 'get_pending_owner',
+      output: {
+        name: '',
+        type: 6,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [
+        {
+          name: 'index',
+          type: 19,
+          typeArguments: null,
+        },
+      ],
+      name: 'get_pool_at',
+      output: {
+        name: '',
+        type: 2,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [],
+      name: 'get_pool_count',
+      output: {
+        name: '',
+        type: 19,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [
+        {
+          name: 'pool',
+          type: 2,
+          typeArguments: null,
+        },
+      ],
+      name: 'get_pool_weights',
+      output: {
+        name: '',
+        type: 1,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [],
+      name: 'get_vault',
+      output: {
+        name: '',
+        type: 2,
+        typeArguments: null,
+      },
+    },
     {
       inputs: [
         {
           name: 'template_exchange_id',
-          type: 1,
+          type: 2,
+          typeArguments: null,
+        },
+        {
+          name: 'vault',
+          type: 2,
+          typeArguments: null,
+        },
+        {
+          name: 'owner',
+          type: 6,
           typeArguments: null,
         },
       ],
@@ -151,11 +528,124 @@ const _abi = {
       inputs: [
         {
           name: 'addr',
-          type: 1,
+          type: 2,
           typeArguments: null,
         },
       ],
       name: 'is_pool',
+      output: {
+        name: '',
+        type: 3,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [
+        {
+          name: 'token',
+          type: 2,
+          typeArguments: null,
+        },
+      ],
+      name: 'is_token_denied',
+      output: {
+        name: '',
+        type: 3,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [
+        {
+          name: 'exchange_id',
+          type: 2,
+          typeArguments: null,
+        },
+      ],
+      name: 'remove_exchange_contract',
+      output: {
+        name: '',
+        type: 0,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [
+        {
+          name: 'template_exchange_id',
+          type: 2,
+          typeArguments: null,
+        },
+      ],
+      name: 'set_stable_template',
+      output: {
+        name: '',
+        type: 0,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [
+        {
+          name: 'token',
+          type: 2,
+          typeArguments: null,
+        },
+        {
+          name: 'denied',
+          type: 3,
+          typeArguments: null,
+        },
+      ],
+      name: 'set_token_denied',
+      output: {
+        name: '',
+        type: 0,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [
+        {
+          name: 'template_exchange_id',
+          type: 2,
+          typeArguments: null,
+        },
+      ],
+      name: 'set_weighted_template',
+      output: {
+        name: '',
+        type: 0,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [],
+      name: 'stable_contract_root',
+      output: {
+        name: '',
+        type: 2,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [
+        {
+          name: 'new_owner',
+          type: 6,
+          typeArguments: null,
+        },
+      ],
+      name: 'transfer_ownership',
+      output: {
+        name: '',
+        type: 0,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [],
+      name: 'weighted_contract_root',
       output: {
         name: '',
         type: 2,
@@ -168,7 +658,7 @@ const _abi = {
       logId: 0,
       loggedType: {
         name: '',
-        type: 3,
+        type: 5,
         typeArguments: [],
       },
     },
@@ -176,7 +666,7 @@ const _abi = {
       logId: 1,
       loggedType: {
         name: '',
-        type: 3,
+        type: 5,
         typeArguments: [],
       },
     },
@@ -184,7 +674,7 @@ const _abi = {
       logId: 2,
       loggedType: {
         name: '',
-        type: 3,
+        type: 5,
         typeArguments: [],
       },
     },
@@ -192,7 +682,7 @@ const _abi = {
       logId: 3,
       loggedType: {
         name: '',
-        type: 3,
+        type: 5,
         typeArguments: [],
       },
     },
@@ -200,7 +690,159 @@ const _abi = {
       logId: 4,
       loggedType: {
         name: '',
-        type: 3,
+        type: 15,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 5,
+      loggedType: {
+        name: '',
+        type: 5,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 6,
+      loggedType: {
+        name: '',
+        type: 5,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 7,
+      loggedType: {
+        name: '',
+        type: 5,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 8,
+      loggedType: {
+        name: '',
+        type: 5,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 9,
+      loggedType: {
+        name: '',
+        type: 5,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 10,
+      loggedType: {
+        name: '',
+        type: 5,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 11,
+      loggedType: {
+        name: '',
+        type: 13,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 12,
+      loggedType: {
+        name: '',
+        type: 5,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 13,
+      loggedType: {
+        name: '',
+        type: 5,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 14,
+      loggedType: {
+        name: '',
+        type: 5,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 15,
+      loggedType: {
+        name: '',
+        type: 5,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 16,
+      loggedType: {
+        name: '',
+        type: 11,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 17,
+      loggedType: {
+        name: '',
+        type: 5,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 18,
+      loggedType: {
+        name: '',
+        type: 12,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 19,
+      loggedType: {
+        name: '',
+        type: 16,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 20,
+      loggedType: {
+        name: '',
+        type: 18,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 21,
+      loggedType: {
+        name: '',
+        type: 5,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 22,
+      loggedType: {
+        name: '',
+        type: 14,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 23,
+      loggedType: {
+        name: '',
+        type: 17,
         typeArguments: [],
       },
     },
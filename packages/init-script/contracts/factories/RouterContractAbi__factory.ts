@@ -253,15 +253,6 @@ const _abi = {
         typeArguments: null,
       },
     },
-    {
-      inputs: [],
-      name: 'null',
-      output: {
-        name: '',
-        type: 0,
-        typeArguments: null,
-      },
-    },
     {
       inputs: [
         {
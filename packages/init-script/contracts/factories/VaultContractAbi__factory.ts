@@ -21,6 +21,12 @@ const _abi = {
     },
     {
       typeId: 2,
+      type: 'bool',
+      components: null,
+      typeParameters: null,
+    },
+    {
+      typeId: 3,
       type: 'enum Error',
       components: [
         {
@@ -28,101 +34,1436 @@ const _abi = {
           type: 0,
           typeArguments: null,
         },
+        {
+          name: 'MustBeCalledByDeployer',
+          type: 0,
+          typeArguments: null,
+        },
+        {
+          name: 'MustBeCalledByPendingOwner',
+          type: 0,
+          typeArguments: null,
+        },
+        {
+          name: 'AlreadyInitialized',
+          type: 0,
+          typeArguments: null,
+        },
+        {
+          name: 'RegistryNotSet',
+          type: 0,
+          typeArguments: null,
+        },
+        {
+          name: 'InvalidRegistry',
+          type: 0,
+          typeArguments: null,
+        },
+        {
+          name: 'InsufficientBalance',
+          type: 0,
+          typeArguments: null,
+        },
+        {
+          name: 'InvalidShares',
+          type: 0,
+          typeArguments: null,
+        },
+        {
+          name: 'NoFeeRecipients',
+          type: 0,
+          typeArguments: null,
+        },
+        {
+          name: 'FeeChangeDelayTooShort',
+          type: 0,
+          typeArguments: null,
+        },
+        {
+          name: 'EtaTooSoon',
+          type: 0,
+          typeArguments: null,
+        },
+        {
+          name: 'NoPendingFeeChange',
+          type: 0,
+          typeArguments: null,
+        },
+        {
+          name: 'FeeChangeNotReady',
+          type: 0,
+          typeArguments: null,
+        },
+        {
+          name: 'FeeTooHigh',
+          type: 0,
+          typeArguments: null,
+        },
+        {
+          name: 'ChangeRateTooHigh',
+          type: 0,
+          typeArguments: null,
+        },
+        {
+          name: 'BuybackNotConfigured',
+          type: 0,
+          typeArguments: null,
+        },
+        {
+          name: 'InvalidBuybackConfig',
+          type: 0,
+          typeArguments: null,
+        },
+        {
+          name: 'InvalidPath',
+          type: 0,
+          typeArguments: null,
+        },
+        {
+          name: 'PoolNotRegistered',
+          type: 0,
+          typeArguments: null,
+        },
+        {
+          name: 'TWAPPeriodTooShort',
+          type: 0,
+          typeArguments: null,
+        },
+        {
+          name: 'InsufficientTWAPHistory',
+          type: 0,
+          typeArguments: null,
+        },
       ],
       typeParameters: null,
     },
     {
-      typeId: 3,
-      type: 'struct VaultFee',
+      typeId: 4,
+      type: 'enum Identity',
       components: [
         {
-          name: 'start_time',
-          type: 5,
+          name: 'Address',
+          type: 7,
           typeArguments: null,
         },
         {
-          name: 'start_fee',
-          type: 4,
+          name: 'ContractId',
+          type: 11,
+          typeArguments: null,
+        },
+      ],
+      typeParameters: null,
+    },
+    {
+      typeId: 5,
+      type: 'generic T',
+      components: null,
+      typeParameters: null,
+    },
+    {
+      typeId: 6,
+      type: 'raw untyped ptr',
+      components: null,
+      typeParameters: null,
+    },
+    {
+      typeId: 7,
+      type: 'struct Address',
+      components: [
+        {
+          name: 'value',
+          type: 1,
+          typeArguments: null,
+        },
+      ],
+      typeParameters: null,
+    },
+    {
+      typeId: 8,
+      type: 'struct Buyback',
+      components: [
+        {
+          name: 'asset',
+          type: 1,
           typeArguments: null,
         },
         {
-          name: 'current_fee',
+          name: 'amount_in',
+          type: 27,
+          typeArguments: null,
+        },
+        {
+          name: 'token',
+          type: 1,
+          typeArguments: null,
+        },
+        {
+          name: 'amount_out',
+          type: 27,
+          typeArguments: null,
+        },
+        {
+          name: 'recipient',
           type: 4,
           typeArguments: null,
         },
+      ],
+      typeParameters: null,
+    },
+    {
+      typeId: 9,
+      type: 'struct BuybackConfig',
+      components: [
         {
-          name: 'change_rate',
+          name: 'router',
+          type: 1,
+          typeArguments: null,
+        },
+        {
+          name: 'token',
+          type: 1,
+          typeArguments: null,
+        },
+        {
+          name: 'recipient',
           type: 4,
           typeArguments: null,
         },
+        {
+          name: 'twap_period',
+          type: 27,
+          typeArguments: null,
+        },
+        {
+          name: 'max_slippage',
+          type: 27,
+          typeArguments: null,
+        },
       ],
       typeParameters: null,
     },
     {
-      typeId: 4,
-      type: 'u16',
-      components: null,
+      typeId: 10,
+      type: 'struct ClaimedFees',
+      components: [
+        {
+          name: 'pool',
+          type: 1,
+          typeArguments: null,
+        },
+        {
+          name: 'token0',
+          type: 1,
+          typeArguments: null,
+        },
+        {
+          name: 'token1',
+          type: 1,
+          typeArguments: null,
+        },
+        {
+          name: 'amount_0',
+          type: 27,
+          typeArguments: null,
+        },
+        {
+          name: 'amount_1',
+          type: 27,
+          typeArguments: null,
+        },
+      ],
       typeParameters: null,
     },
     {
-      typeId: 5,
-      type: 'u32',
-      components: null,
+      typeId: 11,
+      type: 'struct ContractId',
+      components: [
+        {
+          name: 'value',
+          type: 1,
+          typeArguments: null,
+        },
+      ],
       typeParameters: null,
     },
-  ],
-  functions: [
     {
-      inputs: [
+      typeId: 12,
+      type: 'struct FeeChangeCancelled',
+      components: [
         {
           name: 'pool',
           type: 1,
           typeArguments: null,
         },
       ],
-      name: 'claim_fees',
-      output: {
-        name: '',
-        type: 0,
-        typeArguments: null,
-      },
+      typeParameters: null,
     },
     {
-      inputs: [],
-      name: 'get_fees',
-      output: {
-        name: '',
-        type: 3,
-        typeArguments: null,
-      },
+      typeId: 13,
+      type: 'struct FeeChangeExecuted',
+      components: [
+        {
+          name: 'pool',
+          type: 1,
+          typeArguments: null,
+        },
+        {
+          name: 'start_fee',
+          type: 25,
+          typeArguments: null,
+        },
+        {
+          name: 'change_rate',
+          type: 25,
+          typeArguments: null,
+        },
+        {
+          name: 'clear',
+          type: 2,
+          typeArguments: null,
+        },
+      ],
+      typeParameters: null,
     },
     {
-      inputs: [
+      typeId: 14,
+      type: 'struct FeeChangeQueued',
+      components: [
+        {
+          name: 'pool',
+          type: 1,
+          typeArguments: null,
+        },
         {
           name: 'start_fee',
-          type: 4,
+          type: 25,
           typeArguments: null,
         },
         {
           name: 'change_rate',
+          type: 25,
+          typeArguments: null,
+        },
+        {
+          name: 'clear',
+          type: 2,
+          typeArguments: null,
+        },
+        {
+          name: 'eta',
+          type: 27,
+          typeArguments: null,
+        },
+      ],
+      typeParameters: null,
+    },
+    {
+      typeId: 15,
+      type: 'struct FeeRecipient',
+      components: [
+        {
+          name: 'recipient',
           type: 4,
           typeArguments: null,
         },
+        {
+          name: 'share',
+          type: 27,
+          typeArguments: null,
+        },
       ],
-      name: 'set_fees',
-      output: {
-        name: '',
-        type: 0,
-        typeArguments: null,
-      },
+      typeParameters: null,
     },
-  ],
-  loggedTypes: [
     {
-      logId: 0,
-      loggedType: {
-        name: '',
-        type: 2,
+      typeId: 16,
+      type: 'struct FeeRecipientsUpdated',
+      components: [
+        {
+          name: 'recipient_count',
+          type: 27,
+          typeArguments: null,
+        },
+      ],
+      typeParameters: null,
+    },
+    {
+      typeId: 17,
+      type: 'struct FeesClaimed',
+      components: [
+        {
+          name: 'asset',
+          type: 1,
+          typeArguments: null,
+        },
+        {
+          name: 'amount',
+          type: 27,
+          typeArguments: null,
+        },
+      ],
+      typeParameters: null,
+    },
+    {
+      typeId: 18,
+      type: 'struct FeesDistributed',
+      components: [
+        {
+          name: 'asset',
+          type: 1,
+          typeArguments: null,
+        },
+        {
+          name: 'amount',
+          type: 27,
+          typeArguments: null,
+        },
+        {
+          name: 'recipient_count',
+          type: 27,
+          typeArguments: null,
+        },
+      ],
+      typeParameters: null,
+    },
+    {
+      typeId: 19,
+      type: 'struct PendingFeeChange',
+      components: [
+        {
+          name: 'start_fee',
+          type: 25,
+          typeArguments: null,
+        },
+        {
+          name: 'change_rate',
+          type: 25,
+          typeArguments: null,
+        },
+        {
+          name: 'eta',
+          type: 27,
+          typeArguments: null,
+        },
+        {
+          name: 'clear',
+          type: 2,
+          typeArguments: null,
+        },
+        {
+          name: 'queued',
+          type: 2,
+          typeArguments: null,
+        },
+      ],
+      typeParameters: null,
+    },
+    {
+      typeId: 20,
+      type: 'struct PoolsClaimed',
+      components: [
+        {
+          name: 'pools_claimed',
+          type: 27,
+          typeArguments: null,
+        },
+        {
+          name: 'pools_skipped',
+          type: 27,
+          typeArguments: null,
+        },
+      ],
+      typeParameters: null,
+    },
+    {
+      typeId: 21,
+      type: 'struct RawVec',
+      components: [
+        {
+          name: 'ptr',
+          type: 6,
+          typeArguments: null,
+        },
+        {
+          name: 'cap',
+          type: 27,
+          typeArguments: null,
+        },
+      ],
+      typeParameters: [5],
+    },
+    {
+      typeId: 22,
+      type: 'struct TreasuryWithdrawal',
+      components: [
+        {
+          name: 'asset',
+          type: 1,
+          typeArguments: null,
+        },
+        {
+          name: 'amount',
+          type: 27,
+          typeArguments: null,
+        },
+        {
+          name: 'recipient',
+          type: 4,
+          typeArguments: null,
+        },
+      ],
+      typeParameters: null,
+    },
+    {
+      typeId: 23,
+      type: 'struct VaultFee',
+      components: [
+        {
+          name: 'start_time',
+          type: 26,
+          typeArguments: null,
+        },
+        {
+          name: 'start_fee',
+          type: 25,
+          typeArguments: null,
+        },
+        {
+          name: 'current_fee',
+          type: 25,
+          typeArguments: null,
+        },
+        {
+          name: 'change_rate',
+          type: 25,
+          typeArguments: null,
+        },
+      ],
+      typeParameters: null,
+    },
+    {
+      typeId: 24,
+      type: 'struct Vec',
+      components: [
+        {
+          name: 'buf',
+          type: 21,
+          typeArguments: [
+            {
+              name: '',
+              type: 5,
+              typeArguments: null,
+            },
+          ],
+        },
+        {
+          name: 'len',
+          type: 27,
+          typeArguments: null,
+        },
+      ],
+      typeParameters: [5],
+    },
+    {
+      typeId: 25,
+      type: 'u16',
+      components: null,
+      typeParameters: null,
+    },
+    {
+      typeId: 26,
+      type: 'u32',
+      components: null,
+      typeParameters: null,
+    },
+    {
+      typeId: 27,
+      type: 'u64',
+      components: null,
+      typeParameters: null,
+    },
+  ],
+  functions: [
+    {
+      inputs: [],
+      name: 'accept_ownership',
+      output: {
+        name: '',
+        type: 0,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [
+        {
+          name: 'asset',
+          type: 1,
+          typeArguments: null,
+        },
+        {
+          name: 'amount',
+          type: 27,
+          typeArguments: null,
+        },
+        {
+          name: 'pools',
+          type: 24,
+          typeArguments: [
+            {
+              name: '',
+              type: 1,
+              typeArguments: null,
+            },
+          ],
+        },
+      ],
+      name: 'buyback',
+      output: {
+        name: '',
+        type: 27,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [],
+      name: 'cancel_fee_change',
+      output: {
+        name: '',
+        type: 0,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [
+        {
+          name: 'pool',
+          type: 1,
+          typeArguments: null,
+        },
+      ],
+      name: 'cancel_pool_fee_change',
+      output: {
+        name: '',
+        type: 0,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [
+        {
+          name: 'start',
+          type: 27,
+          typeArguments: null,
+        },
+        {
+          name: 'count',
+          type: 27,
+          typeArguments: null,
+        },
+      ],
+      name: 'claim_all',
+      output: {
+        name: '',
+        type: 24,
+        typeArguments: [
+          {
+            name: '',
+            type: 10,
+            typeArguments: null,
+          },
+        ],
+      },
+    },
+    {
+      inputs: [
+        {
+          name: 'pool',
+          type: 1,
+          typeArguments: null,
+        },
+      ],
+      name: 'claim_fees',
+      output: {
+        name: '',
+        type: 0,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [
+        {
+          name: 'pools',
+          type: 24,
+          typeArguments: [
+            {
+              name: '',
+              type: 1,
+              typeArguments: null,
+            },
+          ],
+        },
+      ],
+      name: 'claim_fees_batch',
+      output: {
+        name: '',
+        type: 24,
+        typeArguments: [
+          {
+            name: '',
+            type: 10,
+            typeArguments: null,
+          },
+        ],
+      },
+    },
+    {
+      inputs: [
+        {
+          name: 'asset',
+          type: 1,
+          typeArguments: null,
+        },
+      ],
+      name: 'distribute',
+      output: {
+        name: '',
+        type: 0,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [],
+      name: 'execute_fee_change',
+      output: {
+        name: '',
+        type: 0,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [
+        {
+          name: 'pool',
+          type: 1,
+          typeArguments: null,
+        },
+      ],
+      name: 'execute_pool_fee_change',
+      output: {
+        name: '',
+        type: 0,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [
+        {
+          name: 'asset',
+          type: 1,
+          typeArguments: null,
+        },
+      ],
+      name: 'get_balance',
+      output: {
+        name: '',
+        type: 27,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [],
+      name: 'get_buyback_config',
+      output: {
+        name: '',
+        type: 9,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [],
+      name: 'get_fee_change_delay',
+      output: {
+        name: '',
+        type: 27,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [],
+      name: 'get_fee_recipients',
+      output: {
+        name: '',
+        type: 24,
+        typeArguments: [
+          {
+            name: '',
+            type: 15,
+            typeArguments: null,
+          },
+        ],
+      },
+    },
+    {
+      inputs: [],
+      name: 'get_fees',
+      output: {
+        name: '',
+        type: 23,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [
+        {
+          name: 'pool',
+          type: 1,
+          typeArguments: null,
+        },
+      ],
+      name: 'get_fees_for',
+      output: {
+        name: '',
+        type: 23,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [],
+      name: 'get_max_protocol_fee',
+      output: {
+        name: '',
+        type: 25,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [],
+      name: 'get_owner',
+      output: {
+        name: '',
+        type: 4,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [],
+      name: 'get_pending_fee_change',
+      output: {
+        name: '',
+        type: 19,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [],
+      name: 'get_pending_owner',
+      output: {
+        name: '',
+        type: 4,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [
+        {
+          name: 'pool',
+          type: 1,
+          typeArguments: null,
+        },
+      ],
+      name: 'get_pending_pool_fee_change',
+      output: {
+        name: '',
+        type: 19,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [],
+      name: 'get_registry',
+      output: {
+        name: '',
+        type: 1,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [
+        {
+          name: 'pool',
+          type: 1,
+          typeArguments: null,
+        },
+      ],
+      name: 'has_pool_fees',
+      output: {
+        name: '',
+        type: 2,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [
+        {
+          name: 'owner',
+          type: 4,
+          typeArguments: null,
+        },
+        {
+          name: 'fee_change_delay',
+          type: 27,
+          typeArguments: null,
+        },
+      ],
+      name: 'initialize',
+      output: {
+        name: '',
+        type: 0,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [
+        {
+          name: 'start_fee',
+          type: 25,
+          typeArguments: null,
+        },
+        {
+          name: 'change_rate',
+          type: 25,
+          typeArguments: null,
+        },
+        {
+          name: 'eta',
+          type: 27,
+          typeArguments: null,
+        },
+      ],
+      name: 'queue_fee_change',
+      output: {
+        name: '',
+        type: 0,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [
+        {
+          name: 'pool',
+          type: 1,
+          typeArguments: null,
+        },
+        {
+          name: 'start_fee',
+          type: 25,
+          typeArguments: null,
+        },
+        {
+          name: 'change_rate',
+          type: 25,
+          typeArguments: null,
+        },
+        {
+          name: 'eta',
+          type: 27,
+          typeArguments: null,
+        },
+      ],
+      name: 'queue_pool_fee_change',
+      output: {
+        name: '',
+        type: 0,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [
+        {
+          name: 'pool',
+          type: 1,
+          typeArguments: null,
+        },
+        {
+          name: 'eta',
+          type: 27,
+          typeArguments: null,
+        },
+      ],
+      name: 'queue_pool_fee_clear',
+      output: {
+        name: '',
+        type: 0,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [],
+      name: 'renounce_ownership',
+      output: {
+        name: '',
+        type: 0,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [
+        {
+          name: 'config',
+          type: 9,
+          typeArguments: null,
+        },
+      ],
+      name: 'set_buyback_config',
+      output: {
+        name: '',
+        type: 0,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [
+        {
+          name: 'recipients',
+          type: 24,
+          typeArguments: [
+            {
+              name: '',
+              type: 15,
+              typeArguments: null,
+            },
+          ],
+        },
+      ],
+      name: 'set_fee_recipients',
+      output: {
+        name: '',
+        type: 0,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [
+        {
+          name: 'pool',
+          type: 1,
+          typeArguments: null,
+        },
+        {
+          name: 'enabled',
+          type: 2,
+          typeArguments: null,
+        },
+        {
+          name: 'min_fee',
+          type: 27,
+          typeArguments: null,
+        },
+        {
+          name: 'max_fee',
+          type: 27,
+          typeArguments: null,
+        },
+        {
+          name: 'max_volatility',
+          type: 27,
+          typeArguments: null,
+        },
+        {
+          name: 'lookback',
+          type: 27,
+          typeArguments: null,
+        },
+      ],
+      name: 'set_pool_dynamic_fee',
+      output: {
+        name: '',
+        type: 0,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [
+        {
+          name: 'pool',
+          type: 1,
+          typeArguments: null,
+        },
+        {
+          name: 'max_age',
+          type: 27,
+          typeArguments: null,
+        },
+      ],
+      name: 'set_pool_max_fee_cache_age',
+      output: {
+        name: '',
+        type: 0,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [
+        {
+          name: 'registry',
+          type: 1,
+          typeArguments: null,
+        },
+      ],
+      name: 'set_registry',
+      output: {
+        name: '',
+        type: 0,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [
+        {
+          name: 'pools',
+          type: 24,
+          typeArguments: [
+            {
+              name: '',
+              type: 1,
+              typeArguments: null,
+            },
+          ],
+        },
+      ],
+      name: 'sync_pools',
+      output: {
+        name: '',
+        type: 0,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [
+        {
+          name: 'new_owner',
+          type: 4,
+          typeArguments: null,
+        },
+      ],
+      name: 'transfer_ownership',
+      output: {
+        name: '',
+        type: 0,
+        typeArguments: null,
+      },
+    },
+    {
+      inputs: [
+        {
+          name: 'asset',
+          type: 1,
+          typeArguments: null,
+        },
+        {
+          name: 'amount',
+          type: 27,
+          typeArguments: null,
+        },
+        {
+          name: 'recipient',
+          type: 4,
+          typeArguments: null,
+        },
+      ],
+      name: 'withdraw',
+      output: {
+        name: '',
+        type: 0,
+        typeArguments: null,
+      },
+    },
+  ],
+  loggedTypes: [
+    {
+      logId: 0,
+      loggedType: {
+        name: '',
+        type: 3,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 1,
+      loggedType: {
+        name: '',
+        type: 3,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 2,
+      loggedType: {
+        name: '',
+        type: 3,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 3,
+      loggedType: {
+        name: '',
+        type: 3,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 4,
+      loggedType: {
+        name: '',
+        type: 3,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 5,
+      loggedType: {
+        name: '',
+        type: 14,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 6,
+      loggedType: {
+        name: '',
+        type: 3,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 7,
+      loggedType: {
+        name: '',
+        type: 3,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 8,
+      loggedType: {
+        name: '',
+        type: 13,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 9,
+      loggedType: {
+        name: '',
+        type: 3,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 10,
+      loggedType: {
+        name: '',
+        type: 12,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 11,
+      loggedType: {
+        name: '',
+        type: 3,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 12,
+      loggedType: {
+        name: '',
+        type: 3,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 13,
+      loggedType: {
+        name: '',
+        type: 17,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 14,
+      loggedType: {
+        name: '',
+        type: 20,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 15,
+      loggedType: {
+        name: '',
+        type: 3,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 16,
+      loggedType: {
+        name: '',
+        type: 3,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 17,
+      loggedType: {
+        name: '',
+        type: 3,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 18,
+      loggedType: {
+        name: '',
+        type: 3,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 19,
+      loggedType: {
+        name: '',
+        type: 3,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 20,
+      loggedType: {
+        name: '',
+        type: 3,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 21,
+      loggedType: {
+        name: '',
+        type: 3,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 22,
+      loggedType: {
+        name: '',
+        type: 3,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 23,
+      loggedType: {
+        name: '',
+        type: 3,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 24,
+      loggedType: {
+        name: '',
+        type: 3,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 25,
+      loggedType: {
+        name: '',
+        type: 8,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 26,
+      loggedType: {
+        name: '',
+        type: 3,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 27,
+      loggedType: {
+        name: '',
+        type: 3,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 28,
+      loggedType: {
+        name: '',
+        type: 3,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 29,
+      loggedType: {
+        name: '',
+        type: 22,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 30,
+      loggedType: {
+        name: '',
+        type: 3,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 31,
+      loggedType: {
+        name: '',
+        type: 3,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 32,
+      loggedType: {
+        name: '',
+        type: 16,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 33,
+      loggedType: {
+        name: '',
+        type: 3,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 34,
+      loggedType: {
+        name: '',
+        type: 3,
+        typeArguments: [],
+      },
+    },
+    {
+      logId: 35,
+      loggedType: {
+        name: '',
+        type: 18,
         typeArguments: [],
       },
     },
@@ -0,0 +1,68 @@
+import { readFileSync } from 'fs';
+import { join } from 'path';
+
+import { bn, concat, ScriptTransactionRequest } from 'fuels';
+import type { BigNumberish, BytesLike, CoinQuantityLike, Wallet } from 'fuels';
+
+const ADD_LIQUIDITY_SCRIPT = join(
+  __dirname,
+  '../../../contracts/add_liquidity_script/out/debug/add_liquidity_script.bin'
+);
+
+// Script arguments, laid out as in Sway: the pool, four u64 amounts, then the
+// recipient identity as its variant (0 for an address) followed by the address
+function encodeScriptData(
+  pool: string,
+  amounts: BigNumberish[],
+  recipient: string
+): BytesLike {
+  return concat([
+    pool,
+    ...amounts.map((amount) => bn(amount).toBytes(8)),
+    bn(0).toBytes(8),
+    recipient,
+  ]);
+}
+
+/**
+ * Add liquidity in one transaction, sending the wallet's coins straight to the pool
+ * from the add liquidity script. Coins the pool doesn't use are returned as change.
+ */
+export async function addLiquidityWithScript(
+  wallet: Wallet,
+  pool: string,
+  token0Id: string,
+  token1Id: string,
+  amount0Desired: BigNumberish,
+  amount1Desired: BigNumberish,
+  amount0Min: BigNumberish,
+  amount1Min: BigNumberish,
+  overrides: any
+) {
+  const request = new ScriptTransactionRequest({
+    ...overrides,
+    gasLimit: 100_000_000,
+    script: readFileSync(ADD_LIQUIDITY_SCRIPT),
+    scriptData: encodeScriptData(
+      pool,
+      [amount0Desired, amount1Desired, amount0Min, amount1Min],
+      wallet.address.toB256()
+    ),
+  });
+  request.addContract(pool);
+  // For the LP tokens
+  request.addVariableOutputs(1);
+
+  const fee = request.calculateFee();
+  const quantities: CoinQuantityLike[] = [
+    [amount0Desired, token0Id],
+    [amount1Desired, token1Id],
+  ];
+  if (fee.amount.gt(0)) {
+    quantities.push([fee.amount, fee.assetId]);
+  }
+  request.addCoins(await wallet.getCoinsToSpend(quantities));
+
+  const response = await wallet.sendTransaction(request);
+  return response.wait();
+}
@@ -2,10 +2,11 @@ import { bn, Wallet } from 'fuels';
 
 import '../../load.envs';
 import './loadDockerEnv';
-import { ExchangeContractAbi__factory, RegistryContractAbi__factory, RouterContractAbi__factory, TokenContractAbi__factory } from '../../contracts';
+import { ExchangeContractAbi__factory, RegistryContractAbi__factory, RouterContractAbi__factory, TokenContractAbi__factory, VaultContractAbi__factory } from '../../contracts';
 
 import { initializePool } from './initializePool';
 import { initializeTokenContract } from './initializeTokenContract';
+import { initializeVault } from './initializeVault';
 import { registerPool } from './registerPool';
 
 const {
@@ -16,8 +17,16 @@ const {
   VITE_EXCHANGE_2_ID,
   VITE_TOKEN_1_ID,
   VITE_TOKEN_2_ID,
+  VITE_TOKEN_USDC_ID,
+  VITE_TOKEN_USDT_ID,
+  VITE_TOKEN_STETH_ID,
+  VITE_STABLE_EXCHANGE_USDC_USDT_ID,
+  VITE_STABLE_EXCHANGE_ETH_STETH_ID,
+  VITE_TOKEN_GOVERNANCE_ID,
+  VITE_WEIGHTED_EXCHANGE_GOVERNANCE_ETH_ID,
   ROUTER_CONTRACT_ID,
-  REGISTRY_CONTRACT_ID
+  REGISTRY_CONTRACT_ID,
+  VAULT_CONTRACT_ID
 } = process.env;
 
 if (!WALLET_SECRET) {
@@ -29,9 +38,9 @@ async function main() {
   const wallet = Wallet.fromPrivateKey(WALLET_SECRET!, PROVIDER_URL);
   console.log('using wallet', wallet.address.toHexString());
 
-  if (!ROUTER_CONTRACT_ID || !VITE_EXCHANGE_1_ID || !VITE_EXCHANGE_2_ID || !VITE_TOKEN_1_ID || !VITE_TOKEN_2_ID || !REGISTRY_CONTRACT_ID) {
+  if (!ROUTER_CONTRACT_ID || !VITE_EXCHANGE_1_ID || !VITE_EXCHANGE_2_ID || !VITE_TOKEN_1_ID || !VITE_TOKEN_2_ID || !REGISTRY_CONTRACT_ID || !VAULT_CONTRACT_ID) {
     console.error('Contract addresses missing');
-    console.error({ ROUTER_CONTRACT_ID, VITE_EXCHANGE_1_ID, VITE_EXCHANGE_2_ID, VITE_TOKEN_1_ID, VITE_TOKEN_2_ID, REGISTRY_CONTRACT_ID });
+    console.error({ ROUTER_CONTRACT_ID, VITE_EXCHANGE_1_ID, VITE_EXCHANGE_2_ID, VITE_TOKEN_1_ID, VITE_TOKEN_2_ID, REGISTRY_CONTRACT_ID, VAULT_CONTRACT_ID });
     return
   }
 
@@ -41,6 +50,7 @@ async function main() {
   const exchange2Contract = ExchangeContractAbi__factory.connect(VITE_EXCHANGE_2_ID!, wallet);
   const token1Contract = TokenContractAbi__factory.connect(VITE_TOKEN_1_ID!, wallet);
   const token2Contract = TokenContractAbi__factory.connect(VITE_TOKEN_2_ID!, wallet);
+  const vaultContract = VaultContractAbi__factory.connect(VAULT_CONTRACT_ID, wallet);
   const overrides = {
     gasPrice: bn(GAS_PRICE || 1),
   };
@@ -49,10 +59,47 @@ async function main() {
 
   await initializeTokenContract(token1Contract, overrides);
   await initializeTokenContract(token2Contract, overrides);
-  await registerPool(registryContract, exchange1Contract, overrides);
-  await registerPool(registryContract, exchange2Contract, overrides);
+  await initializeVault(vaultContract, overrides);
+  await registerPool(registryContract, exchange1Contract, VAULT_CONTRACT_ID, overrides);
+  await registerPool(registryContract, exchange2Contract, VAULT_CONTRACT_ID, overrides);
   await initializePool(routerContract, token1Contract, exchange1Contract, overrides);
   await initializePool(routerContract, token2Contract, exchange2Contract, overrides);
+
+  if (!VITE_TOKEN_USDC_ID || !VITE_TOKEN_USDT_ID || !VITE_TOKEN_STETH_ID || !VITE_STABLE_EXCHANGE_USDC_USDT_ID || !VITE_STABLE_EXCHANGE_ETH_STETH_ID) {
+    console.log('Stable pool addresses missing, skipping stable pools');
+    return;
+  }
+
+  const usdcContract = TokenContractAbi__factory.connect(VITE_TOKEN_USDC_ID, wallet);
+  const usdtContract = TokenContractAbi__factory.connect(VITE_TOKEN_USDT_ID, wallet);
+  const stethContract = TokenContractAbi__factory.connect(VITE_TOKEN_STETH_ID, wallet);
+  // Stable pools share the exchange ABI
+  const usdcUsdtContract = ExchangeContractAbi__factory.connect(VITE_STABLE_EXCHANGE_USDC_USDT_ID, wallet);
+  const ethStethContract = ExchangeContractAbi__factory.connect(VITE_STABLE_EXCHANGE_ETH_STETH_ID, wallet);
+  const [stable0Contract, stable1Contract] = usdcContract.id.toB256() < usdtContract.id.toB256()
+    ? [usdcContract, usdtContract]
+    : [usdtContract, usdcContract];
+
+  await initializeTokenContract(usdcContract, overrides);
+  await initializeTokenContract(usdtContract, overrides);
+  await initializeTokenContract(stethContract, overrides);
+  await registerPool(registryContract, usdcUsdtContract, VAULT_CONTRACT_ID, overrides, 'stable');
+  await registerPool(registryContract, ethStethContract, VAULT_CONTRACT_ID, overrides, 'stable');
+  await initializePool(routerContract, stable1Contract, usdcUsdtContract, overrides, stable0Contract);
+  await initializePool(routerContract, stethContract, ethStethContract, overrides);
+
+  if (!VITE_TOKEN_GOVERNANCE_ID || !VITE_WEIGHTED_EXCHANGE_GOVERNANCE_ETH_ID) {
+    console.log('Weighted pool addresses missing, skipping weighted pools');
+    return;
+  }
+
+  const governanceContract = TokenContractAbi__factory.connect(VITE_TOKEN_GOVERNANCE_ID, wallet);
+  // Weighted pools share the exchange ABI
+  const governanceEthContract = ExchangeContractAbi__factory.connect(VITE_WEIGHTED_EXCHANGE_GOVERNANCE_ETH_ID, wallet);
+
+  await initializeTokenContract(governanceContract, overrides);
+  await registerPool(registryContract, governanceEthContract, VAULT_CONTRACT_ID, overrides, 'weighted');
+  await initializePool(routerContract, governanceContract, governanceEthContract, overrides);
 }
 
 main();
@@ -2,17 +2,22 @@ import { bn, NativeAssetId } from 'fuels';
 
 import type { ExchangeContractAbi, RouterContractAbi, TokenContractAbi } from '../../contracts';
 
+import { addLiquidityWithScript } from './addLiquidity';
+
 const { TOKEN_AMOUNT, ETH_AMOUNT } = process.env;
 
 export async function initializePool(
   routerContract: RouterContractAbi,
   tokenContract: TokenContractAbi,
   exchangeContract: ExchangeContractAbi,
-  overrides: any
+  overrides: any,
+  // Pools without ETH pair the token with a second token, ordered as in the pool
+  token0Contract?: TokenContractAbi
 ) {
   const wallet = tokenContract.wallet!;
   const tokenAmount = bn(TOKEN_AMOUNT || '0x44364C5BB');
-  const ethAmount = bn(ETH_AMOUNT || '0xE8F272');
+  const token0Amount = token0Contract ? tokenAmount : bn(ETH_AMOUNT || '0xE8F272');
+  const token0Id = token0Contract ? token0Contract.id.toB256() : NativeAssetId;
   const address = {
     value: wallet.address,
   };
@@ -21,20 +26,22 @@ export async function initializePool(
   };
 
   console.log('Minting tokens')
-  await tokenContract.functions
-    .mint()
-    .txParams({
-      ...overrides,
-      variableOutputs: 1,
-    })
-    .call();
+  for (const contract of token0Contract ? [token0Contract, tokenContract] : [tokenContract]) {
+    await contract.functions
+      .mint()
+      .txParams({
+        ...overrides,
+        variableOutputs: 1,
+      })
+      .call();
+  }
 
   console.log('Balances');
-  console.log('ETH', await wallet.getBalance(NativeAssetId));
+  console.log(token0Contract ? 'Token 0' : 'ETH', await wallet.getBalance(token0Id));
   console.log('Token', await wallet.getBalance(tokenContract.id.toB256()));
 
-  if (ethAmount.gt(await wallet.getBalance(NativeAssetId))) {
-    throw new Error('Insufficient ETH');
+  if (token0Amount.gt(await wallet.getBalance(token0Id))) {
+    throw new Error(token0Contract ? 'Insufficient Tokens' : 'Insufficient ETH');
   }
   if (tokenAmount.gt(await wallet.getBalance(tokenContract.id.toB256()))) {
     throw new Error('Insufficient Tokens');
@@ -50,32 +57,17 @@ export async function initializePool(
 
   console.log('Initialize pool');
 
-  const addLiq = await routerContract
-    .multiCall([
-      routerContract.functions.null().callParams({
-        forward: [ethAmount, NativeAssetId],
-      }),
-      routerContract.functions.null().callParams({
-        forward: [tokenAmount, tokenContract.id.toB256()],
-      }),
-      routerContract.functions.add_liquidity(
-        exchangeContract.id.toB256(), // pool
-        ethAmount, // amount_a_desired
-        tokenAmount, // amount_b_desired
-        0, // amount_a_min
-        0, // amount_b_min
-        { Address: { value: wallet.address.toB256() } }, // recipient
-      ),
-    ])
-    .txParams({
-      ...overrides,
-      variableOutputs: 3,
-      gasLimit: 100_000_000,
-    })
-    .addContracts([
-      exchangeContract.id,
-    ])
-    .call();
+  const addLiq = await addLiquidityWithScript(
+    wallet,
+    exchangeContract.id.toB256(), // pool
+    token0Id,
+    tokenContract.id.toB256(),
+    token0Amount, // amount_0_desired
+    tokenAmount, // amount_1_desired
+    0, // amount_0_min
+    0, // amount_1_min
+    overrides
+  );
 
   console.log(addLiq);
 
@@ -84,14 +76,13 @@ export async function initializePool(
 
   console.log('Running test swap');
 
-  // const result = await routerContract.functions.null(
   const result = await routerContract.functions.swap_exact_input(
         exchangeContract.id.toB256(),
         0,
         { Address: { value: wallet.address.toHexString() } },
       )
       .callParams({
-        forward: [10, NativeAssetId],
+        forward: [10, token0Id],
         gasLimit: 10_000_000,
       })
       .addContracts([exchangeContract.id])
@@ -0,0 +1,22 @@
+import { bn, ZeroBytes32 } from 'fuels';
+
+import type { VaultContractAbi } from '../../contracts';
+
+const { VAULT_FEE_CHANGE_DELAY } = process.env;
+
+export async function initializeVault(vaultContract: VaultContractAbi, overrides: any) {
+  const owner = await vaultContract.functions.get_owner().get();
+  if (owner.value.Address?.value !== ZeroBytes32) {
+    console.log('Vault already initialized');
+    return;
+  }
+
+  // Notice period for protocol fee changes, 48 hours by default
+  const feeChangeDelay = bn(VAULT_FEE_CHANGE_DELAY || 48 * 60 * 60);
+
+  console.log(`Initializing vault with a ${feeChangeDelay.toString()}s fee change delay`);
+  await vaultContract.functions
+    .initialize({ Address: { value: vaultContract.wallet!.address.toB256() } }, feeChangeDelay)
+    .txParams(overrides)
+    .call();
+}
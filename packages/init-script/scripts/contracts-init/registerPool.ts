@@ -7,14 +7,26 @@ const { TOKEN_AMOUNT, ETH_AMOUNT } = process.env;
 export async function registerPool(
   registryContract: RegistryContractAbi,
   exchangeContract: ExchangeContractAbi,
-  overrides: any
+  vaultContractId: string,
+  overrides: any,
+  curve: 'constantProduct' | 'stable' | 'weighted' = 'constantProduct'
 ) {
   console.log('Registering pool');
 
+  const vaultInfo = await exchangeContract.functions.get_vault_info().get();
+  if (vaultInfo.value.vault == ZeroBytes32) {
+    console.log(`Initializing exchange ${exchangeContract.id.toB256()} with vault`);
+    await exchangeContract.functions.initialize(vaultContractId)
+      .txParams(overrides)
+      .addContracts([vaultContractId])
+      .call();
+  }
+
   const root = await registryContract.functions.exchange_contract_root().get();
   if (root.value == ZeroBytes32) {
     console.log('Initializing registry');
-    await registryContract.functions.initialize(exchangeContract.id.toB256())
+    const owner = { Address: { value: registryContract.wallet!.address.toB256() } };
+    await registryContract.functions.initialize(exchangeContract.id.toB256(), vaultContractId, owner)
       .txParams(overrides)
       .addContracts([exchangeContract.id])
       .call();
@@ -22,6 +34,26 @@ export async function registerPool(
     console.log('Registry already initialized');
   }
 
+  if (curve == 'stable') {
+    const stableRoot = await registryContract.functions.stable_contract_root().get();
+    if (stableRoot.value == ZeroBytes32) {
+      console.log('Setting stable exchange template');
+      await registryContract.functions.set_stable_template(exchangeContract.id.toB256())
+        .txParams(overrides)
+        .addContracts([exchangeContract.id])
+        .call();
+    }
+  } else if (curve == 'weighted') {
+    const weightedRoot = await registryContract.functions.weighted_contract_root().get();
+    if (weightedRoot.value == ZeroBytes32) {
+      console.log('Setting weighted exchange template');
+      await registryContract.functions.set_weighted_template(exchangeContract.id.toB256())
+        .txParams(overrides)
+        .addContracts([exchangeContract.id])
+        .call();
+    }
+  }
+
   const isRegistered = await registryContract.functions.is_pool(exchangeContract.id.toB256()).get();
   if (isRegistered.value) {
     console.log(`Exchange ${exchangeContract.id.toB256()} already registered`);
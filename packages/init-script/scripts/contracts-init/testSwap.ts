@@ -13,7 +13,6 @@ export async function testSwap(
 
   console.log('Running test swap');
 
-  // const result = await routerContract.functions.null(
   const result = await routerContract.functions.swap_exact_input(
         exchangeContract.id.toB256(),
         0,
@@ -14,7 +14,7 @@ export async function deployContracts(config: Config) {
     let contractOptions: DeployContractOptions | undefined;
 
     if (typeof options === 'function') {
-      contractOptions = options(contracts);
+      contractOptions = options(contracts, wallet.address.toB256());
     } else if (typeof options === 'object') {
       contractOptions = options;
     }
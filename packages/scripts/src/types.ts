@@ -32,7 +32,10 @@ export type Event =
       data: Array<BuildDeploy>;
     };
 
-export type OptionsFunction = (contracts: Array<ContractDeployed>) => DeployContractOptions;
+export type OptionsFunction = (
+  contracts: Array<ContractDeployed>,
+  deployer: string
+) => DeployContractOptions;
 
 export type ContractConfig = {
   name: string;
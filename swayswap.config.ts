@@ -19,6 +19,35 @@ const getDeployOptions = ({ salt }: { salt?: number } = {}) => ({
 // So the addresses change each deploy
 const saltBase = Math.floor(Date.now() / 10000);
 
+// The vault, registry and limit order contract can only be initialized by the address in their first slot
+const DEPLOYER_SLOT = '0x0000000000000000000000000000000000000000000000000000000000000000';
+
+const TOKEN_0_SLOT = '0x0000000000000000000000000000000000000000000000000000000000000000';
+const TOKEN_1_SLOT = '0x0000000000000000000000000000000000000000000000000000000000000001';
+const TOKEN_0_WEIGHT_SLOT = '0x0000000000000000000000000000000000000000000000000000000000000002';
+
+// Pools store their tokens in ascending order, leaving token 0 as ETH when only one is set
+const getPoolStorageSlots = (tokenIds: string[]) => {
+  const sorted = [...tokenIds].sort();
+  return sorted.length === 1
+    ? [{ key: TOKEN_1_SLOT, value: sorted[0] }]
+    : [
+        { key: TOKEN_0_SLOT, value: sorted[0] },
+        { key: TOKEN_1_SLOT, value: sorted[1] },
+      ];
+};
+
+// Weighted pools read token 0's weight, in basis points, from the first word of its slot
+const getWeightStorageSlot = (weight0: number) => ({
+  key: TOKEN_0_WEIGHT_SLOT,
+  value: '0x' + weight0.toString(16).padStart(16, '0').padEnd(64, '0'),
+});
+
+const getDeployerStorageSlots = (deployer: string) => [{ key: DEPLOYER_SLOT, value: deployer }];
+
+const getContractId = (contracts: { name: string; contractId: string }[], name: string) =>
+  contracts.find((c) => c.name === name)!.contractId;
+
 export default createConfig({
   types: {
     artifacts: './packages/contracts/**/out/debug/**-abi.json',
@@ -28,7 +57,10 @@ export default createConfig({
     {
       name: 'REGISTRY_CONTRACT_ID',
       path: './packages/contracts/registry_contract',
-      options: getDeployOptions({ salt: saltBase }),
+      options: (_, deployer) => ({
+        ...getDeployOptions({ salt: saltBase }),
+        storageSlots: getDeployerStorageSlots(deployer),
+      }),
     },
     {
       name: 'ROUTER_CONTRACT_ID',
@@ -38,7 +70,18 @@ export default createConfig({
     {
       name: 'VAULT_CONTRACT_ID',
       path: './packages/contracts/vault_contract',
-      options: getDeployOptions(),
+      options: (_, deployer) => ({
+        ...getDeployOptions(),
+        storageSlots: getDeployerStorageSlots(deployer),
+      }),
+    },
+    {
+      name: 'LIMIT_ORDER_CONTRACT_ID',
+      path: './packages/contracts/limit_order_contract',
+      options: (_, deployer) => ({
+        ...getDeployOptions(),
+        storageSlots: getDeployerStorageSlots(deployer),
+      }),
     },
     {
       name: 'VITE_TOKEN_1_ID',
@@ -82,6 +125,57 @@ export default createConfig({
         };
       },
     },
+    {
+      name: 'VITE_TOKEN_USDC_ID',
+      path: './packages/contracts/token_contract',
+      options: getDeployOptions({ salt: 3 + saltBase }),
+    },
+    {
+      name: 'VITE_TOKEN_USDT_ID',
+      path: './packages/contracts/token_contract',
+      options: getDeployOptions({ salt: 4 + saltBase }),
+    },
+    {
+      name: 'VITE_TOKEN_STETH_ID',
+      path: './packages/contracts/token_contract',
+      options: getDeployOptions({ salt: 5 + saltBase }),
+    },
+    {
+      name: 'VITE_STABLE_EXCHANGE_USDC_USDT_ID',
+      path: './packages/contracts/stable_exchange_contract',
+      options: (contracts) => ({
+        ...getDeployOptions({ salt: 3 + saltBase }),
+        storageSlots: getPoolStorageSlots([
+          getContractId(contracts, 'VITE_TOKEN_USDC_ID'),
+          getContractId(contracts, 'VITE_TOKEN_USDT_ID'),
+        ]),
+      }),
+    },
+    {
+      name: 'VITE_STABLE_EXCHANGE_ETH_STETH_ID',
+      path: './packages/contracts/stable_exchange_contract',
+      options: (contracts) => ({
+        ...getDeployOptions({ salt: 4 + saltBase }),
+        storageSlots: getPoolStorageSlots([getContractId(contracts, 'VITE_TOKEN_STETH_ID')]),
+      }),
+    },
+    {
+      name: 'VITE_TOKEN_GOVERNANCE_ID',
+      path: './packages/contracts/token_contract',
+      options: getDeployOptions({ salt: 6 + saltBase }),
+    },
+    {
+      // 80% governance token, 20% ETH
+      name: 'VITE_WEIGHTED_EXCHANGE_GOVERNANCE_ETH_ID',
+      path: './packages/contracts/weighted_exchange_contract',
+      options: (contracts) => ({
+        ...getDeployOptions({ salt: 5 + saltBase }),
+        storageSlots: [
+          ...getPoolStorageSlots([getContractId(contracts, 'VITE_TOKEN_GOVERNANCE_ID')]),
+          getWeightStorageSlot(2_000),
+        ],
+      }),
+    },
   ],
   onSuccess: (event) => {
     replaceEventOnEnv(`./packages/app/${OUTPUT_ENV || getEnvName()}`, event);